@@ -87,9 +87,20 @@ pub struct ClientDealProposal {
 
 impl Cbor for ClientDealProposal {}
 
-#[derive(Clone, Debug, PartialEq, Copy, Serialize_tuple, Deserialize_tuple)]
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct DealState {
     pub sector_start_epoch: ChainEpoch, // -1 if not yet included in proven sector
     pub last_updated_epoch: ChainEpoch, // -1 if deal state never updated
     pub slash_epoch: ChainEpoch,        // -1 if deal never slashed
+    /// Provider collateral locked for the deal after activation, on top of the proposal's.
+    /// It is kept out of the proposal so that the client's signature over it stays valid.
+    #[serde(with = "bigint_ser")]
+    pub provider_collateral_added: TokenAmount,
+}
+
+impl DealState {
+    /// The provider collateral locked for the deal, including any added after activation.
+    pub fn provider_collateral(&self, proposal: &DealProposal) -> TokenAmount {
+        &proposal.provider_collateral + &self.provider_collateral_added
+    }
 }
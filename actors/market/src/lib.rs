@@ -4,15 +4,16 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use bitfield::BitField;
-use fil_actors_runtime::runtime::{ActorCode, Runtime};
+use cid::Cid;
+use fil_actors_runtime::runtime::{ActorCode, Policy, Runtime};
 use fil_actors_runtime::{
-    actor_error, wasm_trampoline, ActorDowncast, ActorError, BURNT_FUNDS_ACTOR_ADDR,
+    actor_error, wasm_trampoline, ActorDowncast, ActorError, SetMultimap, BURNT_FUNDS_ACTOR_ADDR,
     CRON_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
     VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 use fvm_shared::actor::builtin::{Type, CALLER_TYPES_SIGNABLE};
 use fvm_shared::address::Address;
-use fvm_shared::bigint::BigInt;
+use fvm_shared::bigint::{BigInt, Integer};
 use fvm_shared::blockstore::Blockstore;
 use fvm_shared::clock::{ChainEpoch, QuantSpec, EPOCH_UNDEFINED};
 use fvm_shared::deal::DealID;
@@ -21,13 +22,14 @@ use fvm_shared::encoding::{to_vec, Cbor, RawBytes};
 use fvm_shared::error::ExitCode;
 use fvm_shared::piece::PieceInfo;
 use fvm_shared::reward::ThisEpochRewardReturn;
-use fvm_shared::sector::StoragePower;
+use fvm_shared::sector::{SectorSize, StoragePower};
 use fvm_shared::{ActorID, MethodNum, METHOD_CONSTRUCTOR, METHOD_SEND};
 use log::info;
 use num_derive::FromPrimitive;
 use num_traits::{FromPrimitive, Signed, Zero};
 
 pub use self::deal::*;
+pub use self::migration::*;
 use self::policy::*;
 pub use self::state::*;
 pub use self::types::*;
@@ -37,6 +39,7 @@ pub mod balance_table; // export for testing
 mod deal;
 #[doc(hidden)]
 pub mod ext; // export for testing
+mod migration;
 mod policy;
 mod state;
 mod types;
@@ -77,6 +80,21 @@ pub enum Method {
     OnMinerSectorsTerminate = 7,
     ComputeDataCommitment = 8,
     CronTick = 9,
+    TerminatePendingDeal = 10,
+    GetDealProposals = 11,
+    BatchActivateDeals = 12,
+    GetMarketTotals = 13,
+    AddDealCollateral = 14,
+    CheckBalanceCoverage = 15,
+    GetDealPieces = 16,
+    ActivateDealsStrict = 17,
+    ExtendDeal = 18,
+    GetDealCollateralBounds = 20,
+    GetNextDealId = 21,
+    AreDealsActivated = 22,
+    GetDealsByEpoch = 23,
+    ValidateDealProposal = 24,
+    CheckClientsCoverage = 25,
 }
 
 /// Market Actor
@@ -96,15 +114,26 @@ impl Actor {
         Ok(())
     }
 
-    /// Deposits the received value into the balance held in escrow.
-    fn add_balance<BS, RT>(rt: &mut RT, provider_or_client: Address) -> Result<(), ActorError>
+    /// Deposits the received value into the balance held in escrow, returning the resulting
+    /// escrow and locked balances.
+    fn add_balance<BS, RT>(
+        rt: &mut RT,
+        provider_or_client: Address,
+    ) -> Result<AddBalanceReturn, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
         let msg_value = rt.message().value_received();
 
-        if msg_value <= TokenAmount::from(0) {
+        if msg_value.is_zero() {
+            if !rt.policy().allow_zero_value_add_balance {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "balance to add must be greater than zero, zero-value add balance is disabled"
+                ));
+            }
+        } else if msg_value.is_negative() {
             return Err(actor_error!(
                 ErrIllegalArgument,
                 "balance to add must be greater than zero was: {}",
@@ -117,7 +146,7 @@ impl Actor {
 
         let (nominal, _, _) = escrow_address(rt, &provider_or_client)?;
 
-        rt.transaction(|st: &mut State, rt| {
+        let (balance, locked) = rt.transaction(|st: &mut State, rt| {
             let mut msm = st.mutator(rt.store());
             msm.with_escrow_table(Permission::Write)
                 .with_locked_table(Permission::Write)
@@ -126,21 +155,28 @@ impl Actor {
                     e.downcast_default(ExitCode::ErrIllegalState, "failed to load state")
                 })?;
 
-            msm.escrow_table.as_mut().unwrap().add(&nominal, &msg_value).map_err(|e| {
+            let escrow_table = msm.escrow_table.as_mut().unwrap();
+            escrow_table.add(&nominal, &msg_value).map_err(|e| {
                 e.downcast_default(
                     ExitCode::ErrIllegalState,
                     "failed to add balance to escrow table",
                 )
             })?;
+            let balance = escrow_table.get(&nominal).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to get escrow balance")
+            })?;
+            let locked = msm.locked_table.as_ref().unwrap().get(&nominal).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to get locked balance")
+            })?;
 
             msm.commit_state().map_err(|e| {
                 e.downcast_default(ExitCode::ErrIllegalState, "failed to flush state")
             })?;
 
-            Ok(())
+            Ok((balance, locked))
         })?;
 
-        Ok(())
+        Ok(AddBalanceReturn { address: nominal, balance, locked })
     }
 
     /// Attempt to withdraw the specified amount from the balance held in escrow.
@@ -218,6 +254,28 @@ impl Actor {
             return Err(actor_error!(ErrIllegalArgument, "Empty deals parameter"));
         }
 
+        // Reject oversized batches before doing any per-deal work.
+        let policy = rt.policy();
+        if params.deals.len() as u64 > policy.max_deals_per_publish {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many deals {}, max {}",
+                params.deals.len(),
+                policy.max_deals_per_publish
+            ));
+        }
+        for (di, deal) in params.deals.iter().enumerate() {
+            if deal.proposal.label.len() > policy.deal_max_label_size {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "deal {} label can be at most {} bytes, is {}",
+                    di,
+                    policy.deal_max_label_size,
+                    deal.proposal.label.len()
+                ));
+            }
+        }
+
         // All deals should have the same provider so get worker once
         let provider_raw = params.deals[0].proposal.provider;
         let provider = rt.resolve_address(&provider_raw).ok_or_else(|| {
@@ -356,6 +414,8 @@ impl Actor {
 
             // check VerifiedClient allowed cap and deduct PieceSize from cap
             // drop deals with a DealSize that cannot be fully covered by VerifiedClient's available DataCap
+            // This must remain the last check: datacap used here is not restored if the deal is
+            // dropped afterwards.
             if deal.proposal.verified_deal {
                 if let Err(e) = rt.send(
                     *VERIFIED_REGISTRY_ACTOR_ADDR,
@@ -428,7 +488,8 @@ impl Actor {
 
                 // We randomize the first epoch for when the deal will be processed so an attacker isn't able to
                 // schedule too many deals for the same tick.
-                let process_epoch = gen_rand_next_epoch(valid_deal.proposal.start_epoch, id);
+                let process_epoch =
+                    gen_rand_next_epoch(rt.policy(), valid_deal.proposal.start_epoch, id);
 
                 msm.deals_by_epoch.as_mut().unwrap().put(process_epoch, id).map_err(|e| {
                     e.downcast_default(ExitCode::ErrIllegalState, "failed to set deal ops by epoch")
@@ -469,7 +530,7 @@ impl Actor {
 
         let mut weights = Vec::with_capacity(params.sectors.len());
         for sector in params.sectors.iter() {
-            let (deal_weight, verified_deal_weight, deal_space) = validate_and_compute_deal_weight(
+            let sector_weights = validate_and_compute_deal_weight(
                 &proposals,
                 &sector.deal_ids,
                 &miner_addr,
@@ -482,7 +543,7 @@ impl Actor {
                     "failed to validate deal proposals for activation",
                 )
             })?;
-            weights.push(SectorWeights { deal_space, deal_weight, verified_deal_weight });
+            weights.push(sector_weights);
         }
 
         Ok(VerifyDealsForActivationReturn { sectors: weights })
@@ -491,6 +552,37 @@ impl Actor {
     /// Verify that a given set of storage deals is valid for a sector currently being ProveCommitted,
     /// update the market's internal state accordingly.
     fn activate_deals<BS, RT>(rt: &mut RT, params: ActivateDealsParams) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        Self::activate_deals_internal(rt, params.deal_ids, params.sector_expiry, None)
+    }
+
+    /// Like ActivateDeals, but additionally checks that the deals' pieces together fit in the
+    /// sector, to catch sealing pipeline errors at activation.
+    fn activate_deals_strict<BS, RT>(
+        rt: &mut RT,
+        params: ActivateDealsStrictParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        Self::activate_deals_internal(
+            rt,
+            params.deal_ids,
+            params.sector_expiry,
+            Some(params.sector_size),
+        )
+    }
+
+    fn activate_deals_internal<BS, RT>(
+        rt: &mut RT,
+        deal_ids: Vec<DealID>,
+        sector_expiry: ChainEpoch,
+        sector_size: Option<SectorSize>,
+    ) -> Result<(), ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
@@ -499,14 +591,25 @@ impl Actor {
         let miner_addr = rt.message().caller();
         let curr_epoch = rt.curr_epoch();
 
+        let mut seen_deal_ids = BTreeSet::new();
+        for deal_id in &deal_ids {
+            if !seen_deal_ids.insert(deal_id) {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "deal id {} present multiple times",
+                    deal_id
+                ));
+            }
+        }
+
         // Update deal states
         rt.transaction(|st: &mut State, rt| {
-            validate_deals_for_activation(
+            let deal_space = validate_deals_for_activation(
                 st,
                 rt.store(),
-                &params.deal_ids,
+                &deal_ids,
                 &miner_addr,
-                params.sector_expiry,
+                sector_expiry,
                 curr_epoch,
             )
             .map_err(|e| {
@@ -514,7 +617,21 @@ impl Actor {
                     ExitCode::ErrIllegalState,
                     "failed to validate deal proposals for activation",
                 )
-            })?;
+            })?
+            .deal_space;
+
+            // Each deal's end epoch is already checked against the sector expiry above.
+            if let Some(sector_size) = sector_size {
+                if deal_space > sector_size as u64 {
+                    return Err(actor_error!(
+                        ErrIllegalArgument,
+                        "deals {:?} total {} bytes of pieces, exceeding sector size {}",
+                        deal_ids,
+                        deal_space,
+                        sector_size as u64
+                    ));
+                }
+            }
 
             let mut msm = st.mutator(rt.store());
             msm.with_deal_states(Permission::Write)
@@ -525,216 +642,1013 @@ impl Actor {
                     e.downcast_default(ExitCode::ErrIllegalState, "failed to load state")
                 })?;
 
-            for deal_id in params.deal_ids {
-                // This construction could be replaced with a single "update deal state"
-                // state method, possibly batched over all deal ids at once.
-                let s = msm.deal_states.as_ref().unwrap().get(deal_id).map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::ErrIllegalState,
-                        format!("failed to get state for deal_id ({})", deal_id),
+            msm.check_deals_activatable(&deal_ids)?;
+            msm.set_deals_activated(&deal_ids, curr_epoch)?;
+
+            msm.commit_state().map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to flush state")
+            })?;
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Activates the deals for a batch of sectors. Unlike ActivateDeals, a sector whose deals
+    /// cannot be activated does not abort the batch: its exit code is reported and the deals
+    /// of the remaining sectors are still activated.
+    fn batch_activate_deals<BS, RT>(
+        rt: &mut RT,
+        params: BatchActivateDealsParams,
+    ) -> Result<BatchActivateDealsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_type(std::iter::once(&Type::Miner))?;
+        let miner_addr = rt.message().caller();
+        let curr_epoch = rt.curr_epoch();
+
+        let codes = rt.transaction(|st: &mut State, rt| {
+            let validated: Vec<Result<(), ActorError>> = params
+                .sectors
+                .iter()
+                .map(|sector| {
+                    validate_deals_for_activation(
+                        st,
+                        rt.store(),
+                        &sector.deal_ids,
+                        &miner_addr,
+                        sector.sector_expiry,
+                        curr_epoch,
                     )
+                    .map(|_| ())
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::ErrIllegalState,
+                            "failed to validate deal proposals for activation",
+                        )
+                    })
+                })
+                .collect();
+
+            let mut msm = st.mutator(rt.store());
+            msm.with_deal_states(Permission::Write)
+                .with_pending_proposals(Permission::ReadOnly)
+                .with_deal_proposals(Permission::ReadOnly)
+                .build()
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::ErrIllegalState, "failed to load state")
+                })?;
+
+            let mut codes = Vec::with_capacity(params.sectors.len());
+            for (i, (sector, validated)) in params.sectors.iter().zip(validated).enumerate() {
+                // Deals activated for an earlier sector in the batch are seen as already
+                // included by the check for later ones.
+                match validated.and_then(|_| msm.check_deals_activatable(&sector.deal_ids)) {
+                    Ok(()) => {
+                        msm.set_deals_activated(&sector.deal_ids, curr_epoch)?;
+                        codes.push(ExitCode::Ok);
+                    }
+                    Err(e) => {
+                        info!("failed to activate deals for sector {} in batch: {}", i, e);
+                        codes.push(e.exit_code());
+                    }
+                }
+            }
+
+            msm.commit_state().map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to flush state")
+            })?;
+            Ok(codes)
+        })?;
+
+        Ok(BatchActivateDealsReturn { codes })
+    }
+
+    /// Terminate a set of deals in response to their containing sector being terminated.
+    /// Slash provider collateral, refund client collateral, and refund partial unpaid escrow
+    /// amount to client.
+    fn on_miner_sectors_terminate<BS, RT>(
+        rt: &mut RT,
+        params: OnMinerSectorsTerminateParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_type(std::iter::once(&Type::Miner))?;
+        let miner_addr = rt.message().caller();
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut msm = st.mutator(rt.store());
+            msm.with_deal_states(Permission::Write)
+                .with_deal_proposals(Permission::ReadOnly)
+                .build()
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::ErrIllegalState, "failed to load state")
+                })?;
+
+            for id in params.deal_ids {
+                let deal = msm.deal_proposals.as_ref().unwrap().get(id).map_err(|e| {
+                    e.downcast_default(ExitCode::ErrIllegalState, "failed to get deal proposal")
                 })?;
-                if s.is_some() {
+                // The deal may have expired and been deleted before the sector is terminated.
+                // Nothing to do, but continue execution for the other deals.
+                if deal.is_none() {
+                    info!("couldn't find deal {}", id);
+                    continue;
+                }
+                let deal = deal.unwrap();
+
+                if deal.provider != miner_addr {
                     return Err(actor_error!(
-                        ErrIllegalArgument,
-                        "deal {} already included in another sector",
-                        deal_id
+                        ErrIllegalState,
+                        "caller {} is not the provider {} of deal {}",
+                        miner_addr,
+                        deal.provider,
+                        id
                     ));
                 }
 
-                let proposal = msm
-                    .deal_proposals
+                // do not slash expired deals
+                if deal.end_epoch <= params.epoch {
+                    info!("deal {} expired, not slashing", id);
+                    continue;
+                }
+
+                let mut state: DealState = msm
+                    .deal_states
                     .as_ref()
                     .unwrap()
-                    .get(deal_id)
+                    .get(id)
+                    .map_err(|e| {
+                        e.downcast_default(ExitCode::ErrIllegalState, "failed to get deal state")
+                    })?
+                    // A deal with a proposal but no state is not activated, but then it should not be
+                    // part of a sector that is terminating.
+                    .ok_or_else(|| actor_error!(ErrIllegalArgument, "no state for deal {}", id))?
+                    .clone();
+
+                // If a deal is already slashed, don't need to do anything
+                if state.slash_epoch != EPOCH_UNDEFINED {
+                    info!("deal {}, already slashed", id);
+                    continue;
+                }
+
+                // mark the deal for slashing here. Actual releasing of locked funds for the client
+                // and slashing of provider collateral happens in cron_tick.
+                state.slash_epoch = params.epoch;
+
+                msm.deal_states.as_mut().unwrap().set(id, state).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to set deal state ({})", id),
+                    )
+                })?;
+            }
+
+            msm.commit_state().map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to flush state")
+            })?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Terminate a published deal that has not yet been activated, at the request of its client.
+    /// Unlock the client's storage fee and the provider's collateral, and burn the client
+    /// collateral penalty, which is the policy's client_termination_penalty fraction of the
+    /// client's collateral (all of it by default).
+    fn terminate_pending_deal<BS, RT>(
+        rt: &mut RT,
+        params: TerminatePendingDealParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_type(CALLER_TYPES_SIGNABLE.iter())?;
+        let caller = rt.message().caller();
+        let deal_id = params.deal_id;
+
+        let (amount_slashed, deal) = rt.transaction(|st: &mut State, rt| {
+            let mut msm = st.mutator(rt.store());
+            msm.with_deal_states(Permission::ReadOnly)
+                .with_locked_table(Permission::Write)
+                .with_escrow_table(Permission::Write)
+                .with_deals_by_epoch(Permission::Write)
+                .with_deal_proposals(Permission::Write)
+                .with_pending_proposals(Permission::Write)
+                .build()
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::ErrIllegalState, "failed to load state")
+                })?;
+
+            let deal = msm
+                .deal_proposals
+                .as_ref()
+                .unwrap()
+                .get(deal_id)
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to get deal_id ({})", deal_id),
+                    )
+                })?
+                .ok_or_else(|| actor_error!(ErrNotFound, "proposal doesn't exist ({})", deal_id))?
+                .clone();
+
+            if deal.client != caller {
+                return Err(actor_error!(
+                    ErrForbidden,
+                    "caller {} is not the client {} of deal {}",
+                    caller,
+                    deal.client,
+                    deal_id
+                ));
+            }
+
+            // A deal that has reached its start epoch can no longer be activated, and is left
+            // to cron to time out, slashing the provider.
+            if rt.curr_epoch() >= deal.start_epoch {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "deal {} reached its start epoch {} and cannot be terminated by the client",
+                    deal_id,
+                    deal.start_epoch
+                ));
+            }
+
+            let state = msm.deal_states.as_ref().unwrap().get(deal_id).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to get deal state")
+            })?;
+            if state.is_some() {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "deal {} has been activated and cannot be terminated by the client",
+                    deal_id
+                ));
+            }
+
+            let dcid = deal.cid().map_err(|e| {
+                ActorError::from(e)
+                    .wrap(format!("failed to calculate cid for proposal {}", deal_id))
+            })?;
+
+            let amount_slashed = msm.process_deal_client_terminated(rt.policy(), &deal)?;
+
+            // Delete the proposal (but not state, which doesn't exist).
+            msm.deal_proposals.as_mut().unwrap().delete(deal_id).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to delete deal proposal {}", deal_id),
+                )
+            })?;
+            msm.pending_deals
+                .as_mut()
+                .unwrap()
+                .delete(&dcid.to_bytes())
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to delete pending proposal {}", deal_id),
+                    )
+                })?
+                .ok_or_else(|| {
+                    actor_error!(
+                        ErrIllegalState,
+                        "failed to delete pending proposal: does not exist"
+                    )
+                })?;
+
+            // An unactivated deal before its start epoch has not been reached by cron, so is
+            // still scheduled only at its initial processing epoch.
+            let process_epoch = gen_rand_next_epoch(rt.policy(), deal.start_epoch, deal_id);
+            msm.deals_by_epoch.as_mut().unwrap().remove(process_epoch, deal_id).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to remove deal {} from epoch {}", deal_id, process_epoch),
+                )
+            })?;
+
+            msm.commit_state().map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to flush state")
+            })?;
+            Ok((amount_slashed, deal))
+        })?;
+
+        if deal.verified_deal {
+            let res = rt.send(
+                *VERIFIED_REGISTRY_ACTOR_ADDR,
+                ext::verifreg::RESTORE_BYTES_METHOD,
+                RawBytes::serialize(ext::verifreg::RestoreBytesParams {
+                    address: deal.client,
+                    deal_size: BigInt::from(deal.piece_size.0),
+                })?,
+                TokenAmount::zero(),
+            );
+            if let Err(e) = res {
+                log::error!(
+                    "failed to send RestoreBytes call to the verifreg actor for terminated \
+                    verified deal, client: {}, deal_size: {}, provider: {}, got code: {:?}. {}",
+                    deal.client,
+                    deal.piece_size.0,
+                    deal.provider,
+                    e.exit_code(),
+                    e.msg()
+                );
+            }
+        }
+
+        if !amount_slashed.is_zero() {
+            rt.send(*BURNT_FUNDS_ACTOR_ADDR, METHOD_SEND, RawBytes::default(), amount_slashed)?;
+        }
+        Ok(())
+    }
+
+    fn compute_data_commitment<BS, RT>(
+        rt: &mut RT,
+        params: ComputeDataCommitmentParams,
+    ) -> Result<ComputeDataCommitmentReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_type(std::iter::once(&Type::Miner))?;
+
+        let st: State = rt.state()?;
+
+        let proposals = DealArray::load(&st.proposals, rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deal proposals")
+        })?;
+        let mut commds = Vec::with_capacity(params.inputs.len());
+        // Identical deal sets for the same seal proof share a CommD, so compute each once.
+        let mut computed: BTreeMap<(i64, &[DealID]), Cid> = BTreeMap::new();
+        for comm_input in params.inputs.iter() {
+            let key = (i64::from(comm_input.sector_type), comm_input.deal_ids.as_slice());
+            if let Some(commd) = computed.get(&key) {
+                commds.push(*commd);
+                continue;
+            }
+            let mut pieces: Vec<PieceInfo> = Vec::with_capacity(comm_input.deal_ids.len());
+            for deal_id in &comm_input.deal_ids {
+                let deal = proposals
+                    .get(*deal_id)
                     .map_err(|e| {
                         e.downcast_default(
                             ExitCode::ErrIllegalState,
                             format!("failed to get deal_id ({})", deal_id),
                         )
                     })?
-                    .ok_or_else(|| actor_error!(ErrNotFound, "no such deal_id: {}", deal_id))?;
+                    .ok_or_else(|| {
+                        actor_error!(ErrNotFound, "proposal doesn't exist ({})", deal_id)
+                    })?;
+                pieces.push(PieceInfo { cid: deal.piece_cid, size: deal.piece_size });
+            }
+            let commd =
+                rt.compute_unsealed_sector_cid(comm_input.sector_type, &pieces).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalArgument,
+                        "failed to compute unsealed sector CID",
+                    )
+                })?;
+            computed.insert(key, commd);
+            commds.push(commd);
+        }
+
+        Ok(ComputeDataCommitmentReturn { commds })
+    }
+
+    /// Fetches a batch of deal proposals, loading the proposals array once.
+    /// Missing proposals are returned as `None` rather than failing the call.
+    fn get_deal_proposals<BS, RT>(
+        rt: &mut RT,
+        params: GetDealProposalsParams,
+    ) -> Result<GetDealProposalsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        if params.deal_ids.len() > DEAL_PROPOSALS_QUERY_MAX {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many deal IDs {}, max {}",
+                params.deal_ids.len(),
+                DEAL_PROPOSALS_QUERY_MAX
+            ));
+        }
+
+        let st: State = rt.state()?;
+        let proposals = DealArray::load(&st.proposals, rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deal proposals")
+        })?;
+
+        let mut found = Vec::with_capacity(params.deal_ids.len());
+        for deal_id in params.deal_ids {
+            let proposal = proposals.get(deal_id).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to get deal_id ({})", deal_id),
+                )
+            })?;
+            found.push(proposal.cloned());
+        }
+
+        Ok(GetDealProposalsReturn { proposals: found })
+    }
+
+    /// Reports, for each deal, whether it has been activated in a sector, so that sealing can
+    /// avoid including a deal that activation would reject as already in another sector.
+    fn are_deals_activated<BS, RT>(
+        rt: &mut RT,
+        params: AreDealsActivatedParams,
+    ) -> Result<AreDealsActivatedReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        if params.deal_ids.len() > DEAL_PROPOSALS_QUERY_MAX {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many deal IDs {}, max {}",
+                params.deal_ids.len(),
+                DEAL_PROPOSALS_QUERY_MAX
+            ));
+        }
+
+        let st: State = rt.state()?;
+        let states = DealMetaArray::load(&st.states, rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deal states")
+        })?;
+
+        let mut activated = Vec::with_capacity(params.deal_ids.len());
+        for deal_id in params.deal_ids {
+            let state = states.get(deal_id).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to get state for deal {}", deal_id),
+                )
+            })?;
+            activated.push(matches!(state, Some(s) if s.sector_start_epoch != EPOCH_UNDEFINED));
+        }
+
+        Ok(AreDealsActivatedReturn { activated })
+    }
+
+    /// Fetches the piece CID and size of a batch of deals, as used to compute CommD.
+    /// Missing deals are skipped.
+    fn get_deal_pieces<BS, RT>(
+        rt: &mut RT,
+        params: GetDealPiecesParams,
+    ) -> Result<GetDealPiecesReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        if params.deal_ids.len() > DEAL_PROPOSALS_QUERY_MAX {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many deal IDs {}, max {}",
+                params.deal_ids.len(),
+                DEAL_PROPOSALS_QUERY_MAX
+            ));
+        }
+
+        let st: State = rt.state()?;
+        let proposals = DealArray::load(&st.proposals, rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deal proposals")
+        })?;
+
+        let mut pieces = Vec::with_capacity(params.deal_ids.len());
+        for deal_id in params.deal_ids {
+            let proposal = proposals.get(deal_id).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to get deal_id ({})", deal_id),
+                )
+            })?;
+            if let Some(deal) = proposal {
+                pieces.push(PieceInfo { cid: deal.piece_cid, size: deal.piece_size });
+            }
+        }
+
+        Ok(GetDealPiecesReturn { pieces })
+    }
+
+    /// Moves funds from the provider's escrow into the locked provider collateral of an active
+    /// deal, without republishing it. The added collateral is recorded in the deal's state, as the
+    /// client signed the proposal, and is unlocked or slashed along with the proposal's.
+    /// The deal's total provider collateral must stay within the bounds required at publication.
+    ///
+    /// Deals that are published but still pending are rejected: they have no deal state to
+    /// record the addition in, and a pending deal is only ever slashed for failing to activate,
+    /// which added collateral would make costlier rather than prevent. Providers top up once the
+    /// deal is activated.
+    fn add_deal_collateral<BS, RT>(
+        rt: &mut RT,
+        params: AddDealCollateralParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let deal_id = params.deal_id;
+        if !params.amount.is_positive() {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "collateral to add must be positive, was {}",
+                params.amount
+            ));
+        }
+
+        let proposal = {
+            let st: State = rt.state()?;
+            let proposals = DealArray::load(&st.proposals, rt.store()).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load deal proposals")
+            })?;
+            proposals
+                .get(deal_id)
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to get deal_id ({})", deal_id),
+                    )
+                })?
+                .ok_or_else(|| actor_error!(ErrNotFound, "no such deal_id: {}", deal_id))?
+                .clone()
+        };
+
+        let (owner, worker, control_addrs) = request_miner_control_addrs(rt, proposal.provider)?;
+        rt.validate_immediate_caller_is(
+            std::iter::once(&owner).chain(std::iter::once(&worker)).chain(&control_addrs),
+        )?;
+
+        let baseline_power = request_current_baseline_power(rt)?;
+        let (network_raw_power, _) = request_current_network_power(rt)?;
+        let (_, max_provider_collateral) = deal_provider_collateral_bounds(
+            proposal.piece_size,
+            &network_raw_power,
+            &baseline_power,
+            &rt.total_fil_circ_supply(),
+        );
+        let curr_epoch = rt.curr_epoch();
+
+        rt.transaction(|st: &mut State, rt| {
+            let mut msm = st.mutator(rt.store());
+            msm.with_deal_states(Permission::Write)
+                .with_escrow_table(Permission::ReadOnly)
+                .with_locked_table(Permission::Write)
+                .build()
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::ErrIllegalState, "failed to load state")
+                })?;
+
+            if curr_epoch >= proposal.end_epoch {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "deal {} ended at epoch {}",
+                    deal_id,
+                    proposal.end_epoch
+                ));
+            }
+            let mut state = msm
+                .deal_states
+                .as_ref()
+                .unwrap()
+                .get(deal_id)
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::ErrIllegalState, "failed to get deal state")
+                })?
+                .ok_or_else(|| {
+                    actor_error!(ErrIllegalArgument, "deal {} has not been activated", deal_id)
+                })?
+                .clone();
+            if state.slash_epoch != EPOCH_UNDEFINED {
+                return Err(actor_error!(ErrIllegalArgument, "deal {} was slashed", deal_id));
+            }
+
+            let provider_collateral = state.provider_collateral(&proposal) + &params.amount;
+            if provider_collateral > max_provider_collateral {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "provider collateral {} of deal {} would exceed the maximum {}",
+                    provider_collateral,
+                    deal_id,
+                    max_provider_collateral
+                ));
+            }
+
+            msm.lock_additional_provider_collateral(&proposal.provider, &params.amount)?;
+
+            state.provider_collateral_added += &params.amount;
+            msm.deal_states.as_mut().unwrap().set(deal_id, state).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to set deal state {}", deal_id),
+                )
+            })?;
+
+            msm.commit_state().map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to flush state")
+            })?;
+            Ok(())
+        })
+    }
+
+    /// Moves a pending deal's end epoch later, as agreed by both parties: the provider sends
+    /// the message and the client signs the amended proposal. Only deals not yet activated in a
+    /// sector may be extended, since an active deal must not outlive its sector.
+    /// The amended proposal must scale the provider collateral up in proportion to the longer
+    /// term, no further than the maximum provider collateral. The storage fee for the added epochs is locked from the client's escrow, the added
+    /// collateral from the provider's, and a verified deal consumes datacap for the added term
+    /// in proportion to the piece size it consumed when published.
+    fn extend_deal<BS, RT>(rt: &mut RT, params: ExtendDealParams) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let deal_id = params.deal_id;
+        let (proposal, active) = {
+            let st: State = rt.state()?;
+            let proposals = DealArray::load(&st.proposals, rt.store()).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load deal proposals")
+            })?;
+            let proposal = proposals
+                .get(deal_id)
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to get deal_id ({})", deal_id),
+                    )
+                })?
+                .ok_or_else(|| actor_error!(ErrNotFound, "no such deal_id: {}", deal_id))?
+                .clone();
+
+            let states = DealMetaArray::load(&st.states, rt.store()).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load deal states")
+            })?;
+            let state = states.get(deal_id).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to get deal state")
+            })?;
+            (proposal, state.is_some())
+        };
+
+        let (owner, worker, control_addrs) = request_miner_control_addrs(rt, proposal.provider)?;
+        rt.validate_immediate_caller_is(
+            std::iter::once(&owner).chain(std::iter::once(&worker)).chain(&control_addrs),
+        )?;
+
+        if active {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "deal {} is active, only pending deals can be extended",
+                deal_id
+            ));
+        }
+
+        if rt.curr_epoch() > proposal.start_epoch {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "deal {} start epoch {} has already elapsed",
+                deal_id,
+                proposal.start_epoch
+            ));
+        }
+        if params.new_end_epoch <= proposal.end_epoch {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "new end epoch {} must be after deal {} end epoch {}",
+                params.new_end_epoch,
+                deal_id,
+                proposal.end_epoch
+            ));
+        }
+
+        let old_duration = proposal.duration();
+        let added_epochs = params.new_end_epoch - proposal.end_epoch;
+        let mut amended = proposal.clone();
+        amended.end_epoch = params.new_end_epoch;
+        amended.provider_collateral = (&proposal.provider_collateral * amended.duration())
+            .div_floor(&BigInt::from(old_duration));
+
+        let (min_dur, max_dur) = deal_duration_bounds(amended.piece_size);
+        if amended.duration() < min_dur || amended.duration() > max_dur {
+            return Err(actor_error!(ErrIllegalArgument, "Deal duration out of bounds."));
+        };
+
+        let baseline_power = request_current_baseline_power(rt)?;
+        let (network_raw_power, _) = request_current_network_power(rt)?;
+        let (_, max_provider_collateral) = deal_provider_collateral_bounds(
+            amended.piece_size,
+            &network_raw_power,
+            &baseline_power,
+            &rt.total_fil_circ_supply(),
+        );
+        if amended.provider_collateral > max_provider_collateral {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "provider collateral {} of extended deal {} would exceed the maximum {}",
+                amended.provider_collateral,
+                deal_id,
+                max_provider_collateral
+            ));
+        }
+
+        let sv_bz = to_vec(&amended)
+            .map_err(|e| ActorError::from(e).wrap("failed to serialize DealProposal"))?;
+        rt.verify_signature(&params.client_signature, &amended.client, &sv_bz).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalArgument, "signature proposal invalid")
+        })?;
+
+        if amended.verified_deal {
+            let added_size = (BigInt::from(amended.piece_size.0) * added_epochs)
+                .div_ceil(&BigInt::from(old_duration));
+            rt.send(
+                *VERIFIED_REGISTRY_ACTOR_ADDR,
+                crate::ext::verifreg::USE_BYTES_METHOD as u64,
+                RawBytes::serialize(UseBytesParams {
+                    address: amended.client,
+                    deal_size: added_size,
+                })?,
+                TokenAmount::zero(),
+            )
+            .map_err(|e| e.wrap(format!("failed to use datacap for extending deal {}", deal_id)))?;
+        }
 
-                let propc = proposal
-                    .cid()
-                    .map_err(|e| ActorError::from(e).wrap("failed to calculate proposal Cid"))?;
+        rt.transaction(|st: &mut State, rt| {
+            let mut msm = st.mutator(rt.store());
+            msm.with_deal_proposals(Permission::Write)
+                .with_pending_proposals(Permission::Write)
+                .with_escrow_table(Permission::ReadOnly)
+                .with_locked_table(Permission::Write)
+                .build()
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::ErrIllegalState, "failed to load state")
+                })?;
 
-                let has =
-                    msm.pending_deals.as_ref().unwrap().has(&propc.to_bytes()).map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::ErrIllegalState,
-                            format!("failed to get pending proposal ({})", propc),
-                        )
-                    })?;
+            let additional_fee = &amended.storage_price_per_epoch * added_epochs;
+            msm.lock_additional_client_storage_fee(&amended.client, &additional_fee)?;
+            let additional_collateral =
+                &amended.provider_collateral - &proposal.provider_collateral;
+            msm.lock_additional_provider_collateral(&amended.provider, &additional_collateral)?;
 
-                if !has {
-                    return Err(actor_error!(
-                        ErrIllegalState,
-                        "tried to activate deal that was not in the pending set ({})",
-                        propc
-                    ));
-                }
+            let old_cid = proposal.cid().map_err(|e| {
+                ActorError::from(e)
+                    .wrap(format!("failed to calculate cid for proposal {}", deal_id))
+            })?;
+            let new_cid = amended.cid().map_err(|e| {
+                ActorError::from(e)
+                    .wrap(format!("failed to calculate cid for proposal {}", deal_id))
+            })?;
+            msm.replace_pending_proposal(deal_id, &old_cid, &new_cid)?;
 
-                msm.deal_states
-                    .as_mut()
-                    .unwrap()
-                    .set(
-                        deal_id,
-                        DealState {
-                            sector_start_epoch: curr_epoch,
-                            last_updated_epoch: EPOCH_UNDEFINED,
-                            slash_epoch: EPOCH_UNDEFINED,
-                        },
-                    )
-                    .map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::ErrIllegalState,
-                            format!("failed to set deal state {}", deal_id),
-                        )
-                    })?;
-            }
+            // The deal's first cron update is scheduled at or after its start epoch, and a deal
+            // can only be extended up to its start epoch, so cron has not yet read the proposal
+            // and reads the amended one from then on. deals_by_epoch needs no change.
+            msm.deal_proposals.as_mut().unwrap().set(deal_id, amended).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to set deal proposal {}", deal_id),
+                )
+            })?;
 
             msm.commit_state().map_err(|e| {
                 e.downcast_default(ExitCode::ErrIllegalState, "failed to flush state")
             })?;
             Ok(())
-        })?;
+        })
+    }
 
-        Ok(())
+    /// Returns the ID the next published deal will be assigned, which is also the number of
+    /// deals ever published.
+    fn get_next_deal_id<BS, RT>(rt: &mut RT) -> Result<GetNextDealIdReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(GetNextDealIdReturn { next_id: st.next_id })
     }
 
-    /// Terminate a set of deals in response to their containing sector being terminated.
-    /// Slash provider collateral, refund client collateral, and refund partial unpaid escrow
-    /// amount to client.
-    fn on_miner_sectors_terminate<BS, RT>(
+    /// Returns the deals cron will process at an epoch.
+    fn get_deals_by_epoch<BS, RT>(
         rt: &mut RT,
-        params: OnMinerSectorsTerminateParams,
-    ) -> Result<(), ActorError>
+        params: GetDealsByEpochParams,
+    ) -> Result<GetDealsByEpochReturn, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        rt.validate_immediate_caller_type(std::iter::once(&Type::Miner))?;
-        let miner_addr = rt.message().caller();
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let deals_by_epoch =
+            SetMultimap::from_root(rt.store(), &st.deal_ops_by_epoch).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load deal ops by epoch")
+            })?;
 
-        rt.transaction(|st: &mut State, rt| {
-            let mut msm = st.mutator(rt.store());
-            msm.with_deal_states(Permission::Write)
-                .with_deal_proposals(Permission::ReadOnly)
-                .build()
-                .map_err(|e| {
-                    e.downcast_default(ExitCode::ErrIllegalState, "failed to load state")
-                })?;
+        let mut deal_ids = Vec::new();
+        deals_by_epoch
+            .for_each(params.epoch, |deal_id| {
+                deal_ids.push(deal_id);
+                Ok(())
+            })
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to read deal ops for epoch {}", params.epoch),
+                )
+            })?;
+        deal_ids.sort_unstable();
+        Ok(GetDealsByEpochReturn { deal_ids })
+    }
 
-            for id in params.deal_ids {
-                let deal = msm.deal_proposals.as_ref().unwrap().get(id).map_err(|e| {
-                    e.downcast_default(ExitCode::ErrIllegalState, "failed to get deal proposal")
-                })?;
-                // The deal may have expired and been deleted before the sector is terminated.
-                // Nothing to do, but continue execution for the other deals.
-                if deal.is_none() {
-                    info!("couldn't find deal {}", id);
-                    continue;
-                }
-                let deal = deal.unwrap();
+    /// Returns the aggregate collateral and storage fees locked in escrow across all deals.
+    fn get_market_totals<BS, RT>(rt: &mut RT) -> Result<GetMarketTotalsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(GetMarketTotalsReturn {
+            total_client_locked_collateral: st.total_client_locked_colateral,
+            total_provider_locked_collateral: st.total_provider_locked_colateral,
+            total_client_storage_fee: st.total_client_storage_fee,
+        })
+    }
 
-                if deal.provider != miner_addr {
-                    return Err(actor_error!(
-                        ErrIllegalState,
-                        "caller {} is not the provider {} of deal {}",
-                        miner_addr,
-                        deal.provider,
-                        id
-                    ));
-                }
+    /// Checks whether an address's escrow could cover an additional lockup, as required of
+    /// clients and providers when publishing deals.
+    fn check_balance_coverage<BS, RT>(
+        rt: &mut RT,
+        params: CheckBalanceCoverageParams,
+    ) -> Result<CheckBalanceCoverageReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
 
-                // do not slash expired deals
-                if deal.end_epoch <= params.epoch {
-                    info!("deal {} expired, not slashing", id);
-                    continue;
-                }
+        if params.amount.is_negative() {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "amount to lock must be non-negative, was {}",
+                params.amount
+            ));
+        }
+        let address = rt.resolve_address(&params.address).ok_or_else(|| {
+            actor_error!(ErrNotFound, "failed to resolve address {}", params.address)
+        })?;
 
-                let mut state: DealState = *msm
-                    .deal_states
-                    .as_ref()
-                    .unwrap()
-                    .get(id)
-                    .map_err(|e| {
-                        e.downcast_default(ExitCode::ErrIllegalState, "failed to get deal state")
-                    })?
-                    // A deal with a proposal but no state is not activated, but then it should not be
-                    // part of a sector that is terminating.
-                    .ok_or_else(|| actor_error!(ErrIllegalArgument, "no state for deal {}", id))?;
+        let mut st: State = rt.state()?;
+        let mut msm = st.mutator(rt.store());
+        msm.with_escrow_table(Permission::ReadOnly)
+            .with_locked_table(Permission::ReadOnly)
+            .build()
+            .map_err(|e| e.downcast_default(ExitCode::ErrIllegalState, "failed to load msm"))?;
+        let covered = msm.balance_covered(address, &params.amount).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to check balance coverage")
+        })?;
 
-                // If a deal is already slashed, don't need to do anything
-                if state.slash_epoch != EPOCH_UNDEFINED {
-                    info!("deal {}, already slashed", id);
-                    continue;
-                }
+        Ok(CheckBalanceCoverageReturn { covered })
+    }
 
-                // mark the deal for slashing here. Actual releasing of locked funds for the client
-                // and slashing of provider collateral happens in cron_tick.
-                state.slash_epoch = params.epoch;
+    /// Like CheckBalanceCoverage, for a batch of addresses as many clients' deals would be
+    /// published together. Addresses that cannot be resolved are reported as not covered.
+    fn check_clients_coverage<BS, RT>(
+        rt: &mut RT,
+        params: CheckClientsCoverageParams,
+    ) -> Result<CheckClientsCoverageReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
 
-                msm.deal_states.as_mut().unwrap().set(id, state).map_err(|e| {
+        let max_entries = rt.policy().max_deals_per_publish;
+        if params.entries.len() as u64 > max_entries {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many entries {}, max {}",
+                params.entries.len(),
+                max_entries
+            ));
+        }
+        if let Some(entry) = params.entries.iter().find(|e| e.amount.is_negative()) {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "amount to lock for {} must be non-negative, was {}",
+                entry.address,
+                entry.amount
+            ));
+        }
+        let addresses: Vec<Option<Address>> =
+            params.entries.iter().map(|e| rt.resolve_address(&e.address)).collect();
+
+        let mut st: State = rt.state()?;
+        let mut msm = st.mutator(rt.store());
+        msm.with_escrow_table(Permission::ReadOnly)
+            .with_locked_table(Permission::ReadOnly)
+            .build()
+            .map_err(|e| e.downcast_default(ExitCode::ErrIllegalState, "failed to load msm"))?;
+
+        let mut covered = Vec::with_capacity(params.entries.len());
+        for (entry, address) in params.entries.iter().zip(addresses) {
+            let entry_covered = match address {
+                Some(address) => msm.balance_covered(address, &entry.amount).map_err(|e| {
                     e.downcast_default(
                         ExitCode::ErrIllegalState,
-                        format!("failed to set deal state ({})", id),
+                        "failed to check balance coverage",
                     )
-                })?;
-            }
+                })?,
+                None => false,
+            };
+            covered.push(entry_covered);
+        }
 
-            msm.commit_state().map_err(|e| {
-                e.downcast_default(ExitCode::ErrIllegalState, "failed to flush state")
-            })?;
-            Ok(())
-        })?;
-        Ok(())
+        Ok(CheckClientsCoverageReturn { covered })
     }
 
-    fn compute_data_commitment<BS, RT>(
+    /// Returns the collateral bounds a deal proposal for a piece of the given size and duration
+    /// must satisfy to be published, at the current network power and circulating supply.
+    fn get_deal_collateral_bounds<BS, RT>(
         rt: &mut RT,
-        params: ComputeDataCommitmentParams,
-    ) -> Result<ComputeDataCommitmentReturn, ActorError>
+        params: GetDealCollateralBoundsParams,
+    ) -> Result<GetDealCollateralBoundsReturn, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        rt.validate_immediate_caller_type(std::iter::once(&Type::Miner))?;
+        rt.validate_immediate_caller_accept_any()?;
 
-        let st: State = rt.state()?;
-
-        let proposals = DealArray::load(&st.proposals, rt.store()).map_err(|e| {
-            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deal proposals")
-        })?;
-        let mut commds = Vec::with_capacity(params.inputs.len());
-        for comm_input in params.inputs.iter() {
-            let mut pieces: Vec<PieceInfo> = Vec::with_capacity(comm_input.deal_ids.len());
-            for deal_id in &comm_input.deal_ids {
-                let deal = proposals
-                    .get(*deal_id)
-                    .map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::ErrIllegalState,
-                            format!("failed to get deal_id ({})", deal_id),
-                        )
-                    })?
-                    .ok_or_else(|| {
-                        actor_error!(ErrNotFound, "proposal doesn't exist ({})", deal_id)
-                    })?;
-                pieces.push(PieceInfo { cid: deal.piece_cid, size: deal.piece_size });
-            }
-            let commd =
-                rt.compute_unsealed_sector_cid(comm_input.sector_type, &pieces).map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::ErrIllegalArgument,
-                        "failed to compute unsealed sector CID",
-                    )
-                })?;
-            commds.push(commd);
+        params
+            .piece_size
+            .validate()
+            .map_err(|e| actor_error!(ErrIllegalArgument, "piece size is invalid: {}", e))?;
+        if params.duration <= 0 {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "duration must be positive, was {}",
+                params.duration
+            ));
         }
 
-        Ok(ComputeDataCommitmentReturn { commds })
+        let baseline_power = request_current_baseline_power(rt)?;
+        let (network_raw_power, _) = request_current_network_power(rt)?;
+
+        let (min_provider_collateral, max_provider_collateral) = deal_provider_collateral_bounds(
+            params.piece_size,
+            &network_raw_power,
+            &baseline_power,
+            &rt.total_fil_circ_supply(),
+        );
+        let (min_client_collateral, max_client_collateral) =
+            deal_client_collateral_bounds(params.piece_size, params.duration);
+
+        Ok(GetDealCollateralBoundsReturn {
+            min_provider_collateral,
+            max_provider_collateral,
+            min_client_collateral,
+            max_client_collateral,
+        })
+    }
+
+    /// Checks a signed deal proposal as PublishStorageDeals would, at the current network power
+    /// and baseline, without publishing it. A rejected proposal is reported in the return value
+    /// rather than as an error.
+    fn validate_deal_proposal<BS, RT>(
+        rt: &mut RT,
+        params: ClientDealProposal,
+    ) -> Result<ValidateDealProposalReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let baseline_power = request_current_baseline_power(rt)?;
+        let (network_raw_power, _) = request_current_network_power(rt)?;
+
+        Ok(match validate_deal(rt, &params, &network_raw_power, &baseline_power) {
+            Ok(()) => ValidateDealProposalReturn {
+                valid: true,
+                exit_code: ExitCode::Ok,
+                reason: String::new(),
+            },
+            Err(e) => ValidateDealProposalReturn {
+                valid: false,
+                exit_code: e.exit_code(),
+                reason: e.msg().to_string(),
+            },
+        })
     }
 
     fn cron_tick<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
@@ -763,7 +1677,15 @@ impl Actor {
                     e.downcast_default(ExitCode::ErrIllegalState, "failed to load state")
                 })?;
 
+            // Deals beyond the per-tick cap are deferred, and last_cron only advances to the
+            // last epoch whose scheduled deals have all been handled.
+            let max_deals = rt.policy().max_deals_per_cron_tick;
+            let mut processed_deals: u64 = 0;
+            let mut last_processed_epoch = last_cron;
             for i in (last_cron + 1)..=rt.curr_epoch() {
+                if processed_deals >= max_deals {
+                    break;
+                }
                 // TODO specs-actors modifies msm as it's iterated through, which is memory unsafe
                 // for now the deal ids are being collected and then iterated on, which could
                 // cause a potential inconsistency in exit code returned if a deal_id fails
@@ -781,22 +1703,29 @@ impl Actor {
                         e.downcast_default(ExitCode::ErrIllegalState, "failed to set deal state")
                     })?;
 
+                let remaining = (max_deals - processed_deals) as usize;
+                let deferred_deal_ids = if deal_ids.len() > remaining {
+                    deal_ids.split_off(remaining)
+                } else {
+                    Vec::new()
+                };
+                processed_deals += deal_ids.len() as u64;
+
                 for deal_id in deal_ids {
-                    let deal = msm
-                        .deal_proposals
-                        .as_ref()
-                        .unwrap()
-                        .get(deal_id)
-                        .map_err(|e| {
+                    let deal =
+                        match msm.deal_proposals.as_ref().unwrap().get(deal_id).map_err(|e| {
                             e.downcast_default(
                                 ExitCode::ErrIllegalState,
                                 format!("failed to get deal_id ({})", deal_id),
                             )
-                        })?
-                        .ok_or_else(|| {
-                            actor_error!(ErrNotFound, "proposal doesn't exist ({})", deal_id)
-                        })?
-                        .clone();
+                        })? {
+                            Some(deal) => deal.clone(),
+                            // A deal removed without its schedule entry must not stall cron.
+                            None => {
+                                log::warn!("skipping deal {} at epoch {}: no proposal", deal_id, i);
+                                continue;
+                            }
+                        };
 
                     let dcid = deal.cid().map_err(|e| {
                         ActorError::from(e)
@@ -829,7 +1758,7 @@ impl Actor {
                             ));
                         }
 
-                        let slashed = msm.process_deal_init_timed_out(&deal)?;
+                        let slashed = msm.process_deal_init_timed_out(rt.policy(), &deal)?;
                         if !slashed.is_zero() {
                             amount_slashed += slashed;
                         }
@@ -895,7 +1824,7 @@ impl Actor {
                     }
 
                     let (slash_amount, next_epoch, remove_deal) =
-                        msm.update_pending_deal_state(&state, &deal, curr_epoch)?;
+                        msm.update_pending_deal_state(rt.policy(), &state, &deal, curr_epoch)?;
                     if slash_amount.is_negative() {
                         return Err(actor_error!(
                             ErrIllegalState,
@@ -985,6 +1914,23 @@ impl Actor {
                         format!("failed to delete deal ops for epoch {}", i),
                     )
                 })?;
+                last_processed_epoch = i;
+
+                // Deferred deals have not been paid or slashed, so their state is untouched and
+                // they are processed in full when their new epoch is reached.
+                if !deferred_deal_ids.is_empty() {
+                    msm.deals_by_epoch
+                        .as_mut()
+                        .unwrap()
+                        .put_many(i + 1, &deferred_deal_ids)
+                        .map_err(|e| {
+                            e.downcast_default(
+                                ExitCode::ErrIllegalState,
+                                format!("failed to defer deal IDs to epoch {}", i + 1),
+                            )
+                        })?;
+                    break;
+                }
             }
 
             // updates_needed is already sorted by epoch.
@@ -997,7 +1943,7 @@ impl Actor {
                 })?;
             }
 
-            msm.st.last_cron = rt.curr_epoch();
+            msm.st.last_cron = last_processed_epoch;
 
             msm.commit_state().map_err(|e| {
                 e.downcast_default(ExitCode::ErrIllegalState, "failed to flush state")
@@ -1036,7 +1982,7 @@ impl Actor {
 }
 
 /// Validates a collection of deal dealProposals for activation, and returns their combined weight,
-/// split into regular deal weight and verified deal weight.
+/// split into regular deal weight and verified deal weight, along with their space and count.
 pub fn validate_deals_for_activation<BS>(
     st: &State,
     store: &BS,
@@ -1044,7 +1990,7 @@ pub fn validate_deals_for_activation<BS>(
     miner_addr: &Address,
     sector_expiry: ChainEpoch,
     curr_epoch: ChainEpoch,
-) -> anyhow::Result<(BigInt, BigInt, u64)>
+) -> anyhow::Result<SectorWeights>
 where
     BS: Blockstore,
 {
@@ -1059,12 +2005,13 @@ pub fn validate_and_compute_deal_weight<BS>(
     miner_addr: &Address,
     sector_expiry: ChainEpoch,
     sector_activation: ChainEpoch,
-) -> anyhow::Result<(BigInt, BigInt, u64)>
+) -> anyhow::Result<SectorWeights>
 where
     BS: Blockstore,
 {
     let mut seen_deal_ids = BTreeSet::new();
     let mut total_deal_space = 0;
+    let mut total_verified_deal_space = 0;
     let mut total_deal_space_time = BigInt::zero();
     let mut total_verified_space_time = BigInt::zero();
     for deal_id in deal_ids {
@@ -1086,18 +2033,25 @@ where
         total_deal_space += proposal.piece_size.0;
         let deal_space_time = deal_weight(proposal);
         if proposal.verified_deal {
+            total_verified_deal_space += proposal.piece_size.0;
             total_verified_space_time += deal_space_time;
         } else {
             total_deal_space_time += deal_space_time;
         }
     }
 
-    Ok((total_deal_space_time, total_verified_space_time, total_deal_space))
+    Ok(SectorWeights {
+        deal_space: total_deal_space,
+        deal_weight: total_deal_space_time,
+        verified_deal_weight: total_verified_space_time,
+        verified_deal_space: total_verified_deal_space,
+        deal_count: deal_ids.len() as u64,
+    })
 }
 
-fn gen_rand_next_epoch(start_epoch: ChainEpoch, deal_id: DealID) -> ChainEpoch {
-    let offset = deal_id as i64 % DEAL_UPDATES_INTERVAL;
-    let q = QuantSpec { unit: DEAL_UPDATES_INTERVAL, offset: 0 };
+fn gen_rand_next_epoch(policy: &Policy, start_epoch: ChainEpoch, deal_id: DealID) -> ChainEpoch {
+    let offset = deal_id as i64 % policy.deal_updates_interval;
+    let q = QuantSpec { unit: policy.deal_updates_interval, offset: 0 };
     let prev_day = q.quantize_down(start_epoch);
     if prev_day + offset >= start_epoch {
         return prev_day + offset;
@@ -1158,11 +2112,11 @@ where
 
     let proposal = &deal.proposal;
 
-    if proposal.label.len() > DEAL_MAX_LABEL_SIZE {
+    if proposal.label.len() > rt.policy().deal_max_label_size {
         return Err(actor_error!(
             ErrIllegalArgument,
             "deal label can be at most {} bytes, is {}",
-            DEAL_MAX_LABEL_SIZE,
+            rt.policy().deal_max_label_size,
             proposal.label.len()
         ));
     }
@@ -1319,8 +2273,8 @@ impl ActorCode for Actor {
                 Ok(RawBytes::default())
             }
             Some(Method::AddBalance) => {
-                Self::add_balance(rt, rt.deserialize_params(params)?)?;
-                Ok(RawBytes::default())
+                let res = Self::add_balance(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
             }
             Some(Method::WithdrawBalance) => {
                 let res = Self::withdraw_balance(rt, rt.deserialize_params(params)?)?;
@@ -1350,6 +2304,66 @@ impl ActorCode for Actor {
                 Self::cron_tick(rt)?;
                 Ok(RawBytes::default())
             }
+            Some(Method::TerminatePendingDeal) => {
+                Self::terminate_pending_deal(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetDealProposals) => {
+                let res = Self::get_deal_proposals(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::BatchActivateDeals) => {
+                let res = Self::batch_activate_deals(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::AddDealCollateral) => {
+                Self::add_deal_collateral(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::ExtendDeal) => {
+                Self::extend_deal(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetDealCollateralBounds) => {
+                let res = Self::get_deal_collateral_bounds(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetNextDealId) => {
+                let res = Self::get_next_deal_id(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::AreDealsActivated) => {
+                let res = Self::are_deals_activated(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetDealsByEpoch) => {
+                let res = Self::get_deals_by_epoch(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ValidateDealProposal) => {
+                let res = Self::validate_deal_proposal(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetMarketTotals) => {
+                let res = Self::get_market_totals(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::CheckBalanceCoverage) => {
+                let res = Self::check_balance_coverage(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::CheckClientsCoverage) => {
+                let res = Self::check_clients_coverage(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetDealPieces) => {
+                let res = Self::get_deal_pieces(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ActivateDealsStrict) => {
+                Self::activate_deals_strict(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
             None => Err(actor_error!(SysErrInvalidMethod, "Invalid method")),
         }
     }
@@ -0,0 +1,41 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fvm_shared::blockstore::Blockstore;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::tuple::*;
+
+use super::{DealMetaArray, DealState, State, STATES_AMT_BITWIDTH};
+use fil_actors_runtime::Array;
+
+/// Deal state in the layout written before provider collateral could be added to active deals.
+/// Deal states in this layout do not decode as [`DealState`], so the market state must be
+/// converted with [`migrate_state`] in the network upgrade that ships this version of the actor.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct PriorDealState {
+    pub sector_start_epoch: ChainEpoch,
+    pub last_updated_epoch: ChainEpoch,
+    pub slash_epoch: ChainEpoch,
+}
+
+/// Converts market state whose deal states are in the prior layout. Deal states keep their
+/// epochs and start with no added provider collateral. The rest of the state is unchanged.
+pub fn migrate_state<BS: Blockstore>(store: &BS, prior: State) -> anyhow::Result<State> {
+    let prior_states = Array::<PriorDealState, _>::load(&prior.states, store)?;
+    let mut states = DealMetaArray::new_with_bit_width(store, STATES_AMT_BITWIDTH);
+    prior_states.for_each(|deal_id, prior_state| {
+        states.set(
+            deal_id,
+            DealState {
+                sector_start_epoch: prior_state.sector_start_epoch,
+                last_updated_epoch: prior_state.last_updated_epoch,
+                slash_epoch: prior_state.slash_epoch,
+                provider_collateral_added: TokenAmount::default(),
+            },
+        )?;
+        Ok(())
+    })?;
+
+    Ok(State { states: states.flush()?, ..prior })
+}
@@ -1,10 +1,11 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::cmp::max;
+use std::cmp::{max, min};
 
 use fil_actors_runtime::network::EPOCHS_IN_DAY;
-use fil_actors_runtime::DealWeight;
+use fil_actors_runtime::runtime::Policy;
+use fil_actors_runtime::{actor_error, ActorError, DealWeight};
 use fvm_shared::bigint::Integer;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
@@ -15,9 +16,6 @@ use num_traits::Zero;
 
 use super::deal::DealProposal;
 
-/// DealUpdatesInterval is the number of blocks between payouts for deals
-pub const DEAL_UPDATES_INTERVAL: i64 = EPOCHS_IN_DAY;
-
 /// Numerator of the percentage of normalized cirulating
 /// supply that must be covered by provider collateral
 const PROV_COLLATERAL_PERCENT_SUPPLY_NUM: i64 = 1;
@@ -26,8 +24,8 @@ const PROV_COLLATERAL_PERCENT_SUPPLY_NUM: i64 = 1;
 /// supply that must be covered by provider collateral
 const PROV_COLLATERAL_PERCENT_SUPPLY_DENOM: i64 = 100;
 
-/// Maximum length of a deal label.
-pub(super) const DEAL_MAX_LABEL_SIZE: usize = 256;
+/// Maximum number of deal proposals that may be fetched in a single query.
+pub(super) const DEAL_PROPOSALS_QUERY_MAX: usize = 1000;
 
 /// Bounds (inclusive) on deal duration.
 pub(super) fn deal_duration_bounds(_size: PaddedPieceSize) -> (ChainEpoch, ChainEpoch) {
@@ -69,9 +67,43 @@ pub(super) fn deal_client_collateral_bounds(
 
 /// Penalty to provider deal collateral if the deadline expires before sector commitment.
 pub(super) fn collateral_penalty_for_deal_activation_missed(
+    policy: &Policy,
+    provider_collateral: TokenAmount,
+) -> Result<TokenAmount, ActorError> {
+    provider_collateral_slash_capped(policy, provider_collateral)
+}
+
+/// Limits a slash of provider deal collateral to the policy's maximum fraction of it.
+pub(super) fn provider_collateral_slash_capped(
+    policy: &Policy,
     provider_collateral: TokenAmount,
-) -> TokenAmount {
-    provider_collateral
+) -> Result<TokenAmount, ActorError> {
+    if policy.provider_collateral_slash_max_denom == 0 {
+        return Err(actor_error!(
+            ErrIllegalState,
+            "provider collateral slash denominator must be positive"
+        ));
+    }
+    let cap = (&provider_collateral * policy.provider_collateral_slash_max_num)
+        .div_floor(&TokenAmount::from(policy.provider_collateral_slash_max_denom));
+    Ok(min(provider_collateral, cap))
+}
+
+/// Penalty to client deal collateral if the client terminates a deal before it is activated:
+/// the policy's fraction of it, never more than the whole collateral.
+pub(super) fn collateral_penalty_for_client_termination(
+    policy: &Policy,
+    client_collateral: TokenAmount,
+) -> Result<TokenAmount, ActorError> {
+    if policy.client_termination_penalty_denom == 0 {
+        return Err(actor_error!(
+            ErrIllegalState,
+            "client termination penalty denominator must be positive"
+        ));
+    }
+    let penalty = (&client_collateral * policy.client_termination_penalty_num)
+        .div_floor(&TokenAmount::from(policy.client_termination_penalty_denom));
+    Ok(min(client_collateral, penalty))
 }
 
 /// Computes the weight for a deal proposal, which is a function of its size and duration.
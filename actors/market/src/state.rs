@@ -4,6 +4,7 @@
 use crate::balance_table::BalanceTable;
 use anyhow::anyhow;
 use cid::Cid;
+use fil_actors_runtime::runtime::Policy;
 use fil_actors_runtime::{
     actor_error, make_empty_map, ActorDowncast, ActorError, Array, Set, SetMultimap,
 };
@@ -21,7 +22,7 @@ use num_traits::{Signed, Zero};
 
 use super::policy::*;
 use super::types::*;
-use super::{DealProposal, DealState, DEAL_UPDATES_INTERVAL};
+use super::{DealProposal, DealState};
 
 /// Market actor state
 #[derive(Clone, Default, Serialize_tuple, Deserialize_tuple)]
@@ -344,6 +345,7 @@ where
     #[allow(clippy::too_many_arguments)]
     pub(super) fn update_pending_deal_state(
         &mut self,
+        policy: &Policy,
         state: &DealState,
         deal: &DealProposal,
         epoch: ChainEpoch,
@@ -422,11 +424,22 @@ where
                     )
                 })?;
 
-            // slash provider collateral
-            let slashed = deal.provider_collateral.clone();
+            // slash provider collateral, returning any amount above the policy's cap
+            let provider_collateral = state.provider_collateral(deal);
+            let slashed = provider_collateral_slash_capped(policy, provider_collateral.clone())?;
             self.slash_balance(&deal.provider, &slashed, Reason::ProviderCollateral)
                 .map_err(|e| e.downcast_default(ExitCode::ErrIllegalState, "slashing balance"))?;
 
+            let unslashed = provider_collateral - &slashed;
+            self.unlock_balance(&deal.provider, &unslashed, Reason::ProviderCollateral).map_err(
+                |e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        "failed to unlock unslashed provider collateral",
+                    )
+                },
+            )?;
+
             return Ok((slashed, EPOCH_UNDEFINED, true));
         }
 
@@ -438,16 +451,99 @@ where
         // We're explicitly not inspecting the end epoch and may process a deal's expiration late,
         // in order to prevent an outsider from loading a cron tick by activating too many deals
         // with the same end epoch.
-        let next = epoch + DEAL_UPDATES_INTERVAL;
+        let next = epoch + policy.deal_updates_interval;
 
         Ok((TokenAmount::zero(), next, false))
     }
 
+    /// Checks that none of the deals are already active and that all of them are still pending.
+    /// Nothing is written, so a failure leaves the state untouched.
+    pub(super) fn check_deals_activatable(&self, deal_ids: &[DealID]) -> Result<(), ActorError> {
+        for &deal_id in deal_ids {
+            let s = self.deal_states.as_ref().unwrap().get(deal_id).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to get state for deal_id ({})", deal_id),
+                )
+            })?;
+            if s.is_some() {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "deal {} already included in another sector",
+                    deal_id
+                ));
+            }
+
+            let proposal = self
+                .deal_proposals
+                .as_ref()
+                .unwrap()
+                .get(deal_id)
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to get deal_id ({})", deal_id),
+                    )
+                })?
+                .ok_or_else(|| actor_error!(ErrNotFound, "no such deal_id: {}", deal_id))?;
+
+            let propc = proposal
+                .cid()
+                .map_err(|e| ActorError::from(e).wrap("failed to calculate proposal Cid"))?;
+
+            let has = self.pending_deals.as_ref().unwrap().has(&propc.to_bytes()).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to get pending proposal ({})", propc),
+                )
+            })?;
+
+            if !has {
+                return Err(actor_error!(
+                    ErrIllegalState,
+                    "tried to activate deal that was not in the pending set ({})",
+                    propc
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the deals as activated in a sector at the current epoch.
+    pub(super) fn set_deals_activated(
+        &mut self,
+        deal_ids: &[DealID],
+        curr_epoch: ChainEpoch,
+    ) -> Result<(), ActorError> {
+        for &deal_id in deal_ids {
+            self.deal_states
+                .as_mut()
+                .unwrap()
+                .set(
+                    deal_id,
+                    DealState {
+                        sector_start_epoch: curr_epoch,
+                        last_updated_epoch: EPOCH_UNDEFINED,
+                        slash_epoch: EPOCH_UNDEFINED,
+                        provider_collateral_added: TokenAmount::zero(),
+                    },
+                )
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to set deal state {}", deal_id),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
     /// Deal start deadline elapsed without appearing in a proven sector.
     /// Slash a portion of provider's collateral, and unlock remaining collaterals
     /// for both provider and client.
     pub(super) fn process_deal_init_timed_out(
         &mut self,
+        policy: &Policy,
         deal: &DealProposal,
     ) -> Result<TokenAmount, ActorError> {
         self.unlock_balance(&deal.client, &deal.total_storage_fee(), Reason::ClientStorageFee)
@@ -463,8 +559,10 @@ where
                 e.downcast_default(ExitCode::ErrIllegalState, "failure unlocking client collateral")
             })?;
 
-        let amount_slashed =
-            collateral_penalty_for_deal_activation_missed(deal.provider_collateral.clone());
+        let amount_slashed = collateral_penalty_for_deal_activation_missed(
+            policy,
+            deal.provider_collateral.clone(),
+        )?;
         let amount_remaining = deal.provider_balance_requirement() - &amount_slashed;
 
         self.slash_balance(&deal.provider, &amount_slashed, Reason::ProviderCollateral).map_err(
@@ -482,6 +580,47 @@ where
         Ok(amount_slashed)
     }
 
+    /// Client terminated a deal that has not been activated. Unlock the storage fee and the
+    /// provider's collateral, and slash the policy's fraction of the client's collateral,
+    /// unlocking the rest.
+    pub(super) fn process_deal_client_terminated(
+        &mut self,
+        policy: &Policy,
+        deal: &DealProposal,
+    ) -> Result<TokenAmount, ActorError> {
+        self.unlock_balance(&deal.client, &deal.total_storage_fee(), Reason::ClientStorageFee)
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    "failure unlocking client storage fee",
+                )
+            })?;
+
+        let amount_slashed =
+            collateral_penalty_for_client_termination(policy, deal.client_collateral.clone())?;
+        let amount_remaining = &deal.client_collateral - &amount_slashed;
+
+        self.slash_balance(&deal.client, &amount_slashed, Reason::ClientCollateral).map_err(
+            |e| e.downcast_default(ExitCode::ErrIllegalState, "failed to slash balance"),
+        )?;
+
+        self.unlock_balance(&deal.client, &amount_remaining, Reason::ClientCollateral).map_err(
+            |e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failure unlocking client collateral")
+            },
+        )?;
+
+        self.unlock_balance(&deal.provider, &deal.provider_collateral, Reason::ProviderCollateral)
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    "failed to unlock deal provider balance",
+                )
+            })?;
+
+        Ok(amount_slashed)
+    }
+
     /// Normal expiration. Unlock collaterals for both miner and client.
     fn process_deal_expired(
         &mut self,
@@ -495,13 +634,14 @@ where
             return Err(actor_error!(ErrIllegalState, "start sector epoch undefined"));
         }
 
-        self.unlock_balance(&deal.provider, &deal.provider_collateral, Reason::ProviderCollateral)
-            .map_err(|e| {
-                e.downcast_default(
-                    ExitCode::ErrIllegalState,
-                    "failed unlocking deal provider balance",
-                )
-            })?;
+        self.unlock_balance(
+            &deal.provider,
+            &state.provider_collateral(deal),
+            Reason::ProviderCollateral,
+        )
+        .map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed unlocking deal provider balance")
+        })?;
 
         self.unlock_balance(&deal.client, &deal.client_collateral, Reason::ClientCollateral)
             .map_err(|e| {
@@ -587,6 +727,78 @@ where
         Ok(())
     }
 
+    /// Locks additional client storage fee for an existing deal.
+    pub(super) fn lock_additional_client_storage_fee(
+        &mut self,
+        client: &Address,
+        amount: &TokenAmount,
+    ) -> Result<(), ActorError> {
+        self.maybe_lock_balance(client, amount)
+            .map_err(|e| e.wrap("failed to lock client funds"))?;
+
+        if let Some(v) = self.total_client_storage_fee.as_mut() {
+            *v += amount;
+        }
+        Ok(())
+    }
+
+    /// Locks additional provider collateral for an existing deal.
+    pub(super) fn lock_additional_provider_collateral(
+        &mut self,
+        provider: &Address,
+        amount: &TokenAmount,
+    ) -> Result<(), ActorError> {
+        self.maybe_lock_balance(provider, amount)
+            .map_err(|e| e.wrap("failed to lock provider funds"))?;
+
+        if let Some(v) = self.total_provider_locked_colateral.as_mut() {
+            *v += amount;
+        }
+        Ok(())
+    }
+
+    /// Pending proposals are keyed by CID, so an amended deal that is still pending is re-keyed
+    /// under its new CID. Deals that are no longer pending are left alone.
+    pub(super) fn replace_pending_proposal(
+        &mut self,
+        deal_id: DealID,
+        old_cid: &Cid,
+        new_cid: &Cid,
+    ) -> Result<(), ActorError> {
+        let pending = self.pending_deals.as_mut().unwrap();
+        let was_pending = pending
+            .delete(&old_cid.to_bytes())
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to delete pending proposal {}", deal_id),
+                )
+            })?
+            .is_some();
+        if was_pending {
+            let exists = pending.has(&new_cid.to_bytes()).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to get pending proposal ({})", new_cid),
+                )
+            })?;
+            if exists {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "cannot amend deal {}: an identical proposal is pending",
+                    deal_id
+                ));
+            }
+            pending.put(new_cid.to_bytes().into()).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to set pending proposal {}", deal_id),
+                )
+            })?;
+        }
+        Ok(())
+    }
+
     fn unlock_balance(
         &mut self,
         addr: &Address,
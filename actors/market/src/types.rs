@@ -7,10 +7,13 @@ use fil_actors_runtime::{Array, DealWeight};
 use fvm_shared::address::Address;
 use fvm_shared::bigint::bigint_ser;
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::encoding::tuple::*;
-use fvm_shared::sector::RegisteredSealProof;
+use fvm_shared::error::ExitCode;
+use fvm_shared::piece::{PaddedPieceSize, PieceInfo};
+use fvm_shared::sector::{RegisteredSealProof, SectorSize};
 
 use super::deal::{ClientDealProposal, DealProposal, DealState};
 
@@ -43,6 +46,156 @@ pub struct OnMinerSectorsTerminateParamsRef<'a> {
     pub deal_ids: &'a [DealID],
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct TerminatePendingDealParams {
+    pub deal_id: DealID,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct AddBalanceReturn {
+    /// Address whose escrow was credited. For a provider this is the miner actor's ID address,
+    /// whoever made the deposit.
+    pub address: Address,
+    /// Escrow balance of that address after the deposit.
+    #[serde(with = "bigint_ser")]
+    pub balance: TokenAmount,
+    /// Portion of the escrow balance locked against deals.
+    #[serde(with = "bigint_ser")]
+    pub locked: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct AddDealCollateralParams {
+    pub deal_id: DealID,
+    #[serde(with = "bigint_ser")]
+    pub amount: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ExtendDealParams {
+    pub deal_id: DealID,
+    pub new_end_epoch: ChainEpoch,
+    /// The client's signature over the proposal with its end epoch set to new_end_epoch.
+    pub client_signature: Signature,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetDealProposalsParams {
+    pub deal_ids: Vec<DealID>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetDealProposalsReturn {
+    /// Proposals aligned with the requested deal IDs, `None` where no proposal exists.
+    pub proposals: Vec<Option<DealProposal>>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct AreDealsActivatedParams {
+    pub deal_ids: Vec<DealID>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct AreDealsActivatedReturn {
+    /// Whether each requested deal has been activated in a sector, in request order.
+    pub activated: Vec<bool>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetDealPiecesParams {
+    pub deal_ids: Vec<DealID>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetDealPiecesReturn {
+    /// Pieces of the requested deals that exist, in request order.
+    pub pieces: Vec<PieceInfo>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetNextDealIdReturn {
+    pub next_id: DealID,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetDealsByEpochParams {
+    pub epoch: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetDealsByEpochReturn {
+    /// Deals queued for processing at the epoch, in ascending ID order.
+    pub deal_ids: Vec<DealID>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetMarketTotalsReturn {
+    #[serde(with = "bigint_ser")]
+    pub total_client_locked_collateral: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub total_provider_locked_collateral: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub total_client_storage_fee: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct CheckBalanceCoverageParams {
+    pub address: Address,
+    /// Amount that would be locked in addition to the address's current lockup.
+    #[serde(with = "bigint_ser")]
+    pub amount: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct CheckBalanceCoverageReturn {
+    pub covered: bool,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct CheckClientsCoverageParams {
+    pub entries: Vec<CheckBalanceCoverageParams>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct CheckClientsCoverageReturn {
+    /// Whether each entry's escrow covers its additional lockup, in request order.
+    pub covered: Vec<bool>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetDealCollateralBoundsParams {
+    pub piece_size: PaddedPieceSize,
+    pub duration: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetDealCollateralBoundsReturn {
+    #[serde(with = "bigint_ser")]
+    pub min_provider_collateral: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub max_provider_collateral: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub min_client_collateral: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub max_client_collateral: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ValidateDealProposalReturn {
+    pub valid: bool,
+    /// Exit code publishing would have dropped the deal with, or `Ok` if it is valid.
+    pub exit_code: ExitCode,
+    /// Reason the deal would be rejected, empty if it is valid.
+    pub reason: String,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct PublishStorageDealsParams {
     pub deals: Vec<ClientDealProposal>,
@@ -85,6 +238,9 @@ pub struct SectorWeights {
     pub deal_weight: DealWeight,
     #[serde(with = "bigint_ser")]
     pub verified_deal_weight: DealWeight,
+    /// Total piece size of the verified deals, included in deal_space.
+    pub verified_deal_space: u64,
+    pub deal_count: u64,
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple)]
@@ -93,6 +249,26 @@ pub struct ActivateDealsParams {
     pub sector_expiry: ChainEpoch,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ActivateDealsStrictParams {
+    pub deal_ids: Vec<DealID>,
+    pub sector_expiry: ChainEpoch,
+    /// Size of the sector the deals are sealed into, which their pieces must fit.
+    pub sector_size: SectorSize,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct BatchActivateDealsParams {
+    /// Deals to activate, one entry per sector.
+    pub sectors: Vec<ActivateDealsParams>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct BatchActivateDealsReturn {
+    /// Exit code of the activation of each sector's deals, in the order given.
+    pub codes: Vec<ExitCode>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ComputeDataCommitmentParams {
     pub inputs: Vec<SectorDataSpec>,
@@ -3,24 +3,48 @@
 
 use std::collections::HashMap;
 
+use cid::multihash::Multihash;
+use cid::Cid;
 use fil_actor_market::balance_table::{BalanceTable, BALANCE_TABLE_BITWIDTH};
 use fil_actor_market::{
-    ext, Actor as MarketActor, Method, State, WithdrawBalanceParams, PROPOSALS_AMT_BITWIDTH,
-    STATES_AMT_BITWIDTH,
+    ext, migrate_state, ActivateDealsParams, ActivateDealsStrictParams, Actor as MarketActor,
+    AddBalanceReturn, AddDealCollateralParams, AreDealsActivatedParams, AreDealsActivatedReturn,
+    BatchActivateDealsParams, BatchActivateDealsReturn, CheckBalanceCoverageParams,
+    CheckBalanceCoverageReturn, CheckClientsCoverageParams, CheckClientsCoverageReturn,
+    ClientDealProposal, ComputeDataCommitmentParams, ComputeDataCommitmentReturn, DealArray,
+    DealMetaArray, DealProposal, DealState, ExtendDealParams, GetDealCollateralBoundsParams,
+    GetDealCollateralBoundsReturn, GetDealPiecesParams, GetDealPiecesReturn,
+    GetDealProposalsParams, GetDealProposalsReturn, GetDealsByEpochParams, GetDealsByEpochReturn,
+    GetMarketTotalsReturn, GetNextDealIdReturn, Method, OnMinerSectorsTerminateParams,
+    PriorDealState, PublishStorageDealsParams, PublishStorageDealsReturn, SectorDataSpec,
+    SectorDeals, State, TerminatePendingDealParams, ValidateDealProposalReturn,
+    VerifyDealsForActivationParams, VerifyDealsForActivationReturn, WithdrawBalanceParams,
+    PROPOSALS_AMT_BITWIDTH, STATES_AMT_BITWIDTH,
 };
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::test_utils::*;
 use fil_actors_runtime::{
-    make_empty_map, ActorError, SetMultimap, STORAGE_MARKET_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    make_empty_map, ActorError, Set, SetMultimap, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR,
+    REWARD_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 use fvm_ipld_amt::Amt;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::bigint_ser::BigIntDe;
-use fvm_shared::clock::EPOCH_UNDEFINED;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::{ChainEpoch, EPOCH_UNDEFINED};
+use fvm_shared::commcid::{FIL_COMMITMENT_UNSEALED, SHA2_256_TRUNC254_PADDED};
+use fvm_shared::crypto::signature::Signature;
+use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
-use fvm_shared::encoding::RawBytes;
+use fvm_shared::encoding::{to_vec, Cbor, RawBytes};
 use fvm_shared::error::ExitCode;
-use fvm_shared::{HAMT_BIT_WIDTH, METHOD_CONSTRUCTOR, METHOD_SEND};
+use fvm_shared::piece::PaddedPieceSize;
+use fvm_shared::reward::ThisEpochRewardReturn;
+use fvm_shared::sector::{RegisteredSealProof, SectorSize, StoragePower};
+use fvm_shared::smooth::FilterEstimate;
+use fvm_shared::{HAMT_BIT_WIDTH, METHOD_CONSTRUCTOR, METHOD_SEND, TOTAL_FILECOIN};
 
 const OWNER_ID: u64 = 101;
 const PROVIDER_ID: u64 = 102;
@@ -350,6 +374,1695 @@ fn worker_withdraw_more_than_available() {
     assert_eq!(get_escrow_balance(&rt, &provider_addr).unwrap(), TokenAmount::from(0u8));
 }
 
+#[test]
+fn get_deal_proposals_for_missing_deals() {
+    let mut rt = setup();
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT_ID));
+    rt.expect_validate_caller_any();
+    let params = GetDealProposalsParams { deal_ids: vec![0, 1, 2] };
+    let ret: GetDealProposalsReturn = rt
+        .call::<MarketActor>(Method::GetDealProposals as u64, &RawBytes::serialize(params).unwrap())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(vec![None, None, None], ret.proposals);
+}
+
+#[test]
+fn get_deal_pieces_skips_missing_deals() {
+    let mut rt = setup();
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT_ID));
+    rt.expect_validate_caller_any();
+    let params = GetDealPiecesParams { deal_ids: vec![0, 1, 2] };
+    let ret: GetDealPiecesReturn = rt
+        .call::<MarketActor>(Method::GetDealPieces as u64, &RawBytes::serialize(params).unwrap())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert!(ret.pieces.is_empty());
+}
+
+#[test]
+fn get_market_totals_after_construction() {
+    let mut rt = setup();
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT_ID));
+    rt.expect_validate_caller_any();
+    let ret: GetMarketTotalsReturn = rt
+        .call::<MarketActor>(Method::GetMarketTotals as u64, &RawBytes::default())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(TokenAmount::from(0), ret.total_client_locked_collateral);
+    assert_eq!(TokenAmount::from(0), ret.total_provider_locked_collateral);
+    assert_eq!(TokenAmount::from(0), ret.total_client_storage_fee);
+}
+
+#[test]
+fn get_next_deal_id_reports_state() {
+    let mut rt = setup();
+    assert_eq!(0, get_next_deal_id(&mut rt));
+
+    let mut st: State = rt.get_state().unwrap();
+    st.next_id = 42;
+    rt.replace_state(&st);
+    assert_eq!(42, get_next_deal_id(&mut rt));
+}
+
+#[test]
+fn are_deals_activated_reports_sector_inclusion() {
+    let mut rt = setup();
+
+    let mut st: State = rt.get_state().unwrap();
+    let mut states = DealMetaArray::load(&st.states, &rt.store).unwrap();
+    for (deal_id, sector_start_epoch) in [(1, 10), (2, EPOCH_UNDEFINED)] {
+        states
+            .set(
+                deal_id,
+                DealState {
+                    sector_start_epoch,
+                    last_updated_epoch: EPOCH_UNDEFINED,
+                    slash_epoch: EPOCH_UNDEFINED,
+                    provider_collateral_added: TokenAmount::from(0),
+                },
+            )
+            .unwrap();
+    }
+    st.states = states.flush().unwrap();
+    rt.replace_state(&st);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT_ID));
+    rt.expect_validate_caller_any();
+    let params = AreDealsActivatedParams { deal_ids: vec![0, 1, 2] };
+    let ret: AreDealsActivatedReturn = rt
+        .call::<MarketActor>(
+            Method::AreDealsActivated as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(vec![false, true, false], ret.activated);
+}
+
+#[test]
+fn get_deals_by_epoch_reports_scheduled_deals() {
+    let mut rt = setup();
+
+    let mut st: State = rt.get_state().unwrap();
+    let mut deals_by_epoch = SetMultimap::from_root(&rt.store, &st.deal_ops_by_epoch).unwrap();
+    deals_by_epoch.put_many(100, &[7, 3, 5]).unwrap();
+    deals_by_epoch.put(101, 9).unwrap();
+    st.deal_ops_by_epoch = deals_by_epoch.root().unwrap();
+    rt.replace_state(&st);
+
+    assert_eq!(vec![3, 5, 7], get_deals_by_epoch(&mut rt, 100));
+    assert_eq!(vec![9], get_deals_by_epoch(&mut rt, 101));
+    assert!(get_deals_by_epoch(&mut rt, 102).is_empty());
+}
+
+#[test]
+fn get_deal_proposals_rejects_too_many_deals() {
+    let mut rt = setup();
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT_ID));
+    rt.expect_validate_caller_any();
+    let params = GetDealProposalsParams { deal_ids: (0..1001).collect() };
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<MarketActor>(
+            Method::GetDealProposals as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+}
+
+#[test]
+fn publish_storage_deals_rejects_oversized_label() {
+    let mut rt = setup();
+    rt.policy.deal_max_label_size = 4;
+
+    let deal = ClientDealProposal {
+        proposal: DealProposal {
+            piece_cid: Cid::default(),
+            piece_size: PaddedPieceSize(2048),
+            verified_deal: false,
+            client: Address::new_id(CLIENT_ID),
+            provider: Address::new_id(PROVIDER_ID),
+            label: "label".to_string(),
+            start_epoch: 100,
+            end_epoch: 200,
+            storage_price_per_epoch: TokenAmount::from(0),
+            provider_collateral: TokenAmount::from(0),
+            client_collateral: TokenAmount::from(0),
+        },
+        client_signature: Signature::new_bls(vec![]),
+    };
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(WORKER_ID));
+    rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+    let params = PublishStorageDealsParams { deals: vec![deal] };
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<MarketActor>(
+            Method::PublishStorageDeals as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+}
+
+#[test]
+fn publish_storage_deals_counts_dropped_deals_toward_lockup() {
+    let mut rt = setup();
+    let client = Address::new_id(CLIENT_ID);
+    let provider = Address::new_id(PROVIDER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    // The client can only afford one of the two deals.
+    add_participant_funds(&mut rt, client, proposal.client_balance_requirement());
+
+    let verified = ClientDealProposal {
+        proposal: DealProposal { verified_deal: true, ..proposal.clone() },
+        client_signature: Signature::new_bls(b"verified".to_vec()),
+    };
+    let unverified = ClientDealProposal {
+        proposal: DealProposal { label: "other".to_string(), ..proposal },
+        client_signature: Signature::new_bls(b"unverified".to_vec()),
+    };
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+    rt.expect_send(
+        provider,
+        ext::miner::CONTROL_ADDRESSES_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0u8),
+        RawBytes::serialize(ext::miner::GetControlAddressesReturnParams {
+            owner: Address::new_id(OWNER_ID),
+            worker,
+            control_addresses: Vec::new(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    expect_query_network_info(&mut rt);
+    expect_client_signature(&mut rt, &verified);
+    // The client has no datacap, so the verified deal is dropped.
+    rt.expect_send(
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        ext::verifreg::USE_BYTES_METHOD as u64,
+        RawBytes::serialize(ext::verifreg::UseBytesParams {
+            address: client,
+            deal_size: BigInt::from(verified.proposal.piece_size.0),
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::ErrIllegalArgument,
+    );
+    expect_client_signature(&mut rt, &unverified);
+
+    // As in the reference implementations, the dropped deal's cost still counts against the
+    // client's lockup, so the deal after it is dropped for insufficient funds.
+    let params = PublishStorageDealsParams { deals: vec![verified, unverified] };
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "All deal proposals invalid",
+        rt.call::<MarketActor>(
+            Method::PublishStorageDeals as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn batch_activate_deals_reports_failures_per_sector() {
+    let mut rt = setup();
+
+    rt.set_caller(*MINER_ACTOR_CODE_ID, Address::new_id(PROVIDER_ID));
+    rt.expect_validate_caller_type(vec![*MINER_ACTOR_CODE_ID]);
+    let params = BatchActivateDealsParams {
+        sectors: vec![
+            ActivateDealsParams { deal_ids: vec![], sector_expiry: 1000 },
+            ActivateDealsParams { deal_ids: vec![42], sector_expiry: 1000 },
+        ],
+    };
+    let ret: BatchActivateDealsReturn = rt
+        .call::<MarketActor>(
+            Method::BatchActivateDeals as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(vec![ExitCode::Ok, ExitCode::ErrNotFound], ret.codes);
+}
+
+#[test]
+fn activate_deals_rejects_duplicate_deal_ids() {
+    let mut rt = setup();
+
+    rt.set_caller(*MINER_ACTOR_CODE_ID, Address::new_id(PROVIDER_ID));
+    rt.expect_validate_caller_type(vec![*MINER_ACTOR_CODE_ID]);
+    let params = ActivateDealsParams { deal_ids: vec![7, 3, 7], sector_expiry: 1000 };
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "deal id 7 present multiple times",
+        rt.call::<MarketActor>(Method::ActivateDeals as u64, &RawBytes::serialize(params).unwrap()),
+    );
+    rt.verify();
+}
+
+#[test]
+fn activate_deals_strict_rejects_pieces_exceeding_sector_size() {
+    let mut rt = setup();
+
+    // The deal's piece is larger than the sector it is activated in.
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.piece_size = PaddedPieceSize(4096);
+    let deal_ids = publish_deals(&mut rt, &[proposal.clone()]);
+
+    rt.set_caller(*MINER_ACTOR_CODE_ID, Address::new_id(PROVIDER_ID));
+    rt.expect_validate_caller_type(vec![*MINER_ACTOR_CODE_ID]);
+    let params = ActivateDealsStrictParams {
+        deal_ids,
+        sector_expiry: proposal.end_epoch,
+        sector_size: SectorSize::_2KiB,
+    };
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "exceeding sector size",
+        rt.call::<MarketActor>(
+            Method::ActivateDealsStrict as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+}
+
+#[test]
+fn verify_deals_for_activation_reports_verified_space_and_count() {
+    let mut rt = setup();
+
+    let mut st: State = rt.get_state().unwrap();
+    let mut proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    for (deal_id, piece_size, verified_deal) in [(0, 2048, false), (1, 1024, true)] {
+        proposals
+            .set(
+                deal_id,
+                DealProposal {
+                    piece_cid: Cid::default(),
+                    piece_size: PaddedPieceSize(piece_size),
+                    verified_deal,
+                    client: Address::new_id(CLIENT_ID),
+                    provider: Address::new_id(PROVIDER_ID),
+                    label: "label".to_string(),
+                    start_epoch: 100,
+                    end_epoch: 200,
+                    storage_price_per_epoch: TokenAmount::from(0),
+                    provider_collateral: TokenAmount::from(0),
+                    client_collateral: TokenAmount::from(0),
+                },
+            )
+            .unwrap();
+    }
+    st.proposals = proposals.flush().unwrap();
+    rt.replace_state(&st);
+
+    rt.set_caller(*MINER_ACTOR_CODE_ID, Address::new_id(PROVIDER_ID));
+    rt.expect_validate_caller_type(vec![*MINER_ACTOR_CODE_ID]);
+    let params = VerifyDealsForActivationParams {
+        sectors: vec![SectorDeals { sector_expiry: 1000, deal_ids: vec![0, 1] }],
+    };
+    let ret: VerifyDealsForActivationReturn = rt
+        .call::<MarketActor>(
+            Method::VerifyDealsForActivation as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(1, ret.sectors.len());
+    let weights = &ret.sectors[0];
+    assert_eq!(3072, weights.deal_space);
+    assert_eq!(1024, weights.verified_deal_space);
+    assert_eq!(2, weights.deal_count);
+    assert_eq!(BigInt::from(100 * 2048), weights.deal_weight);
+    assert_eq!(BigInt::from(100 * 1024), weights.verified_deal_weight);
+}
+
+#[test]
+fn cron_tick_defers_deals_beyond_cap_to_next_epoch() {
+    let mut rt = setup();
+    rt.policy.max_deals_per_cron_tick = 2;
+    // Process every deal first at its start epoch.
+    rt.policy.deal_updates_interval = 1;
+
+    // Publish three deals that are never activated, so time out at their start.
+    let start_epoch = 100;
+    let proposals: Vec<DealProposal> = (0..3)
+        .map(|i| DealProposal {
+            label: format!("deal {}", i),
+            ..generate_deal_proposal(start_epoch, start_epoch + 200 * EPOCHS_IN_DAY)
+        })
+        .collect();
+    publish_deals(&mut rt, &proposals);
+
+    rt.epoch = start_epoch;
+    cron_tick(&mut rt);
+
+    // Two deals are processed and the third moves to the next epoch, which hasn't been reached.
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(start_epoch, st.last_cron);
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    let mut remaining = Vec::new();
+    proposals
+        .for_each(|deal_id, _| {
+            remaining.push(deal_id);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(1, remaining.len());
+    let deals_by_epoch = SetMultimap::from_root(&rt.store, &st.deal_ops_by_epoch).unwrap();
+    let mut deferred = Vec::new();
+    deals_by_epoch
+        .for_each(start_epoch + 1, |deal_id| {
+            deferred.push(deal_id);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(remaining, deferred);
+
+    rt.epoch = start_epoch + 1;
+    cron_tick(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(start_epoch + 1, st.last_cron);
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert!(proposals.get(remaining[0]).unwrap().is_none());
+}
+
+#[test]
+fn deferred_deal_cannot_be_terminated_and_times_out_in_cron() {
+    let mut rt = setup();
+    rt.policy.max_deals_per_cron_tick = 1;
+    rt.policy.deal_updates_interval = 1;
+
+    let start_epoch = 100;
+    let proposals: Vec<DealProposal> = (0..2)
+        .map(|i| DealProposal {
+            label: format!("deal {}", i),
+            ..generate_deal_proposal(start_epoch, start_epoch + 200 * EPOCHS_IN_DAY)
+        })
+        .collect();
+    publish_deals(&mut rt, &proposals);
+
+    // One deal is deferred past its initial processing epoch.
+    rt.epoch = start_epoch;
+    cron_tick(&mut rt);
+    let deferred = get_deals_by_epoch(&mut rt, start_epoch + 1);
+    assert_eq!(1, deferred.len());
+
+    // Having reached its start, the deal is left for cron to time out.
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "reached its start epoch",
+        terminate_pending_deal(&mut rt, Address::new_id(CLIENT_ID), deferred[0]),
+    );
+    rt.verify();
+
+    rt.epoch = start_epoch + 1;
+    cron_tick(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(start_epoch + 1, st.last_cron);
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert!(proposals.get(deferred[0]).unwrap().is_none());
+}
+
+#[test]
+fn cron_tick_skips_scheduled_deal_without_proposal() {
+    let mut rt = setup();
+    rt.policy.deal_updates_interval = 1;
+
+    let start_epoch = 100;
+    let proposal = generate_deal_proposal(start_epoch, start_epoch + 200 * EPOCHS_IN_DAY);
+    let deal_id = publish_deals(&mut rt, &[proposal])[0];
+
+    // Drop the proposal but leave the deal scheduled.
+    let mut st: State = rt.get_state().unwrap();
+    let mut proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    proposals.delete(deal_id).unwrap();
+    st.proposals = proposals.flush().unwrap();
+    rt.replace_state(&st);
+
+    rt.epoch = start_epoch;
+    cron_tick(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(start_epoch, st.last_cron);
+    assert!(get_deals_by_epoch(&mut rt, start_epoch).is_empty());
+}
+
+#[test]
+fn short_deal_updates_interval_settles_payments_each_interval() {
+    let mut rt = setup();
+    rt.policy.deal_updates_interval = 10;
+    let provider = Address::new_id(PROVIDER_ID);
+
+    let start_epoch = 100;
+    let end_epoch = start_epoch + 200 * EPOCHS_IN_DAY;
+    let proposals: Vec<DealProposal> = (0..2)
+        .map(|i| DealProposal {
+            label: format!("deal {}", i),
+            ..generate_deal_proposal(start_epoch, end_epoch)
+        })
+        .collect();
+    let deal_ids = publish_deals(&mut rt, &proposals);
+    activate_deals(&mut rt, end_epoch, &deal_ids);
+
+    // Each deal is first processed at its offset within the interval, from its start epoch.
+    assert_eq!(vec![0], get_deals_by_epoch(&mut rt, 100));
+    assert_eq!(vec![1], get_deals_by_epoch(&mut rt, 101));
+
+    // Processing reschedules each deal a whole interval later, keeping its offset.
+    for epoch in [100, 101, 110, 111].iter().copied() {
+        rt.epoch = epoch;
+        cron_tick(&mut rt);
+        assert!(get_deals_by_epoch(&mut rt, epoch).is_empty());
+        assert_eq!(vec![(epoch % 10) as DealID], get_deals_by_epoch(&mut rt, epoch + 10));
+    }
+
+    // Deal 1 paid for the epoch before its first processing, then both for a whole interval.
+    let price = &proposals[0].storage_price_per_epoch;
+    assert_eq!(price * (1 + 2 * 10), get_escrow_balance(&rt, &provider).unwrap());
+}
+
+fn expect_extension_signature(
+    rt: &mut MockRuntime,
+    amended: &DealProposal,
+    client_signature: &Signature,
+) {
+    rt.expect_verify_signature(ExpectedVerifySig {
+        sig: client_signature.clone(),
+        signer: amended.client,
+        plaintext: to_vec(amended).unwrap(),
+        result: Ok(()),
+    });
+}
+
+fn extend_deal(
+    rt: &mut MockRuntime,
+    deal_id: DealID,
+    new_end_epoch: ChainEpoch,
+    client_signature: Signature,
+) -> Result<RawBytes, ActorError> {
+    let params = ExtendDealParams { deal_id, new_end_epoch, client_signature };
+    rt.call::<MarketActor>(Method::ExtendDeal as u64, &RawBytes::serialize(params).unwrap())
+}
+
+#[test]
+fn extend_deal_locks_additional_storage_fee_and_collateral() {
+    let mut rt = setup();
+    let client = Address::new_id(CLIENT_ID);
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.provider_collateral = TokenAmount::from(576_000);
+    let deal_id = publish_deals(&mut rt, &[proposal.clone()])[0];
+
+    // Extending by 1000 epochs costs 10 per epoch and scales the collateral by
+    // (576_000 + 1000) / 576_000.
+    add_participant_funds(&mut rt, client, TokenAmount::from(10_000));
+    add_provider_funds(&mut rt, provider, owner, worker, TokenAmount::from(1000));
+
+    let new_end_epoch = proposal.end_epoch + 1000;
+    let mut amended = proposal.clone();
+    amended.end_epoch = new_end_epoch;
+    amended.provider_collateral = TokenAmount::from(577_000);
+    let client_signature = Signature::new_bls(b"extension".to_vec());
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    expect_provider_control_address(&mut rt, provider, owner, worker);
+    expect_query_network_info(&mut rt);
+    expect_extension_signature(&mut rt, &amended, &client_signature);
+    extend_deal(&mut rt, deal_id, new_end_epoch, client_signature).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert_eq!(&amended, proposals.get(deal_id).unwrap().unwrap());
+    let pending = Set::from_root(&rt.store, &st.pending_proposals).unwrap();
+    assert!(!pending.has(&proposal.cid().unwrap().to_bytes()).unwrap());
+    assert!(pending.has(&amended.cid().unwrap().to_bytes()).unwrap());
+
+    let total_fee = amended.total_storage_fee();
+    let locked = BalanceTable::from_root(&rt.store, &st.locked_table).unwrap();
+    assert_eq!(total_fee, locked.get(&client).unwrap());
+    assert_eq!(amended.provider_collateral, locked.get(&provider).unwrap());
+    assert_eq!(total_fee, st.total_client_storage_fee);
+    assert_eq!(amended.provider_collateral, st.total_provider_locked_colateral);
+}
+
+#[test]
+fn extend_deal_uses_datacap_for_added_term_of_verified_deal() {
+    let mut rt = setup();
+    let client = Address::new_id(CLIENT_ID);
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.verified_deal = true;
+    let deal_id = publish_deals(&mut rt, &[proposal.clone()])[0];
+    add_participant_funds(&mut rt, client, TokenAmount::from(10_000));
+
+    let new_end_epoch = proposal.end_epoch + 1000;
+    let mut amended = proposal.clone();
+    amended.end_epoch = new_end_epoch;
+    let client_signature = Signature::new_bls(b"extension".to_vec());
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    expect_provider_control_address(&mut rt, provider, owner, worker);
+    expect_query_network_info(&mut rt);
+    expect_extension_signature(&mut rt, &amended, &client_signature);
+    // 2048 bytes for 1000 of the original 576_000 epochs, rounded up.
+    rt.expect_send(
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        ext::verifreg::USE_BYTES_METHOD as u64,
+        RawBytes::serialize(ext::verifreg::UseBytesParams {
+            address: client,
+            deal_size: BigInt::from(4),
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    extend_deal(&mut rt, deal_id, new_end_epoch, client_signature).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert_eq!(new_end_epoch, proposals.get(deal_id).unwrap().unwrap().end_epoch);
+}
+
+#[test]
+fn extend_deal_fails_without_datacap_for_verified_deal() {
+    let mut rt = setup();
+    let client = Address::new_id(CLIENT_ID);
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.verified_deal = true;
+    let deal_id = publish_deals(&mut rt, &[proposal.clone()])[0];
+    add_participant_funds(&mut rt, client, TokenAmount::from(10_000));
+
+    let new_end_epoch = proposal.end_epoch + 1000;
+    let mut amended = proposal.clone();
+    amended.end_epoch = new_end_epoch;
+    let client_signature = Signature::new_bls(b"extension".to_vec());
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    expect_provider_control_address(&mut rt, provider, owner, worker);
+    expect_query_network_info(&mut rt);
+    expect_extension_signature(&mut rt, &amended, &client_signature);
+    rt.expect_send(
+        *VERIFIED_REGISTRY_ACTOR_ADDR,
+        ext::verifreg::USE_BYTES_METHOD as u64,
+        RawBytes::serialize(ext::verifreg::UseBytesParams {
+            address: client,
+            deal_size: BigInt::from(4),
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::ErrIllegalArgument,
+    );
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "failed to use datacap",
+        extend_deal(&mut rt, deal_id, new_end_epoch, client_signature),
+    );
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert_eq!(proposal.end_epoch, proposals.get(deal_id).unwrap().unwrap().end_epoch);
+}
+
+#[test]
+fn extend_deal_rejects_active_deal() {
+    let mut rt = setup();
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    let deal_ids = publish_deals(&mut rt, &[proposal.clone()]);
+    activate_deals(&mut rt, proposal.end_epoch, &deal_ids);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    expect_provider_control_address(&mut rt, provider, owner, worker);
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "only pending deals can be extended",
+        extend_deal(
+            &mut rt,
+            deal_ids[0],
+            proposal.end_epoch + 1000,
+            Signature::new_bls(b"extension".to_vec()),
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn extend_deal_rejects_duration_beyond_bounds() {
+    let mut rt = setup();
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    let deal_id = publish_deals(&mut rt, &[proposal.clone()])[0];
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    expect_provider_control_address(&mut rt, provider, owner, worker);
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "duration out of bounds",
+        extend_deal(
+            &mut rt,
+            deal_id,
+            proposal.start_epoch + 541 * EPOCHS_IN_DAY,
+            Signature::new_bls(vec![]),
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn extend_deal_rejects_collateral_above_maximum() {
+    let mut rt = setup();
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    // Scaling the collateral with the extended term takes it past the maximum.
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.provider_collateral = TOTAL_FILECOIN.clone();
+    let deal_id = publish_deals(&mut rt, &[proposal.clone()])[0];
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    expect_provider_control_address(&mut rt, provider, owner, worker);
+    expect_query_network_info(&mut rt);
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "would exceed the maximum",
+        extend_deal(
+            &mut rt,
+            deal_id,
+            proposal.end_epoch + 1000,
+            Signature::new_bls(b"extension".to_vec()),
+        ),
+    );
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert_eq!(proposal.end_epoch, proposals.get(deal_id).unwrap().unwrap().end_epoch);
+}
+
+fn terminate_pending_deal(
+    rt: &mut MockRuntime,
+    caller: Address,
+    deal_id: DealID,
+) -> Result<RawBytes, ActorError> {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+    rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+    let params = TerminatePendingDealParams { deal_id };
+    rt.call::<MarketActor>(
+        Method::TerminatePendingDeal as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+}
+
+#[test]
+fn terminate_pending_deal_burns_client_collateral_penalty() {
+    let mut rt = setup();
+    rt.policy.client_termination_penalty_num = 1;
+    rt.policy.client_termination_penalty_denom = 4;
+    let client = Address::new_id(CLIENT_ID);
+    let provider = Address::new_id(PROVIDER_ID);
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.client_collateral = TokenAmount::from(1000);
+    proposal.provider_collateral = TokenAmount::from(2000);
+    let deal_id = publish_deals(&mut rt, &[proposal.clone()])[0];
+    let client_escrow = get_escrow_balance(&rt, &client).unwrap();
+
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(250),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    terminate_pending_deal(&mut rt, client, deal_id).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert!(proposals.get(deal_id).unwrap().is_none());
+    let pending = Set::from_root(&rt.store, &st.pending_proposals).unwrap();
+    assert!(!pending.has(&proposal.cid().unwrap().to_bytes()).unwrap());
+    assert!(get_deals_by_epoch(&mut rt, EPOCHS_IN_DAY).is_empty());
+
+    // Only the penalty leaves the client's escrow, and nothing stays locked for either party.
+    let locked = BalanceTable::from_root(&rt.store, &st.locked_table).unwrap();
+    assert_eq!(TokenAmount::from(0), locked.get(&client).unwrap());
+    assert_eq!(TokenAmount::from(0), locked.get(&provider).unwrap());
+    assert_eq!(client_escrow - 250, get_escrow_balance(&rt, &client).unwrap());
+    assert_eq!(proposal.provider_collateral, get_escrow_balance(&rt, &provider).unwrap());
+    assert_eq!(TokenAmount::from(0), st.total_locked());
+}
+
+#[test]
+fn terminate_pending_deal_burns_all_client_collateral_by_default() {
+    let mut rt = setup();
+    let client = Address::new_id(CLIENT_ID);
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.client_collateral = TokenAmount::from(1000);
+    let deal_id = publish_deals(&mut rt, &[proposal.clone()])[0];
+    let client_escrow = get_escrow_balance(&rt, &client).unwrap();
+
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        proposal.client_collateral.clone(),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    terminate_pending_deal(&mut rt, client, deal_id).unwrap();
+    rt.verify();
+
+    assert_eq!(
+        client_escrow - &proposal.client_collateral,
+        get_escrow_balance(&rt, &client).unwrap()
+    );
+}
+
+#[test]
+fn terminate_pending_deal_rejects_caller_other_than_client() {
+    let mut rt = setup();
+
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    let deal_id = publish_deals(&mut rt, &[proposal])[0];
+
+    expect_abort_contains_message(
+        ExitCode::ErrForbidden,
+        "is not the client",
+        terminate_pending_deal(&mut rt, Address::new_id(WORKER_ID), deal_id),
+    );
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert!(proposals.get(deal_id).unwrap().is_some());
+}
+
+#[test]
+fn terminate_pending_deal_rejects_activated_deal() {
+    let mut rt = setup();
+
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    let deal_ids = publish_deals(&mut rt, &[proposal.clone()]);
+    activate_deals(&mut rt, proposal.end_epoch, &deal_ids);
+
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "has been activated",
+        terminate_pending_deal(&mut rt, Address::new_id(CLIENT_ID), deal_ids[0]),
+    );
+    rt.verify();
+}
+
+#[test]
+fn terminate_pending_deal_rejects_deal_at_start_epoch() {
+    let mut rt = setup();
+
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    let deal_id = publish_deals(&mut rt, &[proposal.clone()])[0];
+
+    // The provider can no longer activate the deal, so its timeout slash must stand.
+    rt.epoch = proposal.start_epoch;
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "reached its start epoch",
+        terminate_pending_deal(&mut rt, Address::new_id(CLIENT_ID), deal_id),
+    );
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert!(proposals.get(deal_id).unwrap().is_some());
+}
+
+#[test]
+fn terminate_pending_deal_rejects_zero_penalty_denominator() {
+    let mut rt = setup();
+    rt.policy.client_termination_penalty_denom = 0;
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.client_collateral = TokenAmount::from(1000);
+    let deal_id = publish_deals(&mut rt, &[proposal])[0];
+
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalState,
+        "denominator must be positive",
+        terminate_pending_deal(&mut rt, Address::new_id(CLIENT_ID), deal_id),
+    );
+    rt.verify();
+}
+
+#[test]
+fn timed_out_deal_slashes_capped_provider_collateral() {
+    let mut rt = setup();
+    rt.policy.provider_collateral_slash_max_num = 1;
+    rt.policy.provider_collateral_slash_max_denom = 4;
+    let provider = Address::new_id(PROVIDER_ID);
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.provider_collateral = TokenAmount::from(1000);
+    publish_deals(&mut rt, &[proposal.clone()]);
+
+    // The deal is never activated, so it times out when cron first processes it.
+    rt.epoch = EPOCHS_IN_DAY;
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(250),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    cron_tick(&mut rt);
+
+    // The collateral above the cap is unlocked and stays in the provider's escrow.
+    let st: State = rt.get_state().unwrap();
+    let locked = BalanceTable::from_root(&rt.store, &st.locked_table).unwrap();
+    assert_eq!(TokenAmount::from(0), locked.get(&provider).unwrap());
+    assert_eq!(TokenAmount::from(0), st.total_provider_locked_colateral);
+    assert_eq!(TokenAmount::from(750), get_escrow_balance(&rt, &provider).unwrap());
+}
+
+#[test]
+fn terminated_deal_slashes_capped_provider_collateral() {
+    let mut rt = setup();
+    rt.policy.provider_collateral_slash_max_num = 1;
+    rt.policy.provider_collateral_slash_max_denom = 4;
+    let provider = Address::new_id(PROVIDER_ID);
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.provider_collateral = TokenAmount::from(1000);
+    let deal_ids = publish_deals(&mut rt, &[proposal.clone()]);
+    activate_deals(&mut rt, proposal.end_epoch, &deal_ids);
+
+    let process_epoch = 2 * EPOCHS_IN_DAY;
+    rt.epoch = process_epoch;
+    rt.set_caller(*MINER_ACTOR_CODE_ID, provider);
+    rt.expect_validate_caller_type(vec![*MINER_ACTOR_CODE_ID]);
+    let params = OnMinerSectorsTerminateParams { epoch: process_epoch, deal_ids };
+    rt.call::<MarketActor>(
+        Method::OnMinerSectorsTerminate as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(250),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    cron_tick(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    let locked = BalanceTable::from_root(&rt.store, &st.locked_table).unwrap();
+    assert_eq!(TokenAmount::from(0), locked.get(&provider).unwrap());
+    assert_eq!(TokenAmount::from(0), st.total_provider_locked_colateral);
+    let payment = &proposal.storage_price_per_epoch * (process_epoch - proposal.start_epoch);
+    assert_eq!(payment + 750, get_escrow_balance(&rt, &provider).unwrap());
+}
+
+#[test]
+fn cron_tick_rejects_zero_provider_slash_denominator() {
+    let mut rt = setup();
+    rt.policy.provider_collateral_slash_max_denom = 0;
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.provider_collateral = TokenAmount::from(1000);
+    publish_deals(&mut rt, &[proposal]);
+
+    rt.epoch = EPOCHS_IN_DAY;
+    rt.set_caller(*CRON_ACTOR_CODE_ID, *CRON_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*CRON_ACTOR_ADDR]);
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalState,
+        "denominator must be positive",
+        rt.call::<MarketActor>(Method::CronTick as u64, &RawBytes::default()),
+    );
+    rt.verify();
+}
+
+fn add_deal_collateral(
+    rt: &mut MockRuntime,
+    deal_id: DealID,
+    amount: TokenAmount,
+) -> Result<RawBytes, ActorError> {
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    expect_provider_control_address(rt, provider, owner, worker);
+    expect_query_network_info(rt);
+    let params = AddDealCollateralParams { deal_id, amount };
+    rt.call::<MarketActor>(Method::AddDealCollateral as u64, &RawBytes::serialize(params).unwrap())
+}
+
+#[test]
+fn add_deal_collateral_is_slashed_with_the_proposal_collateral() {
+    let mut rt = setup();
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let mut proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    proposal.provider_collateral = TokenAmount::from(1000);
+    let deal_ids = publish_deals(&mut rt, &[proposal.clone()]);
+    activate_deals(&mut rt, proposal.end_epoch, &deal_ids);
+
+    add_provider_funds(&mut rt, provider, owner, worker, TokenAmount::from(500));
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    add_deal_collateral(&mut rt, deal_ids[0], TokenAmount::from(500)).unwrap();
+    rt.verify();
+
+    // The proposal the client signed is untouched and the addition is kept in the deal state.
+    let st: State = rt.get_state().unwrap();
+    let proposals = DealArray::load(&st.proposals, &rt.store).unwrap();
+    assert_eq!(&proposal, proposals.get(deal_ids[0]).unwrap().unwrap());
+    let states = DealMetaArray::load(&st.states, &rt.store).unwrap();
+    let state = states.get(deal_ids[0]).unwrap().unwrap();
+    assert_eq!(TokenAmount::from(500), state.provider_collateral_added);
+    let locked = BalanceTable::from_root(&rt.store, &st.locked_table).unwrap();
+    assert_eq!(TokenAmount::from(1500), locked.get(&provider).unwrap());
+    assert_eq!(TokenAmount::from(1500), st.total_provider_locked_colateral);
+
+    // Terminating the deal slashes the added collateral along with the proposal's.
+    let process_epoch = 2 * EPOCHS_IN_DAY;
+    rt.epoch = process_epoch;
+    rt.set_caller(*MINER_ACTOR_CODE_ID, provider);
+    rt.expect_validate_caller_type(vec![*MINER_ACTOR_CODE_ID]);
+    let params = OnMinerSectorsTerminateParams { epoch: process_epoch, deal_ids: deal_ids.clone() };
+    rt.call::<MarketActor>(
+        Method::OnMinerSectorsTerminate as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(1500),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    cron_tick(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    let locked = BalanceTable::from_root(&rt.store, &st.locked_table).unwrap();
+    assert_eq!(TokenAmount::from(0), locked.get(&provider).unwrap());
+    assert_eq!(TokenAmount::from(0), st.total_provider_locked_colateral);
+    let payment = &proposal.storage_price_per_epoch * (process_epoch - proposal.start_epoch);
+    assert_eq!(payment, get_escrow_balance(&rt, &provider).unwrap());
+}
+
+#[test]
+fn add_deal_collateral_rejects_pending_deal() {
+    let mut rt = setup();
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    let deal_id = publish_deals(&mut rt, &[proposal])[0];
+    add_provider_funds(&mut rt, provider, owner, worker, TokenAmount::from(500));
+
+    // Collateral is only added to activated deals, which have a deal state to record it in.
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "has not been activated",
+        add_deal_collateral(&mut rt, deal_id, TokenAmount::from(500)),
+    );
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(TokenAmount::from(0), st.total_provider_locked_colateral);
+}
+
+#[test]
+fn add_deal_collateral_rejects_collateral_above_maximum() {
+    let mut rt = setup();
+    let worker = Address::new_id(WORKER_ID);
+
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    let deal_ids = publish_deals(&mut rt, &[proposal.clone()]);
+    activate_deals(&mut rt, proposal.end_epoch, &deal_ids);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "would exceed the maximum",
+        add_deal_collateral(&mut rt, deal_ids[0], TOTAL_FILECOIN.clone() + 1),
+    );
+    rt.verify();
+}
+
+#[test]
+fn add_deal_collateral_rejects_caller_not_controlling_provider() {
+    let mut rt = setup();
+
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    let deal_ids = publish_deals(&mut rt, &[proposal.clone()]);
+    activate_deals(&mut rt, proposal.end_epoch, &deal_ids);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT_ID));
+    expect_provider_control_address(
+        &mut rt,
+        Address::new_id(PROVIDER_ID),
+        Address::new_id(OWNER_ID),
+        Address::new_id(WORKER_ID),
+    );
+    let params = AddDealCollateralParams { deal_id: deal_ids[0], amount: TokenAmount::from(500) };
+    expect_abort(
+        ExitCode::SysErrForbidden,
+        rt.call::<MarketActor>(
+            Method::AddDealCollateral as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn get_deal_collateral_bounds_scales_with_network_power() {
+    let mut rt = setup();
+    rt.circulating_supply = TokenAmount::from(10u64.pow(24));
+    let baseline_power = StoragePower::from(1u64 << 40);
+    let network_raw_power = StoragePower::from(1u64 << 50);
+
+    rt.expect_validate_caller_any();
+    rt.expect_send(
+        *REWARD_ACTOR_ADDR,
+        ext::reward::THIS_EPOCH_REWARD_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ThisEpochRewardReturn {
+            this_epoch_reward_smoothed: FilterEstimate::new(BigInt::from(0), BigInt::from(0)),
+            this_epoch_baseline_power: baseline_power,
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::CURRENT_TOTAL_POWER_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::power::CurrentTotalPowerReturnParams {
+            raw_byte_power: network_raw_power.clone(),
+            quality_adj_power: network_raw_power.clone(),
+            pledge_collateral: TokenAmount::from(0),
+            quality_adj_power_smoothed: FilterEstimate::new(BigInt::from(0), BigInt::from(0)),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    let params =
+        GetDealCollateralBoundsParams { piece_size: PaddedPieceSize(2048), duration: 1000 };
+    let ret: GetDealCollateralBoundsReturn = rt
+        .call::<MarketActor>(
+            Method::GetDealCollateralBounds as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    // 1% of circulating supply, scaled by the piece's share of the larger of network and
+    // baseline power.
+    let expected_min = (&rt.circulating_supply * 2048) / (network_raw_power * 100);
+    assert_eq!(expected_min, ret.min_provider_collateral);
+    assert!(ret.max_provider_collateral > ret.min_provider_collateral);
+    assert_eq!(TokenAmount::from(0), ret.min_client_collateral);
+    assert!(ret.max_client_collateral > ret.min_client_collateral);
+}
+
+#[test]
+fn validate_deal_proposal_reports_rejection_reason() {
+    let mut rt = setup();
+    let proposal = generate_deal_proposal(100, 100 + 200 * EPOCHS_IN_DAY);
+    let deal = ClientDealProposal {
+        proposal: proposal.clone(),
+        client_signature: Signature::new_bls(b"client".to_vec()),
+    };
+
+    let ret = validate_deal_proposal(&mut rt, deal.clone(), Ok(()));
+    assert!(ret.valid);
+    assert_eq!(ExitCode::Ok, ret.exit_code);
+    assert!(ret.reason.is_empty());
+
+    let ret = validate_deal_proposal(&mut rt, deal.clone(), Err(anyhow::anyhow!("bad sig")));
+    assert!(!ret.valid);
+    assert_eq!(ExitCode::ErrIllegalArgument, ret.exit_code);
+    assert!(ret.reason.contains("signature proposal invalid"));
+
+    let mut expired = deal;
+    expired.proposal.start_epoch = rt.epoch - 1;
+    let ret = validate_deal_proposal(&mut rt, expired, Ok(()));
+    assert!(!ret.valid);
+    assert_eq!(ExitCode::ErrIllegalArgument, ret.exit_code);
+    assert!(ret.reason.contains("start epoch has already elapsed"));
+
+    // Nothing was published.
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(0, st.next_id);
+}
+
+#[test]
+fn get_deal_collateral_bounds_rejects_invalid_piece_size() {
+    let mut rt = setup();
+
+    rt.expect_validate_caller_any();
+    let params =
+        GetDealCollateralBoundsParams { piece_size: PaddedPieceSize(1000), duration: 1000 };
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<MarketActor>(
+            Method::GetDealCollateralBounds as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn compute_data_commitment_computes_repeated_inputs_once() {
+    let mut rt = setup();
+
+    let commd = Cid::default();
+    let sector_type = RegisteredSealProof::StackedDRG32GiBV1P1;
+    rt.set_caller(*MINER_ACTOR_CODE_ID, Address::new_id(PROVIDER_ID));
+    rt.expect_validate_caller_type(vec![*MINER_ACTOR_CODE_ID]);
+    // Only a single syscall is expected for the three identical inputs.
+    rt.expect_compute_unsealed_sector_cid(ExpectComputeUnsealedSectorCid {
+        reg: sector_type,
+        pieces: vec![],
+        cid: commd,
+        exit_code: ExitCode::Ok,
+    });
+    let params = ComputeDataCommitmentParams {
+        inputs: (0..3).map(|_| SectorDataSpec { deal_ids: vec![], sector_type }).collect(),
+    };
+    let ret: ComputeDataCommitmentReturn = rt
+        .call::<MarketActor>(
+            Method::ComputeDataCommitment as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(vec![commd; 3], ret.commds);
+}
+
+#[test]
+fn check_balance_coverage_against_escrow() {
+    let mut rt = setup();
+    let client = Address::new_id(CLIENT_ID);
+    add_participant_funds(&mut rt, client, TokenAmount::from(100));
+
+    assert!(check_balance_coverage(&mut rt, client, TokenAmount::from(100)));
+    assert!(!check_balance_coverage(&mut rt, client, TokenAmount::from(101)));
+    // Addresses without escrow can only cover a zero lockup.
+    assert!(check_balance_coverage(&mut rt, Address::new_id(OWNER_ID), TokenAmount::from(0)));
+    assert!(!check_balance_coverage(&mut rt, Address::new_id(OWNER_ID), TokenAmount::from(1)));
+}
+
+#[test]
+fn check_clients_coverage_reports_each_entry() {
+    let mut rt = setup();
+    let client = Address::new_id(CLIENT_ID);
+    add_participant_funds(&mut rt, client, TokenAmount::from(100));
+
+    rt.expect_validate_caller_any();
+    let params = CheckClientsCoverageParams {
+        entries: vec![
+            CheckBalanceCoverageParams { address: client, amount: TokenAmount::from(100) },
+            CheckBalanceCoverageParams { address: client, amount: TokenAmount::from(101) },
+            CheckBalanceCoverageParams {
+                address: Address::new_id(OWNER_ID),
+                amount: TokenAmount::from(1),
+            },
+            // An address that does not resolve to an actor has no escrow.
+            CheckBalanceCoverageParams {
+                address: Address::new_actor(b"unknown"),
+                amount: TokenAmount::from(0),
+            },
+        ],
+    };
+    let ret: CheckClientsCoverageReturn = rt
+        .call::<MarketActor>(
+            Method::CheckClientsCoverage as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(vec![true, false, false, false], ret.covered);
+}
+
+#[test]
+fn worker_top_up_credits_miner_escrow_across_owner_changes() {
+    let mut rt = setup();
+    let provider = Address::new_id(PROVIDER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let ret = worker_add_provider_balance(
+        &mut rt,
+        provider,
+        Address::new_id(OWNER_ID),
+        worker,
+        TokenAmount::from(10),
+    );
+    assert_eq!(provider, ret.address);
+    assert_eq!(TokenAmount::from(10), ret.balance);
+    assert_eq!(TokenAmount::from(0), ret.locked);
+
+    // The deposit still lands against the miner, not its new owner.
+    let ret = worker_add_provider_balance(
+        &mut rt,
+        provider,
+        Address::new_id(CLIENT_ID),
+        worker,
+        TokenAmount::from(5),
+    );
+    assert_eq!(provider, ret.address);
+    assert_eq!(TokenAmount::from(15), ret.balance);
+    assert_eq!(TokenAmount::from(15), get_escrow_balance(&rt, &provider).unwrap());
+    assert_eq!(TokenAmount::from(0), get_escrow_balance(&rt, &worker).unwrap());
+}
+
+#[test]
+fn add_balance_reports_locked_funds() {
+    let mut rt = setup();
+    let client = Address::new_id(CLIENT_ID);
+
+    add_participant_funds(&mut rt, client, TokenAmount::from(100));
+    let mut st: State = rt.get_state().unwrap();
+    let mut locked = BalanceTable::from_root(&rt.store, &st.locked_table).unwrap();
+    locked.add(&client, &TokenAmount::from(40)).unwrap();
+    st.locked_table = locked.root().unwrap();
+    rt.replace_state(&st);
+
+    rt.set_value(TokenAmount::from(10));
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, client);
+    rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+    let ret: AddBalanceReturn = rt
+        .call::<MarketActor>(Method::AddBalance as u64, &RawBytes::serialize(client).unwrap())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(client, ret.address);
+    assert_eq!(TokenAmount::from(110), ret.balance);
+    assert_eq!(TokenAmount::from(40), ret.locked);
+}
+
+#[test]
+fn add_balance_rejects_zero_value_by_default() {
+    let mut rt = setup();
+    let client = Address::new_id(CLIENT_ID);
+
+    rt.set_value(TokenAmount::from(0));
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, client);
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "zero-value add balance is disabled",
+        rt.call::<MarketActor>(Method::AddBalance as u64, &RawBytes::serialize(client).unwrap()),
+    );
+}
+
+#[test]
+fn add_balance_zero_value_creates_escrow_entry_when_allowed() {
+    let mut rt = setup();
+    rt.policy.allow_zero_value_add_balance = true;
+    let client = Address::new_id(CLIENT_ID);
+    let st: State = rt.get_state().unwrap();
+    let empty_escrow_table = st.escrow_table;
+
+    rt.set_value(TokenAmount::from(0));
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, client);
+    rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+    let ret: AddBalanceReturn = rt
+        .call::<MarketActor>(Method::AddBalance as u64, &RawBytes::serialize(client).unwrap())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(TokenAmount::from(0), ret.balance);
+    // The client now has an entry in the escrow table, albeit an empty one.
+    let st: State = rt.get_state().unwrap();
+    assert_ne!(empty_escrow_table, st.escrow_table);
+}
+
+#[test]
+fn migrate_state_converts_prior_deal_states() {
+    let mut rt = setup();
+
+    let mut st: State = rt.get_state().unwrap();
+    let mut prior_states = Amt::new_with_bit_width(&rt.store, STATES_AMT_BITWIDTH);
+    prior_states
+        .set(3, PriorDealState { sector_start_epoch: 10, last_updated_epoch: 20, slash_epoch: -1 })
+        .unwrap();
+    st.states = prior_states.flush().unwrap();
+    st.next_id = 7;
+
+    // Deal states in the prior layout do not decode as the current deal state.
+    let states = DealMetaArray::load(&st.states, &rt.store).unwrap();
+    assert!(states.get(3).is_err());
+
+    let migrated = migrate_state(&rt.store, st.clone()).unwrap();
+    assert_eq!(7, migrated.next_id);
+    assert_eq!(st.proposals, migrated.proposals);
+    let states = DealMetaArray::load(&migrated.states, &rt.store).unwrap();
+    let expected = DealState {
+        sector_start_epoch: 10,
+        last_updated_epoch: 20,
+        slash_epoch: -1,
+        provider_collateral_added: TokenAmount::from(0),
+    };
+    assert_eq!(Some(&expected), states.get(3).unwrap());
+    assert!(states.get(4).unwrap().is_none());
+
+    // The migrated state is usable by the actor.
+    rt.replace_state(&migrated);
+    assert_eq!(7, get_next_deal_id(&mut rt));
+}
+
+fn worker_add_provider_balance(
+    rt: &mut MockRuntime,
+    provider: Address,
+    owner: Address,
+    worker: Address,
+    amount: TokenAmount,
+) -> AddBalanceReturn {
+    rt.set_value(amount.clone());
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+    rt.expect_send(
+        provider,
+        ext::miner::CONTROL_ADDRESSES_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0u8),
+        RawBytes::serialize(ext::miner::GetControlAddressesReturnParams {
+            owner,
+            worker,
+            control_addresses: Vec::new(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    let ret: AddBalanceReturn = rt
+        .call::<MarketActor>(Method::AddBalance as u64, &RawBytes::serialize(provider).unwrap())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    rt.add_balance(amount);
+    ret
+}
+
+fn piece_cid() -> Cid {
+    Cid::new_v1(
+        FIL_COMMITMENT_UNSEALED,
+        Multihash::wrap(SHA2_256_TRUNC254_PADDED, &[0u8; 32]).unwrap(),
+    )
+}
+
+/// Returns a proposal between the test client and provider that passes publish validation,
+/// paying a storage fee and without collateral.
+fn generate_deal_proposal(start_epoch: ChainEpoch, end_epoch: ChainEpoch) -> DealProposal {
+    DealProposal {
+        piece_cid: piece_cid(),
+        piece_size: PaddedPieceSize(2048),
+        verified_deal: false,
+        client: Address::new_id(CLIENT_ID),
+        provider: Address::new_id(PROVIDER_ID),
+        label: "label".to_string(),
+        start_epoch,
+        end_epoch,
+        storage_price_per_epoch: TokenAmount::from(10),
+        provider_collateral: TokenAmount::from(0),
+        client_collateral: TokenAmount::from(0),
+    }
+}
+
+/// Deposits the funds the proposals lock up, then publishes them in one batch from the
+/// provider's worker and returns their deal IDs. Verified proposals are granted datacap.
+fn publish_deals(rt: &mut MockRuntime, proposals: &[DealProposal]) -> Vec<DealID> {
+    let client = Address::new_id(CLIENT_ID);
+    let provider = Address::new_id(PROVIDER_ID);
+    let owner = Address::new_id(OWNER_ID);
+    let worker = Address::new_id(WORKER_ID);
+
+    let client_funds =
+        proposals.iter().fold(TokenAmount::from(0), |sum, p| sum + p.client_balance_requirement());
+    if client_funds > TokenAmount::from(0) {
+        add_participant_funds(rt, client, client_funds);
+    }
+    let provider_funds = proposals
+        .iter()
+        .fold(TokenAmount::from(0), |sum, p| sum + p.provider_balance_requirement());
+    if provider_funds > TokenAmount::from(0) {
+        add_provider_funds(rt, provider, owner, worker, provider_funds);
+    }
+
+    let deals: Vec<ClientDealProposal> = proposals
+        .iter()
+        .map(|proposal| ClientDealProposal {
+            proposal: proposal.clone(),
+            client_signature: Signature::new_bls(b"client".to_vec()),
+        })
+        .collect();
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, worker);
+    rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+    rt.expect_send(
+        provider,
+        ext::miner::CONTROL_ADDRESSES_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0u8),
+        RawBytes::serialize(ext::miner::GetControlAddressesReturnParams {
+            owner,
+            worker,
+            control_addresses: Vec::new(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    expect_query_network_info(rt);
+    for deal in &deals {
+        expect_client_signature(rt, deal);
+        if deal.proposal.verified_deal {
+            rt.expect_send(
+                *VERIFIED_REGISTRY_ACTOR_ADDR,
+                ext::verifreg::USE_BYTES_METHOD as u64,
+                RawBytes::serialize(ext::verifreg::UseBytesParams {
+                    address: client,
+                    deal_size: BigInt::from(deal.proposal.piece_size.0),
+                })
+                .unwrap(),
+                TokenAmount::from(0),
+                RawBytes::default(),
+                ExitCode::Ok,
+            );
+        }
+    }
+
+    let params = PublishStorageDealsParams { deals };
+    let ret: PublishStorageDealsReturn = rt
+        .call::<MarketActor>(
+            Method::PublishStorageDeals as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(proposals.len(), ret.ids.len());
+    ret.ids
+}
+
+/// Activates published deals in a sector of the provider expiring at sector_expiry.
+fn activate_deals(rt: &mut MockRuntime, sector_expiry: ChainEpoch, deal_ids: &[DealID]) {
+    rt.set_caller(*MINER_ACTOR_CODE_ID, Address::new_id(PROVIDER_ID));
+    rt.expect_validate_caller_type(vec![*MINER_ACTOR_CODE_ID]);
+    let params = ActivateDealsParams { deal_ids: deal_ids.to_vec(), sector_expiry };
+    rt.call::<MarketActor>(Method::ActivateDeals as u64, &RawBytes::serialize(params).unwrap())
+        .unwrap();
+    rt.verify();
+}
+
+fn expect_query_network_info(rt: &mut MockRuntime) {
+    rt.expect_send(
+        *REWARD_ACTOR_ADDR,
+        ext::reward::THIS_EPOCH_REWARD_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ThisEpochRewardReturn {
+            this_epoch_reward_smoothed: FilterEstimate::new(BigInt::from(0), BigInt::from(0)),
+            this_epoch_baseline_power: StoragePower::from(1u64 << 40),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::CURRENT_TOTAL_POWER_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::power::CurrentTotalPowerReturnParams {
+            raw_byte_power: StoragePower::from(1u64 << 40),
+            quality_adj_power: StoragePower::from(1u64 << 40),
+            pledge_collateral: TokenAmount::from(0),
+            quality_adj_power_smoothed: FilterEstimate::new(BigInt::from(0), BigInt::from(0)),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+}
+
+fn expect_client_signature(rt: &mut MockRuntime, deal: &ClientDealProposal) {
+    rt.expect_verify_signature(ExpectedVerifySig {
+        sig: deal.client_signature.clone(),
+        signer: deal.proposal.client,
+        plaintext: to_vec(&deal.proposal).unwrap(),
+        result: Ok(()),
+    });
+}
+
+fn validate_deal_proposal(
+    rt: &mut MockRuntime,
+    deal: ClientDealProposal,
+    sig_result: anyhow::Result<()>,
+) -> ValidateDealProposalReturn {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT_ID));
+    rt.expect_validate_caller_any();
+    expect_query_network_info(rt);
+    rt.expect_verify_signature(ExpectedVerifySig {
+        sig: deal.client_signature.clone(),
+        signer: deal.proposal.client,
+        plaintext: to_vec(&deal.proposal).unwrap(),
+        result: sig_result,
+    });
+    let ret: ValidateDealProposalReturn = rt
+        .call::<MarketActor>(
+            Method::ValidateDealProposal as u64,
+            &RawBytes::serialize(deal).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret
+}
+
+fn get_next_deal_id(rt: &mut MockRuntime) -> DealID {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT_ID));
+    rt.expect_validate_caller_any();
+    let ret: GetNextDealIdReturn = rt
+        .call::<MarketActor>(Method::GetNextDealId as u64, &RawBytes::default())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.next_id
+}
+
+fn get_deals_by_epoch(rt: &mut MockRuntime, epoch: ChainEpoch) -> Vec<DealID> {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(CLIENT_ID));
+    rt.expect_validate_caller_any();
+    let params = GetDealsByEpochParams { epoch };
+    let ret: GetDealsByEpochReturn = rt
+        .call::<MarketActor>(Method::GetDealsByEpoch as u64, &RawBytes::serialize(params).unwrap())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.deal_ids
+}
+
+fn check_balance_coverage(rt: &mut MockRuntime, address: Address, amount: TokenAmount) -> bool {
+    rt.expect_validate_caller_any();
+    let params = CheckBalanceCoverageParams { address, amount };
+    let ret: CheckBalanceCoverageReturn = rt
+        .call::<MarketActor>(
+            Method::CheckBalanceCoverage as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.covered
+}
+
 fn expect_provider_control_address(
     rt: &mut MockRuntime,
     provider: Address,
@@ -411,6 +2124,16 @@ fn add_participant_funds(rt: &mut MockRuntime, addr: Address, amount: TokenAmoun
     rt.add_balance(amount);
 }
 
+fn cron_tick(rt: &mut MockRuntime) {
+    rt.set_caller(*CRON_ACTOR_CODE_ID, *CRON_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*CRON_ACTOR_ADDR]);
+    assert_eq!(
+        RawBytes::default(),
+        rt.call::<MarketActor>(Method::CronTick as u64, &RawBytes::default()).unwrap()
+    );
+    rt.verify();
+}
+
 fn construct_and_verify(rt: &mut MockRuntime) {
     rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
     assert_eq!(
@@ -54,6 +54,36 @@ impl<'db, BS: Blockstore> BitFieldQueue<'db, BS> {
         self.add_to_queue(epoch, &values.into_iter().collect())
     }
 
+    /// Removes values from the queue entry for an epoch, deleting the entry if it becomes empty.
+    pub fn remove_from_queue(
+        &mut self,
+        raw_epoch: ChainEpoch,
+        values: &BitField,
+    ) -> anyhow::Result<()> {
+        let epoch: u64 = self.quant.quantize_up(raw_epoch).try_into()?;
+
+        let remaining = match self
+            .amt
+            .get(epoch)
+            .map_err(|e| e.downcast_wrap(format!("failed to lookup queue epoch {}", epoch)))?
+        {
+            Some(bitfield) => bitfield - values,
+            None => return Ok(()),
+        };
+
+        if remaining.is_empty() {
+            self.amt
+                .delete(epoch)
+                .map_err(|e| e.downcast_wrap(format!("failed to delete queue epoch {}", epoch)))?;
+        } else {
+            self.amt
+                .set(epoch, remaining)
+                .map_err(|e| e.downcast_wrap(format!("failed to set queue epoch {}", epoch)))?;
+        }
+
+        Ok(())
+    }
+
     /// Cut cuts the elements from the bits in the given bitfield out of the queue,
     /// shifting other bits down and removing any newly empty entries.
     ///
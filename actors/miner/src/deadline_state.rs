@@ -220,6 +220,20 @@ impl Deadline {
         Ok(Array::load(&self.partitions, store)?)
     }
 
+    /// Returns the entries of the partition expiration queue, in epoch order.
+    pub fn expiration_schedule<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> anyhow::Result<Vec<(ChainEpoch, BitField)>> {
+        let queue: Array<BitField, BS> = Array::load(&self.expirations_epochs, store)?;
+        let mut schedule = Vec::new();
+        queue.for_each(|epoch, partitions| {
+            schedule.push((epoch as ChainEpoch, partitions.clone()));
+            Ok(())
+        })?;
+        Ok(schedule)
+    }
+
     pub fn optimistic_proofs_amt<'db, BS: Blockstore>(
         &self,
         store: &'db BS,
@@ -466,6 +480,29 @@ impl Deadline {
         Ok(total_power)
     }
 
+    /// Returns the number of sectors across this deadline's partitions whose early termination
+    /// is yet to be processed.
+    pub fn early_terminations_count<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<u64> {
+        let partitions = self.partitions_amt(store)?;
+
+        let mut count = 0;
+        for partition_idx in self.early_terminations.iter() {
+            let partition = match partitions.get(partition_idx).map_err(|e| {
+                e.downcast_wrap(format!("failed to load partition {}", partition_idx))
+            })? {
+                Some(partition) => partition,
+                None => continue,
+            };
+            count += partition.early_terminations_count(store).map_err(|e| {
+                e.downcast_wrap(format!(
+                    "failed to count early terminations in partition {}",
+                    partition_idx
+                ))
+            })?;
+        }
+        Ok(count)
+    }
+
     pub fn pop_early_terminations<BS: Blockstore>(
         &mut self,
         store: &BS,
@@ -1294,6 +1331,23 @@ impl Deadline {
         Ok((post.partitions, post.proofs))
     }
 
+    /// Returns the partitions proven by a snapshotted PoSt, without removing it from the
+    /// snapshot.
+    pub fn post_proof_partitions<BS: Blockstore>(
+        &self,
+        store: &BS,
+        idx: u64,
+    ) -> anyhow::Result<BitField> {
+        let proof_arr = self
+            .optimistic_proofs_snapshot_amt(store)
+            .map_err(|e| e.downcast_wrap("failed to load post proofs snapshot amt"))?;
+        let post = proof_arr
+            .get(idx)
+            .map_err(|e| e.downcast_wrap(format!("failed to retrieve proof {}", idx)))?
+            .ok_or_else(|| actor_error!(ErrIllegalArgument, "proof {} not found", idx))?;
+        Ok(post.partitions.clone())
+    }
+
     /// RescheduleSectorExpirations reschedules the expirations of the given sectors
     /// to the target epoch, skipping any sectors it can't find.
     ///
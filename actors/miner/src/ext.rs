@@ -6,6 +6,7 @@ use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::encoding::tuple::*;
 use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
 use fvm_shared::sector::{RegisteredSealProof, StoragePower};
 use fvm_shared::smooth::FilterEstimate;
 
@@ -21,6 +22,7 @@ pub mod market {
     pub const ACTIVATE_DEALS_METHOD: u64 = 6;
     pub const ON_MINER_SECTORS_TERMINATE_METHOD: u64 = 7;
     pub const COMPUTE_DATA_COMMITMENT_METHOD: u64 = 8;
+    pub const BATCH_ACTIVATE_DEALS_METHOD: u64 = 12;
 
     #[derive(Serialize_tuple, Deserialize_tuple, Default)]
     pub struct SectorWeights {
@@ -29,6 +31,8 @@ pub mod market {
         pub deal_weight: DealWeight,
         #[serde(with = "bigint_ser")]
         pub verified_deal_weight: DealWeight,
+        pub verified_deal_space: u64,
+        pub deal_count: u64,
     }
 
     #[derive(Serialize_tuple, Deserialize_tuple)]
@@ -43,6 +47,16 @@ pub mod market {
         pub sector_expiry: ChainEpoch,
     }
 
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    pub struct BatchActivateDealsParams {
+        pub sectors: Vec<ActivateDealsParams>,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    pub struct BatchActivateDealsReturn {
+        pub codes: Vec<ExitCode>,
+    }
+
     #[derive(Serialize_tuple)]
     pub struct ComputeDataCommitmentParamsRef<'a> {
         pub inputs: &'a [SectorDataSpec],
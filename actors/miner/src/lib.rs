@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use std::collections::btree_map::Entry;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::iter;
 use std::ops::Neg;
 
@@ -44,6 +44,7 @@ use fvm_shared::sector::*;
 use fvm_shared::smooth::FilterEstimate;
 use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR, METHOD_SEND};
 use log::{error, info, warn};
+pub use migration::*;
 pub use monies::*;
 use num_derive::FromPrimitive;
 use num_traits::{FromPrimitive, Signed, Zero};
@@ -68,6 +69,7 @@ mod deadlines;
 mod expiration_queue;
 #[doc(hidden)]
 pub mod ext;
+mod migration;
 mod monies;
 mod partition_state;
 mod policy;
@@ -114,6 +116,48 @@ pub enum Method {
     PreCommitSectorBatch = 25,
     ProveCommitAggregate = 26,
     ProveReplicaUpdates = 27,
+    CheckSectorsProven = 28,
+    GetMinerInfo = 29,
+    NextAllocatableSectorNumber = 30,
+    ChangeNetworkAddresses = 31,
+    GetActiveDeals = 32,
+    DeadlineInfoAt = 33,
+    GetUpgradedSectors = 34,
+    CheckTerminationAllowed = 35,
+    EmergencyChangeWorkerAddress = 36,
+    AbandonPreCommits = 37,
+    GetEarlyTerminations = 38,
+    GetFaultState = 39,
+    ProjectSectorReward = 40,
+    GetDeadlineExpirationSchedule = 41,
+    ReserveSectorNumbers = 42,
+    ReleaseSectorNumbers = 43,
+    EstimateAggregateProveCommitFee = 44,
+    EstimateAggregatePreCommitFee = 45,
+    ConsensusFaultStatus = 46,
+    GetSectorsExpiringInRange = 47,
+    ChangeOwnerAddressWithExpiry = 48,
+    EstimateInitialPledge = 49,
+    GetDisputeInfo = 50,
+    CompactVesting = 51,
+    GetPledgeSummary = 52,
+    CanDeclareRecovery = 54,
+    GetSectorCounts = 55,
+    GetPendingChanges = 56,
+    ProveReplicaUpdatesBatch = 57,
+    ReclaimExpiredPreCommitNumbers = 58,
+    GetProvingPeriodInfo = 59,
+    WithdrawDisputeReward = 60,
+    GetDebtStatus = 61,
+    TerminateWorstSectors = 62,
+    GetDeadlineLayout = 63,
+    GetRecentRewards = 64,
+    ComputeFaultExpirations = 65,
+    GetSectorInfos = 66,
+    UpcomingDeadlines = 67,
+    GetPreCommitInfo = 68,
+    GetSectorHealth = 69,
+    SubmitWindowedPoStWithRecoveries = 70,
 }
 
 /// Miner Actor
@@ -145,15 +189,34 @@ impl Actor {
 
         let policy = rt.policy();
         let current_epoch = rt.curr_epoch();
-        let blake2b = |b: &[u8]| rt.hash_blake2b(b);
-        let offset =
-            assign_proving_period_offset(policy, rt.message().receiver(), current_epoch, blake2b)
-                .map_err(|e| {
-                e.downcast_default(
-                    ExitCode::ErrSerialization,
-                    "failed to assign proving period offset",
+        let offset = match fixed_proving_period_offset(policy) {
+            Some(offset) => {
+                if offset < 0 || offset >= policy.wpost_proving_period {
+                    return Err(actor_error!(
+                        ErrIllegalState,
+                        "fixed proving period offset {} out of range [0, {})",
+                        offset,
+                        policy.wpost_proving_period
+                    ));
+                }
+                offset
+            }
+            None => {
+                let blake2b = |b: &[u8]| rt.hash_blake2b(b);
+                assign_proving_period_offset(
+                    policy,
+                    rt.message().receiver(),
+                    current_epoch,
+                    blake2b,
                 )
-            })?;
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrSerialization,
+                        "failed to assign proving period offset",
+                    )
+                })?
+            }
+        };
 
         let period_start = current_proving_period_start(policy, current_epoch, offset);
         if period_start > current_epoch {
@@ -210,6 +273,44 @@ impl Actor {
         })
     }
 
+    /// Returns the static information about this miner.
+    fn get_miner_info<BS, RT>(rt: &mut RT) -> Result<GetMinerInfoReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let state: State = rt.state()?;
+        let info = get_miner_info(rt.store(), &state)?;
+        Ok(GetMinerInfoReturn {
+            owner: info.owner,
+            worker: info.worker,
+            control_addresses: info.control_addresses,
+            peer_id: info.peer_id,
+            multi_address: info.multi_address,
+            window_post_proof_type: info.window_post_proof_type,
+            sector_size: info.sector_size,
+            window_post_partition_sectors: info.window_post_partition_sectors,
+            consensus_fault_elapsed: info.consensus_fault_elapsed,
+        })
+    }
+
+    /// Returns the worker key change and owner change awaiting effect, if any.
+    fn get_pending_changes<BS, RT>(rt: &mut RT) -> Result<GetPendingChangesReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let state: State = rt.state()?;
+        let info = get_miner_info(rt.store(), &state)?;
+        Ok(GetPendingChangesReturn {
+            pending_worker_key: info.pending_worker_key,
+            pending_owner_address: info.pending_owner_address,
+            pending_owner_expiration: info.pending_owner_expiration,
+        })
+    }
+
     /// Will ALWAYS overwrite the existing control addresses with the control addresses passed in the params.
     /// If an empty addresses vector is passed, the control addresses will be cleared.
     /// A worker change will be scheduled if the worker passed in the params is different from the existing worker.
@@ -257,6 +358,53 @@ impl Actor {
         Ok(())
     }
 
+    /// Requests a worker address change staged by the shorter emergency delay, replacing any
+    /// pending request. Intended for recovery from a compromised worker key, so it is only
+    /// available to the owner of a miner without an active consensus fault.
+    fn emergency_change_worker_address<BS, RT>(
+        rt: &mut RT,
+        params: EmergencyChangeWorkerAddressParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let new_worker = resolve_worker_address(rt, params.new_worker)?;
+
+        rt.transaction(|state: &mut State, rt| {
+            let mut info = get_miner_info(rt.store(), state)?;
+
+            rt.validate_immediate_caller_is(std::iter::once(&info.owner))?;
+
+            let curr_epoch = rt.curr_epoch();
+            if consensus_fault_active(&info, curr_epoch) {
+                return Err(actor_error!(
+                    ErrForbidden,
+                    "emergency worker change not allowed during active consensus fault"
+                ));
+            }
+            if new_worker == info.worker {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "new worker {} is already the worker",
+                    new_worker
+                ));
+            }
+
+            info.pending_worker_key = Some(WorkerKeyChange {
+                new_worker,
+                effective_at: curr_epoch + rt.policy().emergency_worker_key_change_delay,
+            });
+            info.last_emergency_worker_change = curr_epoch;
+
+            state.save_info(rt.store(), &info).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "could not save miner info")
+            })?;
+
+            Ok(())
+        })
+    }
+
     /// Triggers a worker address change if a change has been requested and its effective epoch has arrived.
     fn confirm_update_worker_key<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
     where
@@ -279,24 +427,40 @@ impl Actor {
     /// current owner address, revokes any existing proposal.
     /// If invoked by the previously proposed address, with the same proposal, changes the current owner address to be
     /// that proposed address.
-    fn change_owner_address<BS, RT>(rt: &mut RT, new_address: Address) -> Result<(), ActorError>
+    fn change_owner_address<BS, RT>(
+        rt: &mut RT,
+        params: ChangeOwnerAddressWithExpiryParams,
+    ) -> Result<(), ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
         // * Cannot match go checking for undef address, does go impl allow this to be
         // * deserialized over the wire? If so, a workaround will be needed
+        let new_address = params.new_address;
 
         if !matches!(new_address.protocol(), Protocol::ID) {
             return Err(actor_error!(ErrIllegalArgument, "owner address must be an ID address"));
         }
 
+        let current_epoch = rt.curr_epoch();
         rt.transaction(|state: &mut State, rt| {
             let mut info = get_miner_info(rt.store(), state)?;
 
             if rt.message().caller() == info.owner || info.pending_owner_address.is_none() {
                 rt.validate_immediate_caller_is(std::iter::once(&info.owner))?;
+                if let Some(expiration) = params.expiration {
+                    if expiration < current_epoch {
+                        return Err(actor_error!(
+                            ErrIllegalArgument,
+                            "owner change expiration {} is before current epoch {}",
+                            expiration,
+                            current_epoch
+                        ));
+                    }
+                }
                 info.pending_owner_address = Some(new_address);
+                info.pending_owner_expiration = params.expiration;
             } else {
                 let pending_address = info.pending_owner_address.unwrap();
                 rt.validate_immediate_caller_is(std::iter::once(&pending_address))?;
@@ -308,6 +472,16 @@ impl Actor {
                         new_address
                     ));
                 }
+                if let Some(expiration) = info.pending_owner_expiration {
+                    if current_epoch > expiration {
+                        return Err(actor_error!(
+                            ErrForbidden,
+                            "owner change to {} expired at {}, must be proposed again",
+                            pending_address,
+                            expiration
+                        ));
+                    }
+                }
                 info.owner = pending_address;
             }
 
@@ -315,6 +489,7 @@ impl Actor {
             if let Some(p_addr) = info.pending_owner_address {
                 if p_addr == info.owner {
                     info.pending_owner_address = None;
+                    info.pending_owner_expiration = None;
                 }
             }
 
@@ -379,10 +554,42 @@ impl Actor {
         Ok(())
     }
 
-    /// Invoked by miner's worker address to submit their fallback post
+    /// Replaces both the peer ID and the multiaddrs, saving the miner info once.
+    fn change_network_addresses<BS, RT>(
+        rt: &mut RT,
+        params: ChangeNetworkAddressesParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let policy = rt.policy();
+        check_peer_info(policy, &params.new_id, &params.new_multi_addrs)?;
+
+        rt.transaction(|state: &mut State, rt| {
+            let mut info = get_miner_info(rt.store(), state)?;
+
+            rt.validate_immediate_caller_is(
+                info.control_addresses.iter().chain(&[info.worker, info.owner]),
+            )?;
+
+            info.peer_id = params.new_id;
+            info.multi_address = params.new_multi_addrs;
+            state.save_info(rt.store(), &info).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "could not save miner info")
+            })?;
+
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Invoked by miner's worker address to submit their fallback post, optionally declaring
+    /// faulty sectors in the proven partitions recovered.
     fn submit_windowed_post<BS, RT>(
         rt: &mut RT,
         mut params: SubmitWindowedPoStParams,
+        recovery_declarations: Vec<RecoveryDeclaration>,
     ) -> Result<(), ActorError>
     where
         BS: Blockstore,
@@ -425,23 +632,73 @@ impl Actor {
                     params.chain_commit_rand.0.len()
                 ));
             }
-        }
 
-        let post_result = rt.transaction(|state: &mut State, rt| {
-            let info = get_miner_info(rt.store(), state)?;
+            if recovery_declarations.len() as u64 > policy.delcarations_max {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "too many recovery declarations for a single message: {} > {}",
+                    recovery_declarations.len(),
+                    policy.delcarations_max
+                ));
+            }
+        }
 
-            let max_proof_size = info.window_post_proof_type.proof_size().map_err(|e| {
+        // Recoveries declared alongside the proof must be for sectors in the partitions being proven.
+        let mut recoveries = PartitionSectorMap::default();
+        for recovery in recovery_declarations {
+            if recovery.deadline != params.deadline {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "recovery declared for deadline {}, expected {}",
+                    recovery.deadline,
+                    params.deadline
+                ));
+            }
+            if !params.partitions.iter().any(|p| p.index == recovery.partition) {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "recovery declared for partition {} which is not being proven",
+                    recovery.partition
+                ));
+            }
+            let partition = recovery.partition;
+            recoveries.add(partition, recovery.sectors).map_err(|e| {
                 actor_error!(
-                    ErrIllegalState,
-                    "failed to determine max window post proof size: {}",
+                    ErrIllegalArgument,
+                    "failed to process recoveries for partition {}: {}",
+                    partition,
                     e
                 )
             })?;
+        }
+
+        let (post_result, fee_to_burn) = rt.transaction(|state: &mut State, rt| {
+            let info = get_miner_info(rt.store(), state)?;
+
+            let partition_proof_size = info.window_post_proof_type.proof_size().map_err(|e| {
+                actor_error!(ErrIllegalState, "failed to determine window post proof size: {}", e)
+            })?;
 
             rt.validate_immediate_caller_is(
                 info.control_addresses.iter().chain(&[info.worker, info.owner]),
             )?;
 
+            let mut deadlines =
+                state.load_deadlines(rt.store()).map_err(|e| e.wrap("failed to load deadlines"))?;
+
+            let mut deadline =
+                deadlines.load_deadline(rt.policy(), rt.store(), params.deadline).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to load deadline {}", params.deadline),
+                    )
+                })?;
+
+            // Fail fast on an empty deadline, before checking the proof against its partitions.
+            if deadline.live_sectors == 0 {
+                return Err(actor_error!(ErrIllegalArgument, "deadline has no sectors to prove"));
+            }
+
             // Verify that the miner has passed exactly 1 proof.
             if params.proofs.len() != 1 {
                 return Err(actor_error!(
@@ -461,13 +718,16 @@ impl Actor {
                 ));
             }
 
-            // Make sure the proof size doesn't exceed the max. We could probably check for an exact match, but this is safer.
-            let max_size = max_proof_size * params.partitions.len();
-            if params.proofs[0].proof_bytes.len() > max_size {
+            // Window PoSt proofs are a fixed size per partition, so anything other than an exact
+            // match is malformed and can be rejected before the (expensive) proof verification.
+            let expected_size = partition_proof_size * params.partitions.len();
+            if params.proofs[0].proof_bytes.len() != expected_size {
                 return Err(actor_error!(
                     ErrIllegalArgument,
-                    "expect proof to be smaller than {} bytes",
-                    max_size
+                    "expected proof of {} bytes for {} partitions, got {} bytes",
+                    expected_size,
+                    params.partitions.len(),
+                    params.proofs[0].proof_bytes.len()
                 ));
             }
 
@@ -530,29 +790,56 @@ impl Actor {
             }
 
             // Verify the chain commit randomness
-            let comm_rand = rt.get_randomness_from_tickets(
-                DomainSeparationTag::PoStChainCommit,
-                params.chain_commit_epoch,
-                &[],
-            )?;
-            if comm_rand != params.chain_commit_rand {
-                return Err(actor_error!(ErrIllegalArgument, "post commit randomness mismatched"));
+            if !skip_post_commit_rand_check(rt.policy()) {
+                let comm_rand = rt.get_randomness_from_tickets(
+                    DomainSeparationTag::PoStChainCommit,
+                    params.chain_commit_epoch,
+                    &[],
+                )?;
+                if comm_rand != params.chain_commit_rand {
+                    return Err(actor_error!(
+                        ErrIllegalArgument,
+                        "post commit randomness mismatched"
+                    ));
+                }
             }
 
             let sectors = Sectors::load(rt.store(), &state.sectors).map_err(|e| {
                 e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors")
             })?;
 
-            let mut deadlines =
-                state.load_deadlines(rt.store()).map_err(|e| e.wrap("failed to load deadlines"))?;
+            // Declare recoveries before recording the proof, so that the recovered sectors are proven
+            // (and their power restored) by this same submission rather than at the next deadline.
+            let mut fee_to_burn = TokenAmount::zero();
+            if !recoveries.is_empty() {
+                // Verify unlocked funds cover both InitialPledgeRequirement and FeeDebt
+                // and repay fee debt now.
+                fee_to_burn = repay_debts_or_abort(rt, state)?;
 
-            let mut deadline =
-                deadlines.load_deadline(rt.policy(), rt.store(), params.deadline).map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::ErrIllegalState,
-                        format!("failed to load deadline {}", params.deadline),
+                if consensus_fault_active(&info, current_epoch) {
+                    return Err(actor_error!(
+                        ErrForbidden,
+                        "recovery not allowed during active consensus fault"
+                    ));
+                }
+
+                deadline
+                    .declare_faults_recovered(
+                        rt.store(),
+                        &sectors,
+                        info.sector_size,
+                        &mut recoveries,
                     )
-                })?;
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::ErrIllegalState,
+                            format!(
+                                "failed to declare recoveries for deadline {}",
+                                params.deadline
+                            ),
+                        )
+                    })?;
+            }
 
             // Record proven sectors/partitions, returning updates to power and the final set of sectors
             // proven/skipped.
@@ -619,8 +906,17 @@ impl Actor {
                             "failed to load sectors for post verification",
                         )
                     })?;
-                verify_windowed_post(rt, current_deadline.challenge, &sector_infos, params.proofs)
-                    .map_err(|e| e.wrap("window post failed"))?;
+                let valid = verify_windowed_post(
+                    rt,
+                    current_deadline.challenge,
+                    info.window_post_proof_type,
+                    &sector_infos,
+                    params.proofs,
+                )
+                .map_err(|e| e.wrap("window post failed"))?;
+                if !valid {
+                    return Err(actor_error!(ErrIllegalArgument, "window post failed"));
+                }
             }
 
             let deadline_idx = params.deadline;
@@ -637,7 +933,7 @@ impl Actor {
                 e.downcast_default(ExitCode::ErrIllegalState, "failed to save deadlines")
             })?;
 
-            Ok(post_result)
+            Ok((post_result, fee_to_burn))
         })?;
 
         // Restore power for recovered sectors. Remove power for new faults.
@@ -645,11 +941,10 @@ impl Actor {
         // additional accounting state.
         // https://github.com/filecoin-project/specs-actors/issues/414
         request_update_power(rt, post_result.power_delta)?;
+        burn_funds(rt, fee_to_burn)?;
 
         let state: State = rt.state()?;
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
-        })?;
+        check_balance_invariants(rt, &state)?;
 
         Ok(())
     }
@@ -659,7 +954,7 @@ impl Actor {
     fn prove_commit_aggregate<BS, RT>(
         rt: &mut RT,
         mut params: ProveCommitAggregateParams,
-    ) -> Result<(), ActorError>
+    ) -> Result<ProveCommitAggregateReturn, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
@@ -691,12 +986,13 @@ impl Actor {
                 ));
             }
 
-            if params.aggregate_proof.len() > policy.max_aggregated_proof_size {
+            let max_proof_size = policy.max_aggregated_proof_size(rt.network_version());
+            if params.aggregate_proof.len() > max_proof_size {
                 return Err(actor_error!(
                     ErrIllegalArgument,
                     "sector prove-commit proof of size {} exceeds max size of {}",
                     params.aggregate_proof.len(),
-                    policy.max_aggregated_proof_size
+                    max_proof_size
                 ));
             }
         }
@@ -724,7 +1020,8 @@ impl Actor {
                     )
                 })?;
             let prove_commit_due = precommit.pre_commit_epoch + msd;
-            if rt.curr_epoch() > prove_commit_due {
+            let epochs_late = rt.curr_epoch() - prove_commit_due;
+            if epochs_late > rt.policy().prove_commit_grace {
                 log::warn!(
                     "skipping commitment for sector {}, too late at {}, due {}",
                     precommit.info.sector_number,
@@ -747,6 +1044,27 @@ impl Actor {
                 }
             }
 
+            // The miner's Window PoSt proof type was checked at pre-commit, but may have changed
+            // since. Sectors that can't be proven under the current type must not be confirmed.
+            let sector_wpost_proof =
+                precommit.info.seal_proof.registered_window_post_proof().map_err(|_e| {
+                    actor_error!(
+                        ErrIllegalArgument,
+                        "failed to lookup Window PoSt proof type for sector seal proof {}",
+                        i64::from(precommit.info.seal_proof)
+                    )
+                })?;
+            if sector_wpost_proof != info.window_post_proof_type {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "sector {} Window PoSt proof type {} must match miner Window PoSt proof type {} (seal proof type {})",
+                    precommit.info.sector_number,
+                    i64::from(sector_wpost_proof),
+                    i64::from(info.window_post_proof_type),
+                    i64::from(precommit.info.seal_proof)
+                ));
+            }
+
             compute_data_commitments_inputs.push(ext::market::SectorDataSpec {
                 deal_ids: precommit.info.deal_ids.clone(),
                 sector_type: precommit.info.seal_proof,
@@ -818,7 +1136,7 @@ impl Actor {
 
         let rew = request_current_epoch_block_reward(rt)?;
         let pwr = request_current_total_power(rt)?;
-        confirm_sector_proofs_valid_internal(
+        let grace_fee = confirm_sector_proofs_valid_internal(
             rt,
             precommits_to_confirm.clone(),
             &rew.this_epoch_baseline_power,
@@ -834,25 +1152,49 @@ impl Actor {
         let unlocked_balance = state
             .get_unlocked_balance(&rt.current_balance())
             .map_err(|_e| actor_error!(ErrIllegalState, "failed to determine unlocked balance"))?;
-        if unlocked_balance < aggregate_fee {
+        if unlocked_balance < &aggregate_fee + &grace_fee {
             return Err(actor_error!(
                 ErrInsufficientFunds,
-                "remaining unlocked funds after prove-commit {} are insufficient to pay aggregation fee of {}",
+                "remaining unlocked funds after prove-commit {} are insufficient to pay aggregation fee of {} and grace fee of {}",
                 unlocked_balance,
-                aggregate_fee
+                aggregate_fee,
+                grace_fee
             ));
         }
-        burn_funds(rt, aggregate_fee)?;
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
-        })?;
-        Ok(())
+        burn_funds(rt, &aggregate_fee + &grace_fee)?;
+        check_balance_invariants(rt, &state)?;
+        Ok(ProveCommitAggregateReturn { grace_fee })
     }
 
     fn prove_replica_updates<BS, RT>(
         rt: &mut RT,
         params: ProveReplicaUpdatesParams,
     ) -> Result<BitField, ActorError>
+    where
+        BS: Blockstore + Clone,
+        RT: Runtime<BS>,
+    {
+        Self::prove_replica_updates_internal(rt, params, false)
+    }
+
+    /// Like ProveReplicaUpdates, but activates the deals of all updated sectors in a single
+    /// market call rather than one call per sector.
+    fn prove_replica_updates_batch<BS, RT>(
+        rt: &mut RT,
+        params: ProveReplicaUpdatesParams,
+    ) -> Result<BitField, ActorError>
+    where
+        BS: Blockstore + Clone,
+        RT: Runtime<BS>,
+    {
+        Self::prove_replica_updates_internal(rt, params, true)
+    }
+
+    fn prove_replica_updates_internal<BS, RT>(
+        rt: &mut RT,
+        params: ProveReplicaUpdatesParams,
+        batch_activation: bool,
+    ) -> Result<BitField, ActorError>
     where
         // + Clone because we messed up and need to keep a copy around between transactions.
         BS: Blockstore + Clone,
@@ -889,8 +1231,6 @@ impl Actor {
             sector_info: SectorOnChainInfo,
         }
 
-        let mut sectors_deals = Vec::<ext::market::SectorDeals>::new();
-        let mut sectors_data_spec = Vec::<ext::market::SectorDataSpec>::new();
         let mut validated_updates = Vec::<UpdateAndSectorInfo>::new();
         let mut sector_numbers = BitField::new();
         for update in params.updates.iter() {
@@ -902,10 +1242,13 @@ impl Actor {
 
             sector_numbers.set(update.sector_number);
 
-            if update.replica_proof.len() > 4096 {
+            let max_proof_size =
+                max_replica_update_proof_size(rt.policy(), update.update_proof_type);
+            if update.replica_proof.len() > max_proof_size {
                 info!(
-                    "update proof is too large ({}), skipping sector {}",
+                    "update proof is too large ({} > {}), skipping sector {}",
                     update.replica_proof.len(),
+                    max_proof_size,
                     update.sector_number,
                 );
                 continue;
@@ -984,38 +1327,85 @@ impl Actor {
                 continue;
             }
 
-            let res = rt.send(
-                *STORAGE_MARKET_ACTOR_ADDR,
-                ext::market::ACTIVATE_DEALS_METHOD,
-                RawBytes::serialize(ext::market::ActivateDealsParams {
-                    deal_ids: update.deals.clone(),
-                    sector_expiry: sector_info.expiration,
-                })?,
-                TokenAmount::zero(),
-            );
-
-            if res.is_err() {
-                info!(
-                    "failed to activate deals on sector {0}, skipping sector {0}",
-                    update.sector_number,
+            if !batch_activation {
+                let res = rt.send(
+                    *STORAGE_MARKET_ACTOR_ADDR,
+                    ext::market::ACTIVATE_DEALS_METHOD,
+                    RawBytes::serialize(ext::market::ActivateDealsParams {
+                        deal_ids: update.deals.clone(),
+                        sector_expiry: sector_info.expiration,
+                    })?,
+                    TokenAmount::zero(),
                 );
-                continue;
+
+                if res.is_err() {
+                    info!(
+                        "failed to activate deals on sector {0}, skipping sector {0}",
+                        update.sector_number,
+                    );
+                    continue;
+                }
             }
 
-            let expiration = sector_info.expiration;
-            let seal_proof = sector_info.seal_proof;
             validated_updates.push(UpdateAndSectorInfo { update, sector_info });
+        }
 
-            sectors_deals.push(ext::market::SectorDeals {
-                deal_ids: update.deals.clone(),
-                sector_expiry: expiration,
-            });
-            sectors_data_spec.push(ext::market::SectorDataSpec {
-                sector_type: seal_proof,
-                deal_ids: update.deals.clone(),
-            });
+        if batch_activation && !validated_updates.is_empty() {
+            let sectors = validated_updates
+                .iter()
+                .map(|u| ext::market::ActivateDealsParams {
+                    deal_ids: u.update.deals.clone(),
+                    sector_expiry: u.sector_info.expiration,
+                })
+                .collect();
+            let ret: ext::market::BatchActivateDealsReturn = rt
+                .send(
+                    *STORAGE_MARKET_ACTOR_ADDR,
+                    ext::market::BATCH_ACTIVATE_DEALS_METHOD,
+                    RawBytes::serialize(ext::market::BatchActivateDealsParams { sectors })?,
+                    TokenAmount::zero(),
+                )?
+                .deserialize()?;
+            if ret.codes.len() != validated_updates.len() {
+                return Err(actor_error!(
+                    ErrIllegalState,
+                    "batch deal activation returned {} codes, expected {}",
+                    ret.codes.len(),
+                    validated_updates.len()
+                ));
+            }
+
+            validated_updates = validated_updates
+                .into_iter()
+                .zip(ret.codes)
+                .filter_map(|(u, code)| {
+                    if code != ExitCode::Ok {
+                        info!(
+                            "failed to activate deals on sector {0} ({1:?}), skipping sector {0}",
+                            u.update.sector_number, code,
+                        );
+                        return None;
+                    }
+                    Some(u)
+                })
+                .collect();
         }
 
+        let sectors_deals: Vec<ext::market::SectorDeals> = validated_updates
+            .iter()
+            .map(|u| ext::market::SectorDeals {
+                deal_ids: u.update.deals.clone(),
+                sector_expiry: u.sector_info.expiration,
+            })
+            .collect();
+        let sectors_data_spec: Vec<ext::market::SectorDataSpec> = validated_updates
+            .iter()
+            .map(|u| ext::market::SectorDataSpec {
+                sector_type: u.sector_info.seal_proof,
+                deal_ids: u.update.deals.clone(),
+            })
+            .collect();
+
         if validated_updates.is_empty() {
             return Err(actor_error!(ErrIllegalArgument, "no valid updates"));
         }
@@ -1180,11 +1570,13 @@ impl Actor {
                     if initial_pledge_at_upgrade > with_details.sector_info.initial_pledge {
                         let deficit = &initial_pledge_at_upgrade - &with_details.sector_info.initial_pledge;
 
+                        // Deficits of sectors already updated are locked once all sectors are
+                        // replaced, so they are not yet reflected in the unlocked balance.
                         let unlocked_balance = state
                             .get_unlocked_balance(&rt.current_balance())
                             .map_err(|_|
                                 actor_error!(ErrIllegalState, "failed to calculate unlocked balance")
-                            )?;
+                            )? - &pledge_delta;
                         if unlocked_balance < deficit {
                             return Err(actor_error!(
                                 ErrInsufficientFunds,
@@ -1195,13 +1587,6 @@ impl Actor {
                             ));
                         }
 
-                        state.add_initial_pledge(&deficit).map_err(|_e|
-                            actor_error!(
-                                ErrIllegalState,
-                                "failed to add initial pledge"
-                            )
-                        )?;
-
                         new_sector_info.initial_pledge = initial_pledge_at_upgrade;
                     }
 
@@ -1263,6 +1648,15 @@ impl Actor {
                     })?;
             }
 
+            // The partitions' pledge deltas are exactly the sectors' initial pledge deficits, so the
+            // net across all deadlines is locked, and reported to the power actor, at once.
+            state.add_initial_pledge(&pledge_delta).map_err(|_e|
+                actor_error!(
+                    ErrIllegalState,
+                    "failed to add initial pledge"
+                )
+            )?;
+
             let success_len = bf.len();
             if success_len != validated_updates.len() as u64 {
                 return Err(actor_error!(
@@ -1329,7 +1723,7 @@ impl Actor {
         let epoch_reward = request_current_epoch_block_reward(rt)?;
         let power_total = request_current_total_power(rt)?;
 
-        let (pledge_delta, mut to_burn, power_delta, to_reward) =
+        let (pledge_delta, to_burn, power_delta, to_reward) =
             rt.transaction(|st: &mut State, rt| {
                 let policy = rt.policy();
                 let dl_info = st.deadline_info(policy, current_epoch);
@@ -1413,7 +1807,13 @@ impl Actor {
                     })?;
 
                 // Check proof, we fail if validation succeeds.
-                if verify_windowed_post(rt, target_deadline.challenge, &sector_infos, proofs)? {
+                if verify_windowed_post(
+                    rt,
+                    target_deadline.challenge,
+                    info.window_post_proof_type,
+                    &sector_infos,
+                    proofs,
+                )? {
                     return Err(actor_error!(ErrIllegalArgument, "failed to dispute valid post"));
                 } else {
                     info!("Successfully disputed post- window post was invalid");
@@ -1497,8 +1897,16 @@ impl Actor {
         request_update_power(rt, power_delta)?;
         if !to_reward.is_zero() {
             if let Err(e) = rt.send(reporter, METHOD_SEND, RawBytes::default(), to_reward.clone()) {
-                error!("failed to send reward: {}", e);
-                to_burn += to_reward;
+                // Hold the reward for the reporter to withdraw later rather than burning it.
+                error!("failed to send reward, recording it as owed to {}: {}", reporter, e);
+                rt.transaction(|st: &mut State, rt| {
+                    st.add_owed_dispute_reward(rt.store(), &reporter, &to_reward).map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::ErrIllegalState,
+                            "failed to record owed dispute reward",
+                        )
+                    })
+                })?;
             }
         }
 
@@ -1506,12 +1914,38 @@ impl Actor {
         notify_pledge_changed(rt, &pledge_delta)?;
 
         let st: State = rt.state()?;
-        st.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
-        })?;
+        check_balance_invariants(rt, &st)?;
         Ok(())
     }
 
+    /// Sends the caller the rewards it is owed for disputing Window PoSts, which could not be
+    /// sent at the time of the dispute.
+    fn withdraw_dispute_reward<BS, RT>(
+        rt: &mut RT,
+    ) -> Result<WithdrawDisputeRewardReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_type(CALLER_TYPES_SIGNABLE.iter())?;
+        let reporter = rt.message().caller();
+
+        let amount = rt.transaction(|st: &mut State, rt| {
+            st.take_owed_dispute_reward(rt.store(), &reporter).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to take owed dispute reward")
+            })
+        })?;
+        if amount.is_zero() {
+            return Err(actor_error!(ErrNotFound, "no dispute reward owed to {}", reporter));
+        }
+
+        rt.send(reporter, METHOD_SEND, RawBytes::default(), amount.clone())?;
+
+        let st: State = rt.state()?;
+        check_balance_invariants(rt, &st)?;
+        Ok(WithdrawDisputeRewardReturn { amount_withdrawn: amount })
+    }
+
     /// Pledges to seal and commit a single sector.
     /// See PreCommitSectorBatch for details.
     /// This method may be deprecated and removed in the future
@@ -1731,7 +2165,7 @@ impl Actor {
                 // PreCommitCleanUpDelay > 0 here is critical for the batch verification of proofs. Without it, if a proof arrived exactly on the
 			    // due epoch, ProveCommitSector would accept it, then the expiry event would remove it, and then
 			    // ConfirmSectorProofsValid would fail to find it.
-                let clean_up_bound = curr_epoch + msd + rt.policy().expired_pre_commit_clean_up_delay;
+                let clean_up_bound = curr_epoch + msd + rt.policy().prove_commit_grace + rt.policy().expired_pre_commit_clean_up_delay;
                 clean_up_events.push((clean_up_bound, precommit.sector_number));
             }
             // Batch update actor state.
@@ -1745,7 +2179,7 @@ impl Actor {
                         "failed to add pre-commit deposit {}: {}",
                         total_deposit_required, e
                 ))?;
-            state.allocate_sector_numbers(store, &sector_numbers, CollisionPolicy::DenyCollisions)
+            state.allocate_sector_numbers(rt.policy(), store, &sector_numbers, CollisionPolicy::DenyCollisions)
                 .map_err(|e|
                     e.wrap("failed to allocate sector numbers")
                 )?;
@@ -1764,9 +2198,7 @@ impl Actor {
         })?;
         burn_funds(rt, fee_to_burn)?;
         let state: State = rt.state()?;
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariant broken: {}", e))
-        })?;
+        check_balance_invariants(rt, &state)?;
         if needs_cron {
             let new_dl_info = state.deadline_info(rt.policy(), curr_epoch);
             enroll_cron_event(
@@ -1778,13 +2210,87 @@ impl Actor {
         Ok(())
     }
 
+    /// Removes pre-commitments that the miner will never prove, burning their deposits immediately
+    /// rather than at expiry, and releasing the sector numbers for reuse.
+    fn abandon_pre_commits<BS, RT>(
+        rt: &mut RT,
+        mut params: AbandonPreCommitsParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let sector_numbers = params.sectors.validate().map_err(|e| {
+            actor_error!(ErrIllegalArgument, "failed to validate sector numbers: {}", e)
+        })?;
+        let count = sector_numbers.len();
+        if count == 0 {
+            return Err(actor_error!(ErrIllegalArgument, "no sectors to abandon"));
+        }
+        if count > rt.policy().addressed_sectors_max {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many sectors {}, max {}",
+                count,
+                rt.policy().addressed_sectors_max
+            ));
+        }
+
+        let deposit_to_burn = rt.transaction(|state: &mut State, rt| {
+            let info = get_miner_info(rt.store(), state)?;
+            rt.validate_immediate_caller_is(
+                info.control_addresses.iter().chain(&[info.worker, info.owner]),
+            )?;
+
+            state.abandon_pre_commits(rt.policy(), rt.store(), sector_numbers).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to abandon pre-commits")
+            })
+        })?;
+
+        burn_funds(rt, deposit_to_burn)?;
+        let state: State = rt.state()?;
+        check_balance_invariants(rt, &state)?;
+        Ok(())
+    }
+
+    /// Removes pre-commits that have expired without being proven, burning their deposits, and
+    /// releases their sector numbers for reuse without waiting for cron to clean them up.
+    fn reclaim_expired_pre_commit_numbers<BS, RT>(
+        rt: &mut RT,
+    ) -> Result<ReclaimExpiredPreCommitNumbersReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let (deposit_to_burn, freed) = rt.transaction(|state: &mut State, rt| {
+            let info = get_miner_info(rt.store(), state)?;
+            rt.validate_immediate_caller_is(
+                info.control_addresses.iter().chain(&[info.worker, info.owner]),
+            )?;
+
+            state.reclaim_expired_pre_commits(rt.policy(), rt.store(), rt.curr_epoch()).map_err(
+                |e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        "failed to reclaim expired pre-commits",
+                    )
+                },
+            )
+        })?;
+
+        burn_funds(rt, deposit_to_burn)?;
+        let state: State = rt.state()?;
+        check_balance_invariants(rt, &state)?;
+        Ok(ReclaimExpiredPreCommitNumbersReturn { freed })
+    }
+
     /// Checks state of the corresponding sector pre-commitment, then schedules the proof to be verified in bulk
     /// by the power actor.
     /// If valid, the power actor will call ConfirmSectorProofsValid at the end of the same epoch as this message.
     fn prove_commit_sector<BS, RT>(
         rt: &mut RT,
         params: ProveCommitSectorParams,
-    ) -> Result<(), ActorError>
+    ) -> Result<ProveCommitSectorReturn, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
@@ -1834,7 +2340,9 @@ impl Actor {
                 )
             })?;
         let prove_commit_due = precommit.pre_commit_epoch + msd;
-        if rt.curr_epoch() > prove_commit_due {
+        let epochs_late = rt.curr_epoch() - prove_commit_due;
+        let grace = rt.policy().prove_commit_grace;
+        if epochs_late > grace {
             return Err(actor_error!(
                 ErrIllegalArgument,
                 "commitment proof for {} too late at {}, due {}",
@@ -1843,6 +2351,9 @@ impl Actor {
                 prove_commit_due
             ));
         }
+        // The fee is charged when the proof is confirmed and the sector activates, which
+        // happens at the end of this epoch.
+        let grace_fee = late_prove_commit_fee(&precommit.pre_commit_deposit, epochs_late, grace);
 
         let svi = get_verify_info(
             rt,
@@ -1865,7 +2376,7 @@ impl Actor {
             BigInt::zero(),
         )?;
 
-        Ok(())
+        Ok(ProveCommitSectorReturn { grace_fee })
     }
 
     fn confirm_sector_proofs_valid<BS, RT>(
@@ -1897,13 +2408,14 @@ impl Actor {
                     "failed to load pre-committed sectors",
                 )
             })?;
-        confirm_sector_proofs_valid_internal(
+        let grace_fee = confirm_sector_proofs_valid_internal(
             rt,
             precommited_sectors,
             &params.reward_baseline_power,
             &params.reward_smoothed,
             &params.quality_adj_power_smoothed,
-        )
+        )?;
+        burn_funds(rt, grace_fee)
     }
 
     fn check_sector_proven<BS, RT>(
@@ -1936,356 +2448,1628 @@ impl Actor {
         }
     }
 
-    /// Changes the expiration epoch for a sector to a new, later one.
-    /// The sector must not be terminated or faulty.
-    /// The sector's power is recomputed for the new expiration.
-    fn extend_sector_expiration<BS, RT>(
+    /// Reports which of a set of sector numbers have been proven, loading the sectors array once.
+    fn check_sectors_proven<BS, RT>(
         rt: &mut RT,
-        mut params: ExtendSectorExpirationParams,
-    ) -> Result<(), ActorError>
+        mut params: CheckSectorsProvenParams,
+    ) -> Result<CheckSectorsProvenReturn, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        {
-            let policy = rt.policy();
-            if params.extensions.len() as u64 > policy.delcarations_max {
-                return Err(actor_error!(
-                    ErrIllegalArgument,
-                    "too many declarations {}, max {}",
-                    params.extensions.len(),
-                    policy.delcarations_max
-                ));
-            }
-        }
-
-        // limit the number of sectors declared at once
-        // https://github.com/filecoin-project/specs-actors/issues/416
-        let mut sector_count: u64 = 0;
-
-        for decl in &mut params.extensions {
-            let policy = rt.policy();
-            if decl.deadline >= policy.wpost_period_deadlines {
-                return Err(actor_error!(
-                    ErrIllegalArgument,
-                    "deadline {} not in range 0..{}",
-                    decl.deadline,
-                    policy.wpost_period_deadlines
-                ));
-            }
+        rt.validate_immediate_caller_accept_any()?;
 
-            let sectors = match decl.sectors.validate() {
-                Ok(sectors) => sectors,
-                Err(e) => {
-                    return Err(actor_error!(
-                        ErrIllegalArgument,
-                        "failed to validate sectors for deadline {}, partition {}: {}",
-                        decl.deadline,
-                        decl.partition,
-                        e
-                    ));
-                }
-            };
+        let sector_numbers = params.sector_numbers.validate().map_err(|e| {
+            actor_error!(ErrIllegalArgument, "failed to validate sector numbers: {}", e)
+        })?;
 
-            match sector_count.checked_add(sectors.len()) {
-                Some(sum) => sector_count = sum,
-                None => {
-                    return Err(actor_error!(
-                        ErrIllegalArgument,
-                        "sector bitfield integer overflow"
-                    ));
-                }
-            }
+        let count = sector_numbers.len();
+        if count > rt.policy().addressed_sectors_max {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many sectors {}, max {}",
+                count,
+                rt.policy().addressed_sectors_max
+            ));
         }
-
-        {
-            let policy = rt.policy();
-            if sector_count > policy.addressed_sectors_max {
-                return Err(actor_error!(
-                    ErrIllegalArgument,
-                    "too many sectors for declaration {}, max {}",
-                    sector_count,
-                    policy.addressed_sectors_max
-                ));
+        if let Some(last) = sector_numbers.last() {
+            if last > MAX_SECTOR_NUMBER {
+                return Err(actor_error!(ErrIllegalArgument, "sector number out of range"));
             }
         }
 
-        let curr_epoch = rt.curr_epoch();
+        let st: State = rt.state()?;
+        let sectors = Sectors::load(rt.store(), &st.sectors).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors array")
+        })?;
 
-        let (power_delta, pledge_delta) = rt.transaction(|state: &mut State, rt| {
-            let info = get_miner_info(rt.store(), state)?;
-            let nv = rt.network_version();
-            rt.validate_immediate_caller_is(
-                info.control_addresses.iter().chain(&[info.worker, info.owner]),
-            )?;
+        let mut proven = BitField::new();
+        let mut not_proven = BitField::new();
+        for sector_number in sector_numbers.iter() {
+            let found = sectors.get(sector_number).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load proven sector {}", sector_number),
+                )
+            })?;
+            if found.is_some() {
+                proven.set(sector_number);
+            } else {
+                not_proven.set(sector_number);
+            }
+        }
 
-            let store = rt.store();
+        Ok(CheckSectorsProvenReturn { proven, not_proven })
+    }
 
-            let mut deadlines =
-                state.load_deadlines(rt.store()).map_err(|e| e.wrap("failed to load deadlines"))?;
+    /// Returns the on-chain information of a set of sectors, loading the sectors array once.
+    fn get_sector_infos<BS, RT>(
+        rt: &mut RT,
+        mut params: GetSectorInfosParams,
+    ) -> Result<GetSectorInfosReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
 
-            // Group declarations by deadline, and remember iteration order.
-            //
-            let mut decls_by_deadline: Vec<_> = iter::repeat_with(Vec::new)
-                .take(rt.policy().wpost_period_deadlines as usize)
-                .collect();
-            let mut deadlines_to_load = Vec::<u64>::new();
+        let sector_numbers = params.sector_numbers.validate().map_err(|e| {
+            actor_error!(ErrIllegalArgument, "failed to validate sector numbers: {}", e)
+        })?;
 
-            for decl in params.extensions {
-                // the deadline indices are already checked.
-                let decls = &mut decls_by_deadline[decl.deadline as usize];
-                if decls.is_empty() {
-                    deadlines_to_load.push(decl.deadline);
-                }
-                decls.push(decl);
+        let count = sector_numbers.len();
+        if count > rt.policy().addressed_sectors_max {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many sectors {}, max {}",
+                count,
+                rt.policy().addressed_sectors_max
+            ));
+        }
+        if let Some(last) = sector_numbers.last() {
+            if last > MAX_SECTOR_NUMBER {
+                return Err(actor_error!(ErrIllegalArgument, "sector number out of range"));
             }
+        }
 
-            let mut sectors = Sectors::load(rt.store(), &state.sectors).map_err(|e| {
-                e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors array")
-            })?;
-
-            let mut power_delta = PowerPair::zero();
-            let mut pledge_delta = TokenAmount::zero();
-
-            for deadline_idx in deadlines_to_load {
-                let policy = rt.policy();
-                let mut deadline =
-                    deadlines.load_deadline(policy, store, deadline_idx).map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::ErrIllegalState,
-                            format!("failed to load deadline {}", deadline_idx),
-                        )
-                    })?;
+        let st: State = rt.state()?;
+        let sectors = Sectors::load(rt.store(), &st.sectors).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors array")
+        })?;
 
-                let mut partitions = deadline.partitions_amt(store).map_err(|e| {
+        let infos = sector_numbers
+            .iter()
+            .map(|sector_number| {
+                sectors.get(sector_number).map_err(|e| {
                     e.downcast_default(
                         ExitCode::ErrIllegalState,
-                        format!("failed to load partitions for deadline {}", deadline_idx),
+                        format!("failed to load sector {}", sector_number),
                     )
-                })?;
-
-                let quant = state.quant_spec_for_deadline(policy, deadline_idx);
+                })
+            })
+            .collect::<Result<_, _>>()?;
 
-                // Group modified partitions by epoch to which they are extended. Duplicates are ok.
-                let mut partitions_by_new_epoch = BTreeMap::<ChainEpoch, Vec<u64>>::new();
-                let mut epochs_to_reschedule = Vec::<ChainEpoch>::new();
+        Ok(GetSectorInfosReturn { sectors: infos })
+    }
 
-                for decl in &mut decls_by_deadline[deadline_idx as usize] {
-                    let key = PartitionKey { deadline: deadline_idx, partition: decl.partition };
+    /// Returns the on-chain information for a pre-committed sector, including its locked deposit.
+    fn get_pre_commit_info<BS, RT>(
+        rt: &mut RT,
+        params: GetPreCommitInfoParams,
+    ) -> Result<SectorPreCommitOnChainInfo, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
 
-                    let mut partition = partitions
-                        .get(decl.partition)
-                        .map_err(|e| {
-                            e.downcast_default(
-                                ExitCode::ErrIllegalState,
-                                format!("failed to load partition {:?}", key),
-                            )
-                        })?
-                        .cloned()
-                        .ok_or_else(|| actor_error!(ErrNotFound, "no such partition {:?}", key))?;
+        let sector_number = params.sector_number;
+        let st: State = rt.state()?;
+        st.get_precommitted_sector(rt.store(), sector_number)
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load pre-committed sector {}", sector_number),
+                )
+            })?
+            .ok_or_else(|| actor_error!(ErrNotFound, "no pre-committed sector {}", sector_number))
+    }
 
-                    let old_sectors = sectors
-                        .load_sector(&mut decl.sectors)
-                        .map_err(|e| e.wrap("failed to load sectors"))?;
+    /// Returns the remaining lifetime, fault, recovery and termination status, and deadline and
+    /// partition of each of a set of sectors, in a single pass over the miner's partitions.
+    /// Sectors that are not in the sectors array are reported as None.
+    fn get_sector_health<BS, RT>(
+        rt: &mut RT,
+        mut params: GetSectorHealthParams,
+    ) -> Result<GetSectorHealthReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
 
-                    let new_sectors: Vec<SectorOnChainInfo> = old_sectors
-                        .iter()
-                        .map(|sector| {
-                            if !can_extend_seal_proof_type(policy, sector.seal_proof, nv) {
-                                return Err(actor_error!(
-                                    ErrForbidden,
-                                    "cannot extend expiration for sector {} with unsupported \
-                                    seal type {:?}",
-                                    sector.sector_number,
-                                    sector.seal_proof
-                                ));
-                            }
+        let sector_numbers = params.sector_numbers.validate().map_err(|e| {
+            actor_error!(ErrIllegalArgument, "failed to validate sector numbers: {}", e)
+        })?;
 
-                            // This can happen if the sector should have already expired, but hasn't
-                            // because the end of its deadline hasn't passed yet.
-                            if sector.expiration < rt.curr_epoch() {
-                                return Err(actor_error!(
-                                    ErrForbidden,
-                                    "cannot extend expiration for expired sector {} at {}",
-                                    sector.sector_number,
-                                    sector.expiration
-                                ));
-                            }
+        let count = sector_numbers.len();
+        if count > rt.policy().addressed_sectors_max {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many sectors {}, max {}",
+                count,
+                rt.policy().addressed_sectors_max
+            ));
+        }
+        if let Some(last) = sector_numbers.last() {
+            if last > MAX_SECTOR_NUMBER {
+                return Err(actor_error!(ErrIllegalArgument, "sector number out of range"));
+            }
+        }
 
-                            if decl.new_expiration < sector.expiration {
-                                return Err(actor_error!(
-                                    ErrIllegalArgument,
-                                    "cannot reduce sector {} expiration to {} from {}",
-                                    sector.sector_number,
-                                    decl.new_expiration,
-                                    sector.expiration
-                                ));
-                            }
+        let st: State = rt.state()?;
+        let sectors = Sectors::load(rt.store(), &st.sectors).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors array")
+        })?;
 
-                            validate_expiration(
-                                rt,
-                                sector.activation,
-                                decl.new_expiration,
-                                sector.seal_proof,
-                            )?;
+        // Maps each requested sector to its (deadline, partition, faulty, recovering, terminated)
+        // status.
+        let mut locations = BTreeMap::new();
+        let deadlines = st.load_deadlines(rt.store())?;
+        deadlines
+            .for_each(rt.policy(), rt.store(), |deadline_idx, deadline| {
+                deadline.for_each(rt.store(), |partition_idx, partition| {
+                    for sector_number in (sector_numbers & &partition.sectors).iter() {
+                        locations.insert(
+                            sector_number,
+                            (
+                                deadline_idx,
+                                partition_idx,
+                                partition.faults.get(sector_number),
+                                partition.recoveries.get(sector_number),
+                                partition.terminated.get(sector_number),
+                            ),
+                        );
+                    }
+                    Ok(())
+                })
+            })
+            .map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load partitions")
+            })?;
 
-                            // Remove "spent" deal weights
-                            let new_deal_weight = (&sector.deal_weight
-                                * (sector.expiration - curr_epoch))
-                                .div_floor(&BigInt::from(sector.expiration - sector.activation));
+        let curr_epoch = rt.curr_epoch();
+        let health = sector_numbers
+            .iter()
+            .map(|sector_number| {
+                let info = match sectors.get(sector_number).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to load sector {}", sector_number),
+                    )
+                })? {
+                    Some(info) => info,
+                    None => return Ok(None),
+                };
+                let (deadline, partition, is_faulty, is_recovering, is_terminated) =
+                    *locations.get(&sector_number).ok_or_else(|| {
+                        actor_error!(
+                            ErrIllegalState,
+                            "sector {} not due at any deadline",
+                            sector_number
+                        )
+                    })?;
+                Ok(Some(SectorHealth {
+                    sector_number,
+                    expiration: info.expiration,
+                    epochs_remaining: std::cmp::max(0, info.expiration - curr_epoch),
+                    is_faulty,
+                    is_recovering,
+                    is_terminated,
+                    deadline,
+                    partition,
+                }))
+            })
+            .collect::<Result<_, ActorError>>()?;
 
-                            let new_verified_deal_weight = (&sector.verified_deal_weight
-                                * (sector.expiration - curr_epoch))
-                                .div_floor(&BigInt::from(sector.expiration - sector.activation));
+        Ok(GetSectorHealthReturn { sectors: health })
+    }
 
-                            let mut sector = sector.clone();
-                            sector.expiration = decl.new_expiration;
+    /// Lists the deals backing active sectors, paged by sector number. Each page loads at most
+    /// `addressed_sectors_max` sectors.
+    fn get_active_deals<BS, RT>(
+        rt: &mut RT,
+        params: GetActiveDealsParams,
+    ) -> Result<GetActiveDealsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
 
-                            sector.deal_weight = new_deal_weight;
-                            sector.verified_deal_weight = new_verified_deal_weight;
+        let policy = rt.policy();
+        let st: State = rt.state()?;
+        let store = rt.store();
+        let active = st.active_sectors(policy, store).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load active sectors")
+        })?;
+        let sectors = Sectors::load(store, &st.sectors).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors array")
+        })?;
 
-                            Ok(sector)
-                        })
-                        .collect::<Result<_, _>>()?;
+        let mut deal_ids = Vec::new();
+        let mut next_sector = None;
+        let mut remaining = policy.addressed_sectors_max;
+        for sector_number in active.iter().filter(|&n| n >= params.start_sector) {
+            if remaining == 0 {
+                next_sector = Some(sector_number);
+                break;
+            }
+            remaining -= 1;
 
-                    // Overwrite sector infos.
-                    sectors.store(new_sectors.clone()).map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::ErrIllegalState,
-                            format!("failed to update sectors {:?}", decl.sectors),
-                        )
-                    })?;
+            let sector = sectors.must_get(sector_number).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load active sector {}", sector_number),
+                )
+            })?;
+            deal_ids.extend(sector.deal_ids);
+        }
 
-                    // Remove old sectors from partition and assign new sectors.
-                    let (partition_power_delta, partition_pledge_delta) = partition
-                        .replace_sectors(store, &old_sectors, &new_sectors, info.sector_size, quant)
-                        .map_err(|e| {
-                            e.downcast_default(
-                                ExitCode::ErrIllegalState,
-                                format!("failed to replace sector expirations at {:?}", key),
-                            )
-                        })?;
+        Ok(Page { items: deal_ids, next: next_sector })
+    }
 
-                    power_delta += &partition_power_delta;
-                    pledge_delta += partition_pledge_delta; // expected to be zero, see note below.
+    /// Returns the deadline info as computed at the given epoch, which must lie within one
+    /// proving period of the current epoch.
+    fn deadline_info_at<BS, RT>(
+        rt: &mut RT,
+        params: DeadlineInfoAtParams,
+    ) -> Result<DeadlineInfoAtReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
 
-                    partitions.set(decl.partition, partition).map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::ErrIllegalState,
-                            format!("failed to save partition {:?}", key),
-                        )
-                    })?;
+        let curr_epoch = rt.curr_epoch();
+        let max_distance = rt.policy().wpost_proving_period;
+        let distance = params.epoch.checked_sub(curr_epoch).and_then(|d| d.checked_abs());
+        if distance.map_or(true, |distance| distance > max_distance) {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "epoch {} is more than {} epochs from current epoch {}",
+                params.epoch,
+                max_distance,
+                curr_epoch
+            ));
+        }
 
-                    // Record the new partition expiration epoch for setting outside this loop
-                    // over declarations.
-                    let prev_epoch_partitions = partitions_by_new_epoch.entry(decl.new_expiration);
-                    let not_exists = matches!(prev_epoch_partitions, Entry::Vacant(_));
+        let st: State = rt.state()?;
+        let info = st.deadline_info(rt.policy(), params.epoch);
+        Ok(DeadlineInfoAtReturn {
+            period_start: info.period_start,
+            index: info.index,
+            open: info.open,
+            close: info.close,
+            challenge: info.challenge,
+            fault_cutoff: info.fault_cutoff,
+        })
+    }
 
-                    // Add declaration partition
-                    prev_epoch_partitions.or_insert_with(Vec::new).push(decl.partition);
-                    if not_exists {
-                        // reschedule epoch if the partition for new epoch didn't already exist
-                        epochs_to_reschedule.push(decl.new_expiration);
-                    }
-                }
+    /// Returns the timing of the current deadline and those following it, continuing into
+    /// later proving periods as needed.
+    fn upcoming_deadlines<BS, RT>(
+        rt: &mut RT,
+        params: UpcomingDeadlinesParams,
+    ) -> Result<UpcomingDeadlinesReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
 
-                deadline.partitions = partitions.flush().map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::ErrIllegalState,
-                        format!("failed to save partitions for deadline {}", deadline_idx),
-                    )
-                })?;
+        let policy = rt.policy();
+        if params.count > policy.wpost_period_deadlines {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many deadlines {}, max {}",
+                params.count,
+                policy.wpost_period_deadlines
+            ));
+        }
 
-                // Record partitions in deadline expiration queue
-                for epoch in epochs_to_reschedule {
-                    let p_idxs = partitions_by_new_epoch.get(&epoch).unwrap();
-                    deadline.add_expiration_partitions(store, epoch, p_idxs, quant).map_err(
-                        |e| {
-                            e.downcast_default(
-                                ExitCode::ErrIllegalState,
-                                format!(
-                                    "failed to add expiration partitions to \
-                                        deadline {} epoch {}",
-                                    deadline_idx, epoch
-                                ),
-                            )
-                        },
-                    )?;
+        let st: State = rt.state()?;
+        let curr_epoch = rt.curr_epoch();
+        let current = st.deadline_info(policy, curr_epoch);
+        let deadlines = (current.index..current.index + params.count)
+            .map(|i| {
+                let periods = (i / policy.wpost_period_deadlines) as i64;
+                let info = new_deadline_info(
+                    policy,
+                    current.period_start + periods * policy.wpost_proving_period,
+                    i % policy.wpost_period_deadlines,
+                    curr_epoch,
+                );
+                UpcomingDeadline {
+                    index: info.index,
+                    open: info.open,
+                    close: info.close,
+                    challenge: info.challenge,
+                    fault_cutoff: info.fault_cutoff,
                 }
+            })
+            .collect();
 
-                deadlines.update_deadline(policy, store, deadline_idx, &deadline).map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::ErrIllegalState,
-                        format!("failed to save deadline {}", deadline_idx),
-                    )
-                })?;
-            }
+        Ok(UpcomingDeadlinesReturn { deadlines })
+    }
 
-            state.sectors = sectors.amt.flush().map_err(|e| {
-                e.downcast_default(ExitCode::ErrIllegalState, "failed to save sectors")
-            })?;
-            state.save_deadlines(store, deadlines).map_err(|e| {
-                e.downcast_default(ExitCode::ErrIllegalState, "failed to save deadlines")
-            })?;
+    /// Lists the live sectors whose replica has been updated, i.e. those with a sector key CID,
+    /// paged by sector number. Each page loads at most `addressed_sectors_max` sectors.
+    fn get_upgraded_sectors<BS, RT>(
+        rt: &mut RT,
+        params: GetUpgradedSectorsParams,
+    ) -> Result<GetUpgradedSectorsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
 
-            Ok((power_delta, pledge_delta))
+        let policy = rt.policy();
+        let st: State = rt.state()?;
+        let store = rt.store();
+        let live = st.live_sectors(policy, store).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load live sectors")
+        })?;
+        let sectors = Sectors::load(store, &st.sectors).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors array")
         })?;
 
-        request_update_power(rt, power_delta)?;
+        let mut upgraded = Vec::new();
+        let mut next_sector = None;
+        let mut remaining = policy.addressed_sectors_max;
+        for sector_number in live.iter().filter(|&n| n >= params.start_sector) {
+            if remaining == 0 {
+                next_sector = Some(sector_number);
+                break;
+            }
+            remaining -= 1;
 
-        // Note: the pledge delta is expected to be zero, since pledge is not re-calculated for the extension.
-        // But in case that ever changes, we can do the right thing here.
-        notify_pledge_changed(rt, &pledge_delta)?;
-        Ok(())
+            let sector = sectors.must_get(sector_number).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load live sector {}", sector_number),
+                )
+            })?;
+            if sector.sector_key_cid.is_some() {
+                upgraded.push(sector_number);
+            }
+        }
+
+        Ok(Page { items: upgraded, next: next_sector })
     }
 
-    /// Marks some sectors as terminated at the present epoch, earlier than their
-    /// scheduled termination, and adds these sectors to the early termination queue.
-    /// This method then processes up to AddressedSectorsMax sectors and
-    /// AddressedPartitionsMax partitions from the early termination queue,
-    /// terminating deals, paying fines, and returning pledge collateral. While
-    /// sectors remain in this queue:
-    ///
-    ///  1. The miner will be unable to withdraw funds.
-    ///  2. The chain will process up to AddressedSectorsMax sectors and
-    ///     AddressedPartitionsMax per epoch until the queue is empty.
-    ///
-    /// The sectors are immediately ignored for Window PoSt proofs, and should be
-    /// masked in the same way as faulty sectors. A miner may not terminate sectors in the
-    /// current deadline or the next deadline to be proven.
-    ///
-    /// This function may be invoked with no new sectors to explicitly process the
-    /// next batch of sectors.
-    fn terminate_sectors<BS, RT>(
+    /// Returns the smallest sector number not yet allocated, for use in a new pre-commitment.
+    fn next_allocatable_sector_number<BS, RT>(
+        rt: &mut RT,
+    ) -> Result<NextAllocatableSectorNumberReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let sector_number = st.next_allocatable_sector_number(rt.store())?;
+        Ok(NextAllocatableSectorNumberReturn { sector_number })
+    }
+
+    /// Changes the expiration epoch for a sector to a new, later one.
+    /// The sector must not be terminated or faulty.
+    /// The sector's power is recomputed for the new expiration.
+    fn extend_sector_expiration<BS, RT>(
+        rt: &mut RT,
+        mut params: ExtendSectorExpirationParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        {
+            let policy = rt.policy();
+            if params.extensions.len() as u64 > policy.delcarations_max {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "too many declarations {}, max {}",
+                    params.extensions.len(),
+                    policy.delcarations_max
+                ));
+            }
+        }
+
+        // limit the number of sectors declared at once
+        // https://github.com/filecoin-project/specs-actors/issues/416
+        let mut sector_count: u64 = 0;
+
+        for decl in &mut params.extensions {
+            let policy = rt.policy();
+            if decl.deadline >= policy.wpost_period_deadlines {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "deadline {} not in range 0..{}",
+                    decl.deadline,
+                    policy.wpost_period_deadlines
+                ));
+            }
+
+            let sectors = match decl.sectors.validate() {
+                Ok(sectors) => sectors,
+                Err(e) => {
+                    return Err(actor_error!(
+                        ErrIllegalArgument,
+                        "failed to validate sectors for deadline {}, partition {}: {}",
+                        decl.deadline,
+                        decl.partition,
+                        e
+                    ));
+                }
+            };
+
+            match sector_count.checked_add(sectors.len()) {
+                Some(sum) => sector_count = sum,
+                None => {
+                    return Err(actor_error!(
+                        ErrIllegalArgument,
+                        "sector bitfield integer overflow"
+                    ));
+                }
+            }
+        }
+
+        {
+            let policy = rt.policy();
+            if sector_count > policy.addressed_sectors_max {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "too many sectors for declaration {}, max {}",
+                    sector_count,
+                    policy.addressed_sectors_max
+                ));
+            }
+        }
+
+        let curr_epoch = rt.curr_epoch();
+
+        let (power_delta, pledge_delta) = rt.transaction(|state: &mut State, rt| {
+            let info = get_miner_info(rt.store(), state)?;
+            let nv = rt.network_version();
+            rt.validate_immediate_caller_is(
+                info.control_addresses.iter().chain(&[info.worker, info.owner]),
+            )?;
+
+            let store = rt.store();
+
+            let mut deadlines =
+                state.load_deadlines(rt.store()).map_err(|e| e.wrap("failed to load deadlines"))?;
+
+            // Group declarations by deadline, and remember iteration order.
+            //
+            let mut decls_by_deadline: Vec<_> = iter::repeat_with(Vec::new)
+                .take(rt.policy().wpost_period_deadlines as usize)
+                .collect();
+            let mut deadlines_to_load = Vec::<u64>::new();
+
+            for decl in params.extensions {
+                // the deadline indices are already checked.
+                let decls = &mut decls_by_deadline[decl.deadline as usize];
+                if decls.is_empty() {
+                    deadlines_to_load.push(decl.deadline);
+                }
+                decls.push(decl);
+            }
+
+            // Each distinct partition is loaded once, so bound those rather than declarations.
+            let partition_count: usize = decls_by_deadline
+                .iter()
+                .map(|decls| decls.iter().map(|decl| decl.partition).collect::<BTreeSet<_>>().len())
+                .sum();
+            if partition_count as u64 > rt.policy().extension_partitions_max {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "too many partitions {}, max {}",
+                    partition_count,
+                    rt.policy().extension_partitions_max
+                ));
+            }
+
+            let mut sectors = Sectors::load(rt.store(), &state.sectors).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors array")
+            })?;
+
+            let mut power_delta = PowerPair::zero();
+            let mut pledge_delta = TokenAmount::zero();
+
+            for deadline_idx in deadlines_to_load {
+                let policy = rt.policy();
+                let mut deadline =
+                    deadlines.load_deadline(policy, store, deadline_idx).map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::ErrIllegalState,
+                            format!("failed to load deadline {}", deadline_idx),
+                        )
+                    })?;
+
+                let mut partitions = deadline.partitions_amt(store).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to load partitions for deadline {}", deadline_idx),
+                    )
+                })?;
+
+                let quant = state.quant_spec_for_deadline(policy, deadline_idx);
+
+                // Group modified partitions by epoch to which they are extended. Duplicates are ok.
+                let mut partitions_by_new_epoch = BTreeMap::<ChainEpoch, Vec<u64>>::new();
+                let mut epochs_to_reschedule = Vec::<ChainEpoch>::new();
+
+                for decl in &mut decls_by_deadline[deadline_idx as usize] {
+                    let key = PartitionKey { deadline: deadline_idx, partition: decl.partition };
+
+                    let mut partition = partitions
+                        .get(decl.partition)
+                        .map_err(|e| {
+                            e.downcast_default(
+                                ExitCode::ErrIllegalState,
+                                format!("failed to load partition {:?}", key),
+                            )
+                        })?
+                        .cloned()
+                        .ok_or_else(|| actor_error!(ErrNotFound, "no such partition {:?}", key))?;
+
+                    let old_sectors = sectors
+                        .load_sector(&mut decl.sectors)
+                        .map_err(|e| e.wrap("failed to load sectors"))?;
+
+                    let new_sectors: Vec<SectorOnChainInfo> = old_sectors
+                        .iter()
+                        .map(|sector| {
+                            if !can_extend_seal_proof_type(policy, sector.seal_proof, nv) {
+                                return Err(actor_error!(
+                                    ErrForbidden,
+                                    "cannot extend expiration for sector {} with unsupported \
+                                    seal type {:?}",
+                                    sector.sector_number,
+                                    sector.seal_proof
+                                ));
+                            }
+
+                            // This can happen if the sector should have already expired, but hasn't
+                            // because the end of its deadline hasn't passed yet.
+                            if sector.expiration < rt.curr_epoch() {
+                                return Err(actor_error!(
+                                    ErrForbidden,
+                                    "cannot extend expiration for expired sector {} at {}",
+                                    sector.sector_number,
+                                    sector.expiration
+                                ));
+                            }
+
+                            if decl.new_expiration < sector.expiration {
+                                return Err(actor_error!(
+                                    ErrIllegalArgument,
+                                    "cannot reduce sector {} expiration to {} from {}",
+                                    sector.sector_number,
+                                    decl.new_expiration,
+                                    sector.expiration
+                                ));
+                            }
+
+                            validate_expiration(
+                                rt,
+                                sector.activation,
+                                decl.new_expiration,
+                                sector.seal_proof,
+                            )?;
+
+                            // Remove "spent" deal weights
+                            let new_deal_weight = (&sector.deal_weight
+                                * (sector.expiration - curr_epoch))
+                                .div_floor(&BigInt::from(sector.expiration - sector.activation));
+
+                            let new_verified_deal_weight = (&sector.verified_deal_weight
+                                * (sector.expiration - curr_epoch))
+                                .div_floor(&BigInt::from(sector.expiration - sector.activation));
+
+                            let mut sector = sector.clone();
+                            sector.expiration = decl.new_expiration;
+
+                            sector.deal_weight = new_deal_weight;
+                            sector.verified_deal_weight = new_verified_deal_weight;
+
+                            Ok(sector)
+                        })
+                        .collect::<Result<_, _>>()?;
+
+                    // Overwrite sector infos.
+                    sectors.store(new_sectors.clone()).map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::ErrIllegalState,
+                            format!("failed to update sectors {:?}", decl.sectors),
+                        )
+                    })?;
+
+                    // Remove old sectors from partition and assign new sectors.
+                    let (partition_power_delta, partition_pledge_delta) = partition
+                        .replace_sectors(store, &old_sectors, &new_sectors, info.sector_size, quant)
+                        .map_err(|e| {
+                            e.downcast_default(
+                                ExitCode::ErrIllegalState,
+                                format!("failed to replace sector expirations at {:?}", key),
+                            )
+                        })?;
+
+                    power_delta += &partition_power_delta;
+                    pledge_delta += partition_pledge_delta; // expected to be zero, see note below.
+
+                    partitions.set(decl.partition, partition).map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::ErrIllegalState,
+                            format!("failed to save partition {:?}", key),
+                        )
+                    })?;
+
+                    // Record the new partition expiration epoch for setting outside this loop
+                    // over declarations.
+                    let prev_epoch_partitions = partitions_by_new_epoch.entry(decl.new_expiration);
+                    let not_exists = matches!(prev_epoch_partitions, Entry::Vacant(_));
+
+                    // Add declaration partition
+                    prev_epoch_partitions.or_insert_with(Vec::new).push(decl.partition);
+                    if not_exists {
+                        // reschedule epoch if the partition for new epoch didn't already exist
+                        epochs_to_reschedule.push(decl.new_expiration);
+                    }
+                }
+
+                deadline.partitions = partitions.flush().map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to save partitions for deadline {}", deadline_idx),
+                    )
+                })?;
+
+                // Record partitions in deadline expiration queue
+                for epoch in epochs_to_reschedule {
+                    let p_idxs = partitions_by_new_epoch.get(&epoch).unwrap();
+                    deadline.add_expiration_partitions(store, epoch, p_idxs, quant).map_err(
+                        |e| {
+                            e.downcast_default(
+                                ExitCode::ErrIllegalState,
+                                format!(
+                                    "failed to add expiration partitions to \
+                                        deadline {} epoch {}",
+                                    deadline_idx, epoch
+                                ),
+                            )
+                        },
+                    )?;
+                }
+
+                deadlines.update_deadline(policy, store, deadline_idx, &deadline).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to save deadline {}", deadline_idx),
+                    )
+                })?;
+            }
+
+            state.sectors = sectors.amt.flush().map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to save sectors")
+            })?;
+            state.save_deadlines(store, deadlines).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to save deadlines")
+            })?;
+
+            // Not reachable today: each new sector is a copy of the old one with the same
+            // initial pledge, and the expiration queue sums pledge from the sector infos it is
+            // given, so every partition pledge delta is exactly zero whatever the deal weights.
+            // This guards a future change that recomputes pledge on extension, which must not
+            // pass a decrease on to the power actor.
+            if pledge_delta.is_negative() {
+                return Err(actor_error!(
+                    ErrIllegalState,
+                    "extending sector expirations decreased pledge by {}",
+                    -&pledge_delta
+                ));
+            }
+
+            Ok((power_delta, pledge_delta))
+        })?;
+
+        request_update_power(rt, power_delta)?;
+
+        // Note: the pledge delta is expected to be zero, since pledge is not re-calculated for the extension.
+        // But in case that ever changes, we can do the right thing here.
+        notify_pledge_changed(rt, &pledge_delta)?;
+        Ok(())
+    }
+
+    /// Marks some sectors as terminated at the present epoch, earlier than their
+    /// scheduled termination, and adds these sectors to the early termination queue.
+    /// This method then processes up to AddressedSectorsMax sectors and
+    /// AddressedPartitionsMax partitions from the early termination queue,
+    /// terminating deals, paying fines, and returning pledge collateral. While
+    /// sectors remain in this queue:
+    ///
+    ///  1. The miner will be unable to withdraw funds.
+    ///  2. The chain will process up to AddressedSectorsMax sectors and
+    ///     AddressedPartitionsMax per epoch until the queue is empty.
+    ///
+    /// The sectors are immediately ignored for Window PoSt proofs, and should be
+    /// masked in the same way as faulty sectors. A miner may not terminate sectors in the
+    /// current deadline or the next deadline to be proven.
+    ///
+    /// This function may be invoked with no new sectors to explicitly process the
+    /// next batch of sectors.
+    fn terminate_sectors<BS, RT>(
+        rt: &mut RT,
+        params: TerminateSectorsParams,
+    ) -> Result<TerminateSectorsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let state: State = rt.state()?;
+        let info = get_miner_info(rt.store(), &state)?;
+        rt.validate_immediate_caller_is(
+            info.control_addresses.iter().chain(&[info.worker, info.owner]),
+        )?;
+        Self::terminate_sectors_internal(rt, params)
+    }
+
+    /// Terminates sectors on behalf of a caller that has already been validated as the owner,
+    /// worker or a control address.
+    fn terminate_sectors_internal<BS, RT>(
+        rt: &mut RT,
+        params: TerminateSectorsParams,
+    ) -> Result<TerminateSectorsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        // Note: this cannot terminate pre-committed but un-proven sectors.
+        // They must be allowed to expire (and deposit burnt).
+
+        {
+            let policy = rt.policy();
+            if params.terminations.len() as u64 > policy.delcarations_max {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "too many declarations when terminating sectors: {} > {}",
+                    params.terminations.len(),
+                    policy.delcarations_max
+                ));
+            }
+        }
+
+        let mut to_process = DeadlineSectorMap::new();
+
+        for term in params.terminations {
+            let deadline = term.deadline;
+            let partition = term.partition;
+
+            to_process.add(rt.policy(), deadline, partition, term.sectors).map_err(|e| {
+                actor_error!(
+                    ErrIllegalArgument,
+                    "failed to process deadline {}, partition {}: {}",
+                    deadline,
+                    partition,
+                    e
+                )
+            })?;
+        }
+
+        {
+            let policy = rt.policy();
+            to_process
+                .check(policy.addressed_partitions_max, policy.addressed_sectors_max)
+                .map_err(|e| {
+                    actor_error!(ErrIllegalArgument, "cannot process requested parameters: {}", e)
+                })?;
+        }
+
+        let (had_early_terminations, power_delta) = rt.transaction(|state: &mut State, rt| {
+            let had_early_terminations = have_pending_early_terminations(state);
+
+            let info = get_miner_info(rt.store(), state)?;
+
+            let store = rt.store();
+            let curr_epoch = rt.curr_epoch();
+            let mut power_delta = PowerPair::zero();
+
+            let mut deadlines =
+                state.load_deadlines(store).map_err(|e| e.wrap("failed to load deadlines"))?;
+
+            // We're only reading the sectors, so there's no need to save this back.
+            // However, we still want to avoid re-loading this array per-partition.
+            let sectors = Sectors::load(store, &state.sectors).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors")
+            })?;
+
+            for (deadline_idx, partition_sectors) in to_process.iter() {
+                // If the deadline is the current or next deadline to prove, don't allow terminating sectors.
+                // We assume that deadlines are immutable when being proven.
+                if !deadline_is_mutable(
+                    rt.policy(),
+                    state.current_proving_period_start(rt.policy(), curr_epoch),
+                    deadline_idx,
+                    curr_epoch,
+                ) {
+                    return Err(actor_error!(
+                        ErrIllegalArgument,
+                        "cannot terminate sectors in immutable deadline {}",
+                        deadline_idx
+                    ));
+                }
+
+                let quant = state.quant_spec_for_deadline(rt.policy(), deadline_idx);
+                let mut deadline =
+                    deadlines.load_deadline(rt.policy(), store, deadline_idx).map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::ErrIllegalState,
+                            format!("failed to load deadline {}", deadline_idx),
+                        )
+                    })?;
+
+                let removed_power = deadline
+                    .terminate_sectors(
+                        rt.policy(),
+                        store,
+                        &sectors,
+                        curr_epoch,
+                        partition_sectors,
+                        info.sector_size,
+                        quant,
+                    )
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::ErrIllegalState,
+                            format!("failed to terminate sectors in deadline {}", deadline_idx),
+                        )
+                    })?;
+
+                state.early_terminations.set(deadline_idx);
+                power_delta -= &removed_power;
+
+                deadlines.update_deadline(rt.policy(), store, deadline_idx, &deadline).map_err(
+                    |e| {
+                        e.downcast_default(
+                            ExitCode::ErrIllegalState,
+                            format!("failed to update deadline {}", deadline_idx),
+                        )
+                    },
+                )?;
+            }
+
+            state.save_deadlines(store, deadlines).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to save deadlines")
+            })?;
+
+            Ok((had_early_terminations, power_delta))
+        })?;
+        let epoch_reward = request_current_epoch_block_reward(rt)?;
+        let pwr_total = request_current_total_power(rt)?;
+
+        // Now, try to process these sectors.
+        let more = process_early_terminations(
+            rt,
+            &epoch_reward.this_epoch_reward_smoothed,
+            &pwr_total.quality_adj_power_smoothed,
+        )?;
+
+        if more && !had_early_terminations {
+            // We have remaining terminations, and we didn't _previously_
+            // have early terminations to process, schedule a cron job.
+            // NOTE: This isn't quite correct. If we repeatedly fill, empty,
+            // fill, and empty, the queue, we'll keep scheduling new cron
+            // jobs. However, in practice, that shouldn't be all that bad.
+            schedule_early_termination_work(rt)?;
+        }
+        let state: State = rt.state()?;
+        check_balance_invariants(rt, &state)?;
+
+        request_update_power(rt, power_delta)?;
+        Ok(TerminateSectorsReturn { done: !more })
+    }
+
+    /// Terminates up to a number of a deadline's live sectors, choosing those with the lowest
+    /// expected day reward first. The selected sectors are terminated as by TerminateSectors, so
+    /// the same caller restrictions apply and a deadline that is currently immutable (the
+    /// current or next to be proven) is rejected.
+    /// Only the deadline's leading partitions are scanned for candidates, up to the policy's
+    /// addressed partition and sector limits.
+    fn terminate_worst_sectors<BS, RT>(
+        rt: &mut RT,
+        params: TerminateWorstSectorsParams,
+    ) -> Result<TerminateSectorsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let state: State = rt.state()?;
+        let info = get_miner_info(rt.store(), &state)?;
+        rt.validate_immediate_caller_is(
+            info.control_addresses.iter().chain(&[info.worker, info.owner]),
+        )?;
+
+        {
+            let policy = rt.policy();
+            if params.deadline >= policy.wpost_period_deadlines {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "invalid deadline {} of {}",
+                    params.deadline,
+                    policy.wpost_period_deadlines
+                ));
+            }
+            if params.max_sectors == 0 || params.max_sectors > policy.addressed_sectors_max {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "max sectors {} must be in [1, {}]",
+                    params.max_sectors,
+                    policy.addressed_sectors_max
+                ));
+            }
+        }
+
+        let terminations = {
+            let st: State = rt.state()?;
+            let store = rt.store();
+            let deadlines =
+                st.load_deadlines(store).map_err(|e| e.wrap("failed to load deadlines"))?;
+            let deadline =
+                deadlines.load_deadline(rt.policy(), store, params.deadline).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to load deadline {}", params.deadline),
+                    )
+                })?;
+
+            let policy = rt.policy();
+            let mut live_sectors = Vec::new();
+            let mut scanned_sectors = 0;
+            deadline
+                .partitions_amt(store)
+                .and_then(|partitions| {
+                    partitions.for_each_while(|partition_idx, partition| {
+                        let partition_live = partition.live_sectors();
+                        scanned_sectors += partition_live.len();
+                        if live_sectors.len() as u64 >= policy.addressed_partitions_max
+                            || scanned_sectors > policy.addressed_sectors_max
+                        {
+                            return Ok(false);
+                        }
+                        live_sectors.push((partition_idx, partition_live));
+                        Ok(true)
+                    })?;
+                    Ok(())
+                })
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        format!("failed to load partitions for deadline {}", params.deadline),
+                    )
+                })?;
+
+            let sectors = Sectors::load(store, &st.sectors).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors")
+            })?;
+            let mut candidates = Vec::new();
+            for (partition_idx, sector_numbers) in live_sectors.iter() {
+                for sector in sectors.load_sector(sector_numbers)? {
+                    candidates.push((
+                        sector.expected_day_reward,
+                        sector.sector_number,
+                        *partition_idx,
+                    ));
+                }
+            }
+            if candidates.is_empty() {
+                return Err(actor_error!(
+                    ErrIllegalArgument,
+                    "no live sectors in deadline {}",
+                    params.deadline
+                ));
+            }
+
+            // Least profitable first, breaking ties by sector number.
+            candidates.sort();
+            candidates.truncate(params.max_sectors as usize);
+
+            let mut by_partition = BTreeMap::<u64, BitField>::new();
+            for (_, sector_number, partition_idx) in candidates {
+                by_partition.entry(partition_idx).or_insert_with(BitField::new).set(sector_number);
+            }
+            by_partition
+                .into_iter()
+                .map(|(partition, sectors)| TerminationDeclaration {
+                    deadline: params.deadline,
+                    partition,
+                    sectors: sectors.into(),
+                })
+                .collect()
+        };
+
+        Self::terminate_sectors_internal(rt, TerminateSectorsParams { terminations })
+    }
+
+    /// Returns the deadlines with queued early terminations, and the number of sectors queued.
+    fn get_early_terminations<BS, RT>(rt: &mut RT) -> Result<GetEarlyTerminationsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let sectors = st.early_terminations_count(rt.policy(), rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to count early terminations")
+        })?;
+        Ok(GetEarlyTerminationsReturn { deadlines: st.early_terminations, sectors })
+    }
+
+    /// Returns the faulty and recovering sectors of each deadline.
+    fn get_fault_state<BS, RT>(rt: &mut RT) -> Result<GetFaultStateReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let deadlines = st.load_deadlines(rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deadlines")
+        })?;
+
+        let mut fault_states = Vec::with_capacity(rt.policy().wpost_period_deadlines as usize);
+        deadlines
+            .for_each(rt.policy(), rt.store(), |deadline_idx, deadline| {
+                let mut faults = Vec::new();
+                let mut recoveries = Vec::new();
+                deadline
+                    .for_each(rt.store(), |_, partition| {
+                        faults.push(partition.faults.clone());
+                        recoveries.push(partition.recoveries.clone());
+                        Ok(())
+                    })
+                    .map_err(|e| {
+                        e.downcast_wrap(format!(
+                            "failed to load partitions of deadline {}",
+                            deadline_idx
+                        ))
+                    })?;
+                fault_states.push(DeadlineFaultState {
+                    faults: BitField::union(&faults),
+                    recoveries: BitField::union(&recoveries),
+                });
+                Ok(())
+            })
+            .map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load fault state")
+            })?;
+
+        Ok(GetFaultStateReturn { deadlines: fault_states })
+    }
+
+    /// Returns the number of the miner's sectors in each status. Live and terminated counts come
+    /// from the deadlines' cached totals, the pre-commit count is kept in state, and partitions
+    /// are only loaded for deadlines with faults.
+    fn get_sector_counts<BS, RT>(rt: &mut RT) -> Result<GetSectorCountsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let deadlines = st.load_deadlines(rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deadlines")
+        })?;
+
+        let mut counts = GetSectorCountsReturn::default();
+        deadlines
+            .for_each(rt.policy(), rt.store(), |deadline_idx, deadline| {
+                counts.live_sectors += deadline.live_sectors;
+                counts.terminated_sectors += deadline.total_sectors - deadline.live_sectors;
+                // Recoveries are a subset of faults, so neither exist without faulty power.
+                if deadline.faulty_power.is_zero() {
+                    return Ok(());
+                }
+                deadline
+                    .for_each(rt.store(), |_, partition| {
+                        counts.faulty_sectors += partition.faults.len();
+                        counts.recovering_sectors += partition.recoveries.len();
+                        Ok(())
+                    })
+                    .map_err(|e| {
+                        e.downcast_wrap(format!(
+                            "failed to load partitions of deadline {}",
+                            deadline_idx
+                        ))
+                    })
+            })
+            .map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to count deadline sectors")
+            })?;
+
+        counts.precommitted_sectors = st.pre_committed_sector_count;
+
+        Ok(counts)
+    }
+
+    /// Returns the number of partitions in each deadline and the number of sectors in each
+    /// partition.
+    fn get_deadline_layout<BS, RT>(rt: &mut RT) -> Result<GetDeadlineLayoutReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let deadlines = st.load_deadlines(rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deadlines")
+        })?;
+
+        let mut layouts = Vec::with_capacity(rt.policy().wpost_period_deadlines as usize);
+        deadlines
+            .for_each(rt.policy(), rt.store(), |deadline_idx, deadline| {
+                let mut layout = DeadlineLayout::default();
+                deadline
+                    .for_each(rt.store(), |_, partition| {
+                        layout.partition_sector_counts.push(partition.sectors.len());
+                        Ok(())
+                    })
+                    .map_err(|e| {
+                        e.downcast_wrap(format!(
+                            "failed to load partitions of deadline {}",
+                            deadline_idx
+                        ))
+                    })?;
+                layouts.push(layout);
+                Ok(())
+            })
+            .map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load deadline layout")
+            })?;
+
+        Ok(GetDeadlineLayoutReturn { deadlines: layouts })
+    }
+
+    /// Returns the epochs at which partitions of a deadline are scheduled to have sectors expire.
+    fn get_deadline_expiration_schedule<BS, RT>(
+        rt: &mut RT,
+        params: GetDeadlineExpirationScheduleParams,
+    ) -> Result<GetDeadlineExpirationScheduleReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let policy = rt.policy();
+        if params.deadline >= policy.wpost_period_deadlines {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "invalid deadline {} of {}",
+                params.deadline,
+                policy.wpost_period_deadlines
+            ));
+        }
+
+        let st: State = rt.state()?;
+        let deadlines = st.load_deadlines(rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deadlines")
+        })?;
+        let deadline =
+            deadlines.load_deadline(rt.policy(), rt.store(), params.deadline).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load deadline {}", params.deadline),
+                )
+            })?;
+        let entries = deadline
+            .expiration_schedule(rt.store())
+            .map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load expiration queue")
+            })?
+            .into_iter()
+            .map(|(epoch, partitions)| ExpirationScheduleEntry { epoch, partitions })
+            .collect();
+
+        Ok(GetDeadlineExpirationScheduleReturn { entries })
+    }
+
+    /// Lists the sectors scheduled to expire on time within an epoch range, paged by sector
+    /// number. Each page loads at most `addressed_sectors_max` sectors.
+    fn get_sectors_expiring_in_range<BS, RT>(
+        rt: &mut RT,
+        params: GetSectorsExpiringInRangeParams,
+    ) -> Result<GetSectorsExpiringInRangeReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        if params.from_epoch > params.to_epoch {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "invalid epoch range {} to {}",
+                params.from_epoch,
+                params.to_epoch
+            ));
+        }
+
+        let policy = rt.policy();
+        let st: State = rt.state()?;
+        st.sectors_expiring_in_range(
+            policy,
+            rt.store(),
+            params.from_epoch,
+            params.to_epoch,
+            params.start_sector,
+            policy.addressed_sectors_max,
+        )
+        .map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to find expiring sectors")
+        })
+    }
+
+    /// Reports whether the miner is within a consensus fault penalization period, during which
+    /// pre-commits and recoveries are refused.
+    fn consensus_fault_status<BS, RT>(rt: &mut RT) -> Result<ConsensusFaultStatusReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let info = get_miner_info(rt.store(), &st)?;
+        Ok(ConsensusFaultStatusReturn {
+            active: consensus_fault_active(&info, rt.curr_epoch()),
+            elapsed_epoch: info.consensus_fault_elapsed,
+        })
+    }
+
+    /// Returns the start of the miner's current proving period, its current deadline, and the
+    /// proving period offset derived from the start.
+    fn get_proving_period_info<BS, RT>(
+        rt: &mut RT,
+    ) -> Result<GetProvingPeriodInfoReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(GetProvingPeriodInfoReturn {
+            proving_period_start: st.proving_period_start,
+            current_deadline: st.current_deadline,
+            // The start may precede genesis for a new miner, so take the non-negative remainder.
+            proving_period_offset: st
+                .proving_period_start
+                .rem_euclid(rt.policy().wpost_proving_period),
+        })
+    }
+
+    /// Returns the miner's most recent reward applications, oldest first. The history is empty
+    /// unless the policy records reward applications.
+    fn get_recent_rewards<BS, RT>(rt: &mut RT) -> Result<GetRecentRewardsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let rewards = st.load_recent_rewards(rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load recent rewards")
+        })?;
+        Ok(GetRecentRewardsReturn { rewards })
+    }
+
+    /// Returns the miner's fee debt and how RepayDebt would cover it now: first from unvested
+    /// funds, then from the unlocked balance, with any remainder to be sent to the miner.
+    fn get_debt_status<BS, RT>(rt: &mut RT) -> Result<GetDebtStatusReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+
+        let unvested = st.check_unvested_funds(rt.store(), rt.curr_epoch()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load vesting funds")
+        })?;
+        let unlocked_balance = st.get_unlocked_balance(&rt.current_balance()).map_err(|e| {
+            actor_error!(ErrIllegalState, "failed to calculate unlocked balance: {}", e)
+        })?;
+
+        let repayable_from_vesting = std::cmp::min(&st.fee_debt, &unvested).clone();
+        let remaining = &st.fee_debt - &repayable_from_vesting;
+        let additional_funds_needed = if remaining > unlocked_balance {
+            &remaining - &unlocked_balance
+        } else {
+            TokenAmount::zero()
+        };
+        Ok(GetDebtStatusReturn {
+            fee_debt: st.fee_debt,
+            unlocked_balance,
+            repayable_from_vesting,
+            additional_funds_needed,
+        })
+    }
+
+    /// Returns the miner's pledge, locked funds, pre-commit deposits and fee debt, together
+    /// with its current balance.
+    fn get_pledge_summary<BS, RT>(rt: &mut RT) -> Result<GetPledgeSummaryReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(GetPledgeSummaryReturn {
+            initial_pledge: st.initial_pledge,
+            locked_funds: st.locked_funds,
+            pre_commit_deposits: st.pre_commit_deposits,
+            fee_debt: st.fee_debt,
+            balance: rt.current_balance(),
+        })
+    }
+
+    /// Returns the network fee that ProveCommitAggregate would burn for the given number of
+    /// sectors at the current base fee.
+    fn estimate_aggregate_prove_commit_fee<BS, RT>(
+        rt: &mut RT,
+        params: EstimateAggregateFeeParams,
+    ) -> Result<EstimateAggregateFeeReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let policy = rt.policy();
+        if params.sector_count > policy.max_aggregated_sectors {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many sectors addressed, addressed {} want <= {}",
+                params.sector_count,
+                policy.max_aggregated_sectors
+            ));
+        } else if params.sector_count < policy.min_aggregated_sectors {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too few sectors addressed, addressed {} want >= {}",
+                params.sector_count,
+                policy.min_aggregated_sectors
+            ));
+        }
+
+        let fee = aggregate_prove_commit_network_fee(params.sector_count as i64, &rt.base_fee());
+        Ok(EstimateAggregateFeeReturn { fee })
+    }
+
+    /// Returns the network fee that PreCommitSectorBatch would charge for the given number of
+    /// sectors at the current base fee. A batch of a single sector incurs no aggregate fee.
+    fn estimate_aggregate_pre_commit_fee<BS, RT>(
+        rt: &mut RT,
+        params: EstimateAggregateFeeParams,
+    ) -> Result<EstimateAggregateFeeReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let policy = rt.policy();
+        if params.sector_count == 0 {
+            return Err(actor_error!(ErrIllegalArgument, "batch empty"));
+        } else if params.sector_count > policy.pre_commit_sector_batch_max_size as u64 {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "batch of {} too large, max {}",
+                params.sector_count,
+                policy.pre_commit_sector_batch_max_size
+            ));
+        }
+
+        let fee = if params.sector_count > 1 {
+            aggregate_pre_commit_network_fee(params.sector_count as i64, &rt.base_fee())
+        } else {
+            TokenAmount::zero()
+        };
+        Ok(EstimateAggregateFeeReturn { fee })
+    }
+
+    /// Projects the block reward expected for a sector of the given quality-adjusted power over
+    /// the given duration, using the current network reward and power estimates.
+    fn project_sector_reward<BS, RT>(
+        rt: &mut RT,
+        params: ProjectSectorRewardParams,
+    ) -> Result<ProjectSectorRewardReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        if params.qa_power.is_negative() {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "sector power must be non-negative, was {}",
+                params.qa_power
+            ));
+        }
+        if params.duration <= 0 {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "projection duration must be positive, was {}",
+                params.duration
+            ));
+        }
+
+        let rew = request_current_epoch_block_reward(rt)?;
+        let pwr = request_current_total_power(rt)?;
+        let reward = expected_reward_for_power(
+            &rew.this_epoch_reward_smoothed,
+            &pwr.quality_adj_power_smoothed,
+            &params.qa_power,
+            params.duration,
+        );
+        Ok(ProjectSectorRewardReturn { reward })
+    }
+
+    /// Reports what disputing a snapshotted window PoSt would cover, leaving the proof in place.
+    /// Fails outside the deadline's dispute window, as a dispute would.
+    fn get_dispute_info<BS, RT>(
+        rt: &mut RT,
+        params: GetDisputeInfoParams,
+    ) -> Result<GetDisputeInfoReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let policy = rt.policy();
+        if params.deadline >= policy.wpost_period_deadlines {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "invalid deadline {} of {}",
+                params.deadline,
+                policy.wpost_period_deadlines
+            ));
+        }
+
+        let st: State = rt.state()?;
+        // Only proofs that could still be disputed are described.
+        let current_epoch = rt.curr_epoch();
+        let dl_info = st.deadline_info(policy, current_epoch);
+        if !deadline_available_for_optimistic_post_dispute(
+            policy,
+            dl_info.period_start,
+            params.deadline,
+            current_epoch,
+        ) {
+            return Err(actor_error!(
+                ErrForbidden,
+                "can only dispute window posts during the dispute window\
+                ({} epochs after the challenge window closes)",
+                policy.wpost_dispute_window
+            ));
+        }
+
+        let deadlines = st.load_deadlines(rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load deadlines")
+        })?;
+        let deadline =
+            deadlines.load_deadline(rt.policy(), rt.store(), params.deadline).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load deadline {}", params.deadline),
+                )
+            })?;
+
+        let partitions =
+            deadline.post_proof_partitions(rt.store(), params.post_index).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to load proof for dispute")
+            })?;
+        let dispute_info =
+            deadline.load_partitions_for_dispute(rt.store(), partitions.clone()).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    "failed to load partition for dispute",
+                )
+            })?;
+
+        Ok(GetDisputeInfoReturn {
+            partitions,
+            ignored_sectors: dispute_info.ignored_sector_nos,
+            disputed_power: dispute_info.disputed_power,
+        })
+    }
+
+    /// Computes the initial pledge that a sector of the given power would require if it were
+    /// committed now.
+    fn estimate_initial_pledge<BS, RT>(
+        rt: &mut RT,
+        params: EstimateInitialPledgeParams,
+    ) -> Result<EstimateInitialPledgeReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        if params.qa_power.is_negative() {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "sector power must be non-negative, was {}",
+                params.qa_power
+            ));
+        }
+        // The pledge itself doesn't depend on the duration, but only sector lifetimes that
+        // could actually be committed are accepted.
+        let nv = rt.network_version();
+        let min_duration = rt.policy().min_sector_expiration(nv);
+        let max_duration = rt.policy().max_sector_expiration_extension(nv);
+        if params.duration < min_duration || params.duration > max_duration {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "sector duration {} must be in [{}, {}]",
+                params.duration,
+                min_duration,
+                max_duration
+            ));
+        }
+
+        let rew = request_current_epoch_block_reward(rt)?;
+        let pwr = request_current_total_power(rt)?;
+        let initial_pledge = initial_pledge_for_power(
+            &params.qa_power,
+            &rew.this_epoch_baseline_power,
+            &rew.this_epoch_reward_smoothed,
+            &pwr.quality_adj_power_smoothed,
+            &rt.total_fil_circ_supply(),
+        );
+        Ok(EstimateInitialPledgeReturn { initial_pledge })
+    }
+
+    /// Reports which of the sectors in a termination request would currently be rejected by
+    /// TerminateSectors, without terminating anything.
+    fn check_termination_allowed<BS, RT>(
         rt: &mut RT,
         params: TerminateSectorsParams,
-    ) -> Result<TerminateSectorsReturn, ActorError>
+    ) -> Result<CheckTerminationAllowedReturn, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        // Note: this cannot terminate pre-committed but un-proven sectors.
-        // They must be allowed to expire (and deposit burnt).
+        rt.validate_immediate_caller_accept_any()?;
 
-        {
-            let policy = rt.policy();
-            if params.terminations.len() as u64 > policy.delcarations_max {
-                return Err(actor_error!(
-                    ErrIllegalArgument,
-                    "too many declarations when terminating sectors: {} > {}",
-                    params.terminations.len(),
-                    policy.delcarations_max
-                ));
-            }
+        let policy = rt.policy();
+        if params.terminations.len() as u64 > policy.delcarations_max {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "too many declarations when terminating sectors: {} > {}",
+                params.terminations.len(),
+                policy.delcarations_max
+            ));
         }
 
         let mut to_process = DeadlineSectorMap::new();
-
         for term in params.terminations {
             let deadline = term.deadline;
             let partition = term.partition;
 
-            to_process.add(rt.policy(), deadline, partition, term.sectors).map_err(|e| {
+            to_process.add(policy, deadline, partition, term.sectors).map_err(|e| {
                 actor_error!(
                     ErrIllegalArgument,
                     "failed to process deadline {}, partition {}: {}",
@@ -2295,124 +4079,70 @@ impl Actor {
                 )
             })?;
         }
+        to_process.check(policy.addressed_partitions_max, policy.addressed_sectors_max).map_err(
+            |e| actor_error!(ErrIllegalArgument, "cannot process requested parameters: {}", e),
+        )?;
 
-        {
-            let policy = rt.policy();
-            to_process
-                .check(policy.addressed_partitions_max, policy.addressed_sectors_max)
-                .map_err(|e| {
-                    actor_error!(ErrIllegalArgument, "cannot process requested parameters: {}", e)
-                })?;
-        }
-
-        let (had_early_terminations, power_delta) = rt.transaction(|state: &mut State, rt| {
-            let had_early_terminations = have_pending_early_terminations(state);
-
-            let info = get_miner_info(rt.store(), state)?;
-
-            rt.validate_immediate_caller_is(
-                info.control_addresses.iter().chain(&[info.worker, info.owner]),
-            )?;
-
-            let store = rt.store();
-            let curr_epoch = rt.curr_epoch();
-            let mut power_delta = PowerPair::zero();
+        let st: State = rt.state()?;
+        let store = rt.store();
+        let curr_epoch = rt.curr_epoch();
+        let deadlines = st.load_deadlines(store).map_err(|e| e.wrap("failed to load deadlines"))?;
 
-            let mut deadlines =
-                state.load_deadlines(store).map_err(|e| e.wrap("failed to load deadlines"))?;
+        let mut immutable_deadline = BitField::new();
+        let mut not_live = BitField::new();
+        for (deadline_idx, partition_sectors) in to_process.iter() {
+            let mutable = deadline_is_mutable(
+                policy,
+                st.current_proving_period_start(policy, curr_epoch),
+                deadline_idx,
+                curr_epoch,
+            );
 
-            // We're only reading the sectors, so there's no need to save this back.
-            // However, we still want to avoid re-loading this array per-partition.
-            let sectors = Sectors::load(store, &state.sectors).map_err(|e| {
-                e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors")
+            let deadline = deadlines.load_deadline(policy, store, deadline_idx).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load deadline {}", deadline_idx),
+                )
+            })?;
+            let partitions = deadline.partitions_amt(store).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load partitions for deadline {}", deadline_idx),
+                )
             })?;
 
-            for (deadline_idx, partition_sectors) in to_process.iter() {
-                // If the deadline is the current or next deadline to prove, don't allow terminating sectors.
-                // We assume that deadlines are immutable when being proven.
-                if !deadline_is_mutable(
-                    rt.policy(),
-                    state.current_proving_period_start(rt.policy(), curr_epoch),
-                    deadline_idx,
-                    curr_epoch,
-                ) {
-                    return Err(actor_error!(
+            for (partition_idx, sector_numbers) in partition_sectors.iter() {
+                let sector_numbers = sector_numbers.validate().map_err(|e| {
+                    actor_error!(
                         ErrIllegalArgument,
-                        "cannot terminate sectors in immutable deadline {}",
-                        deadline_idx
-                    ));
-                }
-
-                let quant = state.quant_spec_for_deadline(rt.policy(), deadline_idx);
-                let mut deadline =
-                    deadlines.load_deadline(rt.policy(), store, deadline_idx).map_err(|e| {
-                        e.downcast_default(
-                            ExitCode::ErrIllegalState,
-                            format!("failed to load deadline {}", deadline_idx),
-                        )
-                    })?;
-
-                let removed_power = deadline
-                    .terminate_sectors(
-                        rt.policy(),
-                        store,
-                        &sectors,
-                        curr_epoch,
-                        partition_sectors,
-                        info.sector_size,
-                        quant,
+                        "failed to validate terminating sectors: {}",
+                        e
                     )
+                })?;
+
+                // A missing partition has no live sectors.
+                let live = partitions
+                    .get(partition_idx)
                     .map_err(|e| {
                         e.downcast_default(
                             ExitCode::ErrIllegalState,
-                            format!("failed to terminate sectors in deadline {}", deadline_idx),
+                            format!(
+                                "failed to load partition {} in deadline {}",
+                                partition_idx, deadline_idx
+                            ),
                         )
-                    })?;
-
-                state.early_terminations.set(deadline_idx);
-                power_delta -= &removed_power;
+                    })?
+                    .map(|partition| partition.live_sectors())
+                    .unwrap_or_default();
 
-                deadlines.update_deadline(rt.policy(), store, deadline_idx, &deadline).map_err(
-                    |e| {
-                        e.downcast_default(
-                            ExitCode::ErrIllegalState,
-                            format!("failed to update deadline {}", deadline_idx),
-                        )
-                    },
-                )?;
+                not_live = &not_live | &(sector_numbers - &live);
+                if !mutable {
+                    immutable_deadline = &immutable_deadline | sector_numbers;
+                }
             }
-
-            state.save_deadlines(store, deadlines).map_err(|e| {
-                e.downcast_default(ExitCode::ErrIllegalState, "failed to save deadlines")
-            })?;
-
-            Ok((had_early_terminations, power_delta))
-        })?;
-        let epoch_reward = request_current_epoch_block_reward(rt)?;
-        let pwr_total = request_current_total_power(rt)?;
-
-        // Now, try to process these sectors.
-        let more = process_early_terminations(
-            rt,
-            &epoch_reward.this_epoch_reward_smoothed,
-            &pwr_total.quality_adj_power_smoothed,
-        )?;
-
-        if more && !had_early_terminations {
-            // We have remaining terminations, and we didn't _previously_
-            // have early terminations to process, schedule a cron job.
-            // NOTE: This isn't quite correct. If we repeatedly fill, empty,
-            // fill, and empty, the queue, we'll keep scheduling new cron
-            // jobs. However, in practice, that shouldn't be all that bad.
-            schedule_early_termination_work(rt)?;
         }
-        let state: State = rt.state()?;
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariant broken: {}", e))
-        })?;
 
-        request_update_power(rt, power_delta)?;
-        Ok(TerminateSectorsReturn { done: !more })
+        Ok(CheckTerminationAllowedReturn { immutable_deadline, not_live })
     }
 
     fn declare_faults<BS, RT>(rt: &mut RT, params: DeclareFaultsParams) -> Result<(), ActorError>
@@ -2688,14 +4418,85 @@ impl Actor {
 
         burn_funds(rt, fee_to_burn)?;
         let state: State = rt.state()?;
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
-        })?;
+        check_balance_invariants(rt, &state)?;
 
         // Power is not restored yet, but when the recovered sectors are successfully PoSted.
         Ok(())
     }
 
+    /// Reports whether a recovery declaration for the given deadline would be accepted at the
+    /// current epoch, along with the fault cutoff epoch that applies to it.
+    fn can_declare_recovery<BS, RT>(
+        rt: &mut RT,
+        params: CanDeclareRecoveryParams,
+    ) -> Result<CanDeclareRecoveryReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let policy = rt.policy();
+        let curr_epoch = rt.curr_epoch();
+        let target_deadline = declaration_deadline_info(
+            policy,
+            st.current_proving_period_start(policy, curr_epoch),
+            params.deadline,
+            curr_epoch,
+        )
+        .map_err(|e| {
+            actor_error!(
+                ErrIllegalArgument,
+                "invalid recovery declaration deadline {}: {}",
+                params.deadline,
+                e
+            )
+        })?;
+
+        Ok(CanDeclareRecoveryReturn {
+            fault_cutoff_passed: target_deadline.fault_cutoff_passed(),
+            fault_cutoff: target_deadline.fault_cutoff,
+        })
+    }
+
+    /// Returns, for each given deadline, the epoch at which sectors declared faulty there at the
+    /// current epoch would be terminated if not recovered. This does not check whether the
+    /// deadline's fault cutoff has passed.
+    fn compute_fault_expirations<BS, RT>(
+        rt: &mut RT,
+        params: ComputeFaultExpirationsParams,
+    ) -> Result<ComputeFaultExpirationsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let policy = rt.policy();
+        let curr_epoch = rt.curr_epoch();
+        let period_start = st.current_proving_period_start(policy, curr_epoch);
+
+        let expirations = params
+            .deadlines
+            .iter()
+            .map(|&deadline_idx| {
+                let target_deadline =
+                    declaration_deadline_info(policy, period_start, deadline_idx, curr_epoch)
+                        .map_err(|e| {
+                            actor_error!(
+                                ErrIllegalArgument,
+                                "invalid fault declaration deadline {}: {}",
+                                deadline_idx,
+                                e
+                            )
+                        })?;
+                Ok(target_deadline.last() + policy.fault_max_age)
+            })
+            .collect::<Result<_, ActorError>>()?;
+
+        Ok(ComputeFaultExpirationsReturn { expirations })
+    }
+
     /// Compacts a number of partitions at one deadline by removing terminated sectors, re-ordering the remaining sectors,
     /// and assigning them to new partitions so as to completely fill all but one partition with live sectors.
     /// The addressed partitions are removed from the deadline, and new ones appended.
@@ -2838,6 +4639,28 @@ impl Actor {
         Ok(())
     }
 
+    /// Unlocks vested funds and compacts the vesting table, reducing its size in state
+    /// without waiting for the next deadline cron.
+    fn compact_vesting<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let current_epoch = rt.curr_epoch();
+        rt.transaction(|state: &mut State, rt| {
+            let info = get_miner_info(rt.store(), state)?;
+
+            rt.validate_immediate_caller_is(
+                info.control_addresses.iter().chain(&[info.worker, info.owner]),
+            )?;
+
+            state.compact_vesting_funds(rt.store(), current_epoch).map_err(|e| {
+                e.downcast_default(ExitCode::ErrIllegalState, "failed to compact vesting funds")
+            })?;
+            Ok(())
+        })
+    }
+
     /// Compacts sector number allocations to reduce the size of the allocated sector
     /// number bitfield.
     ///
@@ -2849,49 +4672,109 @@ impl Actor {
     /// 99 can be masked out to collapse these two ranges into one.
     fn compact_sector_numbers<BS, RT>(
         rt: &mut RT,
-        mut params: CompactSectorNumbersParams,
+        mut params: CompactSectorNumbersParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let mask_sector_numbers = params
+            .mask_sector_numbers
+            .validate()
+            .map_err(|e| actor_error!(ErrIllegalArgument, "invalid mask bitfield: {}", e))?;
+
+        let last_sector_number = mask_sector_numbers
+            .last()
+            .ok_or_else(|| actor_error!(ErrIllegalArgument, "invalid mask bitfield"))?
+            as SectorNumber;
+
+        if last_sector_number > MAX_SECTOR_NUMBER {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "masked sector number {} exceeded max sector number",
+                last_sector_number
+            ));
+        }
+
+        rt.transaction(|state: &mut State, rt| {
+            let info = get_miner_info(rt.store(), state)?;
+
+            rt.validate_immediate_caller_is(
+                info.control_addresses.iter().chain(&[info.worker, info.owner]),
+            )?;
+
+            state.allocate_sector_numbers(
+                rt.policy(),
+                rt.store(),
+                mask_sector_numbers,
+                CollisionPolicy::AllowCollisions,
+            )?;
+            state.unreserve_sector_numbers(rt.store(), mask_sector_numbers)
+        })?;
+
+        Ok(())
+    }
+
+    /// Allocates sector numbers ahead of pre-commitment, so that they can be handed out to
+    /// sealing workers without conflict. Reserved numbers are allocated, so a worker releases
+    /// its number before pre-committing it.
+    fn reserve_sector_numbers<BS, RT>(
+        rt: &mut RT,
+        mut params: ReserveSectorNumbersParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let sector_numbers = params.sectors.validate().map_err(|e| {
+            actor_error!(ErrIllegalArgument, "failed to validate sector numbers: {}", e)
+        })?;
+        validate_sector_number_reservation(rt.policy(), sector_numbers)?;
+
+        rt.transaction(|state: &mut State, rt| {
+            let info = get_miner_info(rt.store(), state)?;
+            rt.validate_immediate_caller_is(
+                info.control_addresses.iter().chain(&[info.worker, info.owner]),
+            )?;
+
+            state
+                .reserve_sector_numbers(rt.policy(), rt.store(), sector_numbers)
+                .map_err(|e| e.wrap("failed to reserve sector numbers"))
+        })
+    }
+
+    /// Releases reserved sector numbers, to be pre-committed or handed out again. Numbers that
+    /// were not reserved are rejected, so this never frees a pre-committed or masked number.
+    fn release_sector_numbers<BS, RT>(
+        rt: &mut RT,
+        mut params: ReleaseSectorNumbersParams,
     ) -> Result<(), ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        let mask_sector_numbers = params
-            .mask_sector_numbers
-            .validate()
-            .map_err(|e| actor_error!(ErrIllegalArgument, "invalid mask bitfield: {}", e))?;
-
-        let last_sector_number = mask_sector_numbers
-            .last()
-            .ok_or_else(|| actor_error!(ErrIllegalArgument, "invalid mask bitfield"))?
-            as SectorNumber;
-
-        if last_sector_number > MAX_SECTOR_NUMBER {
-            return Err(actor_error!(
-                ErrIllegalArgument,
-                "masked sector number {} exceeded max sector number",
-                last_sector_number
-            ));
-        }
+        let sector_numbers = params.sectors.validate().map_err(|e| {
+            actor_error!(ErrIllegalArgument, "failed to validate sector numbers: {}", e)
+        })?;
+        validate_sector_number_reservation(rt.policy(), sector_numbers)?;
 
         rt.transaction(|state: &mut State, rt| {
             let info = get_miner_info(rt.store(), state)?;
-
             rt.validate_immediate_caller_is(
                 info.control_addresses.iter().chain(&[info.worker, info.owner]),
             )?;
 
-            state.allocate_sector_numbers(
-                rt.store(),
-                mask_sector_numbers,
-                CollisionPolicy::AllowCollisions,
-            )
-        })?;
-
-        Ok(())
+            state
+                .release_sector_numbers(rt.store(), sector_numbers)
+                .map_err(|e| e.wrap("failed to release sector numbers"))
+        })
     }
 
     /// Locks up some amount of a the miner's unlocked balance (including funds received alongside the invoking message).
-    fn apply_rewards<BS, RT>(rt: &mut RT, params: ApplyRewardParams) -> Result<(), ActorError>
+    fn apply_rewards<BS, RT>(
+        rt: &mut RT,
+        params: ApplyRewardParams,
+    ) -> Result<ApplyRewardReturn, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
@@ -2908,14 +4791,37 @@ impl Actor {
                 "cannot penalize a negative amount of funds"
             ));
         }
+        if params.memo.len() > REWARD_MEMO_MAX_SIZE {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "reward memo of {} bytes exceeds max {}",
+                params.memo.len(),
+                REWARD_MEMO_MAX_SIZE
+            ));
+        }
 
-        let (pledge_delta_total, to_burn) = rt.transaction(|st: &mut State, rt| {
+        let (pledge_delta_total, ret) = rt.transaction(|st: &mut State, rt| {
             let mut pledge_delta_total = TokenAmount::zero();
 
             rt.validate_immediate_caller_is(std::iter::once(&*REWARD_ACTOR_ADDR))?;
 
+            if rt.policy().record_reward_applications {
+                let application = RewardApplication {
+                    epoch: rt.curr_epoch(),
+                    reward: params.reward.clone(),
+                    penalty: params.penalty.clone(),
+                    memo: params.memo,
+                };
+                st.record_reward_application(rt.store(), application).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::ErrIllegalState,
+                        "failed to record reward application",
+                    )
+                })?;
+            }
+
             let (reward_to_lock, locked_reward_vesting_spec) =
-                locked_reward_from_reward(params.reward);
+                locked_reward_from_reward(rt.policy(), params.reward);
 
             // This ensures the miner has sufficient funds to lock up amountToLock.
             // This should always be true if reward actor sends reward funds with the message.
@@ -2962,16 +4868,14 @@ impl Actor {
                 })?;
             pledge_delta_total -= &penalty_from_vesting;
             let to_burn = penalty_from_vesting + penalty_from_balance;
-            Ok((pledge_delta_total, to_burn))
+            Ok((pledge_delta_total, ApplyRewardReturn { newly_vested, reward_to_lock, to_burn }))
         })?;
 
         notify_pledge_changed(rt, &pledge_delta_total)?;
-        burn_funds(rt, to_burn)?;
+        burn_funds(rt, ret.to_burn.clone())?;
         let st: State = rt.state()?;
-        st.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
-        })?;
-        Ok(())
+        check_balance_invariants(rt, &st)?;
+        Ok(ret)
     }
 
     fn report_consensus_fault<BS, RT>(
@@ -3074,9 +4978,7 @@ impl Actor {
         notify_pledge_changed(rt, &pledge_delta)?;
 
         let state: State = rt.state()?;
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
-        })?;
+        check_balance_invariants(rt, &state)?;
         Ok(())
     }
 
@@ -3162,13 +5064,11 @@ impl Actor {
         burn_funds(rt, fee_to_burn)?;
         notify_pledge_changed(rt, &newly_vested.neg())?;
 
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
-        })?;
+        check_balance_invariants(rt, &state)?;
         Ok(WithdrawBalanceReturn { amount_withdrawn: amount_withdrawn.clone() })
     }
 
-    fn repay_debt<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
+    fn repay_debt<BS, RT>(rt: &mut RT) -> Result<RepayDebtReturn, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
@@ -3197,10 +5097,8 @@ impl Actor {
         notify_pledge_changed(rt, &from_vesting.neg())?;
         burn_funds(rt, burn_amount)?;
 
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
-        })?;
-        Ok(())
+        check_balance_invariants(rt, &state)?;
+        Ok(RepayDebtReturn { from_vesting })
     }
 
     fn on_deferred_cron_event<BS, RT>(
@@ -3221,11 +5119,22 @@ impl Actor {
         })?;
 
         match payload.event_type {
-            CRON_EVENT_PROVING_DEADLINE => handle_proving_deadline(
-                rt,
-                &params.reward_smoothed,
-                &params.quality_adj_power_smoothed,
-            )?,
+            CRON_EVENT_PROVING_DEADLINE => {
+                let summary = handle_proving_deadline(
+                    rt,
+                    &params.reward_smoothed,
+                    &params.quality_adj_power_smoothed,
+                )?;
+                info!(
+                    "miner {} processed deadline {}: power delta raw {} qa {}, \
+                    continued fault penalty {}",
+                    rt.message().receiver(),
+                    summary.deadline_idx,
+                    summary.power_delta.raw,
+                    summary.power_delta.qa,
+                    summary.continued_fault_penalty
+                );
+            }
             CRON_EVENT_PROCESS_EARLY_TERMINATIONS => {
                 if process_early_terminations(
                     rt,
@@ -3240,9 +5149,7 @@ impl Actor {
             }
         };
         let state: State = rt.state()?;
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
-        })?;
+        check_balance_invariants(rt, &state)?;
         Ok(())
     }
 }
@@ -3304,6 +5211,7 @@ where
                     .map_err(|e| e.wrap("failed to load sector infos"))?;
 
                 penalty += termination_penalty(
+                    rt.policy(),
                     info.sector_size,
                     epoch,
                     reward_smoothed,
@@ -3381,12 +5289,22 @@ where
     Ok(more)
 }
 
+/// The outcome of processing a proving deadline at its close.
+struct DeadlineCronSummary {
+    /// Index of the deadline that was processed.
+    deadline_idx: u64,
+    /// Power change from faults detected and sectors expired at the deadline.
+    power_delta: PowerPair,
+    /// Fee charged for sectors that remained faulty through the deadline.
+    continued_fault_penalty: TokenAmount,
+}
+
 /// Invoked at the end of the last epoch for each proving deadline.
 fn handle_proving_deadline<BS, RT>(
     rt: &mut RT,
     reward_smoothed: &FilterEstimate,
     quality_adj_power_smoothed: &FilterEstimate,
-) -> Result<(), ActorError>
+) -> Result<DeadlineCronSummary, ActorError>
 where
     BS: Blockstore,
     RT: Runtime<BS>,
@@ -3399,6 +5317,11 @@ where
     let mut penalty_total = TokenAmount::zero();
     let mut pledge_delta_total = TokenAmount::zero();
     let mut continue_cron = false;
+    let mut summary = DeadlineCronSummary {
+        deadline_idx: 0,
+        power_delta: PowerPair::zero(),
+        continued_fault_penalty: TokenAmount::zero(),
+    };
 
     let state: State = rt.transaction(|state: &mut State, rt| {
         let policy = rt.policy();
@@ -3438,20 +5361,28 @@ where
         // That way, don't re-schedule a cron callback if one is already scheduled.
         had_early_terminations = have_pending_early_terminations(state);
 
+        summary.deadline_idx = state.deadline_info(policy, rt.curr_epoch()).index;
         let result = state.advance_deadline(policy, rt.store(), rt.curr_epoch()).map_err(|e| {
             e.downcast_default(ExitCode::ErrIllegalState, "failed to advance deadline")
         })?;
 
         // Faults detected by this missed PoSt pay no penalty, but sectors that were already faulty
-        // and remain faulty through this deadline pay the fault fee.
-        let penalty_target = pledge_penalty_for_continued_fault(
-            reward_smoothed,
-            quality_adj_power_smoothed,
-            &result.previously_faulty_power.qa,
-        );
+        // and remain faulty through this deadline pay the fault fee, unless within the policy's
+        // recovery grace.
+        let penalty_target = if policy.in_continued_fault_grace(rt.curr_epoch()) {
+            TokenAmount::zero()
+        } else {
+            pledge_penalty_for_continued_fault(
+                reward_smoothed,
+                quality_adj_power_smoothed,
+                &result.previously_faulty_power.qa,
+            )
+        };
 
         power_delta_total += &result.power_delta;
         pledge_delta_total += &result.pledge_delta;
+        summary.power_delta = result.power_delta;
+        summary.continued_fault_penalty = penalty_target.clone();
 
         state
             .apply_penalty(&penalty_target)
@@ -3491,10 +5422,10 @@ where
 
     // Schedule cron callback for next deadline's last epoch.
     if continue_cron {
-        let new_deadline_info = state.deadline_info(rt.policy(), curr_epoch + 1);
+        let next_cron_epoch = state.next_deadline_cron_epoch(rt.policy(), curr_epoch);
         enroll_cron_event(
             rt,
-            new_deadline_info.last(),
+            next_cron_epoch,
             CronEventPayload { event_type: CRON_EVENT_PROVING_DEADLINE },
         )?;
     } else {
@@ -3518,7 +5449,7 @@ where
         // processed AddressedSectorsMax terminations this epoch.
     }
 
-    Ok(())
+    Ok(summary)
 }
 
 /// Check expiry is exactly *the epoch before* the start of a proving period.
@@ -3533,6 +5464,9 @@ where
     RT: Runtime<BS>,
 {
     let policy = rt.policy();
+    let nv = rt.network_version();
+    let min_sector_expiration = policy.min_sector_expiration(nv);
+    let max_sector_expiration_extension = policy.max_sector_expiration_extension(nv);
 
     // Expiration must be after activation. Check this explicitly to avoid an underflow below.
     if expiration <= activation {
@@ -3545,24 +5479,24 @@ where
     }
 
     // expiration cannot be less than minimum after activation
-    if expiration - activation < policy.min_sector_expiration {
+    if expiration - activation < min_sector_expiration {
         return Err(actor_error!(
             ErrIllegalArgument,
             "invalid expiration {}, total sector lifetime ({}) must exceed {} after activation {}",
             expiration,
             expiration - activation,
-            policy.min_sector_expiration,
+            min_sector_expiration,
             activation
         ));
     }
 
     // expiration cannot exceed MaxSectorExpirationExtension from now
-    if expiration > rt.curr_epoch() + policy.max_sector_expiration_extension {
+    if expiration > rt.curr_epoch() + max_sector_expiration_extension {
         return Err(actor_error!(
             ErrIllegalArgument,
             "invalid expiration {}, cannot be more than {} past current epoch {}",
             expiration,
-            policy.max_sector_expiration_extension,
+            max_sector_expiration_extension,
             rt.curr_epoch()
         ));
     }
@@ -3707,6 +5641,18 @@ where
         return Ok(());
     }
 
+    // Catch power accounting bugs here rather than corrupting the power actor's claims. The
+    // bound comes from the policy alone, so that no state need be loaded.
+    let max_power = max_miner_power(rt.policy());
+    if delta.raw.abs() > max_power.raw || delta.qa.abs() > max_power.qa {
+        return Err(actor_error!(
+            ErrIllegalState,
+            "power delta {:?} exceeds maximum miner power {:?}",
+            delta,
+            max_power
+        ));
+    }
+
     let delta_clone = delta.clone();
 
     rt.send(
@@ -3771,6 +5717,7 @@ fn have_pending_early_terminations(state: &State) -> bool {
 fn verify_windowed_post<BS, RT>(
     rt: &RT,
     challenge_epoch: ChainEpoch,
+    window_post_proof_type: RegisteredPoStProof,
     sectors: &[SectorOnChainInfo],
     proofs: Vec<PoStProof>,
 ) -> Result<bool, ActorError>
@@ -3778,6 +5725,29 @@ where
     BS: Blockstore,
     RT: Runtime<BS>,
 {
+    // Every challenged sector must be provable with the miner's window PoSt proof type, so a
+    // proof over any other sector is invalid. Checking up front names the offending sector
+    // rather than failing the proof as a whole.
+    for sector in sectors {
+        match sector.seal_proof.registered_window_post_proof() {
+            Ok(sector_post_proof) if sector_post_proof == window_post_proof_type => {}
+            Ok(sector_post_proof) => {
+                warn!(
+                    "sector {} seal proof {:?} requires window PoSt proof type {:?}, miner uses {:?}",
+                    sector.sector_number, sector.seal_proof, sector_post_proof, window_post_proof_type
+                );
+                return Ok(false);
+            }
+            Err(e) => {
+                warn!(
+                    "no window PoSt proof type for sector {} seal proof {:?}: {}",
+                    sector.sector_number, sector.seal_proof, e
+                );
+                return Ok(false);
+            }
+        }
+    }
+
     let miner_actor_id: u64 = if let Payload::ID(i) = rt.message().receiver().payload() {
         *i
     } else {
@@ -3834,13 +5804,24 @@ where
         return Err(actor_error!(ErrForbidden, "too early to prove sector"));
     }
 
-    let commds = request_unsealed_sector_cids(
-        rt,
-        &[ext::market::SectorDataSpec {
-            deal_ids: params.deal_ids.clone(),
-            sector_type: params.registered_seal_proof,
-        }],
-    )?;
+    // A sector without deals is all zeros, so its CommD is computed locally rather than
+    // asking the market actor.
+    let unsealed_cid = if params.deal_ids.is_empty() {
+        rt.compute_unsealed_sector_cid(params.registered_seal_proof, &[]).map_err(|e| {
+            e.downcast_default(
+                ExitCode::ErrIllegalArgument,
+                "failed to compute zero unsealed sector CID",
+            )
+        })?
+    } else {
+        request_unsealed_sector_cids(
+            rt,
+            &[ext::market::SectorDataSpec {
+                deal_ids: params.deal_ids.clone(),
+                sector_type: params.registered_seal_proof,
+            }],
+        )?[0]
+    };
 
     let miner_actor_id: u64 = if let Payload::ID(i) = rt.message().receiver().payload() {
         *i
@@ -3874,7 +5855,7 @@ where
         proof: params.proof,
         randomness,
         sealed_cid: params.sealed_cid,
-        unsealed_cid: commds[0],
+        unsealed_cid,
     })
 }
 
@@ -3932,6 +5913,8 @@ where
                 deal_space: 0,
                 deal_weight: 0.into(),
                 verified_deal_weight: 0.into(),
+                verified_deal_space: 0,
+                deal_count: 0,
             });
         }
         return Ok(empty_result);
@@ -4070,6 +6053,20 @@ where
     Ok(resolved)
 }
 
+/// Checks that the miner's balance covers its locked funds, unless disabled by the policy.
+fn check_balance_invariants<BS, RT>(rt: &RT, state: &State) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    if !rt.policy().check_balance_invariants {
+        return Ok(());
+    }
+    state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
+        ActorError::new(ErrBalanceInvariantBroken, format!("balance invariants broken: {}", e))
+    })
+}
+
 fn burn_funds<BS, RT>(rt: &mut RT, amount: TokenAmount) -> Result<(), ActorError>
 where
     BS: Blockstore,
@@ -4191,33 +6188,6 @@ fn validate_partition_contains_sectors(
     }
 }
 
-fn termination_penalty(
-    sector_size: SectorSize,
-    current_epoch: ChainEpoch,
-    reward_estimate: &FilterEstimate,
-    network_qa_power_estimate: &FilterEstimate,
-    sectors: &[SectorOnChainInfo],
-) -> TokenAmount {
-    let mut total_fee = TokenAmount::zero();
-
-    for sector in sectors {
-        let sector_power = qa_power_for_sector(sector_size, sector);
-        let fee = pledge_penalty_for_termination(
-            &sector.expected_day_reward,
-            current_epoch - sector.activation,
-            &sector.expected_storage_pledge,
-            network_qa_power_estimate,
-            &sector_power,
-            reward_estimate,
-            &sector.replaced_day_reward,
-            sector.replaced_sector_age,
-        );
-        total_fee += fee;
-    }
-
-    total_fee
-}
-
 fn consensus_fault_active(info: &MinerInfo, curr_epoch: ChainEpoch) -> bool {
     // For penalization period to last for exactly finality epochs
     // consensus faults are active until currEpoch exceeds ConsensusFaultElapsed
@@ -4306,6 +6276,60 @@ fn check_control_addresses(policy: &Policy, control_addrs: &[Address]) -> Result
     Ok(())
 }
 
+/// Checks a set of sector numbers to reserve or release is non-empty and bounded.
+fn validate_sector_number_reservation(
+    policy: &Policy,
+    sector_numbers: &BitField,
+) -> Result<(), ActorError> {
+    let count = sector_numbers.len();
+    if count == 0 {
+        return Err(actor_error!(ErrIllegalArgument, "no sector numbers"));
+    }
+    if count > policy.addressed_sectors_max {
+        return Err(actor_error!(
+            ErrIllegalArgument,
+            "too many sector numbers {}, max {}",
+            count,
+            policy.addressed_sectors_max
+        ));
+    }
+    if let Some(last) = sector_numbers.last() {
+        if last > MAX_SECTOR_NUMBER {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "sector number {} exceeded max sector number",
+                last
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether Window PoSt chain commit randomness should go unchecked. This is only ever possible
+/// in builds with the `no-post-commit-rand-check` feature, which exists for test vectors.
+#[cfg(feature = "no-post-commit-rand-check")]
+fn skip_post_commit_rand_check(policy: &Policy) -> bool {
+    policy.skip_post_commit_rand_check
+}
+
+#[cfg(not(feature = "no-post-commit-rand-check"))]
+fn skip_post_commit_rand_check(_policy: &Policy) -> bool {
+    false
+}
+
+/// The proving period offset new miners are assigned regardless of address and epoch, if any.
+/// This is only ever set in builds with the `fixed-proving-period-offset` feature, which exists
+/// for integration tests.
+#[cfg(feature = "fixed-proving-period-offset")]
+fn fixed_proving_period_offset(policy: &Policy) -> Option<ChainEpoch> {
+    policy.fixed_proving_period_offset
+}
+
+#[cfg(not(feature = "fixed-proving-period-offset"))]
+fn fixed_proving_period_offset(_policy: &Policy) -> Option<ChainEpoch> {
+    None
+}
+
 fn check_valid_post_proof_type(
     policy: &Policy,
     proof_type: RegisteredPoStProof,
@@ -4361,7 +6385,7 @@ fn confirm_sector_proofs_valid_internal<BS, RT>(
     this_epoch_baseline_power: &BigInt,
     this_epoch_reward_smoothed: &FilterEstimate,
     quality_adj_power_smoothed: &FilterEstimate,
-) -> Result<(), ActorError>
+) -> Result<TokenAmount, ActorError>
 where
     BS: Blockstore,
     RT: Runtime<BS>,
@@ -4369,6 +6393,39 @@ where
     // get network stats from other actors
     let circulating_supply = rt.total_fil_circ_supply();
 
+    // Skip sectors that are already proven, so that a re-delivered confirmation doesn't attempt
+    // to activate their deals again.
+    let requested_count = pre_commits.len();
+    let pre_commits = {
+        let st: State = rt.state()?;
+        let sectors = Sectors::load(rt.store(), &st.sectors).map_err(|e| {
+            e.downcast_default(ExitCode::ErrIllegalState, "failed to load sectors array")
+        })?;
+        let mut unproven = Vec::with_capacity(pre_commits.len());
+        for pre_commit in pre_commits {
+            let existing = sectors.get(pre_commit.info.sector_number).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load sector {}", pre_commit.info.sector_number),
+                )
+            })?;
+            if existing.is_some() {
+                warn!(
+                    "sector {} already proven, skipping duplicate confirmation",
+                    pre_commit.info.sector_number
+                );
+                continue;
+            }
+            unproven.push(pre_commit);
+        }
+        unproven
+    };
+
+    // A confirmation consisting only of already proven sectors is a no-op.
+    if requested_count > 0 && pre_commits.is_empty() {
+        return Ok(TokenAmount::zero());
+    }
+
     // Ideally, we'd combine some of these operations, but at least we have
     // a constant number of them.
     let activation = rt.curr_epoch();
@@ -4406,25 +6463,27 @@ where
         return Err(actor_error!(ErrIllegalArgument, "all prove commits failed to validate"));
     }
 
-    let (total_pledge, newly_vested) = rt.transaction(|state: &mut State, rt| {
+    let (total_pledge, newly_vested, grace_fee) = rt.transaction(|state: &mut State, rt| {
         let policy = rt.policy();
         let store = rt.store();
         let info = get_miner_info(store, state)?;
+        let min_sector_expiration = policy.min_sector_expiration(rt.network_version());
 
         let mut new_sector_numbers = Vec::<SectorNumber>::with_capacity(valid_pre_commits.len());
         let mut deposit_to_unlock = TokenAmount::zero();
         let mut new_sectors = Vec::<SectorOnChainInfo>::new();
         let mut total_pledge = TokenAmount::zero();
+        let mut grace_fee = TokenAmount::zero();
 
         for pre_commit in valid_pre_commits {
             // compute initial pledge
             let duration = pre_commit.info.expiration - activation;
 
             // This should have been caught in precommit, but don't let other sectors fail because of it.
-            if duration < policy.min_sector_expiration {
+            if duration < min_sector_expiration {
                 warn!(
                     "precommit {} has lifetime {} less than minimum {}. ignoring",
-                    pre_commit.info.sector_number, duration, policy.min_sector_expiration,
+                    pre_commit.info.sector_number, duration, min_sector_expiration,
                 );
                 continue;
             }
@@ -4461,6 +6520,22 @@ where
                 &circulating_supply,
             );
 
+            // Sectors activating after their prove-commit due epoch pay the late fee.
+            let msd = max_prove_commit_duration(policy, pre_commit.info.seal_proof).ok_or_else(
+                || {
+                    actor_error!(
+                        ErrIllegalState,
+                        "no max seal duration set for proof type: {:?}",
+                        pre_commit.info.seal_proof
+                    )
+                },
+            )?;
+            grace_fee += late_prove_commit_fee(
+                &pre_commit.pre_commit_deposit,
+                activation - (pre_commit.pre_commit_epoch + msd),
+                policy.prove_commit_grace,
+            );
+
             deposit_to_unlock += &pre_commit.pre_commit_deposit;
             total_pledge += &initial_pledge;
 
@@ -4519,11 +6594,12 @@ where
         let unlocked_balance = state.get_unlocked_balance(&rt.current_balance()).map_err(|e| {
             actor_error!(ErrIllegalState, "failed to calculate unlocked balance: {}", e)
         })?;
-        if unlocked_balance < total_pledge {
+        if unlocked_balance < &total_pledge + &grace_fee {
             return Err(actor_error!(
                 ErrInsufficientFunds,
-                "insufficient funds for aggregate initial pledge requirement {}, available: {}",
+                "insufficient funds for aggregate initial pledge requirement {} and late prove-commit fee {}, available: {}",
                 total_pledge,
+                grace_fee,
                 unlocked_balance
             ));
         }
@@ -4532,17 +6608,16 @@ where
             .add_initial_pledge(&total_pledge)
             .map_err(|e| actor_error!(ErrIllegalState, "failed to add initial pledge: {}", e))?;
 
-        state.check_balance_invariants(&rt.current_balance()).map_err(|e| {
-            ActorError::new(ErrBalanceInvariantBroken, format!("balance invariant broken: {}", e))
-        })?;
+        check_balance_invariants(rt, &state)?;
 
-        Ok((total_pledge, newly_vested))
+        Ok((total_pledge, newly_vested, grace_fee))
     })?;
 
     // Request pledge update for activated sector.
     notify_pledge_changed(rt, &(total_pledge - newly_vested))?;
 
-    Ok(())
+    // The late fee is left for the caller to burn along with any other fees.
+    Ok(grace_fee)
 }
 
 impl ActorCode for Actor {
@@ -4573,7 +6648,7 @@ impl ActorCode for Actor {
                 Ok(RawBytes::default())
             }
             Some(Method::SubmitWindowedPoSt) => {
-                Self::submit_windowed_post(rt, rt.deserialize_params(params)?)?;
+                Self::submit_windowed_post(rt, rt.deserialize_params(params)?, Vec::new())?;
                 Ok(RawBytes::default())
             }
             Some(Method::PreCommitSector) => {
@@ -4581,8 +6656,8 @@ impl ActorCode for Actor {
                 Ok(RawBytes::default())
             }
             Some(Method::ProveCommitSector) => {
-                Self::prove_commit_sector(rt, rt.deserialize_params(params)?)?;
-                Ok(RawBytes::default())
+                let res = Self::prove_commit_sector(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
             }
             Some(Method::ExtendSectorExpiration) => {
                 Self::extend_sector_expiration(rt, rt.deserialize_params(params)?)?;
@@ -4609,8 +6684,8 @@ impl ActorCode for Actor {
                 Ok(RawBytes::default())
             }
             Some(Method::ApplyRewards) => {
-                Self::apply_rewards(rt, rt.deserialize_params(params)?)?;
-                Ok(RawBytes::default())
+                let res = Self::apply_rewards(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
             }
             Some(Method::ReportConsensusFault) => {
                 Self::report_consensus_fault(rt, rt.deserialize_params(params)?)?;
@@ -4641,11 +6716,15 @@ impl ActorCode for Actor {
                 Ok(RawBytes::default())
             }
             Some(Method::RepayDebt) => {
-                Self::repay_debt(rt)?;
-                Ok(RawBytes::default())
+                let res = Self::repay_debt(rt)?;
+                Ok(RawBytes::serialize(res)?)
             }
             Some(Method::ChangeOwnerAddress) => {
-                Self::change_owner_address(rt, rt.deserialize_params(params)?)?;
+                let params = ChangeOwnerAddressWithExpiryParams {
+                    new_address: rt.deserialize_params(params)?,
+                    expiration: None,
+                };
+                Self::change_owner_address(rt, params)?;
                 Ok(RawBytes::default())
             }
             Some(Method::DisputeWindowedPoSt) => {
@@ -4657,13 +6736,186 @@ impl ActorCode for Actor {
                 Ok(RawBytes::default())
             }
             Some(Method::ProveCommitAggregate) => {
-                Self::prove_commit_aggregate(rt, rt.deserialize_params(params)?)?;
-                Ok(RawBytes::default())
+                let res = Self::prove_commit_aggregate(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
             }
             Some(Method::ProveReplicaUpdates) => {
                 let res = Self::prove_replica_updates(rt, rt.deserialize_params(params)?)?;
                 Ok(RawBytes::serialize(res)?)
             }
+            Some(Method::CheckSectorsProven) => {
+                let res = Self::check_sectors_proven(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetSectorInfos) => {
+                let res = Self::get_sector_infos(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetPreCommitInfo) => {
+                let res = Self::get_pre_commit_info(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetSectorHealth) => {
+                let res = Self::get_sector_health(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::SubmitWindowedPoStWithRecoveries) => {
+                let params: SubmitWindowedPoStWithRecoveriesParams =
+                    rt.deserialize_params(params)?;
+                Self::submit_windowed_post(rt, params.post, params.recoveries)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetMinerInfo) => {
+                let res = Self::get_miner_info(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::NextAllocatableSectorNumber) => {
+                let res = Self::next_allocatable_sector_number(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ChangeNetworkAddresses) => {
+                Self::change_network_addresses(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetActiveDeals) => {
+                let res = Self::get_active_deals(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::DeadlineInfoAt) => {
+                let res = Self::deadline_info_at(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::UpcomingDeadlines) => {
+                let res = Self::upcoming_deadlines(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetUpgradedSectors) => {
+                let res = Self::get_upgraded_sectors(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::CheckTerminationAllowed) => {
+                let res = Self::check_termination_allowed(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::EmergencyChangeWorkerAddress) => {
+                Self::emergency_change_worker_address(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::AbandonPreCommits) => {
+                Self::abandon_pre_commits(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetEarlyTerminations) => {
+                let res = Self::get_early_terminations(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetFaultState) => {
+                let res = Self::get_fault_state(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ProjectSectorReward) => {
+                let res = Self::project_sector_reward(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetDeadlineExpirationSchedule) => {
+                let res =
+                    Self::get_deadline_expiration_schedule(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ReserveSectorNumbers) => {
+                Self::reserve_sector_numbers(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::ReleaseSectorNumbers) => {
+                Self::release_sector_numbers(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::EstimateAggregateProveCommitFee) => {
+                let res =
+                    Self::estimate_aggregate_prove_commit_fee(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::EstimateAggregatePreCommitFee) => {
+                let res =
+                    Self::estimate_aggregate_pre_commit_fee(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ConsensusFaultStatus) => {
+                let res = Self::consensus_fault_status(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetSectorsExpiringInRange) => {
+                let res = Self::get_sectors_expiring_in_range(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ChangeOwnerAddressWithExpiry) => {
+                Self::change_owner_address(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::EstimateInitialPledge) => {
+                let res = Self::estimate_initial_pledge(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetDisputeInfo) => {
+                let res = Self::get_dispute_info(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::CompactVesting) => {
+                Self::compact_vesting(rt)?;
+                Ok(RawBytes::default())
+            }
+            Some(Method::GetPledgeSummary) => {
+                let res = Self::get_pledge_summary(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::CanDeclareRecovery) => {
+                let res = Self::can_declare_recovery(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ComputeFaultExpirations) => {
+                let res = Self::compute_fault_expirations(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetSectorCounts) => {
+                let res = Self::get_sector_counts(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetDeadlineLayout) => {
+                let res = Self::get_deadline_layout(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetRecentRewards) => {
+                let res = Self::get_recent_rewards(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetPendingChanges) => {
+                let res = Self::get_pending_changes(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ProveReplicaUpdatesBatch) => {
+                let res = Self::prove_replica_updates_batch(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::ReclaimExpiredPreCommitNumbers) => {
+                let res = Self::reclaim_expired_pre_commit_numbers(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetProvingPeriodInfo) => {
+                let res = Self::get_proving_period_info(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::WithdrawDisputeReward) => {
+                let res = Self::withdraw_dispute_reward(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetDebtStatus) => {
+                let res = Self::get_debt_status(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::TerminateWorstSectors) => {
+                let res = Self::terminate_worst_sectors(rt, rt.deserialize_params(params)?)?;
+                Ok(RawBytes::serialize(res)?)
+            }
             None => Err(actor_error!(SysErrInvalidMethod, "Invalid method")),
         }
     }
@@ -0,0 +1,133 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::anyhow;
+use bitfield::BitField;
+use cid::multihash::Code;
+use cid::Cid;
+use fil_actors_runtime::{make_empty_map, make_map_with_root_and_bitwidth};
+use fvm_shared::address::Address;
+use fvm_shared::bigint::bigint_ser::{self, BigIntDe};
+use fvm_shared::blockstore::{Blockstore, CborStore};
+use fvm_shared::clock::{ChainEpoch, EPOCH_UNDEFINED};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::tuple::*;
+use fvm_shared::encoding::{serde_bytes, BytesDe, Cbor};
+use fvm_shared::sector::{RegisteredPoStProof, SectorSize};
+use fvm_shared::HAMT_BIT_WIDTH;
+
+use super::{MinerInfo, RewardApplication, SectorPreCommitOnChainInfo, State, WorkerKeyChange};
+
+/// Miner state in the layout written before the pre-commit count, reserved sector numbers, owed
+/// dispute rewards and recent reward applications were added. State in this layout does not decode as [`State`],
+/// so every miner's state must be converted with [`migrate_state`] in the network upgrade that
+/// ships this version of the actor.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct PriorState {
+    pub info: Cid,
+    #[serde(with = "bigint_ser")]
+    pub pre_commit_deposits: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub locked_funds: TokenAmount,
+    pub vesting_funds: Cid,
+    #[serde(with = "bigint_ser")]
+    pub fee_debt: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub initial_pledge: TokenAmount,
+    pub pre_committed_sectors: Cid,
+    pub pre_committed_sectors_cleanup: Cid,
+    pub allocated_sectors: Cid,
+    pub sectors: Cid,
+    pub proving_period_start: ChainEpoch,
+    pub current_deadline: u64,
+    pub deadlines: Cid,
+    pub early_terminations: BitField,
+    pub deadline_cron_active: bool,
+}
+
+impl Cbor for PriorState {}
+
+/// Miner info in the layout written before pending owner proposals could expire and emergency
+/// worker changes were recorded. Info in this layout does not decode as [`MinerInfo`], so it is
+/// converted along with the state by [`migrate_state`].
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct PriorMinerInfo {
+    pub owner: Address,
+    pub worker: Address,
+    pub control_addresses: Vec<Address>,
+    pub pending_worker_key: Option<WorkerKeyChange>,
+    #[serde(with = "serde_bytes")]
+    pub peer_id: Vec<u8>,
+    pub multi_address: Vec<BytesDe>,
+    pub window_post_proof_type: RegisteredPoStProof,
+    pub sector_size: SectorSize,
+    pub window_post_partition_sectors: u64,
+    pub consensus_fault_elapsed: ChainEpoch,
+    pub pending_owner_address: Option<Address>,
+}
+
+impl Cbor for PriorMinerInfo {}
+
+/// Converts miner state from the prior layout. The pre-commit count is taken from the existing
+/// pre-commitments. The other added fields start out empty: no sector numbers are reserved, no
+/// dispute rewards are owed and no reward applications are recorded. The miner info is rewritten
+/// with no expiration for a pending owner proposal and no recorded emergency worker change.
+pub fn migrate_state<BS: Blockstore>(store: &BS, prior: PriorState) -> anyhow::Result<State> {
+    let prior_info: PriorMinerInfo = store
+        .get_cbor(&prior.info)?
+        .ok_or_else(|| anyhow!("failed to load prior miner info {}", prior.info))?;
+    let info = store.put_cbor(
+        &MinerInfo {
+            owner: prior_info.owner,
+            worker: prior_info.worker,
+            control_addresses: prior_info.control_addresses,
+            pending_worker_key: prior_info.pending_worker_key,
+            peer_id: prior_info.peer_id,
+            multi_address: prior_info.multi_address,
+            window_post_proof_type: prior_info.window_post_proof_type,
+            sector_size: prior_info.sector_size,
+            window_post_partition_sectors: prior_info.window_post_partition_sectors,
+            consensus_fault_elapsed: prior_info.consensus_fault_elapsed,
+            pending_owner_address: prior_info.pending_owner_address,
+            pending_owner_expiration: None,
+            last_emergency_worker_change: EPOCH_UNDEFINED,
+        },
+        Code::Blake2b256,
+    )?;
+    let precommitted = make_map_with_root_and_bitwidth::<_, SectorPreCommitOnChainInfo>(
+        &prior.pre_committed_sectors,
+        store,
+        HAMT_BIT_WIDTH,
+    )?;
+    let mut pre_committed_sector_count = 0;
+    precommitted.for_each(|_, _| {
+        pre_committed_sector_count += 1;
+        Ok(())
+    })?;
+    let reserved_sector_numbers = store.put_cbor(&BitField::new(), Code::Blake2b256)?;
+    let recent_rewards = store.put_cbor(&Vec::<RewardApplication>::new(), Code::Blake2b256)?;
+    let owed_dispute_rewards = make_empty_map::<_, BigIntDe>(store, HAMT_BIT_WIDTH).flush()?;
+
+    Ok(State {
+        info,
+        pre_commit_deposits: prior.pre_commit_deposits,
+        locked_funds: prior.locked_funds,
+        vesting_funds: prior.vesting_funds,
+        fee_debt: prior.fee_debt,
+        initial_pledge: prior.initial_pledge,
+        pre_committed_sectors: prior.pre_committed_sectors,
+        pre_committed_sector_count,
+        pre_committed_sectors_cleanup: prior.pre_committed_sectors_cleanup,
+        allocated_sectors: prior.allocated_sectors,
+        reserved_sector_numbers,
+        sectors: prior.sectors,
+        proving_period_start: prior.proving_period_start,
+        current_deadline: prior.current_deadline,
+        deadlines: prior.deadlines,
+        early_terminations: prior.early_terminations,
+        deadline_cron_active: prior.deadline_cron_active,
+        owed_dispute_rewards,
+        total_owed_dispute_rewards: TokenAmount::default(),
+        recent_rewards,
+    })
+}
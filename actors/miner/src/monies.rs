@@ -4,19 +4,20 @@
 use std::cmp::{self, max};
 
 use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::runtime::Policy;
 use fil_actors_runtime::EXPECTED_LEADERS_PER_EPOCH;
 use fvm_shared::bigint::num_integer::div_floor;
 use fvm_shared::bigint::{BigInt, Integer};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::math::PRECISION;
-use fvm_shared::sector::StoragePower;
+use fvm_shared::sector::{SectorSize, StoragePower};
 use fvm_shared::smooth::{self, FilterEstimate};
 use fvm_shared::FILECOIN_PRECISION;
 use lazy_static::lazy_static;
 use num_traits::Zero;
 
-use super::{VestSpec, REWARD_VESTING_SPEC};
+use super::{qa_power_for_sector, SectorOnChainInfo, VestSpec};
 
 /// Projection period of expected sector block reward for deposit required to pre-commit a sector.
 /// This deposit is lost if the pre-commitment is not timely followed up by a commitment proof.
@@ -204,6 +205,49 @@ pub fn pledge_penalty_for_termination(
     )
 }
 
+/// Sums the termination penalties of sectors terminated at an epoch. The total is clamped to
+/// the sectors' initial pledge plus the policy's multiple of it, guarding against a penalty
+/// formula regression draining more than the sectors put up.
+pub fn termination_penalty(
+    policy: &Policy,
+    sector_size: SectorSize,
+    current_epoch: ChainEpoch,
+    reward_estimate: &FilterEstimate,
+    network_qa_power_estimate: &FilterEstimate,
+    sectors: &[SectorOnChainInfo],
+) -> TokenAmount {
+    let mut total_fee = TokenAmount::zero();
+    let mut total_pledge = TokenAmount::zero();
+
+    for sector in sectors {
+        let sector_power = qa_power_for_sector(sector_size, sector);
+        let fee = pledge_penalty_for_termination(
+            &sector.expected_day_reward,
+            current_epoch - sector.activation,
+            &sector.expected_storage_pledge,
+            network_qa_power_estimate,
+            &sector_power,
+            reward_estimate,
+            &sector.replaced_day_reward,
+            sector.replaced_sector_age,
+        );
+        total_fee += fee;
+        total_pledge += &sector.initial_pledge;
+    }
+
+    let max_fee = &total_pledge + &total_pledge * policy.termination_penalty_pledge_multiple;
+    if total_fee > max_fee {
+        log::warn!(
+            "termination penalty {} for {} sectors exceeds cap {}, clamping",
+            total_fee,
+            sectors.len(),
+            max_fee
+        );
+        return max_fee;
+    }
+    total_fee
+}
+
 // The penalty for optimistically proving a sector with an invalid window PoSt.
 pub fn pledge_penalty_for_invalid_windowpost(
     reward_estimate: &FilterEstimate,
@@ -278,10 +322,25 @@ pub fn consensus_fault_penalty(this_epoch_reward: TokenAmount) -> TokenAmount {
         .div_floor(&TokenAmount::from(EXPECTED_LEADERS_PER_EPOCH))
 }
 
-/// Returns the amount of a reward to vest, and the vesting schedule, for a reward amount.
-pub fn locked_reward_from_reward(reward: TokenAmount) -> (TokenAmount, &'static VestSpec) {
+/// Fee charged for a prove-commit arriving within the grace window after its due epoch.
+/// The fee grows linearly with lateness, reaching the full pre-commit deposit at the end of
+/// the window.
+pub fn late_prove_commit_fee(
+    pre_commit_deposit: &TokenAmount,
+    epochs_late: ChainEpoch,
+    grace: ChainEpoch,
+) -> TokenAmount {
+    if epochs_late <= 0 || grace <= 0 {
+        return TokenAmount::zero();
+    }
+    (pre_commit_deposit * cmp::min(epochs_late, grace)).div_floor(&TokenAmount::from(grace))
+}
+
+/// Returns the amount of a reward to vest, and the vesting schedule configured by the policy,
+/// for a reward amount.
+pub fn locked_reward_from_reward(policy: &Policy, reward: TokenAmount) -> (TokenAmount, &VestSpec) {
     let lock_amount = (reward * &*LOCKED_REWARD_FACTOR_NUM).div_floor(&*LOCKED_REWARD_FACTOR_DENOM);
-    (lock_amount, &REWARD_VESTING_SPEC)
+    (lock_amount, &policy.reward_vesting_spec)
 }
 
 lazy_static! {
@@ -641,6 +641,19 @@ impl Partition {
         Ok((power_delta, penalized_power, new_faulty_power))
     }
 
+    /// Returns the number of sectors whose early termination is yet to be processed.
+    pub fn early_terminations_count<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<u64> {
+        let early_terminated_queue =
+            BitFieldQueue::new(store, &self.early_terminated, NO_QUANTIZATION)?;
+
+        let mut count = 0;
+        early_terminated_queue.amt.for_each(|_, sectors| {
+            count += sectors.len();
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
     pub fn pop_early_terminations<BS: Blockstore>(
         &mut self,
         store: &BS,
@@ -6,13 +6,15 @@ use std::cmp;
 use cid::{Cid, Version};
 use fil_actors_runtime::network::*;
 use fil_actors_runtime::runtime::Policy;
+pub use fil_actors_runtime::runtime::VestSpec;
 use fil_actors_runtime::{DealWeight, EXPECTED_LEADERS_PER_EPOCH};
 use fvm_shared::bigint::{BigInt, Integer};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::commcid::{FIL_COMMITMENT_SEALED, POSEIDON_BLS12_381_A1_FC1};
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::sector::{
-    RegisteredPoStProof, RegisteredSealProof, SectorQuality, SectorSize, StoragePower,
+    RegisteredPoStProof, RegisteredSealProof, RegisteredUpdateProof, SectorQuality, SectorSize,
+    StoragePower,
 };
 use fvm_shared::version::NetworkVersion;
 use lazy_static::lazy_static;
@@ -69,6 +71,12 @@ pub fn can_extend_seal_proof_type(
     true
 }
 
+/// Maximum size of a replica update proof of the given type. Types that declare no proof size
+/// are bounded by the policy maximum.
+pub fn max_replica_update_proof_size(policy: &Policy, proof: RegisteredUpdateProof) -> usize {
+    proof.proof_size().unwrap_or(policy.max_replica_update_proof_size)
+}
+
 /// Maximum duration to allow for the sealing process for seal algorithms.
 /// Dependent on algorithm and sector size
 pub fn max_prove_commit_duration(
@@ -158,24 +166,31 @@ pub fn qa_power_for_sector(size: SectorSize, sector: &SectorOnChainInfo) -> Stor
     qa_power_for_weight(size, duration, &sector.deal_weight, &sector.verified_deal_weight)
 }
 
+/// Returns the largest power any miner could claim, with the maximum number of sectors all of
+/// the largest size the policy accepts window PoSts for, and at the highest quality.
+pub fn max_miner_power(policy: &Policy) -> PowerPair {
+    let size = policy
+        .valid_post_proof_type
+        .iter()
+        .filter_map(|proof| proof.sector_size().ok())
+        .map(|size| size as u64)
+        .max()
+        .unwrap_or(0);
+    let raw = BigInt::from(size) * policy.sectors_max;
+    let qa = (&raw * &*VERIFIED_DEAL_WEIGHT_MULTIPLIER).div_floor(&QUALITY_BASE_MULTIPLIER);
+    PowerPair { raw, qa }
+}
+
 /// Determine maximum number of deal miner's sector can hold
 pub fn sector_deals_max(policy: &Policy, size: SectorSize) -> u64 {
     cmp::max(256, size as u64 / policy.deal_limit_denominator)
 }
-/// Specification for a linear vesting schedule.
-pub struct VestSpec {
-    pub initial_delay: ChainEpoch, // Delay before any amount starts vesting.
-    pub vest_period: ChainEpoch, // Period over which the total should vest, after the initial delay.
-    pub step_duration: ChainEpoch, // Duration between successive incremental vests (independent of vesting period).
-    pub quantization: ChainEpoch, // Maximum precision of vesting table (limits cardinality of table).
-}
 
-pub const REWARD_VESTING_SPEC: VestSpec = VestSpec {
-    initial_delay: 0,                  // PARAM_FINISH
-    vest_period: 180 * EPOCHS_IN_DAY,  // PARAM_FINISH
-    step_duration: EPOCHS_IN_DAY,      // PARAM_FINISH
-    quantization: 12 * EPOCHS_IN_HOUR, // PARAM_FINISH
-};
+/// Number of recent reward applications retained in miner state.
+pub const RECENT_REWARD_APPLICATIONS_MAX: usize = 16;
+
+/// Maximum size in bytes of the memo accompanying a reward application.
+pub const REWARD_MEMO_MAX_SIZE: usize = 128;
 
 // Default share of block reward allocated as reward to the consensus fault reporter.
 // Applied as epochReward / (expectedLeadersPerEpoch * consensusFaultReporterDefaultShare)
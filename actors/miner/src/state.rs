@@ -16,12 +16,12 @@ use fil_actors_runtime::{
 use fvm_ipld_amt::Error as AmtError;
 use fvm_ipld_hamt::Error as HamtError;
 use fvm_shared::address::Address;
-use fvm_shared::bigint::bigint_ser;
+use fvm_shared::bigint::bigint_ser::{self, BigIntDe};
 use fvm_shared::blockstore::{Blockstore, CborStore};
 use fvm_shared::clock::{ChainEpoch, QuantSpec, EPOCH_UNDEFINED};
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::encoding::tuple::*;
-use fvm_shared::encoding::{serde_bytes, BytesDe, Cbor};
+use fvm_shared::encoding::{serde_bytes, to_vec, BytesDe, Cbor};
 use fvm_shared::error::ExitCode;
 use fvm_shared::sector::{RegisteredPoStProof, SectorNumber, SectorSize, MAX_SECTOR_NUMBER};
 use fvm_shared::HAMT_BIT_WIDTH;
@@ -33,7 +33,7 @@ use super::types::*;
 use super::{
     assign_deadlines, deadline_is_mutable, new_deadline_info_from_offset_and_epoch,
     quant_spec_for_deadline, BitFieldQueue, Deadline, DeadlineInfo, DeadlineSectorMap, Deadlines,
-    PowerPair, Sectors, TerminationResult, VestingFunds,
+    ExpirationSet, PowerPair, Sectors, TerminationResult, VestingFunds,
 };
 
 const PRECOMMIT_EXPIRY_AMT_BITWIDTH: u32 = 6;
@@ -46,6 +46,9 @@ pub const SECTORS_AMT_BITWIDTH: u32 = 5;
 /// that limits a miner actor's behavior (i.e. no balance withdrawals)
 /// Excess balance as computed by st.GetAvailableBalance will be
 /// withdrawable or usable for pre-commit deposit or pledge lock-up.
+///
+/// Changing the fields of this struct changes the state schema: state in the previous layout
+/// must be converted by a migration such as [`migrate_state`](super::migrate_state).
 #[derive(Serialize_tuple, Deserialize_tuple, Clone)]
 pub struct State {
     /// Contains static info about this miner
@@ -74,12 +77,21 @@ pub struct State {
     /// Map, HAMT<SectorNumber, SectorPreCommitOnChainInfo>
     pub pre_committed_sectors: Cid,
 
+    /// Number of entries in pre_committed_sectors, kept so it can be read without walking the
+    /// map.
+    pub pre_committed_sector_count: u64,
+
     // PreCommittedSectorsCleanUp maintains the state required to cleanup expired PreCommittedSectors.
     pub pre_committed_sectors_cleanup: Cid, // BitFieldQueue (AMT[Epoch]*BitField)
 
-    /// Allocated sector IDs. Sector IDs can never be reused once allocated.
+    /// Allocated sector IDs. Sector IDs can never be reused once allocated, unless released by
+    /// abandoning their pre-commitment or releasing their reservation.
     pub allocated_sectors: Cid, // BitField
 
+    /// Sector numbers reserved ahead of pre-commitment. Reserved numbers are also allocated, so
+    /// they cannot be pre-committed until released, and only they can be released.
+    pub reserved_sector_numbers: Cid, // BitField
+
     /// Information for all proven and not-yet-garbage-collected sectors.
     ///
     /// Sectors are removed from this AMT when the partition to which the
@@ -110,6 +122,19 @@ pub struct State {
 
     // True when miner cron is active, false otherwise
     pub deadline_cron_active: bool,
+
+    /// Rewards owed to disputers of invalid Window PoSts whose reward could not be sent, to be
+    /// withdrawn by them later.
+    /// Map, HAMT<Address, TokenAmount>
+    pub owed_dispute_rewards: Cid,
+
+    /// Sum of rewards owed to disputers. These funds are held back from the unlocked balance.
+    #[serde(with = "bigint_ser")]
+    pub total_owed_dispute_rewards: TokenAmount,
+
+    /// The most recent reward applications, oldest first, bounded by
+    /// RECENT_REWARD_APPLICATIONS_MAX. Only recorded when the policy enables it.
+    pub recent_rewards: Cid, // Vec<RewardApplication>
 }
 
 #[derive(PartialEq)]
@@ -136,6 +161,13 @@ impl State {
                     "failed to construct empty precommit map",
                 )
             })?;
+        let empty_owed_rewards_map =
+            make_empty_map::<_, BigIntDe>(store, HAMT_BIT_WIDTH).flush().map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    "failed to construct empty owed rewards map",
+                )
+            })?;
         let empty_precommits_cleanup_array =
             Array::<BitField, BS>::new_with_bit_width(store, PRECOMMIT_EXPIRY_AMT_BITWIDTH)
                 .flush()
@@ -157,6 +189,13 @@ impl State {
         let empty_bitfield = store.put_cbor(&BitField::new(), Code::Blake2b256).map_err(|e| {
             e.downcast_default(ExitCode::ErrIllegalState, "failed to construct empty bitfield")
         })?;
+        let empty_recent_rewards =
+            store.put_cbor(&Vec::<RewardApplication>::new(), Code::Blake2b256).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    "failed to construct empty recent rewards",
+                )
+            })?;
         let deadline = Deadline::new(store)?;
         let empty_deadline = store.put_cbor(&deadline, Code::Blake2b256).map_err(|e| {
             e.downcast_default(ExitCode::ErrIllegalState, "failed to construct illegal state")
@@ -185,7 +224,9 @@ impl State {
             fee_debt: TokenAmount::default(),
 
             pre_committed_sectors: empty_precommit_map,
+            pre_committed_sector_count: 0,
             allocated_sectors: empty_bitfield,
+            reserved_sector_numbers: empty_bitfield,
             sectors: empty_sectors_array,
             proving_period_start: period_start,
             current_deadline: deadline_idx,
@@ -193,6 +234,9 @@ impl State {
             early_terminations: BitField::new(),
             deadline_cron_active: false,
             pre_committed_sectors_cleanup: empty_precommits_cleanup_array,
+            owed_dispute_rewards: empty_owed_rewards_map,
+            total_owed_dispute_rewards: TokenAmount::default(),
+            recent_rewards: empty_recent_rewards,
         })
     }
 
@@ -218,6 +262,29 @@ impl State {
     pub fn deadline_info(&self, policy: &Policy, current_epoch: ChainEpoch) -> DeadlineInfo {
         new_deadline_info_from_offset_and_epoch(policy, self.proving_period_start, current_epoch)
     }
+    /// Returns the epoch at which to enroll the next proving deadline cron event, after the
+    /// deadline has been advanced: the last epoch of the recorded current deadline.
+    /// The recorded proving period start only moves forward when the deadline index wraps, so
+    /// after cron was not invoked for a proving period or more the recorded deadline has already
+    /// elapsed. It is then carried forward by whole proving periods so that the event is never
+    /// enrolled in the past.
+    pub fn next_deadline_cron_epoch(
+        &self,
+        policy: &Policy,
+        current_epoch: ChainEpoch,
+    ) -> ChainEpoch {
+        let mut dl_info = self.recorded_deadline_info(policy, current_epoch);
+        while dl_info.last() <= current_epoch {
+            dl_info = new_deadline_info(
+                policy,
+                dl_info.period_start + policy.wpost_proving_period,
+                dl_info.index,
+                current_epoch,
+            );
+        }
+        dl_info.last()
+    }
+
     // Returns deadline calculations for the state recorded proving period and deadline.
     // This is out of date if the a miner does not have an active miner cron
     pub fn recorded_deadline_info(
@@ -247,9 +314,10 @@ impl State {
     /// If policy is `DenyCollisions`, fails if the set intersects with the sector numbers already allocated.
     pub fn allocate_sector_numbers<BS: Blockstore>(
         &mut self,
+        policy: &Policy,
         store: &BS,
         sector_numbers: &BitField,
-        policy: CollisionPolicy,
+        collision_policy: CollisionPolicy,
     ) -> Result<(), ActorError> {
         let prior_allocation = store
             .get_cbor(&self.allocated_sectors)
@@ -261,7 +329,7 @@ impl State {
             })?
             .ok_or_else(|| actor_error!(ErrIllegalState, "allocated sectors bitfield not found"))?;
 
-        if policy != CollisionPolicy::AllowCollisions {
+        if collision_policy != CollisionPolicy::AllowCollisions {
             // NOTE: A fancy merge algorithm could extract this intersection while merging, below, saving
             // one iteration of the runs
             let collisions = &prior_allocation & sector_numbers;
@@ -274,6 +342,26 @@ impl State {
             }
         }
         let new_allocation = &prior_allocation | sector_numbers;
+        // Masking sector numbers is how an oversized bitfield is compacted, so it must stay possible.
+        if collision_policy != CollisionPolicy::AllowCollisions {
+            let allocation_size = to_vec(&new_allocation)
+                .map_err(|e| {
+                    actor_error!(
+                        ErrIllegalState,
+                        "failed to serialize allocated sectors bitfield: {}",
+                        e
+                    )
+                })?
+                .len();
+            if allocation_size > policy.max_allocated_sectors_size {
+                return Err(actor_error!(
+                    ErrIllegalState,
+                    "allocated sectors bitfield too large ({} > {} bytes), compact sector numbers first",
+                    allocation_size,
+                    policy.max_allocated_sectors_size
+                ));
+            }
+        }
         self.allocated_sectors =
             store.put_cbor(&new_allocation, Code::Blake2b256).map_err(|e| {
                 e.downcast_default(
@@ -287,6 +375,131 @@ impl State {
         Ok(())
     }
 
+    /// Allocates sector numbers and holds them in reserve for later pre-commitment.
+    pub fn reserve_sector_numbers<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        store: &BS,
+        sector_numbers: &BitField,
+    ) -> Result<(), ActorError> {
+        self.allocate_sector_numbers(
+            policy,
+            store,
+            sector_numbers,
+            CollisionPolicy::DenyCollisions,
+        )?;
+        let reserved = self.load_reserved_sector_numbers(store)?;
+        self.save_reserved_sector_numbers(store, &(&reserved | sector_numbers))
+    }
+
+    /// Releases reserved sector numbers, making them available for allocation again.
+    /// Only numbers that are still reserved may be released, so numbers allocated any other way
+    /// stay allocated.
+    pub fn release_sector_numbers<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        sector_numbers: &BitField,
+    ) -> Result<(), ActorError> {
+        let reserved = self.load_reserved_sector_numbers(store)?;
+        let unreserved = sector_numbers - &reserved;
+        if !unreserved.is_empty() {
+            return Err(actor_error!(
+                ErrIllegalArgument,
+                "sector numbers {:?} are not reserved",
+                unreserved
+            ));
+        }
+
+        let prior_allocation: BitField = store
+            .get_cbor(&self.allocated_sectors)
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    "failed to load allocated sectors bitfield",
+                )
+            })?
+            .ok_or_else(|| actor_error!(ErrIllegalState, "allocated sectors bitfield not found"))?;
+        self.allocated_sectors = store
+            .put_cbor(&(&prior_allocation - sector_numbers), Code::Blake2b256)
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    "failed to store allocated sectors bitfield",
+                )
+            })?;
+        self.save_reserved_sector_numbers(store, &(&reserved - sector_numbers))
+    }
+
+    /// Drops sector numbers from the reservation while leaving them allocated, so that they can
+    /// no longer be released. Masking sector numbers allocates them for good this way.
+    pub fn unreserve_sector_numbers<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        sector_numbers: &BitField,
+    ) -> Result<(), ActorError> {
+        let reserved = self.load_reserved_sector_numbers(store)?;
+        if (&reserved & sector_numbers).is_empty() {
+            return Ok(());
+        }
+        self.save_reserved_sector_numbers(store, &(&reserved - sector_numbers))
+    }
+
+    pub fn load_reserved_sector_numbers<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> Result<BitField, ActorError> {
+        store
+            .get_cbor(&self.reserved_sector_numbers)
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    "failed to load reserved sector numbers bitfield",
+                )
+            })?
+            .ok_or_else(|| {
+                actor_error!(ErrIllegalState, "reserved sector numbers bitfield not found")
+            })
+    }
+
+    fn save_reserved_sector_numbers<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        reserved: &BitField,
+    ) -> Result<(), ActorError> {
+        self.reserved_sector_numbers = store.put_cbor(reserved, Code::Blake2b256).map_err(|e| {
+            e.downcast_default(
+                ExitCode::ErrIllegalState,
+                "failed to store reserved sector numbers bitfield",
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Returns the smallest sector number that has not yet been allocated (or masked).
+    pub fn next_allocatable_sector_number<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> Result<SectorNumber, ActorError> {
+        let allocated_sectors: BitField = store
+            .get_cbor(&self.allocated_sectors)
+            .map_err(|e| {
+                e.downcast_default(
+                    ExitCode::ErrIllegalState,
+                    "failed to load allocated sectors bitfield",
+                )
+            })?
+            .ok_or_else(|| actor_error!(ErrIllegalState, "allocated sectors bitfield not found"))?;
+
+        let next = match allocated_sectors.ranges().next() {
+            Some(first) if first.start == 0 => first.end,
+            _ => 0,
+        };
+        if next > MAX_SECTOR_NUMBER {
+            return Err(actor_error!(ErrIllegalState, "all sector numbers have been allocated"));
+        }
+        Ok(next)
+    }
+
     /// Stores a pre-committed sector info, failing if the sector number is already present.
     pub fn put_precommitted_sectors<BS: Blockstore>(
         &mut self,
@@ -305,6 +518,7 @@ impl State {
             if !modified {
                 return Err(anyhow!("sector {} already pre-commited", sector_no));
             }
+            self.pre_committed_sector_count += 1;
         }
 
         self.pre_committed_sectors = precommitted.flush()?;
@@ -360,13 +574,75 @@ impl State {
         )?;
 
         for &sector_num in sector_nums {
-            precommitted.delete(&u64_key(sector_num))?;
+            if precommitted.delete(&u64_key(sector_num))?.is_some() {
+                self.pre_committed_sector_count -= 1;
+            }
         }
 
         self.pre_committed_sectors = precommitted.flush()?;
         Ok(())
     }
 
+    /// Records a reward owed to a disputer, adding to any reward already owed to them.
+    pub fn add_owed_dispute_reward<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        reporter: &Address,
+        amount: &TokenAmount,
+    ) -> Result<(), HamtError> {
+        let mut owed = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &self.owed_dispute_rewards,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+        let prev = owed.get(&reporter.to_bytes())?.map(|v| v.0.clone()).unwrap_or_default();
+        owed.set(reporter.to_bytes().into(), BigIntDe(prev + amount))?;
+        self.owed_dispute_rewards = owed.flush()?;
+        self.total_owed_dispute_rewards += amount;
+        Ok(())
+    }
+
+    /// Removes and returns the reward owed to a disputer, which is zero if none is owed.
+    pub fn take_owed_dispute_reward<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        reporter: &Address,
+    ) -> Result<TokenAmount, HamtError> {
+        let mut owed = make_map_with_root_and_bitwidth::<_, BigIntDe>(
+            &self.owed_dispute_rewards,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+        let amount = owed.delete(&reporter.to_bytes())?.map(|(_, v)| v.0).unwrap_or_default();
+        self.owed_dispute_rewards = owed.flush()?;
+        self.total_owed_dispute_rewards -= &amount;
+        Ok(amount)
+    }
+
+    pub fn load_recent_rewards<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> anyhow::Result<Vec<RewardApplication>> {
+        store
+            .get_cbor(&self.recent_rewards)?
+            .ok_or_else(|| anyhow!("failed to load recent rewards {}", self.recent_rewards))
+    }
+
+    /// Records a reward application, dropping the oldest once the history is full.
+    pub fn record_reward_application<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        application: RewardApplication,
+    ) -> anyhow::Result<()> {
+        let mut recent_rewards = self.load_recent_rewards(store)?;
+        if recent_rewards.len() >= RECENT_REWARD_APPLICATIONS_MAX {
+            recent_rewards.remove(0);
+        }
+        recent_rewards.push(application);
+        self.recent_rewards = store.put_cbor(&recent_rewards, Code::Blake2b256)?;
+        Ok(())
+    }
+
     pub fn has_sector_number<BS: Blockstore>(
         &self,
         store: &BS,
@@ -729,6 +1005,130 @@ impl State {
             )
     }
 
+    /// Returns a page of the sectors scheduled to expire on time at an epoch in `[from, to]`,
+    /// loading at most `limit` sectors numbered from `start_sector`.
+    /// Candidates are found through the deadline and partition expiration queues, so only the
+    /// sectors in matching queue entries are loaded rather than every sector.
+    pub fn sectors_expiring_in_range<BS: Blockstore>(
+        &self,
+        policy: &Policy,
+        store: &BS,
+        from: ChainEpoch,
+        to: ChainEpoch,
+        start_sector: SectorNumber,
+        limit: u64,
+    ) -> anyhow::Result<Page<SectorNumber>> {
+        // Queue entries are quantized up to deadline boundaries, so a sector expiring within the
+        // range may be queued at any epoch up to a proving period after its end.
+        let queue_end = to.saturating_add(policy.wpost_proving_period);
+        let in_queue_range = |epoch: ChainEpoch| epoch >= from && epoch < queue_end;
+
+        let mut candidates = Vec::new();
+        let deadlines = self.load_deadlines(store)?;
+        deadlines.for_each(policy, store, |deadline_idx, deadline| {
+            let partition_idxs: Vec<BitField> = deadline
+                .expiration_schedule(store)?
+                .into_iter()
+                .filter(|(epoch, _)| in_queue_range(*epoch))
+                .map(|(_, partitions)| partitions)
+                .collect();
+            let partition_idxs = BitField::union(&partition_idxs);
+            if partition_idxs.is_empty() {
+                return Ok(());
+            }
+
+            let partitions = deadline.partitions_amt(store)?;
+            for partition_idx in partition_idxs.iter() {
+                let partition = partitions.get(partition_idx)?.ok_or_else(|| {
+                    anyhow!("missing partition {} in deadline {}", partition_idx, deadline_idx)
+                })?;
+                let expirations: Array<ExpirationSet, BS> =
+                    Array::load(&partition.expirations_epochs, store)?;
+                expirations.for_each(|epoch, set| {
+                    if in_queue_range(epoch as ChainEpoch) {
+                        candidates.push(set.on_time_sectors.clone());
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        })?;
+
+        let candidates = BitField::union(&candidates);
+        let sectors = Sectors::load(store, &self.sectors)?;
+        let mut expiring = Vec::new();
+        let mut next = None;
+        let mut remaining = limit;
+        for sector_number in candidates.iter().filter(|&n| n >= start_sector) {
+            if remaining == 0 {
+                next = Some(sector_number);
+                break;
+            }
+            remaining -= 1;
+
+            let sector = sectors.must_get(sector_number)?;
+            if sector.expiration >= from && sector.expiration <= to {
+                expiring.push(sector_number);
+            }
+        }
+        Ok(Page { items: expiring, next })
+    }
+
+    /// Returns the sectors across all deadlines that have not been terminated.
+    pub fn live_sectors<BS: Blockstore>(
+        &self,
+        policy: &Policy,
+        store: &BS,
+    ) -> anyhow::Result<BitField> {
+        let deadlines = self.load_deadlines(store)?;
+        let mut live = BitField::new();
+        deadlines.for_each(policy, store, |_, deadline| {
+            deadline.for_each(store, |_, partition| {
+                live = &live | &partition.live_sectors();
+                Ok(())
+            })
+        })?;
+        Ok(live)
+    }
+
+    /// Returns the sectors across all deadlines that are neither faulty, terminated, nor unproven.
+    pub fn active_sectors<BS: Blockstore>(
+        &self,
+        policy: &Policy,
+        store: &BS,
+    ) -> anyhow::Result<BitField> {
+        let deadlines = self.load_deadlines(store)?;
+        let mut active = BitField::new();
+        deadlines.for_each(policy, store, |_, deadline| {
+            deadline.for_each(store, |_, partition| {
+                active = &active | &partition.active_sectors();
+                Ok(())
+            })
+        })?;
+        Ok(active)
+    }
+
+    /// Returns the number of sectors across all deadlines whose early termination is yet to be
+    /// processed.
+    pub fn early_terminations_count<BS: Blockstore>(
+        &self,
+        policy: &Policy,
+        store: &BS,
+    ) -> anyhow::Result<u64> {
+        let deadlines = self.load_deadlines(store)?;
+        let mut count = 0;
+        for deadline_idx in self.early_terminations.iter() {
+            let deadline = deadlines.load_deadline(policy, store, deadline_idx)?;
+            count += deadline.early_terminations_count(store).map_err(|e| {
+                e.downcast_wrap(format!(
+                    "failed to count early terminations in deadline {}",
+                    deadline_idx
+                ))
+            })?;
+        }
+        Ok(count)
+    }
+
     pub fn save_deadlines<BS: Blockstore>(
         &mut self,
         store: &BS,
@@ -842,6 +1242,30 @@ impl State {
         Ok(amount_unlocked)
     }
 
+    /// Unlocks vested funds and compacts the remaining vesting table.
+    /// Returns the amount unlocked.
+    pub fn compact_vesting_funds<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<TokenAmount> {
+        let mut vesting_funds = self.load_vesting_funds(store)?;
+
+        let amount_unlocked = vesting_funds.unlock_vested_funds(current_epoch);
+        self.locked_funds -= &amount_unlocked;
+        if self.locked_funds.is_negative() {
+            return Err(anyhow!(
+                "negative locked funds {} after unlocking {}",
+                self.locked_funds,
+                amount_unlocked
+            ));
+        }
+        vesting_funds.compact();
+
+        self.save_vesting_funds(store, &vesting_funds)?;
+        Ok(amount_unlocked)
+    }
+
     /// Draws from vesting table and unlocked funds to repay up to the fee debt.
     /// Returns the amount unlocked from the vesting table and the amount taken from
     /// current balance. If the fee debt exceeds the total amount available for repayment
@@ -960,10 +1384,28 @@ impl State {
             .fold(TokenAmount::zero(), |acc, fund| acc + &fund.amount))
     }
 
+    /// Returns the amount of funds that have not vested by the provided epoch, which may be
+    /// unlocked early to repay fee debt.
+    pub fn check_unvested_funds<BS: Blockstore>(
+        &self,
+        store: &BS,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<TokenAmount> {
+        let vesting_funds = self.load_vesting_funds(store)?;
+        Ok(vesting_funds
+            .funds
+            .iter()
+            .skip_while(|fund| fund.epoch < current_epoch)
+            .fold(TokenAmount::zero(), |acc, fund| acc + &fund.amount))
+    }
+
     /// Unclaimed funds that are not locked -- includes funds used to cover initial pledge requirement.
     pub fn get_unlocked_balance(&self, actor_balance: &TokenAmount) -> anyhow::Result<TokenAmount> {
-        let unlocked_balance =
-            actor_balance - &self.locked_funds - &self.pre_commit_deposits - &self.initial_pledge;
+        let unlocked_balance = actor_balance
+            - &self.locked_funds
+            - &self.pre_commit_deposits
+            - &self.initial_pledge
+            - &self.total_owed_dispute_rewards;
         if unlocked_balance.is_negative() {
             return Err(anyhow!("negative unlocked balance {}", unlocked_balance));
         }
@@ -993,8 +1435,17 @@ impl State {
         if self.fee_debt.is_negative() {
             return Err(anyhow!("fee debt is negative: {}", self.fee_debt));
         }
+        if self.total_owed_dispute_rewards.is_negative() {
+            return Err(anyhow!(
+                "owed dispute rewards are negative: {}",
+                self.total_owed_dispute_rewards
+            ));
+        }
 
-        let min_balance = &self.pre_commit_deposits + &self.locked_funds + &self.initial_pledge;
+        let min_balance = &self.pre_commit_deposits
+            + &self.locked_funds
+            + &self.initial_pledge
+            + &self.total_owed_dispute_rewards;
         if balance < &min_balance {
             return Err(anyhow!("fee debt is negative: {}", self.fee_debt));
         }
@@ -1079,6 +1530,122 @@ impl State {
         Ok(deposit_to_burn)
     }
 
+    /// Removes pre-committed sectors that can no longer be proven, because their prove-commit
+    /// due epoch and grace window have passed, and releases their sector numbers for reuse.
+    /// Unlike cron, this doesn't wait for the clean-up delay. The sectors are left in the
+    /// clean-up queue, where cron will skip them as already deleted.
+    /// Returns the pre-commit deposits of the removed sectors, which are to be burnt, and the
+    /// number of sector numbers released.
+    pub fn reclaim_expired_pre_commits<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        store: &BS,
+        current_epoch: ChainEpoch,
+    ) -> anyhow::Result<(TokenAmount, u64)> {
+        // Clean-up epochs are the expiry plus the delay, quantized up to the end of a deadline,
+        // so any expired pre-commit is queued before this bound.
+        let quant = self.quant_spec_every_deadline(policy);
+        let queued_until = current_epoch
+            + policy.expired_pre_commit_clean_up_delay
+            + policy.wpost_challenge_window;
+        let cleanup_queue = BitFieldQueue::new(store, &self.pre_committed_sectors_cleanup, quant)?;
+        let mut queued = Vec::new();
+        cleanup_queue.amt.for_each_while(|epoch, sectors| {
+            if epoch as ChainEpoch > queued_until {
+                return Ok(false);
+            }
+            queued.push(sectors.clone());
+            Ok(true)
+        })?;
+
+        let mut deposit_to_burn = TokenAmount::zero();
+        let mut removed = Vec::new();
+        for sector_number in BitField::union(&queued).iter() {
+            let precommit = match self.get_precommitted_sector(store, sector_number)? {
+                Some(precommit) => precommit,
+                // already committed/deleted
+                None => continue,
+            };
+            if pre_commit_expiry(policy, &precommit)? >= current_epoch {
+                continue;
+            }
+            deposit_to_burn += &precommit.pre_commit_deposit;
+            removed.push(sector_number);
+        }
+        if removed.is_empty() {
+            return Ok((deposit_to_burn, 0));
+        }
+
+        self.delete_precommitted_sectors(store, &removed)?;
+        self.pre_commit_deposits -= &deposit_to_burn;
+        if self.pre_commit_deposits.is_negative() {
+            return Err(anyhow!(
+                "pre-commit reclaim caused negative deposits: {}",
+                self.pre_commit_deposits
+            ));
+        }
+
+        let removed: BitField = removed.into_iter().collect();
+        let prior_allocation: BitField = store
+            .get_cbor(&self.allocated_sectors)?
+            .ok_or_else(|| anyhow!("allocated sectors bitfield not found"))?;
+        self.allocated_sectors =
+            store.put_cbor(&(&prior_allocation - &removed), Code::Blake2b256)?;
+
+        Ok((deposit_to_burn, removed.len()))
+    }
+
+    /// Removes pre-committed sectors that will never be proven, along with their clean-up queue
+    /// entries, and releases their sector numbers for reuse.
+    /// Returns the pre-commit deposits of the removed sectors, which are to be burnt.
+    pub fn abandon_pre_commits<BS: Blockstore>(
+        &mut self,
+        policy: &Policy,
+        store: &BS,
+        sector_numbers: &BitField,
+    ) -> anyhow::Result<TokenAmount> {
+        let mut deposit_to_burn = TokenAmount::zero();
+        let mut cleanup_queue = BitFieldQueue::new(
+            store,
+            &self.pre_committed_sectors_cleanup,
+            self.quant_spec_every_deadline(policy),
+        )?;
+
+        let mut precommits_to_delete = Vec::new();
+        for sector_number in sector_numbers.iter() {
+            let precommit =
+                self.get_precommitted_sector(store, sector_number)?.ok_or_else(|| {
+                    actor_error!(ErrNotFound, "no pre-commit for sector {}", sector_number)
+                })?;
+
+            let clean_up_bound = pre_commit_clean_up_bound(policy, &precommit)?;
+            cleanup_queue
+                .remove_from_queue(clean_up_bound, &[sector_number].iter().copied().collect())?;
+
+            precommits_to_delete.push(sector_number);
+            deposit_to_burn += precommit.pre_commit_deposit;
+        }
+
+        self.pre_committed_sectors_cleanup = cleanup_queue.amt.flush()?;
+        self.delete_precommitted_sectors(store, &precommits_to_delete)?;
+
+        let prior_allocation: BitField = store
+            .get_cbor(&self.allocated_sectors)?
+            .ok_or_else(|| anyhow!("allocated sectors bitfield not found"))?;
+        self.allocated_sectors =
+            store.put_cbor(&(&prior_allocation - sector_numbers), Code::Blake2b256)?;
+
+        self.pre_commit_deposits -= &deposit_to_burn;
+        if self.pre_commit_deposits.is_negative() {
+            return Err(anyhow!(
+                "abandoning pre-commits caused negative deposits: {}",
+                self.pre_commit_deposits
+            ));
+        }
+
+        Ok(deposit_to_burn)
+    }
+
     pub fn advance_deadline<BS: Blockstore>(
         &mut self,
         policy: &Policy,
@@ -1244,6 +1811,14 @@ pub struct MinerInfo {
     /// A proposed new owner account for this miner.
     /// Must be confirmed by a message from the pending address itself.
     pub pending_owner_address: Option<Address>,
+
+    /// The last epoch at which the pending owner address may confirm the change,
+    /// or None if the proposal does not expire.
+    pub pending_owner_expiration: Option<ChainEpoch>,
+
+    /// The epoch at which the owner last requested an emergency worker key change,
+    /// or EPOCH_UNDEFINED if it never has.
+    pub last_emergency_worker_change: ChainEpoch,
 }
 
 impl MinerInfo {
@@ -1275,6 +1850,27 @@ impl MinerInfo {
             window_post_partition_sectors,
             consensus_fault_elapsed: EPOCH_UNDEFINED,
             pending_owner_address: None,
+            pending_owner_expiration: None,
+            last_emergency_worker_change: EPOCH_UNDEFINED,
         })
     }
 }
+
+/// Returns the last epoch at which a pre-commit may still be proven, including the grace window.
+fn pre_commit_expiry(
+    policy: &Policy,
+    precommit: &SectorPreCommitOnChainInfo,
+) -> anyhow::Result<ChainEpoch> {
+    let msd = max_prove_commit_duration(policy, precommit.info.seal_proof).ok_or_else(|| {
+        anyhow!("no max seal duration for proof type: {:?}", precommit.info.seal_proof)
+    })?;
+    Ok(precommit.pre_commit_epoch + msd + policy.prove_commit_grace)
+}
+
+/// Recomputes the epoch at which a pre-commit was scheduled for clean-up when it was recorded.
+fn pre_commit_clean_up_bound(
+    policy: &Policy,
+    precommit: &SectorPreCommitOnChainInfo,
+) -> anyhow::Result<ChainEpoch> {
+    Ok(pre_commit_expiry(policy, precommit)? + policy.expired_pre_commit_clean_up_delay)
+}
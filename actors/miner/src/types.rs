@@ -1,7 +1,7 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use bitfield::UnvalidatedBitField;
+use bitfield::{BitField, UnvalidatedBitField};
 use cid::Cid;
 use fil_actors_runtime::DealWeight;
 use fvm_shared::address::Address;
@@ -10,14 +10,16 @@ use fvm_shared::clock::ChainEpoch;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::encoding::tuple::*;
-use fvm_shared::encoding::{serde_bytes, BytesDe};
+use fvm_shared::encoding::{serde_bytes, BytesDe, RawBytes};
 use fvm_shared::randomness::Randomness;
 use fvm_shared::sector::{
     PoStProof, RegisteredPoStProof, RegisteredSealProof, RegisteredUpdateProof, SectorNumber,
-    StoragePower,
+    SectorSize, StoragePower,
 };
 use fvm_shared::smooth::FilterEstimate;
 
+use super::PowerPair;
+
 pub type CronEvent = i64;
 
 pub const CRON_EVENT_WORKER_KEY_CHANGE: CronEvent = 0;
@@ -55,12 +57,47 @@ pub struct GetControlAddressesReturn {
     pub control_addresses: Vec<Address>,
 }
 
+#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetMinerInfoReturn {
+    pub owner: Address,
+    pub worker: Address,
+    pub control_addresses: Vec<Address>,
+    #[serde(with = "serde_bytes")]
+    pub peer_id: Vec<u8>,
+    pub multi_address: Vec<BytesDe>,
+    pub window_post_proof_type: RegisteredPoStProof,
+    pub sector_size: SectorSize,
+    pub window_post_partition_sectors: u64,
+    pub consensus_fault_elapsed: ChainEpoch,
+}
+
+/// Worker and owner changes that have been proposed but have not yet taken effect.
+#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetPendingChangesReturn {
+    pub pending_worker_key: Option<WorkerKeyChange>,
+    pub pending_owner_address: Option<Address>,
+    pub pending_owner_expiration: Option<ChainEpoch>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ChangeWorkerAddressParams {
     pub new_worker: Address,
     pub new_control_addresses: Vec<Address>,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct EmergencyChangeWorkerAddressParams {
+    pub new_worker: Address,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ChangeOwnerAddressWithExpiryParams {
+    pub new_address: Address,
+    /// The last epoch at which the proposed owner may confirm the change, if any.
+    pub expiration: Option<ChainEpoch>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ChangePeerIDParams {
     #[serde(with = "serde_bytes")]
@@ -72,6 +109,13 @@ pub struct ChangeMultiaddrsParams {
     pub new_multi_addrs: Vec<BytesDe>,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ChangeNetworkAddressesParams {
+    #[serde(with = "serde_bytes")]
+    pub new_id: Vec<u8>,
+    pub new_multi_addrs: Vec<BytesDe>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ConfirmSectorProofsParams {
     pub sectors: Vec<SectorNumber>,
@@ -113,6 +157,15 @@ pub struct SubmitWindowedPoStParams {
     pub chain_commit_rand: Randomness,
 }
 
+/// A Window PoSt submission that also declares faulty sectors in the proven partitions recovered,
+/// so they are proven by this same submission rather than at the deadline's next occurrence.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct SubmitWindowedPoStWithRecoveriesParams {
+    pub post: SubmitWindowedPoStParams,
+    /// Each declaration must target the submission's deadline and a partition being proven.
+    pub recoveries: Vec<RecoveryDeclaration>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ProveCommitSectorParams {
     pub sector_number: SectorNumber,
@@ -125,6 +178,163 @@ pub struct CheckSectorProvenParams {
     pub sector_number: SectorNumber,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct AbandonPreCommitsParams {
+    pub sectors: UnvalidatedBitField,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ReclaimExpiredPreCommitNumbersReturn {
+    /// Number of sector numbers released for reuse.
+    pub freed: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetProvingPeriodInfoReturn {
+    /// First epoch of the current proving period.
+    pub proving_period_start: ChainEpoch,
+    /// Index of the deadline currently being proven.
+    pub current_deadline: u64,
+    /// Offset of proving period starts within the proving period, as assigned at construction.
+    pub proving_period_offset: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct CheckSectorsProvenParams {
+    pub sector_numbers: UnvalidatedBitField,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct CheckSectorsProvenReturn {
+    /// Requested sectors present in the sectors array.
+    pub proven: BitField,
+    /// Requested sectors not (or no longer) present in the sectors array.
+    pub not_proven: BitField,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetSectorInfosParams {
+    pub sector_numbers: UnvalidatedBitField,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetSectorInfosReturn {
+    /// Information for each requested sector in ascending sector number order, or None for
+    /// sectors not in the sectors array.
+    pub sectors: Vec<Option<SectorOnChainInfo>>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetPreCommitInfoParams {
+    pub sector_number: SectorNumber,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetSectorHealthParams {
+    pub sector_numbers: UnvalidatedBitField,
+}
+
+/// Remaining lifetime and fault status of a sector, with the partition it is proven in.
+#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorHealth {
+    pub sector_number: SectorNumber,
+    pub expiration: ChainEpoch,
+    /// Epochs until the sector expires, zero if it already has.
+    pub epochs_remaining: ChainEpoch,
+    pub is_faulty: bool,
+    /// Whether the sector is faulty and declared as recovering.
+    pub is_recovering: bool,
+    /// Whether the sector has been terminated, early or at its expiration, but not yet
+    /// compacted out of its partition.
+    pub is_terminated: bool,
+    pub deadline: u64,
+    pub partition: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetSectorHealthReturn {
+    /// Health of each requested sector in ascending sector number order, or None for sectors
+    /// not in the sectors array.
+    pub sectors: Vec<Option<SectorHealth>>,
+}
+
+/// One page of the results of a listing method. Listing methods bound the work done in a
+/// single call, and return a cursor from which the caller may request the following page.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Position from which to continue the listing, `None` once it is complete.
+    pub next: Option<u64>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetActiveDealsParams {
+    /// Lowest sector number from which to list deals.
+    pub start_sector: SectorNumber,
+}
+
+/// Deals backing the active sectors, listed a page of sectors at a time.
+/// The cursor is the sector number from which to request the next page.
+pub type GetActiveDealsReturn = Page<DealID>;
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetUpgradedSectorsParams {
+    /// Lowest sector number from which to list upgraded sectors.
+    pub start_sector: SectorNumber,
+}
+
+/// Live sectors that have been upgraded with a replica update and so carry a sector key,
+/// listed a page of sectors at a time.
+/// The cursor is the sector number from which to request the next page.
+pub type GetUpgradedSectorsReturn = Page<SectorNumber>;
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct DeadlineInfoAtParams {
+    pub epoch: ChainEpoch,
+}
+
+#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct DeadlineInfoAtReturn {
+    /// First epoch of the proving period containing the requested epoch.
+    pub period_start: ChainEpoch,
+    /// Index of the deadline containing the requested epoch.
+    pub index: u64,
+    pub open: ChainEpoch,
+    pub close: ChainEpoch,
+    pub challenge: ChainEpoch,
+    pub fault_cutoff: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct UpcomingDeadlinesParams {
+    /// Number of deadlines to return, at most a full proving period's worth.
+    pub count: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct UpcomingDeadlinesReturn {
+    /// The current deadline followed by those after it, in order.
+    pub deadlines: Vec<UpcomingDeadline>,
+}
+
+#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct UpcomingDeadline {
+    pub index: u64,
+    pub open: ChainEpoch,
+    pub close: ChainEpoch,
+    pub challenge: ChainEpoch,
+    pub fault_cutoff: ChainEpoch,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ExtendSectorExpirationParams {
     pub extensions: Vec<ExpirationExtension>,
@@ -150,6 +360,215 @@ pub struct TerminationDeclaration {
     pub sectors: UnvalidatedBitField,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct TerminateWorstSectorsParams {
+    pub deadline: u64,
+    /// Maximum number of sectors to terminate.
+    pub max_sectors: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct CheckTerminationAllowedReturn {
+    /// Requested sectors in a deadline that cannot currently be modified.
+    pub immutable_deadline: BitField,
+    /// Requested sectors that are not live in the given partition.
+    pub not_live: BitField,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetEarlyTerminationsReturn {
+    /// Deadlines with early terminations yet to be processed.
+    pub deadlines: BitField,
+    /// Number of sectors across those deadlines awaiting early termination processing.
+    pub sectors: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ReserveSectorNumbersParams {
+    pub sectors: UnvalidatedBitField,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ReleaseSectorNumbersParams {
+    pub sectors: UnvalidatedBitField,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetDeadlineExpirationScheduleParams {
+    pub deadline: u64,
+}
+
+/// Partitions of a deadline with sectors scheduled to expire at an epoch.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ExpirationScheduleEntry {
+    pub epoch: ChainEpoch,
+    pub partitions: BitField,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetDeadlineExpirationScheduleReturn {
+    /// Scheduled expirations, in epoch order.
+    pub entries: Vec<ExpirationScheduleEntry>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetSectorsExpiringInRangeParams {
+    pub from_epoch: ChainEpoch,
+    /// Last epoch of the range, inclusive.
+    pub to_epoch: ChainEpoch,
+    /// Lowest sector number from which to list expiring sectors.
+    pub start_sector: SectorNumber,
+}
+
+/// Sectors expiring on time within the range, listed a page of candidate sectors at a time.
+/// The cursor is the sector number from which to request the next page.
+pub type GetSectorsExpiringInRangeReturn = Page<SectorNumber>;
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ProveCommitSectorReturn {
+    /// Fee for proving the sector within the grace window after its due epoch. It is burnt
+    /// only if the proof is confirmed and the sector activates.
+    #[serde(with = "bigint_ser")]
+    pub grace_fee: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ProveCommitAggregateReturn {
+    /// Total fee burnt for activated sectors proven within the grace window after their due
+    /// epochs.
+    #[serde(with = "bigint_ser")]
+    pub grace_fee: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetDisputeInfoParams {
+    pub deadline: u64,
+    pub post_index: u64,
+}
+
+/// What a dispute of an optimistically accepted window PoSt would cover.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetDisputeInfoReturn {
+    /// Partitions proven by the PoSt.
+    pub partitions: BitField,
+    /// Sectors of the proven partitions that were not required to be proven.
+    pub ignored_sectors: BitField,
+    /// Power that would be penalized if the dispute succeeded.
+    pub disputed_power: PowerPair,
+}
+
+/// The funds a miner has committed as collateral, alongside its balance.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetPledgeSummaryReturn {
+    #[serde(with = "bigint_ser")]
+    pub initial_pledge: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub locked_funds: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub pre_commit_deposits: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub fee_debt: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub balance: TokenAmount,
+}
+
+/// Counts of the miner's sectors by status. Terminated sectors are those still held in
+/// partitions, which are dropped when the partitions are compacted.
+#[derive(Debug, Default, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetSectorCountsReturn {
+    pub live_sectors: u64,
+    pub faulty_sectors: u64,
+    pub recovering_sectors: u64,
+    pub terminated_sectors: u64,
+    pub precommitted_sectors: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct DeadlineLayout {
+    /// Number of sectors in each of the deadline's partitions, including terminated sectors not
+    /// yet compacted away. Its length is the deadline's partition count.
+    pub partition_sector_counts: Vec<u64>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetDeadlineLayoutReturn {
+    /// Layout of each deadline, indexed by deadline.
+    pub deadlines: Vec<DeadlineLayout>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ConsensusFaultStatusReturn {
+    /// Whether the miner is currently penalized for a consensus fault.
+    pub active: bool,
+    /// The last epoch of the penalization period, or EPOCH_UNDEFINED if the miner has never
+    /// been penalized.
+    pub elapsed_epoch: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct EstimateAggregateFeeParams {
+    pub sector_count: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct EstimateAggregateFeeReturn {
+    #[serde(with = "bigint_ser")]
+    pub fee: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct ProjectSectorRewardParams {
+    /// Quality-adjusted power of the sector.
+    #[serde(with = "bigint_ser")]
+    pub qa_power: StoragePower,
+    /// Number of epochs over which to project the reward.
+    pub duration: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ProjectSectorRewardReturn {
+    #[serde(with = "bigint_ser")]
+    pub reward: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct EstimateInitialPledgeParams {
+    /// Quality-adjusted power of the sector.
+    #[serde(with = "bigint_ser")]
+    pub qa_power: StoragePower,
+    /// Intended lifetime of the sector, in epochs.
+    pub duration: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct EstimateInitialPledgeReturn {
+    #[serde(with = "bigint_ser")]
+    pub initial_pledge: TokenAmount,
+}
+
+/// Faulty and recovering sectors of a single deadline, aggregated across its partitions.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct DeadlineFaultState {
+    pub faults: BitField,
+    pub recoveries: BitField,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetFaultStateReturn {
+    /// Fault state of each deadline, indexed by deadline.
+    pub deadlines: Vec<DeadlineFaultState>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct TerminateSectorsReturn {
     // Set to true if all early termination work has been completed. When
@@ -190,6 +609,33 @@ pub struct RecoveryDeclaration {
     pub sectors: UnvalidatedBitField,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct CanDeclareRecoveryParams {
+    pub deadline: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct CanDeclareRecoveryReturn {
+    /// Whether the fault cutoff for the deadline has passed, so a recovery would be rejected.
+    pub fault_cutoff_passed: bool,
+    /// The epoch from which declarations for the deadline are no longer accepted.
+    pub fault_cutoff: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ComputeFaultExpirationsParams {
+    pub deadlines: Vec<u64>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ComputeFaultExpirationsReturn {
+    /// Epoch at which sectors faulted now at each requested deadline would be terminated if not
+    /// recovered, in the order requested.
+    pub expirations: Vec<ChainEpoch>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct CompactPartitionsParams {
     pub deadline: u64,
@@ -217,6 +663,12 @@ pub struct WithdrawBalanceParams {
     pub amount_requested: TokenAmount,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct NextAllocatableSectorNumberReturn {
+    pub sector_number: SectorNumber,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 #[serde(transparent)]
 pub struct WithdrawBalanceReturn {
@@ -224,6 +676,28 @@ pub struct WithdrawBalanceReturn {
     pub amount_withdrawn: TokenAmount,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetDebtStatusReturn {
+    #[serde(with = "bigint_ser")]
+    pub fee_debt: TokenAmount,
+    /// Balance not locked in vesting, pre-commit deposits or initial pledge.
+    #[serde(with = "bigint_ser")]
+    pub unlocked_balance: TokenAmount,
+    /// Funds RepayDebt would unlock from the vesting table to repay the debt.
+    #[serde(with = "bigint_ser")]
+    pub repayable_from_vesting: TokenAmount,
+    /// Funds that must be sent to the miner before RepayDebt can clear the debt in full.
+    #[serde(with = "bigint_ser")]
+    pub additional_funds_needed: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct WithdrawDisputeRewardReturn {
+    #[serde(with = "bigint_ser")]
+    pub amount_withdrawn: TokenAmount,
+}
+
 #[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct WorkerKeyChange {
     /// Must be an ID address
@@ -320,6 +794,48 @@ pub struct ApplyRewardParams {
     pub reward: TokenAmount,
     #[serde(with = "bigint_ser")]
     pub penalty: TokenAmount,
+    /// Opaque tag identifying the source of the reward, recorded with the application.
+    /// Omitted by callers predating it.
+    #[serde(default)]
+    pub memo: RawBytes,
+}
+
+/// A reward application recorded in the miner's recent reward history.
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct RewardApplication {
+    pub epoch: ChainEpoch,
+    #[serde(with = "bigint_ser")]
+    pub reward: TokenAmount,
+    #[serde(with = "bigint_ser")]
+    pub penalty: TokenAmount,
+    pub memo: RawBytes,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetRecentRewardsReturn {
+    /// The most recent reward applications, oldest first.
+    pub rewards: Vec<RewardApplication>,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ApplyRewardReturn {
+    /// Funds unlocked from the vesting table before the reward was locked.
+    #[serde(with = "bigint_ser")]
+    pub newly_vested: TokenAmount,
+    /// Portion of the reward locked in the vesting table.
+    #[serde(with = "bigint_ser")]
+    pub reward_to_lock: TokenAmount,
+    /// Penalty burnt from vesting funds and balance.
+    #[serde(with = "bigint_ser")]
+    pub to_burn: TokenAmount,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct RepayDebtReturn {
+    /// Unvested funds taken from the vesting table to repay fee debt.
+    #[serde(with = "bigint_ser")]
+    pub from_vesting: TokenAmount,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy, Serialize_tuple, Deserialize_tuple)]
@@ -102,6 +102,21 @@ impl VestingFunds {
         );
     }
 
+    /// Merges entries vesting at the same epoch and drops empty entries, leaving the total
+    /// amount in the table unchanged.
+    pub fn compact(&mut self) {
+        let funds = mem::take(&mut self.funds);
+        for fund in funds {
+            if fund.amount.is_zero() {
+                continue;
+            }
+            match self.funds.last_mut() {
+                Some(last) if last.epoch == fund.epoch => last.amount += fund.amount,
+                _ => self.funds.push(fund),
+            }
+        }
+    }
+
     pub fn unlock_unvested_funds(
         &mut self,
         current_epoch: ChainEpoch,
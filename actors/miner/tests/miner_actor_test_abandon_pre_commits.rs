@@ -0,0 +1,140 @@
+use fil_actor_miner::{
+    max_prove_commit_duration, AbandonPreCommitsParams, CollisionPolicy, Method,
+    SectorPreCommitInfo, SectorPreCommitOnChainInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::BURNT_FUNDS_ACTOR_ADDR;
+
+use bitfield::BitField;
+use cid::Cid;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+
+mod util;
+
+// Records a pre-commitment in state as PreCommitSectorBatch would.
+fn pre_commit(rt: &mut MockRuntime, h: &util::ActorHarness, sector_number: u64, deposit: u64) {
+    let mut st: State = rt.get_state().unwrap();
+    let deposit = TokenAmount::from(deposit);
+    st.put_precommitted_sectors(
+        &rt.store,
+        vec![SectorPreCommitOnChainInfo {
+            info: SectorPreCommitInfo {
+                seal_proof: h.seal_proof_type,
+                sector_number,
+                sealed_cid: Cid::default(),
+                seal_rand_epoch: 0,
+                deal_ids: vec![],
+                expiration: rt.policy.min_sector_expiration(rt.network_version) + 1,
+                replace_capacity: false,
+                replace_sector_deadline: 0,
+                replace_sector_partition: 0,
+                replace_sector_number: 0,
+            },
+            pre_commit_deposit: deposit.clone(),
+            pre_commit_epoch: rt.epoch,
+            deal_weight: BigInt::from(0),
+            verified_deal_weight: BigInt::from(0),
+        }],
+    )
+    .unwrap();
+    st.add_pre_commit_deposit(&deposit).unwrap();
+    st.allocate_sector_numbers(
+        &rt.policy,
+        &rt.store,
+        &[sector_number].iter().copied().collect(),
+        CollisionPolicy::DenyCollisions,
+    )
+    .unwrap();
+    let msd = max_prove_commit_duration(&rt.policy, h.seal_proof_type).unwrap();
+    let clean_up_bound = rt.epoch + msd + rt.policy.expired_pre_commit_clean_up_delay;
+    st.add_pre_commit_clean_ups(&rt.policy, &rt.store, vec![(clean_up_bound, sector_number)])
+        .unwrap();
+    rt.replace_state(&st);
+    rt.add_balance(deposit);
+}
+
+fn abandon_pre_commits(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    sectors: &[u64],
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    let sectors: BitField = sectors.iter().copied().collect();
+    let params = AbandonPreCommitsParams { sectors: sectors.into() };
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.push(h.worker);
+    caller_addrs.push(h.owner);
+    rt.expect_validate_caller_addr(caller_addrs);
+    rt.call::<fil_actor_miner::Actor>(
+        Method::AbandonPreCommits as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+}
+
+#[test]
+fn test_abandon_pre_commits_burns_deposit_and_frees_numbers() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    pre_commit(&mut rt, &h, 0, 100);
+    pre_commit(&mut rt, &h, 1, 200);
+    assert_eq!(2, h.next_allocatable_sector_number(&mut rt));
+
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(100),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    abandon_pre_commits(&mut rt, &h, &[0]).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.get_precommitted_sector(&rt.store, 0).unwrap().is_none());
+    assert!(st.get_precommitted_sector(&rt.store, 1).unwrap().is_some());
+    assert_eq!(TokenAmount::from(200), st.pre_commit_deposits);
+    assert_eq!(0, h.next_allocatable_sector_number(&mut rt));
+
+    // The abandoned sector's clean-up is no longer scheduled, so a new pre-commit reusing
+    // its number is not removed at the old expiry.
+    let msd = max_prove_commit_duration(&rt.policy, h.seal_proof_type).unwrap();
+    // Clean-up epochs are quantized up to the end of a deadline.
+    let old_expiry = rt.epoch
+        + msd
+        + rt.policy.expired_pre_commit_clean_up_delay
+        + rt.policy.wpost_challenge_window;
+    rt.epoch += 1000;
+    pre_commit(&mut rt, &h, 0, 300);
+
+    let mut st: State = rt.get_state().unwrap();
+    let burnt = st.cleanup_expired_pre_commits(&rt.policy, &rt.store, old_expiry).unwrap();
+    assert_eq!(TokenAmount::from(200), burnt);
+    assert!(st.get_precommitted_sector(&rt.store, 0).unwrap().is_some());
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_abandon_pre_commits_fails_for_missing_pre_commit() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    pre_commit(&mut rt, &h, 0, 100);
+
+    expect_abort(ExitCode::ErrNotFound, abandon_pre_commits(&mut rt, &h, &[0, 1]));
+    rt.reset();
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.get_precommitted_sector(&rt.store, 0).unwrap().is_some());
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,56 @@
+use fil_actor_miner::{SectorOnChainInfo, State};
+use fil_actors_runtime::test_utils::*;
+
+mod util;
+
+#[test]
+fn test_no_active_deals_on_new_miner() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let ret = h.get_active_deals(&mut rt, 0);
+    assert!(ret.items.is_empty());
+    assert_eq!(None, ret.next);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_unproven_sector_deals_are_not_active() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    let sectors: Vec<SectorOnChainInfo> = [1, 2]
+        .iter()
+        .map(|&sector_number| SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            deal_ids: vec![sector_number * 10],
+            expiration: rt.policy.min_sector_expiration(rt.network_version),
+            ..Default::default()
+        })
+        .collect();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        sectors,
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+
+    // Newly assigned sectors are unproven until their first window PoSt.
+    let ret = h.get_active_deals(&mut rt, 0);
+    assert!(ret.items.is_empty());
+    assert_eq!(None, ret.next);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,68 @@
+use fil_actor_miner::{
+    aggregate_pre_commit_network_fee, aggregate_prove_commit_network_fee,
+    EstimateAggregateFeeParams, EstimateAggregateFeeReturn, Method,
+};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+fn estimate_fee(
+    rt: &mut MockRuntime,
+    method: Method,
+    sector_count: u64,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    rt.expect_validate_caller_any();
+    let params = EstimateAggregateFeeParams { sector_count };
+    rt.call::<fil_actor_miner::Actor>(method as u64, &RawBytes::serialize(params).unwrap())
+}
+
+fn fee(rt: &mut MockRuntime, method: Method, sector_count: u64) -> TokenAmount {
+    let ret: EstimateAggregateFeeReturn =
+        estimate_fee(rt, method, sector_count).unwrap().deserialize().unwrap();
+    rt.verify();
+    ret.fee
+}
+
+#[test]
+fn test_estimates_follow_base_fee() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let base_fee = TokenAmount::from(1_000_000_000u64);
+    rt.base_fee = base_fee.clone();
+
+    assert_eq!(
+        aggregate_prove_commit_network_fee(10, &base_fee),
+        fee(&mut rt, Method::EstimateAggregateProveCommitFee, 10)
+    );
+    assert_eq!(
+        aggregate_pre_commit_network_fee(10, &base_fee),
+        fee(&mut rt, Method::EstimateAggregatePreCommitFee, 10)
+    );
+    // A single pre-commit is not aggregated.
+    assert_eq!(TokenAmount::from(0), fee(&mut rt, Method::EstimateAggregatePreCommitFee, 1));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_prove_commit_estimate_rejects_too_few_sectors() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let too_few = rt.policy.min_aggregated_sectors - 1;
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        estimate_fee(&mut rt, Method::EstimateAggregateProveCommitFee, too_few),
+    );
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,78 @@
+use fil_actor_miner::{CanDeclareRecoveryParams, Method, State};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+#[test]
+fn test_recovery_allowed_before_fault_cutoff() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    let current = st.deadline_info(&rt.policy, rt.epoch);
+    rt.epoch = current.open;
+    // Two deadlines ahead, the fault cutoff is still in the future.
+    let deadline = (current.index + 2) % rt.policy.wpost_period_deadlines;
+    let expected_cutoff =
+        current.open + 2 * rt.policy.wpost_challenge_window - rt.policy.fault_declaration_cutoff;
+
+    let ret = h.can_declare_recovery(&mut rt, deadline);
+    assert!(!ret.fault_cutoff_passed);
+    assert_eq!(expected_cutoff, ret.fault_cutoff);
+
+    // Once the cutoff is reached, the declaration would be rejected.
+    rt.epoch = expected_cutoff;
+    let ret = h.can_declare_recovery(&mut rt, deadline);
+    assert!(ret.fault_cutoff_passed);
+    assert_eq!(expected_cutoff, ret.fault_cutoff);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_recovery_rejected_after_fault_cutoff() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    let current = st.deadline_info(&rt.policy, rt.epoch);
+    // The next deadline's cutoff precedes its opening by more than a challenge window.
+    let deadline = (current.index + 1) % rt.policy.wpost_period_deadlines;
+
+    let ret = h.can_declare_recovery(&mut rt, deadline);
+    assert!(ret.fault_cutoff_passed);
+    assert_eq!(
+        current.open + rt.policy.wpost_challenge_window - rt.policy.fault_declaration_cutoff,
+        ret.fault_cutoff
+    );
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_can_declare_recovery_rejects_invalid_deadline() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let params = CanDeclareRecoveryParams { deadline: rt.policy.wpost_period_deadlines };
+    rt.expect_validate_caller_any();
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<fil_actor_miner::Actor>(
+            Method::CanDeclareRecovery as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.reset();
+
+    util::check_state_invariants(&rt);
+}
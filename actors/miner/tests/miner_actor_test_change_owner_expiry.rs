@@ -0,0 +1,89 @@
+use fil_actor_miner::{ChangeOwnerAddressWithExpiryParams, Method, State};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+fn change_owner(
+    rt: &mut MockRuntime,
+    caller: Address,
+    new_address: Address,
+    expiration: Option<ChainEpoch>,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+    rt.expect_validate_caller_addr(vec![caller]);
+    let params = ChangeOwnerAddressWithExpiryParams { new_address, expiration };
+    let ret = rt.call::<fil_actor_miner::Actor>(
+        Method::ChangeOwnerAddressWithExpiry as u64,
+        &RawBytes::serialize(params).unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+#[test]
+fn test_confirm_before_expiry() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let new_owner = Address::new_id(999);
+    rt.epoch = 100;
+    change_owner(&mut rt, h.owner, new_owner, Some(110)).unwrap();
+
+    rt.epoch = 110;
+    change_owner(&mut rt, new_owner, new_owner, None).unwrap();
+
+    let st: State = rt.get_state().unwrap();
+    let info = st.get_info(&rt.store).unwrap();
+    assert_eq!(new_owner, info.owner);
+    assert_eq!(None, info.pending_owner_address);
+    assert_eq!(None, info.pending_owner_expiration);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_confirm_after_expiry_requires_new_proposal() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let new_owner = Address::new_id(999);
+    rt.epoch = 100;
+    change_owner(&mut rt, h.owner, new_owner, Some(110)).unwrap();
+
+    rt.epoch = 111;
+    expect_abort(ExitCode::ErrForbidden, change_owner(&mut rt, new_owner, new_owner, None));
+
+    // The owner may propose again with a fresh expiry.
+    change_owner(&mut rt, h.owner, new_owner, Some(120)).unwrap();
+    change_owner(&mut rt, new_owner, new_owner, None).unwrap();
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(new_owner, st.get_info(&rt.store).unwrap().owner);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_rejects_expiration_in_the_past() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    rt.epoch = 100;
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        change_owner(&mut rt, h.owner, Address::new_id(999), Some(99)),
+    );
+
+    util::check_state_invariants(&rt);
+}
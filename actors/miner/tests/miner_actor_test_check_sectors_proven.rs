@@ -0,0 +1,60 @@
+use fil_actor_miner::{SectorOnChainInfo, State};
+use fil_actors_runtime::test_utils::*;
+
+use bitfield::BitField;
+
+mod util;
+
+#[test]
+fn test_no_sectors_proven_on_new_miner() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let (proven, not_proven) = h.check_sectors_proven(&mut rt, &[1, 2, 3]);
+    assert!(proven.is_empty());
+    assert_eq!(vec![1, 2, 3], not_proven.iter().collect::<Vec<_>>());
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_splits_proven_and_unproven_sectors() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    let sectors = [2, 5]
+        .iter()
+        .map(|&sector_number| SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            ..Default::default()
+        })
+        .collect();
+    st.put_sectors(&rt.store, sectors).unwrap();
+    rt.replace_state(&st);
+
+    let (proven, not_proven) = h.check_sectors_proven(&mut rt, &[1, 2, 3, 5]);
+    assert_eq!(vec![2, 5], proven.iter().collect::<Vec<_>>());
+    assert_eq!(vec![1, 3], not_proven.iter().collect::<Vec<_>>());
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_empty_request() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let (proven, not_proven) = h.check_sectors_proven(&mut rt, &[]);
+    assert_eq!(BitField::new(), proven);
+    assert_eq!(BitField::new(), not_proven);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,109 @@
+use fil_actor_miner::{
+    deadline_is_mutable, CheckTerminationAllowedReturn, Method, SectorOnChainInfo, State,
+    TerminateSectorsParams, TerminationDeclaration,
+};
+use fil_actors_runtime::test_utils::*;
+
+use bitfield::BitField;
+use fvm_shared::encoding::RawBytes;
+
+mod util;
+
+fn check_termination_allowed(
+    rt: &mut MockRuntime,
+    terminations: Vec<TerminationDeclaration>,
+) -> CheckTerminationAllowedReturn {
+    let params = TerminateSectorsParams { terminations };
+    rt.expect_validate_caller_any();
+    let ret = rt
+        .call::<fil_actor_miner::Actor>(
+            Method::CheckTerminationAllowed as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret
+}
+
+#[test]
+fn test_check_termination_reports_blocked_sectors() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    let sector = SectorOnChainInfo {
+        sector_number: 1,
+        seal_proof: h.seal_proof_type,
+        expiration: rt.policy.min_sector_expiration(rt.network_version),
+        ..Default::default()
+    };
+    st.put_sectors(&rt.store, vec![sector.clone()]).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        vec![sector],
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+
+    let (deadline, partition) = st.find_sector(&rt.policy, &rt.store, 1).unwrap();
+    let mutable = deadline_is_mutable(
+        &rt.policy,
+        st.current_proving_period_start(&rt.policy, rt.epoch),
+        deadline,
+        rt.epoch,
+    );
+
+    // Sector 2 was never added, so it cannot be terminated.
+    let requested: BitField = [1, 2].iter().copied().collect();
+    let ret = check_termination_allowed(
+        &mut rt,
+        vec![TerminationDeclaration { deadline, partition, sectors: requested.clone().into() }],
+    );
+
+    let expected_not_live: BitField = [2].iter().copied().collect();
+    assert_eq!(expected_not_live, ret.not_live);
+    if mutable {
+        assert!(ret.immutable_deadline.is_empty());
+    } else {
+        assert_eq!(requested, ret.immutable_deadline);
+    }
+
+    // Nothing was terminated.
+    let st_after: State = rt.get_state().unwrap();
+    assert_eq!(st.deadlines, st_after.deadlines);
+    assert!(st_after.early_terminations.is_empty());
+}
+
+#[test]
+fn test_check_termination_in_current_deadline_is_blocked() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    let current = st.deadline_info(&rt.policy, rt.epoch).index;
+
+    let requested: BitField = [5].iter().copied().collect();
+    let ret = check_termination_allowed(
+        &mut rt,
+        vec![TerminationDeclaration {
+            deadline: current,
+            partition: 0,
+            sectors: requested.clone().into(),
+        }],
+    );
+
+    assert_eq!(requested, ret.immutable_deadline);
+    assert_eq!(requested, ret.not_live);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,80 @@
+use fil_actor_miner::{
+    ConfirmSectorProofsParams, Method, SectorOnChainInfo, SectorPreCommitInfo,
+    SectorPreCommitOnChainInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::STORAGE_POWER_ACTOR_ADDR;
+
+use cid::Cid;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+
+mod util;
+
+#[test]
+fn test_duplicate_confirmation_is_noop() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // Simulate a sector that has been proven while its pre-commit is delivered again.
+    let sector_number = 100;
+    let mut st: State = rt.get_state().unwrap();
+    st.put_precommitted_sectors(
+        &rt.store,
+        vec![SectorPreCommitOnChainInfo {
+            info: SectorPreCommitInfo {
+                seal_proof: h.seal_proof_type,
+                sector_number,
+                sealed_cid: Cid::default(),
+                seal_rand_epoch: 0,
+                deal_ids: vec![1, 2],
+                expiration: rt.policy.min_sector_expiration(rt.network_version) + 1,
+                replace_capacity: false,
+                replace_sector_deadline: 0,
+                replace_sector_partition: 0,
+                replace_sector_number: 0,
+            },
+            pre_commit_deposit: TokenAmount::from(0),
+            pre_commit_epoch: 0,
+            deal_weight: BigInt::from(0),
+            verified_deal_weight: BigInt::from(0),
+        }],
+    )
+    .unwrap();
+    st.put_sectors(
+        &rt.store,
+        vec![SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            ..Default::default()
+        }],
+    )
+    .unwrap();
+    rt.replace_state(&st);
+
+    let params = ConfirmSectorProofsParams {
+        sectors: vec![sector_number],
+        reward_smoothed: h.epoch_reward_smooth.clone(),
+        reward_baseline_power: h.baseline_power.clone(),
+        quality_adj_power_smoothed: h.epoch_qa_power_smooth.clone(),
+    };
+
+    // No deal activation, power or pledge updates are expected.
+    rt.set_caller(*POWER_ACTOR_CODE_ID, *STORAGE_POWER_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*STORAGE_POWER_ACTOR_ADDR]);
+    rt.call::<fil_actor_miner::Actor>(
+        Method::ConfirmSectorProofsValid as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+
+    let st_after: State = rt.get_state().unwrap();
+    assert_eq!(st.initial_pledge, st_after.initial_pledge);
+    assert!(st_after.get_precommitted_sector(&rt.store, sector_number).unwrap().is_some());
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,45 @@
+use fil_actor_miner::State;
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::clock::EPOCH_UNDEFINED;
+
+mod util;
+
+#[test]
+fn test_no_consensus_fault_on_new_miner() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let ret = h.consensus_fault_status(&mut rt);
+    assert!(!ret.active);
+    assert_eq!(EPOCH_UNDEFINED, ret.elapsed_epoch);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_consensus_fault_active_until_elapsed() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let elapsed = 100;
+    let mut st: State = rt.get_state().unwrap();
+    let mut info = st.get_info(&rt.store).unwrap();
+    info.consensus_fault_elapsed = elapsed;
+    st.save_info(&rt.store, &info).unwrap();
+    rt.replace_state(&st);
+
+    rt.epoch = elapsed;
+    let ret = h.consensus_fault_status(&mut rt);
+    assert!(ret.active);
+    assert_eq!(elapsed, ret.elapsed_epoch);
+
+    rt.epoch = elapsed + 1;
+    assert!(!h.consensus_fault_status(&mut rt).active);
+
+    util::check_state_invariants(&rt);
+}
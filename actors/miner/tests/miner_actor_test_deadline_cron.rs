@@ -0,0 +1,141 @@
+use fil_actor_miner::{new_deadline_info, pledge_penalty_for_continued_fault, DeadlineInfo, State};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::BURNT_FUNDS_ACTOR_ADDR;
+
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+
+mod util;
+
+// Keeps the deadline cron running with a pre-commit deposit, returning the first deadline's
+// last epoch.
+fn setup(rt: &mut MockRuntime, h: &util::ActorHarness) -> ChainEpoch {
+    h.construct_and_verify(rt);
+    let deposit = TokenAmount::from(100);
+    let mut st: State = rt.get_state().unwrap();
+    st.add_pre_commit_deposit(&deposit).unwrap();
+    rt.replace_state(&st);
+    rt.add_balance(deposit);
+    st.deadline_info(&rt.policy, rt.epoch).last()
+}
+
+// Adds two sectors to the open deadline that were already faulty, and so stay faulty through it,
+// returning the deadline with the epoch set to its last.
+fn setup_continued_faults(rt: &mut MockRuntime, h: &util::ActorHarness) -> DeadlineInfo {
+    setup(rt, h);
+    rt.set_balance(TokenAmount::from(10u64.pow(18)) * 1_000_000);
+    rt.epoch = 10;
+    let dl_info = h.add_proven_sectors(rt, &[h.seal_proof_type, h.seal_proof_type]);
+    h.record_faults(rt, &dl_info, &[0, 1]);
+    rt.epoch = dl_info.last();
+    dl_info
+}
+
+#[test]
+fn test_deadline_cron_enrolls_end_of_next_deadline() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+    let first_last = setup(&mut rt, &h);
+
+    // Cron runs at the last epoch of a deadline and enrolls the last epoch of the next one.
+    rt.epoch = first_last;
+    let next = first_last + rt.policy.wpost_challenge_window;
+    h.on_deadline_cron(&mut rt, next);
+
+    rt.epoch = next;
+    h.on_deadline_cron(&mut rt, next + rt.policy.wpost_challenge_window);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_deadline_cron_enrolls_future_epoch_after_gap_of_several_deadlines() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+    let first_last = setup(&mut rt, &h);
+
+    // Cron is next invoked during deadline 3, which it advances past.
+    rt.epoch = first_last + 3 * rt.policy.wpost_challenge_window - 7;
+    let expected = new_deadline_info(&rt.policy, 0, 4, rt.epoch).last();
+    assert!(expected > rt.epoch);
+    h.on_deadline_cron(&mut rt, expected);
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(4, st.current_deadline);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_deadline_cron_enrolls_future_epoch_after_gap_of_more_than_a_period() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+    let first_last = setup(&mut rt, &h);
+
+    // Cron is next invoked during deadline 6 of the following proving period. The deadline
+    // index doesn't wrap, so the recorded proving period start is left a period behind.
+    let window = rt.policy.wpost_challenge_window;
+    rt.epoch = first_last + (rt.policy.wpost_period_deadlines as i64 + 5) * window + 7;
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(6, st.deadline_info(&rt.policy, rt.epoch).index);
+
+    let expected = new_deadline_info(&rt.policy, rt.policy.wpost_proving_period, 7, rt.epoch);
+    assert!(expected.last() > rt.epoch);
+    h.on_deadline_cron(&mut rt, expected.last());
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(7, st.current_deadline);
+    assert!(st.recorded_deadline_info(&rt.policy, rt.epoch).last() < rt.epoch);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_deadline_cron_waives_continued_fault_penalty_within_grace() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+    let dl_info = setup_continued_faults(&mut rt, &h);
+
+    // The grace runs through the deadline's last epoch, so no penalty is burnt.
+    rt.policy.continued_fault_grace_until_epoch = Some(dl_info.last() + 1);
+    h.on_deadline_cron(&mut rt, dl_info.last() + rt.policy.wpost_challenge_window);
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(TokenAmount::from(0), st.fee_debt);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_deadline_cron_penalizes_continued_faults_after_grace() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+    let dl_info = setup_continued_faults(&mut rt, &h);
+
+    // The grace ends at the deadline's last epoch, so the faults pay the full penalty.
+    rt.policy.continued_fault_grace_until_epoch = Some(dl_info.last());
+    let penalty = pledge_penalty_for_continued_fault(
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &(BigInt::from(h.sector_size as u64) * 2),
+    );
+    assert!(penalty > TokenAmount::from(0));
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        penalty,
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    h.on_deadline_cron(&mut rt, dl_info.last() + rt.policy.wpost_challenge_window);
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(TokenAmount::from(0), st.fee_debt);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,96 @@
+use fil_actor_miner::{ext, pledge_penalty_for_continued_fault, State};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{BURNT_FUNDS_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+
+use lazy_static::lazy_static;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+
+mod util;
+
+lazy_static! {
+    static ref MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+}
+
+/// Keeps the messages logged at info level or above.
+struct CaptureLogger;
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            MESSAGES.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger;
+
+#[test]
+fn test_deadline_cron_logs_summary_of_processed_deadline() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Info);
+
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    // A pre-commit deposit keeps the deadline cron running.
+    let mut st: State = rt.get_state().unwrap();
+    st.add_pre_commit_deposit(&TokenAmount::from(100)).unwrap();
+    rt.replace_state(&st);
+    rt.set_balance(TokenAmount::from(10u64.pow(18)) * 1_000_000);
+
+    // Sector 0 was already faulty, and sector 1 is detected faulty for missing its proof.
+    rt.epoch = 10;
+    let dl_info = h.add_proven_sectors(&mut rt, &[h.seal_proof_type, h.seal_proof_type]);
+    h.record_faults(&mut rt, &dl_info, &[0]);
+    rt.epoch = dl_info.last();
+
+    let sector_power = BigInt::from(h.sector_size as u64);
+    let penalty = pledge_penalty_for_continued_fault(
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &sector_power,
+    );
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_CLAIMED_POWER_METHOD,
+        RawBytes::serialize(ext::power::UpdateClaimedPowerParams {
+            raw_byte_delta: -sector_power.clone(),
+            quality_adjusted_delta: -sector_power.clone(),
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        penalty.clone(),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    h.on_deadline_cron(&mut rt, dl_info.last() + rt.policy.wpost_challenge_window);
+
+    let expected = format!(
+        "miner {} processed deadline {}: power delta raw {} qa {}, continued fault penalty {}",
+        rt.receiver, dl_info.index, -&sector_power, -&sector_power, penalty
+    );
+    assert!(MESSAGES.lock().unwrap().contains(&expected));
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,163 @@
+use fil_actor_miner::{
+    new_deadline_info, DeadlineInfoAtParams, DeadlineInfoAtReturn, Method, State, UpcomingDeadline,
+    UpcomingDeadlinesParams, UpcomingDeadlinesReturn,
+};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+fn expected_deadline_info_at(rt: &MockRuntime, epoch: ChainEpoch) -> DeadlineInfoAtReturn {
+    let st: State = rt.get_state().unwrap();
+    let info = st.deadline_info(&rt.policy, epoch);
+    DeadlineInfoAtReturn {
+        period_start: info.period_start,
+        index: info.index,
+        open: info.open,
+        close: info.close,
+        challenge: info.challenge,
+        fault_cutoff: info.fault_cutoff,
+    }
+}
+
+fn deadline_info_at_fails(rt: &mut MockRuntime, epoch: ChainEpoch) {
+    let params = DeadlineInfoAtParams { epoch };
+    rt.expect_validate_caller_any();
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<fil_actor_miner::Actor>(
+            Method::DeadlineInfoAt as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn test_deadline_info_at_current_epoch() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let epoch = rt.epoch;
+    assert_eq!(expected_deadline_info_at(&rt, epoch), h.deadline_info_at(&mut rt, epoch));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_deadline_info_at_future_epoch() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let epoch = rt.epoch + rt.policy.wpost_challenge_window * 3;
+    let info = h.deadline_info_at(&mut rt, epoch);
+    assert_eq!(expected_deadline_info_at(&rt, epoch), info);
+    assert_eq!(3, info.index);
+    assert!(info.open <= epoch && epoch < info.close);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_deadline_info_at_rejects_distant_epoch() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let epoch = rt.epoch + rt.policy.wpost_proving_period + 1;
+    deadline_info_at_fails(&mut rt, epoch);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_deadline_info_at_rejects_extreme_epochs() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // The distance to these epochs doesn't fit in an epoch.
+    rt.epoch = 10;
+    deadline_info_at_fails(&mut rt, ChainEpoch::MIN);
+    rt.epoch = -10;
+    deadline_info_at_fails(&mut rt, ChainEpoch::MAX);
+
+    util::check_state_invariants(&rt);
+}
+
+fn upcoming_deadlines(rt: &mut MockRuntime, count: u64) -> Vec<UpcomingDeadline> {
+    rt.expect_validate_caller_any();
+    let ret: UpcomingDeadlinesReturn = rt
+        .call::<fil_actor_miner::Actor>(
+            Method::UpcomingDeadlines as u64,
+            &RawBytes::serialize(UpcomingDeadlinesParams { count }).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.deadlines
+}
+
+#[test]
+fn test_upcoming_deadlines_cross_period_boundary() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // Move into the last deadline of the proving period.
+    let st: State = rt.get_state().unwrap();
+    let current = st.deadline_info(&rt.policy, rt.epoch);
+    let last_index = rt.policy.wpost_period_deadlines - 1;
+    rt.epoch = new_deadline_info(&rt.policy, current.period_start, last_index, rt.epoch).open;
+
+    let deadlines = upcoming_deadlines(&mut rt, 2);
+    assert_eq!(2, deadlines.len());
+    let last = st.deadline_info(&rt.policy, rt.epoch);
+    assert_eq!(
+        UpcomingDeadline {
+            index: last_index,
+            open: last.open,
+            close: last.close,
+            challenge: last.challenge,
+            fault_cutoff: last.fault_cutoff,
+        },
+        deadlines[0]
+    );
+    assert_eq!(0, deadlines[1].index);
+    assert_eq!(current.period_start + rt.policy.wpost_proving_period, deadlines[1].open);
+    assert_eq!(deadlines[0].close, deadlines[1].open);
+    assert_eq!(deadlines[1].open - rt.policy.fault_declaration_cutoff, deadlines[1].fault_cutoff);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_upcoming_deadlines_rejects_more_than_a_period() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let count = rt.policy.wpost_period_deadlines + 1;
+    rt.expect_validate_caller_any();
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<fil_actor_miner::Actor>(
+            Method::UpcomingDeadlines as u64,
+            &RawBytes::serialize(UpcomingDeadlinesParams { count }).unwrap(),
+        ),
+    );
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,62 @@
+use fil_actor_miner::{SectorOnChainInfo, State};
+use fil_actors_runtime::test_utils::*;
+
+mod util;
+
+#[test]
+fn test_deadline_layout_after_construction() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let layout = h.get_deadline_layout(&mut rt);
+    assert_eq!(rt.policy.wpost_period_deadlines as usize, layout.deadlines.len());
+    assert!(layout.deadlines.iter().all(|dl| dl.partition_sector_counts.is_empty()));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_deadline_layout_counts_partition_sectors() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let sector_count = 5;
+    let sectors: Vec<SectorOnChainInfo> = (0..sector_count)
+        .map(|sector_number| SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            expiration: rt.policy.min_sector_expiration(rt.network_version),
+            ..Default::default()
+        })
+        .collect();
+    let mut st: State = rt.get_state().unwrap();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        sectors,
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+
+    let layout = h.get_deadline_layout(&mut rt);
+    let counts: Vec<u64> =
+        layout.deadlines.iter().flat_map(|dl| dl.partition_sector_counts.clone()).collect();
+    assert!(counts.iter().all(|count| *count > 0));
+    assert_eq!(sector_count, counts.iter().sum::<u64>());
+
+    // Each sector is counted in the partition it was assigned to.
+    for sector_number in 0..sector_count {
+        let (dl_idx, p_idx) = st.find_sector(&rt.policy, &rt.store, sector_number).unwrap();
+        assert!(layout.deadlines[dl_idx as usize].partition_sector_counts[p_idx as usize] > 0);
+    }
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,112 @@
+use fil_actor_miner::{ext, Method, RepayDebtReturn, State};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{BURNT_FUNDS_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+use fvm_shared::bigint::bigint_ser::BigIntSer;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+
+mod util;
+
+#[test]
+fn test_debt_status_reports_funds_needed_to_clear_debt() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    st.add_locked_funds(
+        &rt.store,
+        rt.epoch,
+        &TokenAmount::from(100),
+        &rt.policy.reward_vesting_spec,
+    )
+    .unwrap();
+    st.fee_debt = TokenAmount::from(300);
+    rt.replace_state(&st);
+    rt.set_balance(TokenAmount::from(150));
+
+    // RepayDebt would take all 100 unvested and the 50 unlocked, leaving 150 to be sent.
+    let status = h.get_debt_status(&mut rt);
+    assert_eq!(TokenAmount::from(300), status.fee_debt);
+    assert_eq!(TokenAmount::from(50), status.unlocked_balance);
+    assert_eq!(TokenAmount::from(100), status.repayable_from_vesting);
+    assert_eq!(TokenAmount::from(150), status.additional_funds_needed);
+}
+
+#[test]
+fn test_debt_status_needs_nothing_when_balance_covers_debt() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    st.fee_debt = TokenAmount::from(30);
+    rt.replace_state(&st);
+    rt.set_balance(TokenAmount::from(100));
+
+    let status = h.get_debt_status(&mut rt);
+    assert_eq!(TokenAmount::from(100), status.unlocked_balance);
+    assert_eq!(TokenAmount::from(0), status.repayable_from_vesting);
+    assert_eq!(TokenAmount::from(0), status.additional_funds_needed);
+}
+
+#[test]
+fn test_repay_debt_returns_amount_taken_from_vesting() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    st.add_locked_funds(
+        &rt.store,
+        rt.epoch,
+        &TokenAmount::from(100),
+        &rt.policy.reward_vesting_spec,
+    )
+    .unwrap();
+    st.fee_debt = TokenAmount::from(300);
+    rt.replace_state(&st);
+    rt.set_balance(TokenAmount::from(150));
+
+    // All 100 unvested funds and the 50 unlocked are burnt towards the debt.
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_PLEDGE_TOTAL_METHOD,
+        RawBytes::serialize(BigIntSer(&TokenAmount::from(-100))).unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(150),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.extend_from_slice(&[h.worker, h.owner]);
+    rt.expect_validate_caller_addr(caller_addrs);
+    let ret: RepayDebtReturn = rt
+        .call::<fil_actor_miner::Actor>(Method::RepayDebt as u64, &RawBytes::default())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(TokenAmount::from(100), ret.from_vesting);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(TokenAmount::from(150), st.fee_debt);
+    assert_eq!(TokenAmount::from(0), st.locked_funds);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,113 @@
+use fil_actor_miner::{GetDisputeInfoParams, GetDisputeInfoReturn, Method};
+use fil_actors_runtime::test_utils::*;
+
+use bitfield::BitField;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+fn get_dispute_info(
+    rt: &mut MockRuntime,
+    deadline: u64,
+    post_index: u64,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    rt.expect_validate_caller_any();
+    let params = GetDisputeInfoParams { deadline, post_index };
+    rt.call::<fil_actor_miner::Actor>(
+        Method::GetDisputeInfo as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+}
+
+#[test]
+fn test_dispute_info_describes_optimistic_post() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.epoch = 10;
+    let dl_info = h.add_proven_sectors(&mut rt, &[h.seal_proof_type, h.seal_proof_type]);
+    h.submit_optimistic_post(&mut rt, &dl_info);
+    h.close_deadline_for_dispute(&mut rt, &dl_info);
+
+    let ret: GetDisputeInfoReturn =
+        get_dispute_info(&mut rt, dl_info.index, 0).unwrap().deserialize().unwrap();
+    rt.verify();
+
+    let disputed_power = BigInt::from(h.sector_size as u64) * 2;
+    assert_eq!([0].iter().copied().collect::<BitField>(), ret.partitions);
+    assert!(ret.ignored_sectors.is_empty());
+    assert_eq!(disputed_power, ret.disputed_power.raw);
+    assert_eq!(disputed_power, ret.disputed_power.qa);
+
+    // The proof is left in place, so it can be described again.
+    let again: GetDisputeInfoReturn =
+        get_dispute_info(&mut rt, dl_info.index, 0).unwrap().deserialize().unwrap();
+    rt.verify();
+    assert_eq!(ret.partitions, again.partitions);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_dispute_info_rejects_deadline_outside_dispute_window() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.epoch = 10;
+    let dl_info = h.add_proven_sectors(&mut rt, &[h.seal_proof_type]);
+    h.submit_optimistic_post(&mut rt, &dl_info);
+
+    // The deadline's challenge window is still open.
+    expect_abort_contains_message(
+        ExitCode::ErrForbidden,
+        "can only dispute window posts during the dispute window",
+        get_dispute_info(&mut rt, dl_info.index, 0),
+    );
+
+    // The dispute window has passed.
+    rt.epoch = dl_info.close + rt.policy.wpost_dispute_window;
+    expect_abort_contains_message(
+        ExitCode::ErrForbidden,
+        "can only dispute window posts during the dispute window",
+        get_dispute_info(&mut rt, dl_info.index, 0),
+    );
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_dispute_info_rejects_missing_proof() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.epoch = 10;
+    let dl_info = h.add_proven_sectors(&mut rt, &[h.seal_proof_type]);
+    h.submit_optimistic_post(&mut rt, &dl_info);
+    h.close_deadline_for_dispute(&mut rt, &dl_info);
+
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "proof 1 not found",
+        get_dispute_info(&mut rt, dl_info.index, 1),
+    );
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_dispute_info_rejects_invalid_deadline() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let deadline = rt.policy.wpost_period_deadlines;
+    expect_abort(ExitCode::ErrIllegalArgument, get_dispute_info(&mut rt, deadline, 0));
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,153 @@
+use fil_actor_miner::{
+    ext, pledge_penalty_for_invalid_windowpost, Method, State, WithdrawDisputeRewardReturn,
+    BASE_REWARD_FOR_DISPUTED_WINDOW_POST,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{BURNT_FUNDS_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sector::RegisteredSealProof;
+use fvm_shared::METHOD_SEND;
+
+mod util;
+
+fn withdraw_dispute_reward(
+    rt: &mut MockRuntime,
+    reporter: Address,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, reporter);
+    rt.expect_validate_caller_type((*CALLER_TYPES_SIGNABLE).clone());
+    rt.call::<fil_actor_miner::Actor>(Method::WithdrawDisputeReward as u64, &RawBytes::default())
+}
+
+#[test]
+fn test_dispute_reward_is_recorded_as_owed_when_send_fails() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.set_balance(TokenAmount::from(10u64.pow(18)) * 1_000_000);
+    rt.epoch = 10;
+    // A sector of another proof type makes the optimistically accepted proof invalid.
+    let dl_info = h.add_proven_sectors(
+        &mut rt,
+        &[RegisteredSealProof::StackedDRG64GiBV1, RegisteredSealProof::StackedDRG32GiBV1],
+    );
+    h.submit_optimistic_post(&mut rt, &dl_info);
+    h.close_deadline_for_dispute(&mut rt, &dl_info);
+
+    let reporter = Address::new_id(1234);
+    let disputed_power = BigInt::from(h.sector_size as u64) * 2;
+    let penalty_base = pledge_penalty_for_invalid_windowpost(
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &disputed_power,
+    );
+    let reward = BASE_REWARD_FOR_DISPUTED_WINDOW_POST.clone();
+    h.expect_query_network_info(&mut rt);
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_CLAIMED_POWER_METHOD,
+        RawBytes::serialize(ext::power::UpdateClaimedPowerParams {
+            raw_byte_delta: -disputed_power.clone(),
+            quality_adjusted_delta: -disputed_power,
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        reporter,
+        METHOD_SEND,
+        RawBytes::default(),
+        reward.clone(),
+        RawBytes::default(),
+        ExitCode::SysErrInvalidReceiver,
+    );
+    // Only the penalty beyond the reward is burned.
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        penalty_base,
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    h.dispute_windowed_post(&mut rt, reporter, dl_info.index, 0).unwrap();
+    rt.verify();
+
+    // The mock runtime deducts a failed send's value, so restore it to withdraw.
+    rt.add_balance(reward.clone());
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(reward, st.total_owed_dispute_rewards);
+
+    rt.expect_send(
+        reporter,
+        METHOD_SEND,
+        RawBytes::default(),
+        reward.clone(),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    let ret: WithdrawDisputeRewardReturn =
+        withdraw_dispute_reward(&mut rt, reporter).unwrap().deserialize().unwrap();
+    rt.verify();
+    assert_eq!(reward, ret.amount_withdrawn);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_owed_dispute_reward_is_held_and_withdrawn() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.set_balance(TokenAmount::from(1000));
+
+    // Rewards owed for disputes whose reward send failed accumulate per reporter.
+    let reporter = Address::new_id(1234);
+    let mut st: State = rt.get_state().unwrap();
+    st.add_owed_dispute_reward(&rt.store, &reporter, &TokenAmount::from(100)).unwrap();
+    st.add_owed_dispute_reward(&rt.store, &reporter, &TokenAmount::from(50)).unwrap();
+    rt.replace_state(&st);
+    assert_eq!(TokenAmount::from(850), st.get_unlocked_balance(&TokenAmount::from(1000)).unwrap());
+
+    rt.expect_send(
+        reporter,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(150),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    let ret: WithdrawDisputeRewardReturn =
+        withdraw_dispute_reward(&mut rt, reporter).unwrap().deserialize().unwrap();
+    rt.verify();
+    assert_eq!(TokenAmount::from(150), ret.amount_withdrawn);
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(TokenAmount::from(0), st.total_owed_dispute_rewards);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_withdraw_dispute_reward_fails_when_nothing_owed() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    expect_abort_contains_message(
+        ExitCode::ErrNotFound,
+        "no dispute reward owed",
+        withdraw_dispute_reward(&mut rt, Address::new_id(1234)),
+    );
+    rt.verify();
+}
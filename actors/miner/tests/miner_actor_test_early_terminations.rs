@@ -0,0 +1,17 @@
+use fil_actors_runtime::test_utils::*;
+
+mod util;
+
+#[test]
+fn test_no_early_terminations_on_new_miner() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let ret = h.get_early_terminations(&mut rt);
+    assert!(ret.deadlines.is_empty());
+    assert_eq!(0, ret.sectors);
+
+    util::check_state_invariants(&rt);
+}
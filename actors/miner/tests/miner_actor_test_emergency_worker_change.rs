@@ -0,0 +1,71 @@
+use fil_actor_account::Method as AccountMethod;
+use fil_actor_miner::{EmergencyChangeWorkerAddressParams, Method, State};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+fn emergency_change_worker(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    new_worker: Address,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    rt.actor_code_cids.insert(new_worker, *ACCOUNT_ACTOR_CODE_ID);
+    rt.expect_send(
+        new_worker,
+        AccountMethod::PubkeyAddress as u64,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(util::new_bls_addr(1)).unwrap(),
+        ExitCode::Ok,
+    );
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.owner);
+    rt.expect_validate_caller_addr(vec![h.owner]);
+    let params = EmergencyChangeWorkerAddressParams { new_worker };
+    rt.call::<fil_actor_miner::Actor>(
+        Method::EmergencyChangeWorkerAddress as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+}
+
+#[test]
+fn test_emergency_change_uses_emergency_delay() {
+    let mut rt = MockRuntime::default();
+    rt.policy.emergency_worker_key_change_delay = 10;
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let epoch: ChainEpoch = 100;
+    rt.epoch = epoch;
+    let new_worker = Address::new_id(999);
+    emergency_change_worker(&mut rt, &h, new_worker).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let info = st.get_info(&rt.store).unwrap();
+    let pending = info.pending_worker_key.unwrap();
+    assert_eq!(new_worker, pending.new_worker);
+    assert_eq!(epoch + 10, pending.effective_at);
+    assert_eq!(epoch, info.last_emergency_worker_change);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_emergency_change_rejects_current_worker() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    expect_abort(ExitCode::ErrIllegalArgument, emergency_change_worker(&mut rt, &h, h.worker));
+
+    util::check_state_invariants(&rt);
+}
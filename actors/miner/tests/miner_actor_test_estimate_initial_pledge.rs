@@ -0,0 +1,108 @@
+use fil_actor_miner::{
+    ext, initial_pledge_for_power, EstimateInitialPledgeParams, EstimateInitialPledgeReturn, Method,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::reward::ThisEpochRewardReturn;
+use fvm_shared::sector::StoragePower;
+
+mod util;
+
+fn estimate_initial_pledge(
+    rt: &mut MockRuntime,
+    qa_power: StoragePower,
+    duration: ChainEpoch,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    rt.expect_validate_caller_any();
+    let params = EstimateInitialPledgeParams { qa_power, duration };
+    rt.call::<fil_actor_miner::Actor>(
+        Method::EstimateInitialPledge as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+}
+
+#[test]
+fn test_estimate_initial_pledge_uses_current_network_conditions() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    rt.circulating_supply = TokenAmount::from(10u64.pow(24));
+    rt.expect_send(
+        *REWARD_ACTOR_ADDR,
+        ext::reward::THIS_EPOCH_REWARD_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ThisEpochRewardReturn {
+            this_epoch_reward_smoothed: h.epoch_reward_smooth.clone(),
+            this_epoch_baseline_power: h.baseline_power.clone(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::CURRENT_TOTAL_POWER_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::power::CurrentTotalPowerReturn {
+            raw_byte_power: h.baseline_power.clone(),
+            quality_adj_power: h.baseline_power.clone(),
+            pledge_collateral: TokenAmount::from(0),
+            quality_adj_power_smoothed: h.epoch_qa_power_smooth.clone(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+
+    let qa_power = StoragePower::from(h.sector_size as u64);
+    let duration = rt.policy.min_sector_expiration(rt.network_version);
+    let ret: EstimateInitialPledgeReturn =
+        estimate_initial_pledge(&mut rt, qa_power.clone(), duration)
+            .unwrap()
+            .deserialize()
+            .unwrap();
+    rt.verify();
+
+    let expected = initial_pledge_for_power(
+        &qa_power,
+        &h.baseline_power,
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &rt.circulating_supply,
+    );
+    assert_eq!(expected, ret.initial_pledge);
+    assert!(ret.initial_pledge > BigInt::from(0));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_estimate_initial_pledge_rejects_invalid_duration() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let too_short = rt.policy.min_sector_expiration(rt.network_version) - 1;
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        estimate_initial_pledge(&mut rt, StoragePower::from(1), too_short),
+    );
+    rt.reset();
+
+    let too_long = rt.policy.max_sector_expiration_extension(rt.network_version) + 1;
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        estimate_initial_pledge(&mut rt, StoragePower::from(1), too_long),
+    );
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,67 @@
+use fil_actor_miner::{GetDeadlineExpirationScheduleParams, Method, SectorOnChainInfo, State};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+#[test]
+fn test_expiration_schedule_of_assigned_sector() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let sector_number = 1;
+    let expiration = rt.policy.min_sector_expiration(rt.network_version);
+    let mut st: State = rt.get_state().unwrap();
+    let sectors = vec![SectorOnChainInfo {
+        sector_number,
+        seal_proof: h.seal_proof_type,
+        expiration,
+        ..Default::default()
+    }];
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        sectors,
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+
+    let (deadline, partition) = st.find_sector(&rt.policy, &rt.store, sector_number).unwrap();
+    let ret = h.get_deadline_expiration_schedule(&mut rt, deadline);
+    assert_eq!(1, ret.entries.len());
+    assert_eq!(
+        st.quant_spec_for_deadline(&rt.policy, deadline).quantize_up(expiration),
+        ret.entries[0].epoch
+    );
+    assert!(ret.entries[0].partitions.get(partition));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_expiration_schedule_rejects_invalid_deadline() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    rt.expect_validate_caller_any();
+    let params = GetDeadlineExpirationScheduleParams { deadline: rt.policy.wpost_period_deadlines };
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<fil_actor_miner::Actor>(
+            Method::GetDeadlineExpirationSchedule as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,141 @@
+use fil_actor_miner::{
+    ext, qa_power_for_sector, ExpirationExtension, ExtendSectorExpirationParams, Method,
+    SectorOnChainInfo, Sectors, State,
+};
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::STORAGE_POWER_ACTOR_ADDR;
+
+use bitfield::BitField;
+use fvm_shared::bigint::{BigInt, Integer};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+#[test]
+fn test_extend_prorates_full_verified_deal_weight() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // A sector filled with verified deal space carries the largest weight it can have. An odd
+    // lifetime makes prorating round.
+    let sector_number = 100;
+    let expiration = 200 * EPOCHS_IN_DAY + 7;
+    let old_sector = SectorOnChainInfo {
+        sector_number,
+        seal_proof: h.seal_proof_type,
+        activation: 0,
+        expiration,
+        verified_deal_weight: BigInt::from(h.sector_size as u64) * expiration,
+        ..Default::default()
+    };
+    let mut st: State = rt.get_state().unwrap();
+    st.put_sectors(&rt.store, vec![old_sector.clone()]).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        vec![old_sector.clone()],
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    let (dl_idx, p_idx) = st.find_sector(&rt.policy, &rt.store, sector_number).unwrap();
+    let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+    let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_idx).unwrap();
+    let mut partitions = deadline.partitions_amt(&rt.store).unwrap();
+    let mut partition = partitions.get(p_idx).unwrap().unwrap().clone();
+    partition.activate_unproven();
+    partitions.set(p_idx, partition).unwrap();
+    deadline.partitions = partitions.flush().unwrap();
+    deadlines.update_deadline(&rt.policy, &rt.store, dl_idx, &deadline).unwrap();
+    st.save_deadlines(&rt.store, deadlines).unwrap();
+    rt.replace_state(&st);
+
+    rt.epoch = 100 * EPOCHS_IN_DAY + 3;
+    let new_expiration = 300 * EPOCHS_IN_DAY;
+    let mut new_sector = old_sector.clone();
+    new_sector.expiration = new_expiration;
+    new_sector.verified_deal_weight = (&old_sector.verified_deal_weight * (expiration - rt.epoch))
+        .div_floor(&BigInt::from(expiration));
+
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_CLAIMED_POWER_METHOD,
+        RawBytes::serialize(ext::power::UpdateClaimedPowerParams {
+            raw_byte_delta: BigInt::from(0),
+            quality_adjusted_delta: qa_power_for_sector(h.sector_size, &new_sector)
+                - qa_power_for_sector(h.sector_size, &old_sector),
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    let sectors: BitField = [sector_number].iter().copied().collect();
+    let params = ExtendSectorExpirationParams {
+        extensions: vec![ExpirationExtension {
+            deadline: dl_idx,
+            partition: p_idx,
+            sectors: sectors.into(),
+            new_expiration,
+        }],
+    };
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(
+        h.control_addrs.iter().chain(&[h.worker, h.owner]).cloned().collect(),
+    );
+    rt.call::<fil_actor_miner::Actor>(
+        Method::ExtendSectorExpiration as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+
+    // The sector's pledge is untouched, so no pledge change is reported.
+    let st: State = rt.get_state().unwrap();
+    let sectors = Sectors::load(&rt.store, &st.sectors).unwrap();
+    let sector = sectors.get(sector_number).unwrap().unwrap();
+    assert_eq!(new_expiration, sector.expiration);
+    assert_eq!(new_sector.verified_deal_weight, sector.verified_deal_weight);
+    assert_eq!(old_sector.initial_pledge, sector.initial_pledge);
+    assert_eq!(BigInt::from(0), sector.deal_weight);
+}
+
+#[test]
+fn test_extend_rejects_too_many_partitions() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.policy.extension_partitions_max = 1;
+
+    // Two declarations for the same partition count once; a second partition exceeds the cap.
+    let extension = |partition| ExpirationExtension {
+        deadline: 1,
+        partition,
+        sectors: BitField::new().into(),
+        new_expiration: 300 * EPOCHS_IN_DAY,
+    };
+    let params =
+        ExtendSectorExpirationParams { extensions: vec![extension(0), extension(0), extension(1)] };
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(
+        h.control_addrs.iter().chain(&[h.worker, h.owner]).cloned().collect(),
+    );
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "too many partitions 2, max 1",
+        rt.call::<fil_actor_miner::Actor>(
+            Method::ExtendSectorExpiration as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.reset();
+
+    util::check_state_invariants(&rt);
+}
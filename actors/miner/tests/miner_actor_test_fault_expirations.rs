@@ -0,0 +1,73 @@
+use fil_actor_miner::{
+    ComputeFaultExpirationsParams, ComputeFaultExpirationsReturn, Method, State,
+};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+fn compute_fault_expirations(rt: &mut MockRuntime, deadlines: Vec<u64>) -> Vec<ChainEpoch> {
+    rt.expect_validate_caller_any();
+    let params = ComputeFaultExpirationsParams { deadlines };
+    let ret: ComputeFaultExpirationsReturn = rt
+        .call::<fil_actor_miner::Actor>(
+            Method::ComputeFaultExpirations as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.expirations
+}
+
+#[test]
+fn test_fault_expirations_follow_target_deadlines() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    let current = st.deadline_info(&rt.policy, rt.epoch);
+    rt.epoch = current.open;
+    let window = rt.policy.wpost_challenge_window;
+    let next = (current.index + 1) % rt.policy.wpost_period_deadlines;
+    let later = (current.index + 2) % rt.policy.wpost_period_deadlines;
+
+    let expirations = compute_fault_expirations(&mut rt, vec![later, current.index, next]);
+    assert_eq!(
+        vec![
+            current.open + 3 * window - 1 + rt.policy.fault_max_age,
+            current.open + window - 1 + rt.policy.fault_max_age,
+            current.open + 2 * window - 1 + rt.policy.fault_max_age,
+        ],
+        expirations
+    );
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_fault_expirations_reject_invalid_deadline() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    rt.expect_validate_caller_any();
+    let params =
+        ComputeFaultExpirationsParams { deadlines: vec![0, rt.policy.wpost_period_deadlines] };
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<fil_actor_miner::Actor>(
+            Method::ComputeFaultExpirations as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,20 @@
+use fil_actors_runtime::test_utils::*;
+
+mod util;
+
+#[test]
+fn test_no_faults_on_new_miner() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let ret = h.get_fault_state(&mut rt);
+    assert_eq!(rt.policy.wpost_period_deadlines, ret.deadlines.len() as u64);
+    for deadline in ret.deadlines {
+        assert!(deadline.faults.is_empty());
+        assert!(deadline.recoveries.is_empty());
+    }
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,68 @@
+// The fixed offset is only honoured in builds for integration tests.
+#![cfg(feature = "fixed-proving-period-offset")]
+
+use fil_actor_account::Method as AccountMethod;
+use fil_actor_miner::{Actor, Method, MinerConstructorParams as ConstructorParams, State};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::INIT_ACTOR_ADDR;
+
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+#[test]
+fn test_fixed_offset_sets_proving_period_start() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    rt.epoch = 1000;
+    rt.policy.fixed_proving_period_offset = Some(100);
+    h.construct_and_verify(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(100, st.proving_period_start);
+    assert_eq!(900 / rt.policy.wpost_challenge_window as u64, st.current_deadline);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_fixed_offset_out_of_range_is_rejected() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    let offset = rt.policy.wpost_proving_period;
+    rt.policy.fixed_proving_period_offset = Some(offset);
+
+    let params = ConstructorParams {
+        owner: h.owner,
+        worker: h.worker,
+        control_addresses: h.control_addrs.clone(),
+        window_post_proof_type: h.window_post_proof_type,
+        peer_id: vec![0],
+        multi_addresses: vec![],
+    };
+    rt.actor_code_cids.insert(h.owner, *ACCOUNT_ACTOR_CODE_ID);
+    rt.actor_code_cids.insert(h.worker, *ACCOUNT_ACTOR_CODE_ID);
+    for a in h.control_addrs.iter() {
+        rt.actor_code_cids.insert(*a, *ACCOUNT_ACTOR_CODE_ID);
+    }
+    rt.set_caller(*INIT_ACTOR_CODE_ID, *INIT_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*INIT_ACTOR_ADDR]);
+    rt.expect_send(
+        h.worker,
+        AccountMethod::PubkeyAddress as u64,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(h.worker_key).unwrap(),
+        ExitCode::Ok,
+    );
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalState,
+        &format!("fixed proving period offset {} out of range", offset),
+        rt.call::<Actor>(Method::Constructor as u64, &RawBytes::serialize(params).unwrap()),
+    );
+    rt.verify();
+}
@@ -0,0 +1,365 @@
+use fil_actor_miner::{
+    aggregate_prove_commit_network_fee, ext, initial_pledge_for_power, late_prove_commit_fee,
+    max_prove_commit_duration, qa_power_for_weight, CollisionPolicy, ConfirmSectorProofsParams,
+    Method, ProveCommitAggregateParams, ProveCommitAggregateReturn, ProveCommitSectorParams,
+    ProveCommitSectorReturn, SectorPreCommitInfo, SectorPreCommitOnChainInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{
+    BURNT_FUNDS_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
+};
+
+use bitfield::BitField;
+use cid::multihash::Multihash;
+use cid::Cid;
+use fvm_shared::bigint::bigint_ser::BigIntSer;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::randomness::DomainSeparationTag;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::{Cbor, RawBytes};
+use fvm_shared::error::ExitCode;
+use fvm_shared::randomness::Randomness;
+use fvm_shared::sector::{AggregateSealVerifyInfo, SealVerifyInfo, SectorID, SectorNumber};
+use fvm_shared::METHOD_SEND;
+
+mod util;
+
+const IPLD_RAW: u64 = 0x55;
+const GRACE: ChainEpoch = 100;
+const EPOCHS_LATE: ChainEpoch = 50;
+
+#[test]
+fn test_no_fee_without_grace_or_lateness() {
+    let deposit = TokenAmount::from(1000);
+    assert_eq!(TokenAmount::from(0), late_prove_commit_fee(&deposit, 0, 10));
+    assert_eq!(TokenAmount::from(0), late_prove_commit_fee(&deposit, -5, 10));
+    assert_eq!(TokenAmount::from(0), late_prove_commit_fee(&deposit, 5, 0));
+}
+
+#[test]
+fn test_fee_escalates_to_full_deposit() {
+    let deposit = TokenAmount::from(1000);
+    assert_eq!(TokenAmount::from(100), late_prove_commit_fee(&deposit, 1, 10));
+    assert_eq!(TokenAmount::from(500), late_prove_commit_fee(&deposit, 5, 10));
+    assert_eq!(deposit, late_prove_commit_fee(&deposit, 10, 10));
+}
+
+/// Sets up the miner with a prove-commit grace window and moves to an epoch late enough that
+/// pre-commits made at `late_pre_commit_epoch` are inside it.
+fn setup(rt: &mut MockRuntime, h: &util::ActorHarness) -> ChainEpoch {
+    h.construct_and_verify(rt);
+    rt.policy.prove_commit_grace = GRACE;
+    rt.set_balance(TokenAmount::from(10u64.pow(18)) * 1_000_000);
+    let msd = max_prove_commit_duration(&rt.policy, h.seal_proof_type).unwrap();
+    rt.epoch = msd + 2 * EPOCHS_LATE;
+    rt.epoch - msd - EPOCHS_LATE
+}
+
+fn put_pre_commits(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    pre_commits: &[(SectorNumber, ChainEpoch, Vec<u64>)],
+) -> Vec<SectorPreCommitOnChainInfo> {
+    let expiration = rt.epoch + rt.policy.min_sector_expiration(rt.network_version) + 1;
+    let pre_commits: Vec<SectorPreCommitOnChainInfo> = pre_commits
+        .iter()
+        .map(|(sector_number, pre_commit_epoch, deal_ids)| SectorPreCommitOnChainInfo {
+            info: SectorPreCommitInfo {
+                seal_proof: h.seal_proof_type,
+                sector_number: *sector_number,
+                sealed_cid: Cid::default(),
+                seal_rand_epoch: pre_commit_epoch - 1,
+                deal_ids: deal_ids.clone(),
+                expiration,
+                replace_capacity: false,
+                replace_sector_deadline: 0,
+                replace_sector_partition: 0,
+                replace_sector_number: 0,
+            },
+            pre_commit_deposit: TokenAmount::from(1000),
+            pre_commit_epoch: *pre_commit_epoch,
+            deal_weight: BigInt::from(0),
+            verified_deal_weight: BigInt::from(0),
+        })
+        .collect();
+
+    let mut st: State = rt.get_state().unwrap();
+    st.put_precommitted_sectors(&rt.store, pre_commits.clone()).unwrap();
+    st.add_pre_commit_deposit(&(TokenAmount::from(1000) * pre_commits.len())).unwrap();
+    st.allocate_sector_numbers(
+        &rt.policy,
+        &rt.store,
+        &pre_commits.iter().map(|p| p.info.sector_number).collect(),
+        CollisionPolicy::DenyCollisions,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+    pre_commits
+}
+
+fn expect_pledge_update(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    pre_commit: &SectorPreCommitOnChainInfo,
+    sector_count: usize,
+) {
+    let qa_power = qa_power_for_weight(
+        h.sector_size,
+        pre_commit.info.expiration - rt.epoch,
+        &BigInt::from(0),
+        &BigInt::from(0),
+    );
+    let sector_pledge = initial_pledge_for_power(
+        &qa_power,
+        &h.baseline_power,
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &rt.circulating_supply,
+    );
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_PLEDGE_TOTAL_METHOD,
+        RawBytes::serialize(BigIntSer(&(sector_pledge * sector_count))).unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+}
+
+fn expect_burn(rt: &mut MockRuntime, amount: TokenAmount) {
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        amount,
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+}
+
+fn confirm_sector_proofs_valid(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    sectors: Vec<SectorNumber>,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    let params = ConfirmSectorProofsParams {
+        sectors,
+        reward_smoothed: h.epoch_reward_smooth.clone(),
+        reward_baseline_power: h.baseline_power.clone(),
+        quality_adj_power_smoothed: h.epoch_qa_power_smooth.clone(),
+    };
+    rt.set_caller(*POWER_ACTOR_CODE_ID, *STORAGE_POWER_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*STORAGE_POWER_ACTOR_ADDR]);
+    rt.call::<fil_actor_miner::Actor>(
+        Method::ConfirmSectorProofsValid as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+}
+
+#[test]
+fn test_late_prove_commit_sector_fee_is_charged_at_confirmation() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+    let late_pre_commit_epoch = setup(&mut rt, &h);
+
+    let pre_commits = put_pre_commits(&mut rt, &h, &[(7, late_pre_commit_epoch, vec![])]);
+    let precommit = &pre_commits[0];
+    let expected_fee = TokenAmount::from(500);
+
+    // Submitting the proof reports the fee without charging it.
+    let interactive_epoch = precommit.pre_commit_epoch + rt.policy.pre_commit_challenge_delay;
+    let unsealed_cid = Cid::new_v1(IPLD_RAW, Multihash::wrap(0, b"zero commd").unwrap());
+    let seal_rand = Randomness(vec![1; 32]);
+    let seal_int_rand = Randomness(vec![2; 32]);
+    let entropy = rt.receiver.marshal_cbor().unwrap();
+    let proof = vec![0u8; 192];
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_any();
+    rt.expect_compute_unsealed_sector_cid(ExpectComputeUnsealedSectorCid {
+        reg: h.seal_proof_type,
+        pieces: vec![],
+        cid: unsealed_cid,
+        exit_code: ExitCode::Ok,
+    });
+    rt.expect_get_randomness_from_tickets(
+        DomainSeparationTag::SealRandomness,
+        precommit.info.seal_rand_epoch,
+        entropy.clone(),
+        seal_rand.clone(),
+    );
+    rt.expect_get_randomness_from_beacon(
+        DomainSeparationTag::InteractiveSealChallengeSeed,
+        interactive_epoch,
+        entropy,
+        seal_int_rand.clone(),
+    );
+    let svi = SealVerifyInfo {
+        registered_proof: h.seal_proof_type,
+        sector_id: SectorID { miner: 0, number: 7 },
+        deal_ids: vec![],
+        interactive_randomness: seal_int_rand,
+        proof: proof.clone(),
+        randomness: seal_rand,
+        sealed_cid: precommit.info.sealed_cid,
+        unsealed_cid,
+    };
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::SUBMIT_POREP_FOR_BULK_VERIFY_METHOD,
+        RawBytes::serialize(&svi).unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    let ret: ProveCommitSectorReturn = rt
+        .call::<fil_actor_miner::Actor>(
+            Method::ProveCommitSector as u64,
+            &RawBytes::serialize(ProveCommitSectorParams { sector_number: 7, proof }).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(expected_fee, ret.grace_fee);
+
+    // The fee is burnt once the proof is confirmed and the sector activates.
+    expect_pledge_update(&mut rt, &h, precommit, 1);
+    expect_burn(&mut rt, expected_fee);
+    confirm_sector_proofs_valid(&mut rt, &h, vec![7]).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.pre_commit_deposits.is_zero());
+    assert!(st.get_sector(&rt.store, 7).unwrap().is_some());
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_late_fee_is_not_charged_for_sector_failing_activation() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+    let late_pre_commit_epoch = setup(&mut rt, &h);
+
+    let pre_commits = put_pre_commits(
+        &mut rt,
+        &h,
+        &[(0, late_pre_commit_epoch, vec![]), (1, late_pre_commit_epoch, vec![1])],
+    );
+
+    // Sector 1's deals fail to activate, so only sector 0 pays the fee.
+    rt.expect_send(
+        *STORAGE_MARKET_ACTOR_ADDR,
+        ext::market::ACTIVATE_DEALS_METHOD,
+        RawBytes::serialize(ext::market::ActivateDealsParams {
+            deal_ids: vec![1],
+            sector_expiry: pre_commits[1].info.expiration,
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::ErrIllegalArgument,
+    );
+    expect_pledge_update(&mut rt, &h, &pre_commits[0], 1);
+    expect_burn(&mut rt, TokenAmount::from(500));
+    confirm_sector_proofs_valid(&mut rt, &h, vec![0, 1]).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.get_sector(&rt.store, 0).unwrap().is_some());
+    assert!(st.get_sector(&rt.store, 1).unwrap().is_none());
+    assert_eq!(TokenAmount::from(1000), st.pre_commit_deposits);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_prove_commit_aggregate_charges_late_fee_with_aggregate_fee() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+    let late_pre_commit_epoch = setup(&mut rt, &h);
+
+    // Sector 0 is inside the grace window, the others are due this epoch.
+    let on_time_pre_commit_epoch = late_pre_commit_epoch + EPOCHS_LATE;
+    let sector_count = rt.policy.min_aggregated_sectors;
+    let specs: Vec<(SectorNumber, ChainEpoch, Vec<u64>)> = (0..sector_count)
+        .map(|sector_number| {
+            let epoch =
+                if sector_number == 0 { late_pre_commit_epoch } else { on_time_pre_commit_epoch };
+            (sector_number, epoch, vec![])
+        })
+        .collect();
+    let pre_commits = put_pre_commits(&mut rt, &h, &specs);
+    let expected_fee = TokenAmount::from(500);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(
+        h.control_addrs.iter().chain(&[h.worker, h.owner]).cloned().collect(),
+    );
+    let data_specs: Vec<ext::market::SectorDataSpec> = pre_commits
+        .iter()
+        .map(|_| ext::market::SectorDataSpec { deal_ids: vec![], sector_type: h.seal_proof_type })
+        .collect();
+    let unsealed_cid = Cid::new_v1(IPLD_RAW, Multihash::wrap(0, b"zero commd").unwrap());
+    rt.expect_send(
+        *STORAGE_MARKET_ACTOR_ADDR,
+        ext::market::COMPUTE_DATA_COMMITMENT_METHOD,
+        RawBytes::serialize(ext::market::ComputeDataCommitmentParamsRef { inputs: &data_specs })
+            .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::market::ComputeDataCommitmentReturn {
+            commds: vec![unsealed_cid; pre_commits.len()],
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    let entropy = rt.receiver.marshal_cbor().unwrap();
+    let mut svis = Vec::new();
+    for precommit in &pre_commits {
+        let interactive_epoch = precommit.pre_commit_epoch + rt.policy.pre_commit_challenge_delay;
+        rt.expect_get_randomness_from_tickets(
+            DomainSeparationTag::SealRandomness,
+            precommit.info.seal_rand_epoch,
+            entropy.clone(),
+            Randomness(vec![1; 32]),
+        );
+        rt.expect_get_randomness_from_beacon(
+            DomainSeparationTag::InteractiveSealChallengeSeed,
+            interactive_epoch,
+            entropy.clone(),
+            Randomness(vec![2; 32]),
+        );
+        svis.push(AggregateSealVerifyInfo {
+            sector_number: precommit.info.sector_number,
+            randomness: Randomness(vec![1; 32]),
+            interactive_randomness: Randomness(vec![2; 32]),
+            sealed_cid: precommit.info.sealed_cid,
+            unsealed_cid,
+        });
+    }
+    rt.expect_aggregate_verify_seals(svis, vec![], ExitCode::Ok);
+    h.expect_query_network_info(&mut rt);
+    expect_pledge_update(&mut rt, &h, &pre_commits[0], pre_commits.len());
+    let aggregate_fee = aggregate_prove_commit_network_fee(pre_commits.len() as i64, &rt.base_fee);
+    expect_burn(&mut rt, aggregate_fee + &expected_fee);
+
+    let sectors: BitField = (0..sector_count).collect();
+    let params =
+        ProveCommitAggregateParams { sector_numbers: sectors.into(), aggregate_proof: vec![] };
+    let ret: ProveCommitAggregateReturn = rt
+        .call::<fil_actor_miner::Actor>(
+            Method::ProveCommitAggregate as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(expected_fee, ret.grace_fee);
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.pre_commit_deposits.is_zero());
+
+    util::check_state_invariants(&rt);
+}
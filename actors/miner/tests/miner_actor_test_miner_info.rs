@@ -0,0 +1,43 @@
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::clock::EPOCH_UNDEFINED;
+use fvm_shared::encoding::BytesDe;
+
+mod util;
+
+#[test]
+fn test_get_miner_info_after_construction() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let info = h.get_miner_info(&mut rt);
+    assert_eq!(h.owner, info.owner);
+    assert_eq!(h.worker, info.worker);
+    assert_eq!(h.control_addrs, info.control_addresses);
+    assert_eq!(vec![0], info.peer_id);
+    assert!(info.multi_address.is_empty());
+    assert_eq!(h.window_post_proof_type, info.window_post_proof_type);
+    assert_eq!(h.sector_size, info.sector_size);
+    assert_eq!(h.partition_size, info.window_post_partition_sectors);
+    assert_eq!(EPOCH_UNDEFINED, info.consensus_fault_elapsed);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_get_miner_info_reflects_peer_info_changes() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    h.set_peer_id(&mut rt, vec![1, 2, 3]);
+    h.set_multiaddr(&mut rt, vec![BytesDe(vec![1, 3, 3, 7])]);
+
+    let info = h.get_miner_info(&mut rt);
+    assert_eq!(vec![1, 2, 3], info.peer_id);
+    assert_eq!(vec![BytesDe(vec![1, 3, 3, 7])], info.multi_address);
+
+    util::check_state_invariants(&rt);
+}
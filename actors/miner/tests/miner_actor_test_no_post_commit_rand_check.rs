@@ -0,0 +1,87 @@
+// Chain commit randomness can only go unchecked in builds for test vectors.
+#![cfg(feature = "no-post-commit-rand-check")]
+
+use fil_actor_miner::{
+    Actor, DeadlineInfo, Method, PoStPartition, PoStProof, State, SubmitWindowedPoStParams,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::ActorError;
+
+use bitfield::BitField;
+use fvm_shared::crypto::randomness::DomainSeparationTag;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::randomness::Randomness;
+
+mod util;
+
+/// Submits a Window PoSt for partition 0 of the deadline, committed to the given randomness.
+fn submit_post(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    dl_info: &DeadlineInfo,
+    chain_commit_rand: Randomness,
+) -> Result<RawBytes, ActorError> {
+    let params = SubmitWindowedPoStParams {
+        deadline: dl_info.index,
+        partitions: vec![PoStPartition { index: 0, skipped: BitField::new().into() }],
+        proofs: vec![PoStProof {
+            post_proof: h.window_post_proof_type,
+            proof_bytes: vec![0; h.window_post_proof_type.proof_size().unwrap()],
+        }],
+        chain_commit_epoch: rt.epoch - 1,
+        chain_commit_rand,
+    };
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.extend_from_slice(&[h.worker, h.owner]);
+    rt.expect_validate_caller_addr(caller_addrs);
+    rt.call::<Actor>(Method::SubmitWindowedPoSt as u64, &RawBytes::serialize(params).unwrap())
+}
+
+#[test]
+fn test_post_with_mismatched_randomness_accepted_when_check_skipped() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.epoch = 10;
+    let dl_info = h.add_proven_sectors(&mut rt, &[h.seal_proof_type]);
+
+    // The chain is never asked for randomness, so any committed randomness is accepted.
+    rt.policy.skip_post_commit_rand_check = true;
+    submit_post(&mut rt, &h, &dl_info, Randomness(vec![9; 32])).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let deadlines = st.load_deadlines(&rt.store).unwrap();
+    let deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_info.index).unwrap();
+    assert!(deadline.partitions_posted.get(0));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_post_with_mismatched_randomness_rejected_by_default() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.epoch = 10;
+    let dl_info = h.add_proven_sectors(&mut rt, &[h.seal_proof_type]);
+
+    rt.expect_get_randomness_from_tickets(
+        DomainSeparationTag::PoStChainCommit,
+        rt.epoch - 1,
+        vec![],
+        Randomness(vec![1; 32]),
+    );
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "post commit randomness mismatched",
+        submit_post(&mut rt, &h, &dl_info, Randomness(vec![9; 32])),
+    );
+    rt.verify();
+
+    util::check_state_invariants(&rt);
+}
@@ -109,3 +109,26 @@ fn cant_set_large_multiaddrs() {
 
     util::check_state_invariants(&rt);
 }
+
+#[test]
+fn can_set_peer_id_and_multiaddrs_together() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    h.set_network_addresses(&mut rt, vec![1, 2, 3], vec![BytesDe(vec![1, 3, 3, 7])]);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn cant_set_network_addresses_with_large_peer_id() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+    let peer_id = vec![0; rt.policy.max_peer_id_length + 1];
+
+    h.construct_and_verify(&mut rt);
+    h.set_network_addresses_fail(&mut rt, peer_id, vec![BytesDe(vec![1, 3, 3, 7])]);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,77 @@
+use fil_actor_miner::{
+    ChangeOwnerAddressWithExpiryParams, GetPendingChangesReturn, Method, State, WorkerKeyChange,
+};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::address::Address;
+use fvm_shared::encoding::RawBytes;
+
+mod util;
+
+#[test]
+fn test_no_pending_changes_after_construction() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    assert_eq!(
+        GetPendingChangesReturn {
+            pending_worker_key: None,
+            pending_owner_address: None,
+            pending_owner_expiration: None,
+        },
+        h.get_pending_changes(&mut rt)
+    );
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_pending_owner_change_reported() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let new_owner = Address::new_id(999);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.owner);
+    rt.expect_validate_caller_addr(vec![h.owner]);
+    let params =
+        ChangeOwnerAddressWithExpiryParams { new_address: new_owner, expiration: Some(50) };
+    rt.call::<fil_actor_miner::Actor>(
+        Method::ChangeOwnerAddressWithExpiry as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+
+    let changes = h.get_pending_changes(&mut rt);
+    assert_eq!(None, changes.pending_worker_key);
+    assert_eq!(Some(new_owner), changes.pending_owner_address);
+    assert_eq!(Some(50), changes.pending_owner_expiration);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_pending_worker_change_reported() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let change = WorkerKeyChange { new_worker: Address::new_id(555), effective_at: 900 };
+    let mut st: State = rt.get_state().unwrap();
+    let mut info = st.get_info(&rt.store).unwrap();
+    info.pending_worker_key =
+        Some(WorkerKeyChange { new_worker: change.new_worker, effective_at: change.effective_at });
+    st.save_info(&rt.store, &info).unwrap();
+    rt.replace_state(&st);
+
+    let changes = h.get_pending_changes(&mut rt);
+    assert_eq!(Some(change), changes.pending_worker_key);
+    assert_eq!(None, changes.pending_owner_address);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,29 @@
+use fil_actor_miner::State;
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::econ::TokenAmount;
+
+mod util;
+
+#[test]
+fn test_pledge_summary_reports_state_and_balance() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    st.initial_pledge = TokenAmount::from(100);
+    st.locked_funds = TokenAmount::from(200);
+    st.pre_commit_deposits = TokenAmount::from(300);
+    st.fee_debt = TokenAmount::from(40);
+    rt.replace_state(&st);
+    rt.set_balance(TokenAmount::from(1000));
+
+    let summary = h.get_pledge_summary(&mut rt);
+    assert_eq!(TokenAmount::from(100), summary.initial_pledge);
+    assert_eq!(TokenAmount::from(200), summary.locked_funds);
+    assert_eq!(TokenAmount::from(300), summary.pre_commit_deposits);
+    assert_eq!(TokenAmount::from(40), summary.fee_debt);
+    assert_eq!(TokenAmount::from(1000), summary.balance);
+}
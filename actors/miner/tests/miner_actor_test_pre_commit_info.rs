@@ -0,0 +1,65 @@
+use fil_actor_miner::{
+    GetPreCommitInfoParams, Method, SectorPreCommitInfo, SectorPreCommitOnChainInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+
+use cid::Cid;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+#[test]
+fn test_get_pre_commit_info_returns_deposit() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let precommit = SectorPreCommitOnChainInfo {
+        info: SectorPreCommitInfo {
+            seal_proof: h.seal_proof_type,
+            sector_number: 7,
+            sealed_cid: Cid::default(),
+            seal_rand_epoch: 0,
+            deal_ids: vec![],
+            expiration: rt.policy.min_sector_expiration(rt.network_version) + 1,
+            replace_capacity: false,
+            replace_sector_deadline: 0,
+            replace_sector_partition: 0,
+            replace_sector_number: 0,
+        },
+        pre_commit_deposit: TokenAmount::from(1234),
+        pre_commit_epoch: rt.epoch,
+        deal_weight: BigInt::from(0),
+        verified_deal_weight: BigInt::from(0),
+    };
+    let mut st: State = rt.get_state().unwrap();
+    st.put_precommitted_sectors(&rt.store, vec![precommit.clone()]).unwrap();
+    rt.replace_state(&st);
+
+    assert_eq!(precommit, h.get_pre_commit_info(&mut rt, 7));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_get_pre_commit_info_not_found() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    rt.expect_validate_caller_any();
+    expect_abort(
+        ExitCode::ErrNotFound,
+        rt.call::<fil_actor_miner::Actor>(
+            Method::GetPreCommitInfo as u64,
+            &RawBytes::serialize(GetPreCommitInfoParams { sector_number: 7 }).unwrap(),
+        ),
+    );
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,65 @@
+use fil_actor_miner::{Method, PreCommitSectorParams};
+use fil_actors_runtime::test_utils::*;
+
+use cid::multihash::Multihash;
+use cid::Cid;
+use fvm_shared::commcid::{FIL_COMMITMENT_SEALED, POSEIDON_BLS12_381_A1_FC1};
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::version::NetworkVersion;
+
+mod util;
+
+fn pre_commit_replace_capacity(
+    network_version: NetworkVersion,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.network_version = network_version;
+    rt.epoch = 1000;
+
+    let params = PreCommitSectorParams {
+        seal_proof: h.seal_proof_type,
+        sector_number: 100,
+        sealed_cid: Cid::new_v1(
+            FIL_COMMITMENT_SEALED,
+            Multihash::wrap(POSEIDON_BLS12_381_A1_FC1, &[1u8; 32]).unwrap(),
+        ),
+        seal_rand_epoch: rt.epoch - 1,
+        deal_ids: vec![1],
+        expiration: rt.epoch + rt.policy.max_sector_expiration_extension(rt.network_version),
+        replace_capacity: true,
+        replace_sector_deadline: 0,
+        replace_sector_partition: 0,
+        replace_sector_number: 1,
+    };
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let ret = rt.call::<fil_actor_miner::Actor>(
+        Method::PreCommitSector as u64,
+        &RawBytes::serialize(params).unwrap(),
+    );
+    util::check_state_invariants(&rt);
+    ret
+}
+
+#[test]
+fn test_replace_capacity_rejected_from_network_version_16() {
+    expect_abort_contains_message(
+        ExitCode::SysErrForbidden,
+        "cc upgrade through precommit discontinued",
+        pre_commit_replace_capacity(NetworkVersion::V16),
+    );
+}
+
+// Confirming a pre-committed upgrade does not reschedule the replaced sector, so upgrades are
+// rejected at every network version rather than leaving the replaced sector's power in place.
+#[test]
+fn test_replace_capacity_rejected_before_network_version_16() {
+    expect_abort_contains_message(
+        ExitCode::SysErrForbidden,
+        "cc upgrade through precommit discontinued",
+        pre_commit_replace_capacity(NetworkVersion::V15),
+    );
+}
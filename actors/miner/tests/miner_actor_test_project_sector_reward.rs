@@ -0,0 +1,95 @@
+use fil_actor_miner::{
+    expected_reward_for_power, ext, Method, ProjectSectorRewardParams, ProjectSectorRewardReturn,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::reward::ThisEpochRewardReturn;
+use fvm_shared::sector::StoragePower;
+
+mod util;
+
+fn project_sector_reward(
+    rt: &mut MockRuntime,
+    qa_power: StoragePower,
+    duration: ChainEpoch,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    rt.expect_validate_caller_any();
+    let params = ProjectSectorRewardParams { qa_power, duration };
+    rt.call::<fil_actor_miner::Actor>(
+        Method::ProjectSectorReward as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+}
+
+#[test]
+fn test_project_sector_reward_uses_current_estimates() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    rt.expect_send(
+        *REWARD_ACTOR_ADDR,
+        ext::reward::THIS_EPOCH_REWARD_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ThisEpochRewardReturn {
+            this_epoch_reward_smoothed: h.epoch_reward_smooth.clone(),
+            this_epoch_baseline_power: h.baseline_power.clone(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::CURRENT_TOTAL_POWER_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::power::CurrentTotalPowerReturn {
+            raw_byte_power: h.baseline_power.clone(),
+            quality_adj_power: h.baseline_power.clone(),
+            pledge_collateral: TokenAmount::from(0),
+            quality_adj_power_smoothed: h.epoch_qa_power_smooth.clone(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+
+    let qa_power = StoragePower::from(h.sector_size as u64);
+    let duration = rt.policy.wpost_proving_period;
+    let ret: ProjectSectorRewardReturn =
+        project_sector_reward(&mut rt, qa_power.clone(), duration).unwrap().deserialize().unwrap();
+    rt.verify();
+
+    let expected = expected_reward_for_power(
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &qa_power,
+        duration,
+    );
+    assert_eq!(expected, ret.reward);
+    assert!(ret.reward > BigInt::from(0));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_project_sector_reward_rejects_non_positive_duration() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        project_sector_reward(&mut rt, StoragePower::from(1), 0),
+    );
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,70 @@
+use fil_actor_miner::{
+    Method, ProveCommitAggregateParams, SectorPreCommitInfo, SectorPreCommitOnChainInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+
+use bitfield::BitField;
+use cid::Cid;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sector::RegisteredPoStProof;
+
+mod util;
+
+#[test]
+fn test_rejects_pre_commits_stale_for_window_post_proof_type() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let sector_numbers: Vec<u64> = (0..rt.policy.min_aggregated_sectors).collect();
+    let mut st: State = rt.get_state().unwrap();
+    let pre_commits = sector_numbers
+        .iter()
+        .map(|&sector_number| SectorPreCommitOnChainInfo {
+            info: SectorPreCommitInfo {
+                seal_proof: h.seal_proof_type,
+                sector_number,
+                sealed_cid: Cid::default(),
+                seal_rand_epoch: 0,
+                deal_ids: vec![],
+                expiration: rt.policy.min_sector_expiration(rt.network_version) + 1,
+                replace_capacity: false,
+                replace_sector_deadline: 0,
+                replace_sector_partition: 0,
+                replace_sector_number: 0,
+            },
+            pre_commit_deposit: TokenAmount::from(0),
+            pre_commit_epoch: rt.epoch,
+            deal_weight: BigInt::from(0),
+            verified_deal_weight: BigInt::from(0),
+        })
+        .collect();
+    st.put_precommitted_sectors(&rt.store, pre_commits).unwrap();
+
+    // The miner's proof type changes after the sectors were pre-committed.
+    let mut info = st.get_info(&rt.store).unwrap();
+    info.window_post_proof_type = RegisteredPoStProof::StackedDRGWindow64GiBV1;
+    st.save_info(&rt.store, &info).unwrap();
+    rt.replace_state(&st);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(
+        h.control_addrs.iter().chain(&[h.worker, h.owner]).cloned().collect(),
+    );
+    let sectors: BitField = sector_numbers.iter().copied().collect();
+    let params =
+        ProveCommitAggregateParams { sector_numbers: sectors.into(), aggregate_proof: vec![] };
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "must match miner Window PoSt proof type",
+        rt.call::<fil_actor_miner::Actor>(
+            Method::ProveCommitAggregate as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.verify();
+}
@@ -0,0 +1,119 @@
+use fil_actor_miner::{
+    ext, CollisionPolicy, Method, ProveCommitSectorParams, SectorPreCommitInfo,
+    SectorPreCommitOnChainInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::STORAGE_POWER_ACTOR_ADDR;
+
+use cid::multihash::Multihash;
+use cid::Cid;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::crypto::randomness::DomainSeparationTag;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::{Cbor, RawBytes};
+use fvm_shared::error::ExitCode;
+use fvm_shared::randomness::Randomness;
+use fvm_shared::sector::{SealVerifyInfo, SectorID};
+
+mod util;
+
+const IPLD_RAW: u64 = 0x55;
+
+#[test]
+fn test_cc_prove_commit_computes_commd_without_market() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.epoch = 10;
+
+    let sector_number = 7;
+    let sealed_cid = Cid::default();
+    let precommit = SectorPreCommitOnChainInfo {
+        info: SectorPreCommitInfo {
+            seal_proof: h.seal_proof_type,
+            sector_number,
+            sealed_cid,
+            seal_rand_epoch: rt.epoch - 1,
+            deal_ids: vec![],
+            expiration: rt.policy.min_sector_expiration(rt.network_version) + 1,
+            replace_capacity: false,
+            replace_sector_deadline: 0,
+            replace_sector_partition: 0,
+            replace_sector_number: 0,
+        },
+        pre_commit_deposit: TokenAmount::from(0),
+        pre_commit_epoch: rt.epoch,
+        deal_weight: BigInt::from(0),
+        verified_deal_weight: BigInt::from(0),
+    };
+    let mut st: State = rt.get_state().unwrap();
+    st.put_precommitted_sectors(&rt.store, vec![precommit.clone()]).unwrap();
+    st.allocate_sector_numbers(
+        &rt.policy,
+        &rt.store,
+        &[sector_number].iter().copied().collect(),
+        CollisionPolicy::DenyCollisions,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+
+    let interactive_epoch = precommit.pre_commit_epoch + rt.policy.pre_commit_challenge_delay;
+    rt.epoch = interactive_epoch + 1;
+
+    let unsealed_cid = Cid::new_v1(IPLD_RAW, Multihash::wrap(0, b"zero commd").unwrap());
+    let seal_rand = Randomness(vec![1; 32]);
+    let seal_int_rand = Randomness(vec![2; 32]);
+    let entropy = rt.receiver.marshal_cbor().unwrap();
+    let proof = vec![0u8; 192];
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_any();
+    // The zero CommD is computed with a syscall and no deal pieces, with no send to the market.
+    rt.expect_compute_unsealed_sector_cid(ExpectComputeUnsealedSectorCid {
+        reg: h.seal_proof_type,
+        pieces: vec![],
+        cid: unsealed_cid,
+        exit_code: ExitCode::Ok,
+    });
+    rt.expect_get_randomness_from_tickets(
+        DomainSeparationTag::SealRandomness,
+        precommit.info.seal_rand_epoch,
+        entropy.clone(),
+        seal_rand.clone(),
+    );
+    rt.expect_get_randomness_from_beacon(
+        DomainSeparationTag::InteractiveSealChallengeSeed,
+        interactive_epoch,
+        entropy,
+        seal_int_rand.clone(),
+    );
+    let svi = SealVerifyInfo {
+        registered_proof: h.seal_proof_type,
+        sector_id: SectorID { miner: 0, number: sector_number },
+        deal_ids: vec![],
+        interactive_randomness: seal_int_rand,
+        proof: proof.clone(),
+        randomness: seal_rand,
+        sealed_cid,
+        unsealed_cid,
+    };
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::SUBMIT_POREP_FOR_BULK_VERIFY_METHOD,
+        RawBytes::serialize(&svi).unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+
+    let params = ProveCommitSectorParams { sector_number, proof };
+    rt.call::<fil_actor_miner::Actor>(
+        Method::ProveCommitSector as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+
+    util::check_state_invariants(&rt);
+}
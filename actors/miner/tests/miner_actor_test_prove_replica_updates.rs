@@ -0,0 +1,349 @@
+use fil_actor_miner::{
+    ext, initial_pledge_for_power, max_replica_update_proof_size, qa_power_for_weight, Method,
+    PartitionSectorMap, ProveReplicaUpdatesParams, ReplicaUpdate, SectorOnChainInfo, Sectors,
+    State,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{REWARD_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+use bitfield::BitField;
+use cid::multihash::Multihash;
+use cid::Cid;
+use fvm_shared::bigint::bigint_ser::BigIntSer;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::commcid::{
+    FIL_COMMITMENT_SEALED, FIL_COMMITMENT_UNSEALED, POSEIDON_BLS12_381_A1_FC1,
+    SHA2_256_TRUNC254_PADDED,
+};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::reward::ThisEpochRewardReturn;
+use fvm_shared::sector::{RegisteredUpdateProof, ReplicaUpdateInfo, SectorNumber};
+
+mod util;
+
+/// Stores a proven CC sector directly in state, returning its deadline and partition.
+fn add_proven_sector(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    sector_number: SectorNumber,
+) -> (u64, u64) {
+    let mut st: State = rt.get_state().unwrap();
+    let sector = SectorOnChainInfo {
+        sector_number,
+        seal_proof: h.seal_proof_type,
+        expiration: rt.policy.max_sector_expiration_extension(rt.network_version),
+        ..Default::default()
+    };
+    st.put_sectors(&rt.store, vec![sector.clone()]).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        vec![sector],
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    let (dl_idx, p_idx) = st.find_sector(&rt.policy, &rt.store, sector_number).unwrap();
+
+    let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+    let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_idx).unwrap();
+    let mut partitions = deadline.partitions_amt(&rt.store).unwrap();
+    let mut partition = partitions.get(p_idx).unwrap().unwrap().clone();
+    partition.activate_unproven();
+    partitions.set(p_idx, partition).unwrap();
+    deadline.partitions = partitions.flush().unwrap();
+    deadlines.update_deadline(&rt.policy, &rt.store, dl_idx, &deadline).unwrap();
+    st.save_deadlines(&rt.store, deadlines).unwrap();
+    rt.replace_state(&st);
+
+    (dl_idx, p_idx)
+}
+
+fn replica_update(sector_number: SectorNumber, deadline: u64, partition: u64) -> ReplicaUpdate {
+    ReplicaUpdate {
+        sector_number,
+        deadline,
+        partition,
+        new_sealed_cid: Cid::new_v1(
+            FIL_COMMITMENT_SEALED,
+            Multihash::wrap(POSEIDON_BLS12_381_A1_FC1, b"sealed").unwrap(),
+        ),
+        deals: vec![1],
+        update_proof_type: RegisteredUpdateProof::StackedDRG32GiBV1,
+        replica_proof: vec![],
+    }
+}
+
+fn prove_replica_updates(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    method: Method,
+    updates: Vec<ReplicaUpdate>,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(
+        h.control_addrs.iter().chain(&[h.owner, h.worker]).cloned().collect(),
+    );
+    let params = ProveReplicaUpdatesParams { updates };
+    rt.call::<fil_actor_miner::Actor>(method as u64, &RawBytes::serialize(params).unwrap())
+}
+
+#[test]
+fn test_skips_sector_pending_early_termination() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // Terminate a sector, leaving it in the early terminations queue.
+    let sector_number = 100;
+    let (dl_idx, p_idx) = add_proven_sector(&mut rt, &h, sector_number);
+    let mut st: State = rt.get_state().unwrap();
+    let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+    let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_idx).unwrap();
+    let sectors = Sectors::load(&rt.store, &st.sectors).unwrap();
+    let mut to_terminate = PartitionSectorMap::default();
+    let terminated: BitField = [sector_number].iter().copied().collect();
+    to_terminate.add(p_idx, terminated.into()).unwrap();
+    deadline
+        .terminate_sectors(
+            &rt.policy,
+            &rt.store,
+            &sectors,
+            rt.epoch,
+            &mut to_terminate,
+            h.sector_size,
+            st.quant_spec_for_deadline(&rt.policy, dl_idx),
+        )
+        .unwrap();
+    deadlines.update_deadline(&rt.policy, &rt.store, dl_idx, &deadline).unwrap();
+    st.save_deadlines(&rt.store, deadlines).unwrap();
+    st.early_terminations.set(dl_idx);
+    rt.replace_state(&st);
+    // A sector awaiting early termination processing is already terminated, so the only
+    // update is skipped as inactive before any deals are activated for it.
+    let partition = deadline.load_partition(&rt.store, p_idx).unwrap();
+    assert!(partition.terminated.get(sector_number));
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "no valid updates",
+        prove_replica_updates(
+            &mut rt,
+            &h,
+            Method::ProveReplicaUpdates,
+            vec![replica_update(sector_number, dl_idx, p_idx)],
+        ),
+    );
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let sectors = Sectors::load(&rt.store, &st.sectors).unwrap();
+    assert!(sectors.get(sector_number).unwrap().unwrap().sector_key_cid.is_none());
+}
+
+#[test]
+fn test_batch_skips_sectors_whose_deals_fail_to_activate() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let sector_number = 100;
+    let (dl_idx, p_idx) = add_proven_sector(&mut rt, &h, sector_number);
+
+    // All sectors' deals are activated in one market call, which reports a failure per sector.
+    rt.expect_send(
+        *STORAGE_MARKET_ACTOR_ADDR,
+        ext::market::BATCH_ACTIVATE_DEALS_METHOD,
+        RawBytes::serialize(ext::market::BatchActivateDealsParams {
+            sectors: vec![ext::market::ActivateDealsParams {
+                deal_ids: vec![1],
+                sector_expiry: rt.policy.max_sector_expiration_extension(rt.network_version),
+            }],
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::market::BatchActivateDealsReturn {
+            codes: vec![ExitCode::ErrIllegalArgument],
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "no valid updates",
+        prove_replica_updates(
+            &mut rt,
+            &h,
+            Method::ProveReplicaUpdatesBatch,
+            vec![replica_update(sector_number, dl_idx, p_idx)],
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn test_skips_oversized_update_proof() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let sector_number = 100;
+    let (dl_idx, p_idx) = add_proven_sector(&mut rt, &h, sector_number);
+
+    // The update is skipped before any deals are activated for it.
+    let mut update = replica_update(sector_number, dl_idx, p_idx);
+    update.replica_proof =
+        vec![0; max_replica_update_proof_size(&rt.policy, update.update_proof_type) + 1];
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "no valid updates",
+        prove_replica_updates(&mut rt, &h, Method::ProveReplicaUpdates, vec![update]),
+    );
+    rt.verify();
+}
+
+#[test]
+fn test_notifies_pledge_change_once_across_deadlines() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.circulating_supply = TokenAmount::from(10u64.pow(18));
+    rt.add_balance(&rt.circulating_supply * 10);
+
+    // Sectors in different deadlines each raise their initial pledge when updated.
+    let first = add_proven_sector(&mut rt, &h, 100);
+    let second = add_proven_sector(&mut rt, &h, 101);
+    assert_ne!(first.0, second.0);
+    let updates =
+        vec![replica_update(100, first.0, first.1), replica_update(101, second.0, second.1)];
+    let sector_expiry = rt.policy.max_sector_expiration_extension(rt.network_version);
+
+    for _ in 0..updates.len() {
+        rt.expect_send(
+            *STORAGE_MARKET_ACTOR_ADDR,
+            ext::market::ACTIVATE_DEALS_METHOD,
+            RawBytes::serialize(ext::market::ActivateDealsParams {
+                deal_ids: vec![1],
+                sector_expiry,
+            })
+            .unwrap(),
+            TokenAmount::from(0),
+            RawBytes::default(),
+            ExitCode::Ok,
+        );
+    }
+    let sectors_deals: Vec<_> = updates
+        .iter()
+        .map(|_| ext::market::SectorDeals { sector_expiry, deal_ids: vec![1] })
+        .collect();
+    rt.expect_send(
+        *STORAGE_MARKET_ACTOR_ADDR,
+        ext::market::VERIFY_DEALS_FOR_ACTIVATION_METHOD,
+        RawBytes::serialize(ext::market::VerifyDealsForActivationParamsRef {
+            sectors: &sectors_deals,
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::market::VerifyDealsForActivationReturn {
+            sectors: updates.iter().map(|_| Default::default()).collect(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    let unsealed_cid = Cid::new_v1(
+        FIL_COMMITMENT_UNSEALED,
+        Multihash::wrap(SHA2_256_TRUNC254_PADDED, b"unsealed").unwrap(),
+    );
+    let data_specs: Vec<_> = updates
+        .iter()
+        .map(|_| ext::market::SectorDataSpec { deal_ids: vec![1], sector_type: h.seal_proof_type })
+        .collect();
+    rt.expect_send(
+        *STORAGE_MARKET_ACTOR_ADDR,
+        ext::market::COMPUTE_DATA_COMMITMENT_METHOD,
+        RawBytes::serialize(ext::market::ComputeDataCommitmentParamsRef { inputs: &data_specs })
+            .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::market::ComputeDataCommitmentReturn {
+            commds: vec![unsealed_cid; updates.len()],
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *REWARD_ACTOR_ADDR,
+        ext::reward::THIS_EPOCH_REWARD_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ThisEpochRewardReturn {
+            this_epoch_reward_smoothed: h.epoch_reward_smooth.clone(),
+            this_epoch_baseline_power: h.baseline_power.clone(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::CURRENT_TOTAL_POWER_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::power::CurrentTotalPowerReturn {
+            raw_byte_power: h.baseline_power.clone(),
+            quality_adj_power: h.baseline_power.clone(),
+            pledge_collateral: TokenAmount::from(0),
+            quality_adj_power_smoothed: h.epoch_qa_power_smooth.clone(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    for update in &updates {
+        rt.expect_replica_verify(
+            ReplicaUpdateInfo {
+                update_proof_type: update.update_proof_type,
+                new_sealed_cid: update.new_sealed_cid,
+                old_sealed_cid: Cid::default(),
+                new_unsealed_cid: unsealed_cid,
+                proof: vec![],
+            },
+            ExitCode::Ok,
+        );
+    }
+
+    // The pledge raised for both deadlines is reported in a single notification.
+    let qa_power = qa_power_for_weight(
+        h.sector_size,
+        sector_expiry - rt.epoch,
+        &BigInt::from(0),
+        &BigInt::from(0),
+    );
+    let sector_pledge = initial_pledge_for_power(
+        &qa_power,
+        &h.baseline_power,
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &rt.circulating_supply,
+    );
+    let total_pledge = &sector_pledge * updates.len();
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_PLEDGE_TOTAL_METHOD,
+        RawBytes::serialize(BigIntSer(&total_pledge)).unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    prove_replica_updates(&mut rt, &h, Method::ProveReplicaUpdates, updates).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(total_pledge, st.initial_pledge);
+    let sectors = Sectors::load(&rt.store, &st.sectors).unwrap();
+    assert_eq!(sector_pledge, sectors.get(100).unwrap().unwrap().initial_pledge);
+    assert_eq!(sector_pledge, sectors.get(101).unwrap().unwrap().initial_pledge);
+}
@@ -0,0 +1,41 @@
+use fil_actor_miner::State;
+use fil_actors_runtime::test_utils::*;
+
+mod util;
+
+#[test]
+fn test_proving_period_info_reports_state() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    let info = h.get_proving_period_info(&mut rt);
+    assert_eq!(st.proving_period_start, info.proving_period_start);
+    assert_eq!(st.current_deadline, info.current_deadline);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_proving_period_offset_is_stable_across_periods() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let offset = h.get_proving_period_info(&mut rt).proving_period_offset;
+    assert!(offset >= 0 && offset < rt.policy.wpost_proving_period);
+
+    // Advancing the period start by whole periods, as cron does, leaves the offset unchanged.
+    let mut st: State = rt.get_state().unwrap();
+    st.proving_period_start += 3 * rt.policy.wpost_proving_period;
+    st.current_deadline = 5;
+    rt.replace_state(&st);
+
+    let info = h.get_proving_period_info(&mut rt);
+    assert_eq!(st.proving_period_start, info.proving_period_start);
+    assert_eq!(5, info.current_deadline);
+    assert_eq!(offset, info.proving_period_offset);
+}
@@ -0,0 +1,162 @@
+use fil_actor_miner::{
+    ext, ApplyRewardParams, ApplyRewardReturn, GetRecentRewardsReturn, Method, RewardApplication,
+    State, VestSpec, RECENT_REWARD_APPLICATIONS_MAX,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{BURNT_FUNDS_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+use fvm_shared::bigint::bigint_ser::BigIntSer;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+
+mod util;
+
+fn apply_rewards(rt: &mut MockRuntime, params: RawBytes) {
+    rt.set_caller(*REWARD_ACTOR_CODE_ID, *REWARD_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*REWARD_ACTOR_ADDR]);
+    rt.call::<fil_actor_miner::Actor>(Method::ApplyRewards as u64, &params).unwrap();
+    rt.verify();
+}
+
+fn get_recent_rewards(rt: &mut MockRuntime) -> Vec<RewardApplication> {
+    rt.expect_validate_caller_any();
+    let ret: GetRecentRewardsReturn = rt
+        .call::<fil_actor_miner::Actor>(Method::GetRecentRewards as u64, &RawBytes::default())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.rewards
+}
+
+#[test]
+fn test_recent_rewards_keep_latest_applications() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.policy.record_reward_applications = true;
+    assert!(get_recent_rewards(&mut rt).is_empty());
+
+    let total = RECENT_REWARD_APPLICATIONS_MAX as i64 + 2;
+    for epoch in 0..total {
+        rt.epoch = epoch;
+        let params = ApplyRewardParams {
+            reward: TokenAmount::from(0),
+            penalty: TokenAmount::from(0),
+            memo: RawBytes::new(epoch.to_be_bytes().to_vec()),
+        };
+        apply_rewards(&mut rt, RawBytes::serialize(params).unwrap());
+    }
+
+    let rewards = get_recent_rewards(&mut rt);
+    assert_eq!(RECENT_REWARD_APPLICATIONS_MAX, rewards.len());
+    assert_eq!(2, rewards[0].epoch);
+    assert_eq!(total - 1, rewards.last().unwrap().epoch);
+    assert_eq!(RawBytes::new(2i64.to_be_bytes().to_vec()), rewards[0].memo);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_apply_rewards_accepts_params_without_memo() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.policy.record_reward_applications = true;
+
+    // Callers predating the memo send only the reward and penalty.
+    let zero = TokenAmount::from(0);
+    apply_rewards(&mut rt, RawBytes::serialize((BigIntSer(&zero), BigIntSer(&zero))).unwrap());
+
+    let rewards = get_recent_rewards(&mut rt);
+    assert_eq!(1, rewards.len());
+    assert!(rewards[0].memo.is_empty());
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_reward_applications_not_recorded_by_default() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let st: State = rt.get_state().unwrap();
+
+    let params = ApplyRewardParams {
+        reward: TokenAmount::from(0),
+        penalty: TokenAmount::from(0),
+        memo: RawBytes::new(b"block reward".to_vec()),
+    };
+    apply_rewards(&mut rt, RawBytes::serialize(params).unwrap());
+
+    assert!(get_recent_rewards(&mut rt).is_empty());
+    let after: State = rt.get_state().unwrap();
+    assert_eq!(st.recent_rewards, after.recent_rewards);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_apply_rewards_returns_vested_locked_and_burnt_amounts() {
+    let mut rt = MockRuntime::default();
+    rt.policy.reward_vesting_spec =
+        VestSpec { initial_delay: 0, vest_period: 10, step_duration: 1, quantization: 1 };
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // 100 locked at epoch 0 vests in equal steps, so 40 of it has vested by epoch 5.
+    let mut st: State = rt.get_state().unwrap();
+    st.add_locked_funds(&rt.store, 0, &TokenAmount::from(100), &rt.policy.reward_vesting_spec)
+        .unwrap();
+    rt.replace_state(&st);
+    rt.epoch = 5;
+    rt.set_balance(TokenAmount::from(1100));
+
+    // Three quarters of the reward is locked, and the penalty is repaid from vesting funds.
+    let pledge_delta = TokenAmount::from(750 - 40 - 100);
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_PLEDGE_TOTAL_METHOD,
+        RawBytes::serialize(BigIntSer(&pledge_delta)).unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(100),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+
+    let params = ApplyRewardParams {
+        reward: TokenAmount::from(1000),
+        penalty: TokenAmount::from(100),
+        memo: RawBytes::default(),
+    };
+    rt.set_caller(*REWARD_ACTOR_CODE_ID, *REWARD_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*REWARD_ACTOR_ADDR]);
+    let ret: ApplyRewardReturn = rt
+        .call::<fil_actor_miner::Actor>(
+            Method::ApplyRewards as u64,
+            &RawBytes::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    assert_eq!(TokenAmount::from(40), ret.newly_vested);
+    assert_eq!(TokenAmount::from(750), ret.reward_to_lock);
+    assert_eq!(TokenAmount::from(100), ret.to_burn);
+
+    util::check_state_invariants(&rt);
+}
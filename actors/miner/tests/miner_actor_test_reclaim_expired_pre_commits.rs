@@ -0,0 +1,173 @@
+use fil_actor_miner::{
+    max_prove_commit_duration, CollisionPolicy, Method, ReclaimExpiredPreCommitNumbersReturn,
+    SectorPreCommitInfo, SectorPreCommitOnChainInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::BURNT_FUNDS_ACTOR_ADDR;
+
+use cid::Cid;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+
+mod util;
+
+// Records a pre-commitment in state as PreCommitSectorBatch would.
+fn pre_commit(rt: &mut MockRuntime, h: &util::ActorHarness, sector_number: u64, deposit: u64) {
+    let mut st: State = rt.get_state().unwrap();
+    let deposit = TokenAmount::from(deposit);
+    st.put_precommitted_sectors(
+        &rt.store,
+        vec![SectorPreCommitOnChainInfo {
+            info: SectorPreCommitInfo {
+                seal_proof: h.seal_proof_type,
+                sector_number,
+                sealed_cid: Cid::default(),
+                seal_rand_epoch: 0,
+                deal_ids: vec![],
+                expiration: rt.policy.min_sector_expiration(rt.network_version) + 1,
+                replace_capacity: false,
+                replace_sector_deadline: 0,
+                replace_sector_partition: 0,
+                replace_sector_number: 0,
+            },
+            pre_commit_deposit: deposit.clone(),
+            pre_commit_epoch: rt.epoch,
+            deal_weight: BigInt::from(0),
+            verified_deal_weight: BigInt::from(0),
+        }],
+    )
+    .unwrap();
+    st.add_pre_commit_deposit(&deposit).unwrap();
+    st.allocate_sector_numbers(
+        &rt.policy,
+        &rt.store,
+        &[sector_number].iter().copied().collect(),
+        CollisionPolicy::DenyCollisions,
+    )
+    .unwrap();
+    st.add_pre_commit_clean_ups(
+        &rt.policy,
+        &rt.store,
+        vec![(clean_up_bound(rt, h, rt.epoch), sector_number)],
+    )
+    .unwrap();
+    rt.replace_state(&st);
+    rt.add_balance(deposit);
+}
+
+// The last epoch at which a pre-commit made at an epoch can be proven.
+fn expiry(rt: &MockRuntime, h: &util::ActorHarness, epoch: ChainEpoch) -> ChainEpoch {
+    let msd = max_prove_commit_duration(&rt.policy, h.seal_proof_type).unwrap();
+    epoch + msd + rt.policy.prove_commit_grace
+}
+
+fn clean_up_bound(rt: &MockRuntime, h: &util::ActorHarness, epoch: ChainEpoch) -> ChainEpoch {
+    expiry(rt, h, epoch) + rt.policy.expired_pre_commit_clean_up_delay
+}
+
+fn reclaim_expired_pre_commit_numbers(rt: &mut MockRuntime, h: &util::ActorHarness) -> u64 {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.push(h.worker);
+    caller_addrs.push(h.owner);
+    rt.expect_validate_caller_addr(caller_addrs);
+    let ret: ReclaimExpiredPreCommitNumbersReturn = rt
+        .call::<fil_actor_miner::Actor>(
+            Method::ReclaimExpiredPreCommitNumbers as u64,
+            &RawBytes::default(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.freed
+}
+
+#[test]
+fn test_reclaim_burns_deposits_and_frees_numbers() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    pre_commit(&mut rt, &h, 0, 100);
+    pre_commit(&mut rt, &h, 1, 200);
+    assert_eq!(2, h.next_allocatable_sector_number(&mut rt));
+
+    // Clean-up epochs are quantized up to the end of a deadline.
+    rt.epoch = clean_up_bound(&rt, &h, rt.epoch) + rt.policy.wpost_challenge_window;
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(300),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    assert_eq!(2, reclaim_expired_pre_commit_numbers(&mut rt, &h));
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.get_precommitted_sector(&rt.store, 0).unwrap().is_none());
+    assert!(st.get_precommitted_sector(&rt.store, 1).unwrap().is_none());
+    assert_eq!(TokenAmount::from(0), st.pre_commit_deposits);
+    assert_eq!(0, h.next_allocatable_sector_number(&mut rt));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_reclaim_leaves_unexpired_pre_commits() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    pre_commit(&mut rt, &h, 0, 100);
+
+    assert_eq!(0, reclaim_expired_pre_commit_numbers(&mut rt, &h));
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.get_precommitted_sector(&rt.store, 0).unwrap().is_some());
+    assert_eq!(TokenAmount::from(100), st.pre_commit_deposits);
+    assert_eq!(1, h.next_allocatable_sector_number(&mut rt));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_reclaim_frees_numbers_once_grace_window_has_passed() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.policy.prove_commit_grace = 10;
+    let pre_commit_epoch = rt.epoch;
+    pre_commit(&mut rt, &h, 0, 100);
+
+    // The sector may still be proven at the end of the grace window.
+    rt.epoch = expiry(&rt, &h, pre_commit_epoch);
+    assert_eq!(0, reclaim_expired_pre_commit_numbers(&mut rt, &h));
+    assert_eq!(1, h.next_allocatable_sector_number(&mut rt));
+
+    // The number is freed right after, well before cron would clean the pre-commit up.
+    rt.epoch += 1;
+    assert!(rt.epoch < clean_up_bound(&rt, &h, pre_commit_epoch));
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        TokenAmount::from(100),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    assert_eq!(1, reclaim_expired_pre_commit_numbers(&mut rt, &h));
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.get_precommitted_sector(&rt.store, 0).unwrap().is_none());
+    assert_eq!(TokenAmount::from(0), st.pre_commit_deposits);
+    assert_eq!(0, h.next_allocatable_sector_number(&mut rt));
+
+    util::check_state_invariants(&rt);
+}
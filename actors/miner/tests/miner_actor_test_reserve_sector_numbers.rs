@@ -0,0 +1,203 @@
+use fil_actor_miner::{
+    CollisionPolicy, CompactSectorNumbersParams, Method, PreCommitSectorBatchParams,
+    ReleaseSectorNumbersParams, ReserveSectorNumbersParams, SectorPreCommitInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+
+use bitfield::BitField;
+use cid::multihash::Multihash;
+use cid::Cid;
+use fvm_shared::commcid::{FIL_COMMITMENT_SEALED, POSEIDON_BLS12_381_A1_FC1};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+fn call_as_worker(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    method: Method,
+    params: RawBytes,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.extend_from_slice(&[h.worker, h.owner]);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(caller_addrs);
+    rt.call::<fil_actor_miner::Actor>(method as u64, &params)
+}
+
+fn reserve(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    sectors: &[u64],
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    let sectors: BitField = sectors.iter().copied().collect();
+    let params = ReserveSectorNumbersParams { sectors: sectors.into() };
+    call_as_worker(rt, h, Method::ReserveSectorNumbers, RawBytes::serialize(params).unwrap())
+}
+
+fn release(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    sectors: &[u64],
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    let sectors: BitField = sectors.iter().copied().collect();
+    let params = ReleaseSectorNumbersParams { sectors: sectors.into() };
+    call_as_worker(rt, h, Method::ReleaseSectorNumbers, RawBytes::serialize(params).unwrap())
+}
+
+fn reserved(rt: &MockRuntime) -> BitField {
+    let st: State = rt.get_state().unwrap();
+    st.load_reserved_sector_numbers(&rt.store).unwrap()
+}
+
+#[test]
+fn test_reserved_numbers_are_allocated() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    reserve(&mut rt, &h, &[0, 1, 2]).unwrap();
+    rt.verify();
+
+    assert_eq!(3, reserved(&rt).len());
+    assert_eq!(3, h.next_allocatable_sector_number(&mut rt));
+
+    // Reserving an allocated number again is a collision.
+    expect_abort(ExitCode::ErrIllegalArgument, reserve(&mut rt, &h, &[2, 3]));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_release_frees_reserved_numbers() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    reserve(&mut rt, &h, &[0, 1, 2]).unwrap();
+    rt.verify();
+    release(&mut rt, &h, &[0, 1]).unwrap();
+    rt.verify();
+
+    let reserved = reserved(&rt);
+    assert_eq!(1, reserved.len());
+    assert!(reserved.get(2));
+    assert_eq!(0, h.next_allocatable_sector_number(&mut rt));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_release_rejects_unreserved_numbers() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    reserve(&mut rt, &h, &[0]).unwrap();
+    rt.verify();
+
+    // Number 1 is allocated but not reserved, so the whole release is rejected.
+    let mut st: State = rt.get_state().unwrap();
+    st.allocate_sector_numbers(
+        &rt.policy,
+        &rt.store,
+        &[1].iter().copied().collect(),
+        CollisionPolicy::DenyCollisions,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "are not reserved",
+        release(&mut rt, &h, &[0, 1]),
+    );
+    rt.verify();
+    assert_eq!(2, h.next_allocatable_sector_number(&mut rt));
+    assert!(reserved(&rt).get(0));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_masked_numbers_leave_the_reservation() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    reserve(&mut rt, &h, &[0, 5]).unwrap();
+    rt.verify();
+
+    let mask: BitField = (0..4).collect();
+    let params = CompactSectorNumbersParams { mask_sector_numbers: mask.into() };
+    call_as_worker(&mut rt, &h, Method::CompactSectorNumbers, RawBytes::serialize(params).unwrap())
+        .unwrap();
+    rt.verify();
+
+    // Number 0 is masked for good, so only number 5 can still be released.
+    let reserved = reserved(&rt);
+    assert_eq!(1, reserved.len());
+    assert!(reserved.get(5));
+    expect_abort(ExitCode::ErrIllegalArgument, release(&mut rt, &h, &[0]));
+    rt.verify();
+    release(&mut rt, &h, &[5]).unwrap();
+    rt.verify();
+    assert_eq!(4, h.next_allocatable_sector_number(&mut rt));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_pre_commit_rejects_reserved_number() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.epoch = 1000;
+    rt.set_balance(TokenAmount::from(10u64.pow(18)) * 1_000_000);
+
+    reserve(&mut rt, &h, &[100]).unwrap();
+    rt.verify();
+
+    let params = PreCommitSectorBatchParams {
+        sectors: vec![SectorPreCommitInfo {
+            seal_proof: h.seal_proof_type,
+            sector_number: 100,
+            sealed_cid: Cid::new_v1(
+                FIL_COMMITMENT_SEALED,
+                Multihash::wrap(POSEIDON_BLS12_381_A1_FC1, &[1u8; 32]).unwrap(),
+            ),
+            seal_rand_epoch: rt.epoch - 1,
+            deal_ids: vec![],
+            expiration: rt.epoch + rt.policy.max_sector_expiration_extension(rt.network_version),
+            replace_capacity: false,
+            replace_sector_deadline: 0,
+            replace_sector_partition: 0,
+            replace_sector_number: 0,
+        }],
+    };
+    h.expect_query_network_info(&mut rt);
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "already allocated",
+        call_as_worker(
+            &mut rt,
+            &h,
+            Method::PreCommitSectorBatch,
+            RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.get_precommitted_sector(&rt.store, 100).unwrap().is_none());
+    assert!(reserved(&rt).get(100));
+
+    util::check_state_invariants(&rt);
+}
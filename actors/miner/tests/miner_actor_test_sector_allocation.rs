@@ -0,0 +1,99 @@
+use fil_actor_miner::{CollisionPolicy, State};
+use fil_actors_runtime::test_utils::*;
+
+use bitfield::BitField;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+fn allocate(rt: &mut MockRuntime, sector_numbers: &[u64]) {
+    let mut st: State = rt.get_state().unwrap();
+    let sector_numbers: BitField = sector_numbers.iter().copied().collect();
+    st.allocate_sector_numbers(
+        &rt.policy,
+        &rt.store,
+        &sector_numbers,
+        CollisionPolicy::DenyCollisions,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+}
+
+#[test]
+fn test_next_allocatable_sector_number_on_new_miner() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    assert_eq!(0, h.next_allocatable_sector_number(&mut rt));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_next_allocatable_sector_number_follows_allocations() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    allocate(&mut rt, &[0, 1, 2]);
+    assert_eq!(3, h.next_allocatable_sector_number(&mut rt));
+
+    // Gaps in the allocation are reused before higher numbers.
+    allocate(&mut rt, &[4, 5]);
+    assert_eq!(3, h.next_allocatable_sector_number(&mut rt));
+
+    allocate(&mut rt, &[3]);
+    assert_eq!(6, h.next_allocatable_sector_number(&mut rt));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_next_allocatable_sector_number_below_first_allocation() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    allocate(&mut rt, &[10, 11]);
+    assert_eq!(0, h.next_allocatable_sector_number(&mut rt));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_allocation_rejected_when_bitfield_too_large() {
+    let mut rt = MockRuntime::default();
+    rt.policy.max_allocated_sectors_size = 8;
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // Widely spaced sector numbers encode poorly.
+    let mut st: State = rt.get_state().unwrap();
+    let sector_numbers: BitField = (0..10).map(|i| i * 1000).collect();
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalState,
+        "compact sector numbers",
+        st.allocate_sector_numbers(
+            &rt.policy,
+            &rt.store,
+            &sector_numbers,
+            CollisionPolicy::DenyCollisions,
+        ),
+    );
+
+    // Masking remains possible regardless of size.
+    st.allocate_sector_numbers(
+        &rt.policy,
+        &rt.store,
+        &sector_numbers,
+        CollisionPolicy::AllowCollisions,
+    )
+    .unwrap();
+
+    util::check_state_invariants(&rt);
+}
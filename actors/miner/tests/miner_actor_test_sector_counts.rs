@@ -0,0 +1,89 @@
+use fil_actor_miner::{
+    GetSectorCountsReturn, SectorPreCommitInfo, SectorPreCommitOnChainInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+
+use cid::Cid;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+
+mod util;
+
+fn pre_commits(
+    rt: &MockRuntime,
+    h: &util::ActorHarness,
+    count: u64,
+) -> Vec<SectorPreCommitOnChainInfo> {
+    (0..count)
+        .map(|sector_number| SectorPreCommitOnChainInfo {
+            info: SectorPreCommitInfo {
+                seal_proof: h.seal_proof_type,
+                sector_number,
+                sealed_cid: Cid::default(),
+                seal_rand_epoch: 0,
+                deal_ids: vec![],
+                expiration: rt.policy.min_sector_expiration(rt.network_version) + 1,
+                replace_capacity: false,
+                replace_sector_deadline: 0,
+                replace_sector_partition: 0,
+                replace_sector_number: 0,
+            },
+            pre_commit_deposit: TokenAmount::from(0),
+            pre_commit_epoch: rt.epoch,
+            deal_weight: BigInt::from(0),
+            verified_deal_weight: BigInt::from(0),
+        })
+        .collect()
+}
+
+#[test]
+fn test_sector_counts_after_construction() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    assert_eq!(GetSectorCountsReturn::default(), h.get_sector_counts(&mut rt));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_sector_counts_include_pre_commits() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    st.put_precommitted_sectors(&rt.store, pre_commits(&rt, &h, 3)).unwrap();
+    rt.replace_state(&st);
+
+    let counts = h.get_sector_counts(&mut rt);
+    assert_eq!(3, counts.precommitted_sectors);
+    assert_eq!(0, counts.live_sectors);
+    assert_eq!(0, counts.faulty_sectors);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_pre_commit_count_follows_deletions() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    st.put_precommitted_sectors(&rt.store, pre_commits(&rt, &h, 3)).unwrap();
+    assert_eq!(3, st.pre_committed_sector_count);
+
+    // Sector 5 was never pre-committed, so only sectors 0 and 2 are removed.
+    st.delete_precommitted_sectors(&rt.store, &[0, 2, 5]).unwrap();
+    assert_eq!(1, st.pre_committed_sector_count);
+    rt.replace_state(&st);
+
+    assert_eq!(1, h.get_sector_counts(&mut rt).precommitted_sectors);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,125 @@
+use fil_actor_miner::{Partition, SectorHealth, SectorOnChainInfo, State};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::clock::ChainEpoch;
+
+mod util;
+
+/// Assigns sectors numbered from zero to deadlines, all in a single partition, returning their
+/// expiration and the deadline and partition they are assigned to.
+fn setup_sectors(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    count: u64,
+) -> (ChainEpoch, u64, u64) {
+    let expiration = rt.policy.max_sector_expiration_extension(rt.network_version);
+    let sectors: Vec<SectorOnChainInfo> = (0..count)
+        .map(|sector_number| SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            expiration,
+            ..Default::default()
+        })
+        .collect();
+    let mut st: State = rt.get_state().unwrap();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        sectors,
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+    let (dl_idx, p_idx) = st.find_sector(&rt.policy, &rt.store, 0).unwrap();
+    (expiration, dl_idx, p_idx)
+}
+
+/// Rewrites the partition at the given deadline and partition index.
+fn update_partition(rt: &mut MockRuntime, dl_idx: u64, p_idx: u64, f: impl FnOnce(&mut Partition)) {
+    let mut st: State = rt.get_state().unwrap();
+    let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+    let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_idx).unwrap();
+    let mut partitions = deadline.partitions_amt(&rt.store).unwrap();
+    let mut partition = partitions.get(p_idx).unwrap().unwrap().clone();
+    f(&mut partition);
+    partitions.set(p_idx, partition).unwrap();
+    deadline.partitions = partitions.flush().unwrap();
+    deadlines.update_deadline(&rt.policy, &rt.store, dl_idx, &deadline).unwrap();
+    st.save_deadlines(&rt.store, deadlines).unwrap();
+    rt.replace_state(&st);
+}
+
+#[test]
+fn test_get_sector_health() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let (expiration, dl_idx, p_idx) = setup_sectors(&mut rt, &h, 3);
+
+    // Sector 1 is faulty, and sector 2 is faulty but declared recovering.
+    update_partition(&mut rt, dl_idx, p_idx, |partition| {
+        partition.faults = [1, 2].iter().copied().collect();
+        partition.recoveries = [2].iter().copied().collect();
+    });
+
+    rt.epoch = 100;
+    let health = h.get_sector_health(&mut rt, &[0, 1, 2]);
+    let expected = |sector_number, is_faulty, is_recovering| {
+        Some(SectorHealth {
+            sector_number,
+            expiration,
+            epochs_remaining: expiration - 100,
+            is_faulty,
+            is_recovering,
+            is_terminated: false,
+            deadline: dl_idx,
+            partition: p_idx,
+        })
+    };
+    assert_eq!(
+        vec![expected(0, false, false), expected(1, true, false), expected(2, true, true)],
+        health
+    );
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_get_sector_health_terminated_sector() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let (_, dl_idx, p_idx) = setup_sectors(&mut rt, &h, 2);
+
+    // Sector 1 is terminated but not yet compacted out of its partition.
+    update_partition(&mut rt, dl_idx, p_idx, |partition| {
+        partition.terminated = [1].iter().copied().collect();
+    });
+
+    let health = h.get_sector_health(&mut rt, &[0, 1]);
+    assert!(!health[0].as_ref().unwrap().is_terminated);
+    assert!(health[1].as_ref().unwrap().is_terminated);
+}
+
+#[test]
+fn test_get_sector_health_missing_sector() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let (_, dl_idx, p_idx) = setup_sectors(&mut rt, &h, 1);
+
+    // Sectors not in the sectors array are reported as missing rather than failing the call.
+    let health = h.get_sector_health(&mut rt, &[0, 7]);
+    assert_eq!(2, health.len());
+    let sector = health[0].as_ref().unwrap();
+    assert_eq!((0, dl_idx, p_idx), (sector.sector_number, sector.deadline, sector.partition));
+    assert_eq!(None, health[1]);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,55 @@
+use fil_actor_miner::{Method, SectorOnChainInfo, State};
+use fil_actors_runtime::test_utils::*;
+
+use bitfield::BitField;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+#[test]
+fn test_get_sector_infos_aligns_with_requested_sectors() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let sectors: Vec<SectorOnChainInfo> = [1, 3]
+        .iter()
+        .map(|&sector_number| SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            expiration: rt.policy.min_sector_expiration(rt.network_version),
+            ..Default::default()
+        })
+        .collect();
+    let mut st: State = rt.get_state().unwrap();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    rt.replace_state(&st);
+
+    let infos = h.get_sector_infos(&mut rt, &[3, 1, 2]);
+    assert_eq!(vec![Some(sectors[0].clone()), None, Some(sectors[1].clone())], infos);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_get_sector_infos_rejects_too_many_sectors() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.policy.addressed_sectors_max = 2;
+
+    rt.expect_validate_caller_any();
+    let sector_numbers: BitField = [1, 2, 3].iter().copied().collect();
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<fil_actor_miner::Actor>(
+            Method::GetSectorInfos as u64,
+            &RawBytes::serialize(sector_numbers).unwrap(),
+        ),
+    );
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,115 @@
+use fil_actor_miner::{Actor, GetSectorsExpiringInRangeParams, Method, SectorOnChainInfo, State};
+use fil_actors_runtime::test_utils::*;
+
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+mod util;
+
+#[test]
+fn test_sectors_expiring_in_range() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let first_expiration = rt.policy.min_sector_expiration(rt.network_version);
+    let second_expiration = first_expiration + 10 * rt.policy.wpost_proving_period;
+    let mut st: State = rt.get_state().unwrap();
+    let sectors: Vec<SectorOnChainInfo> = [(1, first_expiration), (2, second_expiration)]
+        .iter()
+        .map(|&(sector_number, expiration)| SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            expiration,
+            ..Default::default()
+        })
+        .collect();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        sectors,
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+
+    let expiring = |rt: &mut MockRuntime, from, to| {
+        let page = h.get_sectors_expiring_in_range(rt, from, to, 0);
+        assert_eq!(None, page.next);
+        page.items
+    };
+    assert!(expiring(&mut rt, 0, first_expiration - 1).is_empty());
+    assert_eq!(vec![1], expiring(&mut rt, first_expiration, first_expiration));
+    assert_eq!(vec![2], expiring(&mut rt, first_expiration + 1, second_expiration));
+    assert_eq!(vec![1, 2], expiring(&mut rt, 0, second_expiration));
+    assert_eq!(vec![1, 2], expiring(&mut rt, 0, ChainEpoch::MAX));
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_sectors_expiring_rejects_inverted_range() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    rt.expect_validate_caller_any();
+    let params = GetSectorsExpiringInRangeParams { from_epoch: 10, to_epoch: 9, start_sector: 0 };
+    expect_abort(
+        ExitCode::ErrIllegalArgument,
+        rt.call::<Actor>(
+            Method::GetSectorsExpiringInRange as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.reset();
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_sectors_expiring_are_listed_a_page_at_a_time() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let expiration = rt.policy.min_sector_expiration(rt.network_version);
+    let mut st: State = rt.get_state().unwrap();
+    let sectors: Vec<SectorOnChainInfo> = (1..=3)
+        .map(|sector_number| SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            expiration,
+            ..Default::default()
+        })
+        .collect();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        sectors,
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+    rt.policy.addressed_sectors_max = 2;
+
+    let first = h.get_sectors_expiring_in_range(&mut rt, 0, expiration, 0);
+    assert_eq!(vec![1, 2], first.items);
+    assert_eq!(Some(3), first.next);
+
+    let second = h.get_sectors_expiring_in_range(&mut rt, 0, expiration, first.next.unwrap());
+    assert_eq!(vec![3], second.items);
+    assert_eq!(None, second.next);
+
+    util::check_state_invariants(&rt);
+}
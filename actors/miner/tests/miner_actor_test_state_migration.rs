@@ -0,0 +1,118 @@
+use fil_actor_miner::{
+    migrate_state, MinerInfo, PriorMinerInfo, PriorState, SectorPreCommitInfo,
+    SectorPreCommitOnChainInfo, State,
+};
+use fil_actors_runtime::test_utils::*;
+
+use cid::multihash::Code;
+use cid::Cid;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::blockstore::CborStore;
+use fvm_shared::clock::EPOCH_UNDEFINED;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::{from_slice, to_vec};
+
+mod util;
+
+#[test]
+fn test_migrate_state_keeps_fields_and_starts_added_fields_empty() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    let precommits = (0..2)
+        .map(|sector_number| SectorPreCommitOnChainInfo {
+            info: SectorPreCommitInfo {
+                seal_proof: h.seal_proof_type,
+                sector_number,
+                sealed_cid: Cid::default(),
+                seal_rand_epoch: 0,
+                deal_ids: vec![],
+                expiration: rt.policy.min_sector_expiration(rt.network_version) + 1,
+                replace_capacity: false,
+                replace_sector_deadline: 0,
+                replace_sector_partition: 0,
+                replace_sector_number: 0,
+            },
+            pre_commit_deposit: TokenAmount::from(0),
+            pre_commit_epoch: rt.epoch,
+            deal_weight: BigInt::from(0),
+            verified_deal_weight: BigInt::from(0),
+        })
+        .collect();
+    st.put_precommitted_sectors(&rt.store, precommits).unwrap();
+
+    // Miner info in the prior layout, with an owner change proposed.
+    let info = st.get_info(&rt.store).unwrap();
+    let prior_info = PriorMinerInfo {
+        owner: info.owner,
+        worker: info.worker,
+        control_addresses: info.control_addresses.clone(),
+        pending_worker_key: None,
+        peer_id: info.peer_id.clone(),
+        multi_address: info.multi_address.clone(),
+        window_post_proof_type: info.window_post_proof_type,
+        sector_size: info.sector_size,
+        window_post_partition_sectors: info.window_post_partition_sectors,
+        consensus_fault_elapsed: info.consensus_fault_elapsed,
+        pending_owner_address: Some(Address::new_id(999)),
+    };
+    let prior_info_cid = rt.store.put_cbor(&prior_info, Code::Blake2b256).unwrap();
+    assert!(rt.store.get_cbor::<MinerInfo>(&prior_info_cid).is_err());
+
+    let prior = PriorState {
+        info: prior_info_cid,
+        pre_commit_deposits: TokenAmount::from(1),
+        locked_funds: TokenAmount::from(2),
+        vesting_funds: st.vesting_funds,
+        fee_debt: TokenAmount::from(3),
+        initial_pledge: TokenAmount::from(4),
+        pre_committed_sectors: st.pre_committed_sectors,
+        pre_committed_sectors_cleanup: st.pre_committed_sectors_cleanup,
+        allocated_sectors: st.allocated_sectors,
+        sectors: st.sectors,
+        proving_period_start: st.proving_period_start,
+        current_deadline: st.current_deadline,
+        deadlines: st.deadlines,
+        early_terminations: st.early_terminations.clone(),
+        deadline_cron_active: true,
+    };
+
+    // State in the prior layout does not decode as the current state.
+    let encoded = to_vec(&prior).unwrap();
+    assert!(from_slice::<State>(&encoded).is_err());
+
+    let mut migrated = migrate_state(&rt.store, from_slice(&encoded).unwrap()).unwrap();
+    let migrated_info = migrated.get_info(&rt.store).unwrap();
+    assert_eq!(info.owner, migrated_info.owner);
+    assert_eq!(info.worker, migrated_info.worker);
+    assert_eq!(info.control_addresses, migrated_info.control_addresses);
+    assert_eq!(info.peer_id, migrated_info.peer_id);
+    assert_eq!(info.sector_size, migrated_info.sector_size);
+    assert_eq!(info.window_post_partition_sectors, migrated_info.window_post_partition_sectors);
+    // The pending owner proposal carries over without an expiration.
+    assert_eq!(Some(Address::new_id(999)), migrated_info.pending_owner_address);
+    assert_eq!(None, migrated_info.pending_owner_expiration);
+    // No emergency worker change is on record, so the first one is not rate limited.
+    assert_eq!(EPOCH_UNDEFINED, migrated_info.last_emergency_worker_change);
+    assert_eq!(TokenAmount::from(1), migrated.pre_commit_deposits);
+    assert_eq!(TokenAmount::from(2), migrated.locked_funds);
+    assert_eq!(TokenAmount::from(3), migrated.fee_debt);
+    assert_eq!(TokenAmount::from(4), migrated.initial_pledge);
+    assert_eq!(st.allocated_sectors, migrated.allocated_sectors);
+    assert_eq!(st.pre_committed_sectors, migrated.pre_committed_sectors);
+    // The pre-commit count is recovered from the existing pre-commitments.
+    assert_eq!(2, migrated.pre_committed_sector_count);
+    assert_eq!(st.deadlines, migrated.deadlines);
+    assert!(migrated.deadline_cron_active);
+
+    assert!(migrated.load_reserved_sector_numbers(&rt.store).unwrap().is_empty());
+    assert_eq!(st.owed_dispute_rewards, migrated.owed_dispute_rewards);
+    assert_eq!(TokenAmount::from(0), migrated.total_owed_dispute_rewards);
+    assert!(migrated.load_recent_rewards(&rt.store).unwrap().is_empty());
+    let owed = migrated.take_owed_dispute_reward(&rt.store, &Address::new_id(1234)).unwrap();
+    assert_eq!(TokenAmount::from(0), owed);
+}
@@ -0,0 +1,266 @@
+use fil_actor_miner::{
+    ext, new_deadline_info, Method, SectorOnChainInfo, State, TerminateSectorsReturn,
+    TerminateWorstSectorsParams,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{ActorError, REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::reward::ThisEpochRewardReturn;
+
+mod util;
+
+/// Stores proven CC sectors with the given expected day rewards directly in state,
+/// returning the deadline they were all assigned to.
+fn add_proven_sectors(rt: &mut MockRuntime, h: &util::ActorHarness, rewards: &[u64]) -> u64 {
+    let mut st: State = rt.get_state().unwrap();
+    let sectors: Vec<SectorOnChainInfo> = rewards
+        .iter()
+        .enumerate()
+        .map(|(i, reward)| SectorOnChainInfo {
+            sector_number: i as u64,
+            seal_proof: h.seal_proof_type,
+            expiration: rt.policy.max_sector_expiration_extension(rt.network_version),
+            expected_day_reward: TokenAmount::from(*reward),
+            ..Default::default()
+        })
+        .collect();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        sectors,
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    let (dl_idx, p_idx) = st.find_sector(&rt.policy, &rt.store, 0).unwrap();
+
+    let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+    let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_idx).unwrap();
+    let mut partitions = deadline.partitions_amt(&rt.store).unwrap();
+    let mut partition = partitions.get(p_idx).unwrap().unwrap().clone();
+    partition.activate_unproven();
+    partitions.set(p_idx, partition).unwrap();
+    deadline.partitions = partitions.flush().unwrap();
+    deadlines.update_deadline(&rt.policy, &rt.store, dl_idx, &deadline).unwrap();
+    st.save_deadlines(&rt.store, deadlines).unwrap();
+    rt.replace_state(&st);
+
+    dl_idx
+}
+
+fn terminate_worst_sectors(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    deadline: u64,
+    max_sectors: u64,
+) -> Result<TerminateSectorsReturn, ActorError> {
+    terminate_worst_sectors_as(rt, h, h.worker, deadline, max_sectors)
+}
+
+fn terminate_worst_sectors_as(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    caller: Address,
+    deadline: u64,
+    max_sectors: u64,
+) -> Result<TerminateSectorsReturn, ActorError> {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.push(h.worker);
+    caller_addrs.push(h.owner);
+    rt.expect_validate_caller_addr(caller_addrs);
+    let params = TerminateWorstSectorsParams { deadline, max_sectors };
+    let ret = rt.call::<fil_actor_miner::Actor>(
+        Method::TerminateWorstSectors as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )?;
+    rt.verify();
+    Ok(ret.deserialize().unwrap())
+}
+
+#[test]
+fn test_terminates_lowest_reward_sectors_first() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let dl_idx = add_proven_sectors(&mut rt, &h, &[30, 10, 20]);
+
+    rt.expect_send(
+        *REWARD_ACTOR_ADDR,
+        ext::reward::THIS_EPOCH_REWARD_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ThisEpochRewardReturn {
+            this_epoch_reward_smoothed: h.epoch_reward_smooth.clone(),
+            this_epoch_baseline_power: h.baseline_power.clone(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::CURRENT_TOTAL_POWER_METHOD,
+        RawBytes::default(),
+        TokenAmount::from(0),
+        RawBytes::serialize(ext::power::CurrentTotalPowerReturn {
+            raw_byte_power: h.baseline_power.clone(),
+            quality_adj_power: h.baseline_power.clone(),
+            pledge_collateral: TokenAmount::from(0),
+            quality_adj_power_smoothed: h.epoch_qa_power_smooth.clone(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    let removed = BigInt::from(2 * h.sector_size as u64);
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_CLAIMED_POWER_METHOD,
+        RawBytes::serialize(ext::power::UpdateClaimedPowerParams {
+            raw_byte_delta: -removed.clone(),
+            quality_adjusted_delta: -removed,
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+
+    let ret = terminate_worst_sectors(&mut rt, &h, dl_idx, 2).unwrap();
+    assert!(ret.done);
+
+    // Sectors 1 and 2 have the lowest expected rewards; sector 0 survives.
+    let st: State = rt.get_state().unwrap();
+    let deadlines = st.load_deadlines(&rt.store).unwrap();
+    let deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_idx).unwrap();
+    let partition = deadline.load_partition(&rt.store, 0).unwrap();
+    assert_eq!(vec![1, 2], partition.terminated.iter().collect::<Vec<_>>());
+    assert_eq!(vec![0], partition.live_sectors().iter().collect::<Vec<_>>());
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_terminate_worst_sectors_respects_immutable_deadline() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let dl_idx = add_proven_sectors(&mut rt, &h, &[10]);
+
+    // Move to the epoch at which the sectors' deadline is being proven.
+    let st: State = rt.get_state().unwrap();
+    let period_start = st.current_proving_period_start(&rt.policy, rt.epoch);
+    rt.epoch = new_deadline_info(&rt.policy, period_start, dl_idx, rt.epoch).open;
+
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "immutable deadline",
+        terminate_worst_sectors(&mut rt, &h, dl_idx, 1),
+    );
+    rt.reset();
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_terminate_worst_sectors_rejects_empty_deadline() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "no live sectors",
+        terminate_worst_sectors(&mut rt, &h, 5, 1),
+    );
+    rt.reset();
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_terminate_worst_sectors_rejects_unauthorized_caller() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // The caller is checked before the parameters or the deadline are looked at.
+    expect_abort(
+        ExitCode::SysErrForbidden,
+        terminate_worst_sectors_as(&mut rt, &h, Address::new_id(1234), 5, 0),
+    );
+    rt.reset();
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_terminate_worst_sectors_scans_only_addressable_partitions() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // Sectors 0 and 1 fill partition 0 of a mutable deadline, and the least profitable
+    // sector 2 is alone in partition 1.
+    let dl_idx = 5;
+    let mut st: State = rt.get_state().unwrap();
+    let sectors: Vec<SectorOnChainInfo> = [30, 40, 10]
+        .iter()
+        .enumerate()
+        .map(|(i, reward)| SectorOnChainInfo {
+            sector_number: i as u64,
+            seal_proof: h.seal_proof_type,
+            expiration: rt.policy.max_sector_expiration_extension(rt.network_version),
+            expected_day_reward: TokenAmount::from(*reward),
+            ..Default::default()
+        })
+        .collect();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+    let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_idx).unwrap();
+    let quant = st.quant_spec_for_deadline(&rt.policy, dl_idx);
+    deadline.add_sectors(&rt.store, 2, true, &sectors, h.sector_size, quant).unwrap();
+    deadlines.update_deadline(&rt.policy, &rt.store, dl_idx, &deadline).unwrap();
+    st.save_deadlines(&rt.store, deadlines).unwrap();
+    rt.replace_state(&st);
+
+    // Only partition 0 is scanned, so its least profitable sector is terminated.
+    rt.policy.addressed_partitions_max = 1;
+    h.expect_query_network_info(&mut rt);
+    let removed = BigInt::from(h.sector_size as u64);
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_CLAIMED_POWER_METHOD,
+        RawBytes::serialize(ext::power::UpdateClaimedPowerParams {
+            raw_byte_delta: -removed.clone(),
+            quality_adjusted_delta: -removed,
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    let ret = terminate_worst_sectors(&mut rt, &h, dl_idx, 1).unwrap();
+    assert!(ret.done);
+
+    let st: State = rt.get_state().unwrap();
+    let deadlines = st.load_deadlines(&rt.store).unwrap();
+    let deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_idx).unwrap();
+    let partition = deadline.load_partition(&rt.store, 0).unwrap();
+    assert_eq!(vec![0], partition.terminated.iter().collect::<Vec<_>>());
+    let partition = deadline.load_partition(&rt.store, 1).unwrap();
+    assert_eq!(vec![2], partition.live_sectors().iter().collect::<Vec<_>>());
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,144 @@
+use fil_actor_miner::{
+    expected_reward_for_power, initial_pledge_for_power, pledge_penalty_for_termination,
+    qa_power_for_sector, termination_penalty, SectorOnChainInfo, INITIAL_PLEDGE_PROJECTION_PERIOD,
+};
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::runtime::Policy;
+
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::sector::{RegisteredSealProof, SectorSize};
+use fvm_shared::smooth::FilterEstimate;
+
+const SECTOR_SIZE: SectorSize = SectorSize::_32GiB;
+
+fn sector(day_reward: TokenAmount, initial_pledge: TokenAmount) -> SectorOnChainInfo {
+    SectorOnChainInfo {
+        seal_proof: RegisteredSealProof::StackedDRG32GiBV1,
+        expected_storage_pledge: &day_reward * 20,
+        expected_day_reward: day_reward,
+        initial_pledge,
+        ..Default::default()
+    }
+}
+
+fn estimates() -> (FilterEstimate, FilterEstimate) {
+    (
+        FilterEstimate::new(BigInt::from(10u64.pow(19)), BigInt::from(0)),
+        FilterEstimate::new(BigInt::from(1u64 << 50), BigInt::from(0)),
+    )
+}
+
+#[test]
+fn test_termination_penalty_sums_sector_penalties() {
+    let policy = Policy::default();
+    let (reward, power) = estimates();
+    let epoch = 10 * EPOCHS_IN_DAY;
+    let sectors = vec![
+        sector(TokenAmount::from(1000), TokenAmount::from(10u64.pow(9))),
+        sector(TokenAmount::from(3000), TokenAmount::from(10u64.pow(9))),
+    ];
+
+    let expected: TokenAmount = sectors
+        .iter()
+        .map(|s| {
+            pledge_penalty_for_termination(
+                &s.expected_day_reward,
+                epoch - s.activation,
+                &s.expected_storage_pledge,
+                &power,
+                &qa_power_for_sector(SECTOR_SIZE, s),
+                &reward,
+                &s.replaced_day_reward,
+                s.replaced_sector_age,
+            )
+        })
+        .sum();
+    assert_eq!(
+        expected,
+        termination_penalty(&policy, SECTOR_SIZE, epoch, &reward, &power, &sectors)
+    );
+}
+
+#[test]
+fn test_termination_penalty_is_capped_by_pledge() {
+    let policy = Policy { termination_penalty_pledge_multiple: 2, ..Default::default() };
+    let (reward, power) = estimates();
+
+    // An enormous day reward and sector age would otherwise penalize far more than was pledged.
+    let huge = BigInt::from(10u64.pow(18)) * BigInt::from(10u64.pow(18));
+    let sectors: Vec<_> = (0..1000).map(|_| sector(huge.clone(), TokenAmount::from(100))).collect();
+    let penalty =
+        termination_penalty(&policy, SECTOR_SIZE, 1000 * EPOCHS_IN_DAY, &reward, &power, &sectors);
+    assert_eq!(TokenAmount::from(1000 * 100 * 3), penalty);
+}
+
+/// Returns a sector without deals activated at the epoch, with the rewards and pledge the
+/// miner actor computes from the network estimates at activation.
+fn activated_sector(
+    activation: ChainEpoch,
+    reward: &FilterEstimate,
+    power: &FilterEstimate,
+) -> SectorOnChainInfo {
+    let qa_power = BigInt::from(SECTOR_SIZE as u64);
+    let circulating_supply = BigInt::from(10u64.pow(18)) * 300_000_000;
+    SectorOnChainInfo {
+        seal_proof: RegisteredSealProof::StackedDRG32GiBV1,
+        activation,
+        expected_day_reward: expected_reward_for_power(reward, power, &qa_power, EPOCHS_IN_DAY),
+        expected_storage_pledge: expected_reward_for_power(
+            reward,
+            power,
+            &qa_power,
+            INITIAL_PLEDGE_PROJECTION_PERIOD,
+        ),
+        initial_pledge: initial_pledge_for_power(
+            &qa_power,
+            &power.estimate(),
+            reward,
+            power,
+            &circulating_supply,
+        ),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_default_cap_does_not_bind_for_realistic_sectors() {
+    let policy = Policy::default();
+    // 20 FIL per epoch over 10 EiB of network power.
+    let reward = FilterEstimate::new(BigInt::from(10u64.pow(18)) * 20, BigInt::from(0));
+    let power = FilterEstimate::new(BigInt::from(10u64 << 60), BigInt::from(0));
+
+    // A sector past the lifetime cap, and one upgraded from a sector activated when rewards
+    // per unit of power were four times higher. The upgrade keeps the higher pledge.
+    let old = activated_sector(0, &reward, &power);
+    let mut upgraded = activated_sector(300 * EPOCHS_IN_DAY, &reward, &power);
+    let replaced = activated_sector(
+        0,
+        &FilterEstimate::new(BigInt::from(10u64.pow(18)) * 80, BigInt::from(0)),
+        &power,
+    );
+    upgraded.replaced_day_reward = replaced.expected_day_reward;
+    upgraded.replaced_sector_age = 300 * EPOCHS_IN_DAY;
+    upgraded.initial_pledge = std::cmp::max(upgraded.initial_pledge, replaced.initial_pledge);
+    let sectors = vec![old, upgraded];
+
+    // Terminate long after activation, at current estimates both lower and ten times higher
+    // than at activation.
+    let epoch = 400 * EPOCHS_IN_DAY;
+    for current_reward in vec![
+        FilterEstimate::new(BigInt::from(10u64.pow(18)) * 5, BigInt::from(0)),
+        FilterEstimate::new(BigInt::from(10u64.pow(18)) * 200, BigInt::from(0)),
+    ] {
+        let uncapped =
+            Policy { termination_penalty_pledge_multiple: u64::MAX, ..Default::default() };
+        let expected =
+            termination_penalty(&uncapped, SECTOR_SIZE, epoch, &current_reward, &power, &sectors);
+        assert_eq!(
+            expected,
+            termination_penalty(&policy, SECTOR_SIZE, epoch, &current_reward, &power, &sectors)
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use fil_actor_miner::{SectorOnChainInfo, State};
+use fil_actors_runtime::test_utils::*;
+
+use cid::Cid;
+use fvm_shared::sector::SectorNumber;
+
+mod util;
+
+/// Adds live sectors with the given numbers to the miner's deadlines, with a sector key for
+/// those listed as upgraded.
+fn add_sectors(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    sector_numbers: &[SectorNumber],
+    upgraded: &[SectorNumber],
+) {
+    let mut st: State = rt.get_state().unwrap();
+    let sectors: Vec<SectorOnChainInfo> = sector_numbers
+        .iter()
+        .map(|&sector_number| SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            expiration: rt.policy.min_sector_expiration(rt.network_version),
+            sector_key_cid: if upgraded.contains(&sector_number) {
+                Some(Cid::default())
+            } else {
+                None
+            },
+            ..Default::default()
+        })
+        .collect();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+    st.assign_sectors_to_deadlines(
+        &rt.policy,
+        &rt.store,
+        rt.epoch,
+        sectors,
+        h.partition_size,
+        h.sector_size,
+    )
+    .unwrap();
+    rt.replace_state(&st);
+}
+
+#[test]
+fn test_no_upgraded_sectors_on_new_miner() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let ret = h.get_upgraded_sectors(&mut rt, 0);
+    assert!(ret.items.is_empty());
+    assert_eq!(None, ret.next);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_upgraded_sectors_have_sector_key() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    add_sectors(&mut rt, &h, &[1, 2, 3], &[1, 3]);
+
+    let ret = h.get_upgraded_sectors(&mut rt, 0);
+    assert_eq!(vec![1, 3], ret.items);
+    assert_eq!(None, ret.next);
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_upgraded_sectors_are_listed_a_page_at_a_time() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    add_sectors(&mut rt, &h, &[1, 2, 3, 4, 5], &[1, 3, 4, 5]);
+    rt.policy.addressed_sectors_max = 2;
+
+    // Each page loads two sectors, whether or not they were upgraded.
+    let first = h.get_upgraded_sectors(&mut rt, 0);
+    assert_eq!(vec![1], first.items);
+    assert_eq!(Some(3), first.next);
+
+    let second = h.get_upgraded_sectors(&mut rt, first.next.unwrap());
+    assert_eq!(vec![3, 4], second.items);
+    assert_eq!(Some(5), second.next);
+
+    let third = h.get_upgraded_sectors(&mut rt, second.next.unwrap());
+    assert_eq!(vec![5], third.items);
+    assert_eq!(None, third.next);
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,108 @@
+use fil_actor_miner::{Method, State, VestSpec};
+use fil_actors_runtime::test_utils::*;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+
+mod util;
+
+const SHORT_VESTING_SPEC: VestSpec =
+    VestSpec { initial_delay: 0, vest_period: 10, step_duration: 1, quantization: 1 };
+
+#[test]
+fn test_unlock_vested_funds_under_short_schedule() {
+    let mut rt = MockRuntime::default();
+    rt.policy.reward_vesting_spec = SHORT_VESTING_SPEC;
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    let newly_vested = st
+        .add_locked_funds(&rt.store, 0, &TokenAmount::from(100), &rt.policy.reward_vesting_spec)
+        .unwrap();
+    assert_eq!(TokenAmount::from(0), newly_vested);
+    assert_eq!(TokenAmount::from(100), st.locked_funds);
+
+    // Funds vest in equal steps each epoch, so those scheduled before epoch 5 are unlocked.
+    let unlocked = st.unlock_vested_funds(&rt.store, 5).unwrap();
+    assert_eq!(TokenAmount::from(40), unlocked);
+    assert_eq!(TokenAmount::from(60), st.locked_funds);
+
+    // Nothing further unlocks within the same epoch.
+    let unlocked = st.unlock_vested_funds(&rt.store, 5).unwrap();
+    assert_eq!(TokenAmount::from(0), unlocked);
+
+    // Everything is unlocked once the vesting period has elapsed.
+    let unlocked = st.unlock_vested_funds(&rt.store, 11).unwrap();
+    assert_eq!(TokenAmount::from(60), unlocked);
+    assert_eq!(TokenAmount::from(0), st.locked_funds);
+}
+
+#[test]
+fn test_initial_delay_defers_unlocking() {
+    let mut rt = MockRuntime::default();
+    rt.policy.reward_vesting_spec = VestSpec { initial_delay: 20, ..SHORT_VESTING_SPEC };
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let mut st: State = rt.get_state().unwrap();
+    st.add_locked_funds(&rt.store, 0, &TokenAmount::from(100), &rt.policy.reward_vesting_spec)
+        .unwrap();
+
+    let unlocked = st.unlock_vested_funds(&rt.store, 20).unwrap();
+    assert_eq!(TokenAmount::from(0), unlocked);
+
+    let unlocked = st.unlock_vested_funds(&rt.store, 31).unwrap();
+    assert_eq!(TokenAmount::from(100), unlocked);
+    assert_eq!(TokenAmount::from(0), st.locked_funds);
+}
+
+#[test]
+fn test_default_policy_uses_reward_vesting_schedule() {
+    let rt = MockRuntime::default();
+    let spec = &rt.policy.reward_vesting_spec;
+
+    assert_eq!(0, spec.initial_delay);
+    assert_eq!(180 * fil_actors_runtime::network::EPOCHS_IN_DAY, spec.vest_period);
+}
+
+#[test]
+fn test_compact_vesting_conserves_locked_funds() {
+    let mut rt = MockRuntime::default();
+    rt.policy.reward_vesting_spec = SHORT_VESTING_SPEC;
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    // Overlapping schedules, including a reward too small to vest in every step.
+    let mut st: State = rt.get_state().unwrap();
+    for (epoch, amount) in [(0, 100), (2, 3), (4, 50)] {
+        st.add_locked_funds(&rt.store, epoch, &TokenAmount::from(amount), &SHORT_VESTING_SPEC)
+            .unwrap();
+    }
+    rt.replace_state(&st);
+    let locked_before = st.locked_funds.clone();
+    let funds_before = st.load_vesting_funds(&rt.store).unwrap().funds;
+
+    let epoch = 6;
+    rt.epoch = epoch;
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.extend_from_slice(&[h.worker, h.owner]);
+    rt.expect_validate_caller_addr(caller_addrs);
+    rt.call::<fil_actor_miner::Actor>(Method::CompactVesting as u64, &RawBytes::default()).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let funds = st.load_vesting_funds(&rt.store).unwrap().funds;
+    let vested: TokenAmount =
+        funds_before.iter().filter(|f| f.epoch < epoch).map(|f| f.amount.clone()).sum();
+    let unvested: TokenAmount = funds.iter().map(|f| f.amount.clone()).sum();
+
+    // Only vested funds are released, and the table still accounts for all locked funds.
+    assert_eq!(locked_before, &vested + &unvested);
+    assert_eq!(st.locked_funds, unvested);
+    assert!(funds.iter().all(|f| f.epoch >= epoch && f.amount > TokenAmount::from(0)));
+    assert!(funds.windows(2).all(|w| w[0].epoch < w[1].epoch));
+}
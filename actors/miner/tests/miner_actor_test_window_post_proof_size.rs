@@ -0,0 +1,116 @@
+use fil_actor_miner::{
+    Method, PoStPartition, PoStProof, SectorOnChainInfo, State, SubmitWindowedPoStParams,
+};
+use fil_actors_runtime::test_utils::*;
+
+use bitfield::BitField;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::randomness::Randomness;
+
+mod util;
+
+/// Adds a sector to deadline 0, so that proofs submitted for it are checked.
+fn add_sector(rt: &mut MockRuntime, h: &util::ActorHarness) {
+    let mut st: State = rt.get_state().unwrap();
+    let sector = SectorOnChainInfo {
+        sector_number: 0,
+        seal_proof: h.seal_proof_type,
+        expiration: rt.policy.max_sector_expiration_extension(rt.network_version),
+        ..Default::default()
+    };
+    st.put_sectors(&rt.store, vec![sector.clone()]).unwrap();
+
+    let quant = st.quant_spec_for_deadline(&rt.policy, 0);
+    let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+    let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, 0).unwrap();
+    deadline
+        .add_sectors(&rt.store, h.partition_size, false, &[sector], h.sector_size, quant)
+        .unwrap();
+    deadlines.update_deadline(&rt.policy, &rt.store, 0, &deadline).unwrap();
+    st.save_deadlines(&rt.store, deadlines).unwrap();
+    rt.replace_state(&st);
+}
+
+fn submit_post_with_proof_size(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    partitions: u64,
+    proof_size: usize,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    let params = SubmitWindowedPoStParams {
+        deadline: 0,
+        partitions: (0..partitions)
+            .map(|index| PoStPartition { index, skipped: BitField::new().into() })
+            .collect(),
+        proofs: vec![PoStProof {
+            post_proof: h.window_post_proof_type,
+            proof_bytes: vec![0; proof_size],
+        }],
+        chain_commit_epoch: rt.epoch - 1,
+        chain_commit_rand: Randomness(vec![]),
+    };
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.extend_from_slice(&[h.worker, h.owner]);
+    rt.expect_validate_caller_addr(caller_addrs);
+    rt.call::<fil_actor_miner::Actor>(
+        Method::SubmitWindowedPoSt as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+}
+
+#[test]
+fn test_rejects_proof_shorter_than_expected() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    add_sector(&mut rt, &h);
+
+    let size = h.window_post_proof_type.proof_size().unwrap();
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "expected proof of",
+        submit_post_with_proof_size(&mut rt, &h, 2, 2 * size - 1),
+    );
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_rejects_proof_concentrated_in_one_partition() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    add_sector(&mut rt, &h);
+
+    // Two partitions' worth of bytes submitted for a single partition.
+    let size = h.window_post_proof_type.proof_size().unwrap();
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "expected proof of",
+        submit_post_with_proof_size(&mut rt, &h, 1, 2 * size),
+    );
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_rejects_proof_for_empty_deadline_before_checking_its_size() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+
+    let size = h.window_post_proof_type.proof_size().unwrap();
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "deadline has no sectors to prove",
+        submit_post_with_proof_size(&mut rt, &h, 2, 2 * size - 1),
+    );
+
+    util::check_state_invariants(&rt);
+}
@@ -0,0 +1,137 @@
+use fil_actor_miner::{
+    ext, pledge_penalty_for_invalid_windowpost, Method, PoStPartition, PoStProof,
+    RecoveryDeclaration, State, SubmitWindowedPoStParams, SubmitWindowedPoStWithRecoveriesParams,
+    BASE_REWARD_FOR_DISPUTED_WINDOW_POST,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{BURNT_FUNDS_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+
+use bitfield::BitField;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::crypto::randomness::DomainSeparationTag;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+use fvm_shared::randomness::Randomness;
+use fvm_shared::sector::RegisteredSealProof;
+use fvm_shared::METHOD_SEND;
+
+mod util;
+
+// Sector 0 is sealed with a proof whose window PoSt type differs from the miner's.
+const SEAL_PROOFS: [RegisteredSealProof; 2] =
+    [RegisteredSealProof::StackedDRG64GiBV1, RegisteredSealProof::StackedDRG32GiBV1];
+
+#[test]
+fn test_submit_rejects_proof_over_sector_of_other_proof_type() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.epoch = 10;
+    let dl_info = h.add_proven_sectors(&mut rt, &SEAL_PROOFS);
+    h.record_faults(&mut rt, &dl_info, &[0]);
+
+    // Recovering sector 0 requires verifying the proof, which fails without a verify syscall.
+    let params = SubmitWindowedPoStWithRecoveriesParams {
+        post: SubmitWindowedPoStParams {
+            deadline: dl_info.index,
+            partitions: vec![PoStPartition { index: 0, skipped: BitField::new().into() }],
+            proofs: vec![PoStProof {
+                post_proof: h.window_post_proof_type,
+                proof_bytes: vec![0; h.window_post_proof_type.proof_size().unwrap()],
+            }],
+            chain_commit_epoch: rt.epoch - 1,
+            chain_commit_rand: Randomness(vec![1; 32]),
+        },
+        recoveries: vec![RecoveryDeclaration {
+            deadline: dl_info.index,
+            partition: 0,
+            sectors: [0].iter().copied().collect::<BitField>().into(),
+        }],
+    };
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.extend_from_slice(&[h.worker, h.owner]);
+    rt.expect_validate_caller_addr(caller_addrs);
+    rt.expect_get_randomness_from_tickets(
+        DomainSeparationTag::PoStChainCommit,
+        rt.epoch - 1,
+        vec![],
+        Randomness(vec![1; 32]),
+    );
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "window post failed",
+        rt.call::<fil_actor_miner::Actor>(
+            Method::SubmitWindowedPoStWithRecoveries as u64,
+            &RawBytes::serialize(params).unwrap(),
+        ),
+    );
+    rt.verify();
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_dispute_succeeds_for_proof_over_sector_of_other_proof_type() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    rt.set_balance(TokenAmount::from(10u64.pow(18)) * 1_000_000);
+    rt.epoch = 10;
+    let dl_info = h.add_proven_sectors(&mut rt, &SEAL_PROOFS);
+    h.submit_optimistic_post(&mut rt, &dl_info);
+    h.close_deadline_for_dispute(&mut rt, &dl_info);
+
+    let reporter = Address::new_id(1234);
+    let disputed_power = BigInt::from(h.sector_size as u64) * 2;
+    let penalty_base = pledge_penalty_for_invalid_windowpost(
+        &h.epoch_reward_smooth,
+        &h.epoch_qa_power_smooth,
+        &disputed_power,
+    );
+    h.expect_query_network_info(&mut rt);
+    // The proof is invalid without a verify syscall, so both sectors become faulty.
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_CLAIMED_POWER_METHOD,
+        RawBytes::serialize(ext::power::UpdateClaimedPowerParams {
+            raw_byte_delta: -disputed_power.clone(),
+            quality_adjusted_delta: -disputed_power,
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        reporter,
+        METHOD_SEND,
+        RawBytes::default(),
+        BASE_REWARD_FOR_DISPUTED_WINDOW_POST.clone(),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        RawBytes::default(),
+        penalty_base,
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    h.dispute_windowed_post(&mut rt, reporter, dl_info.index, 0).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let deadlines = st.load_deadlines(&rt.store).unwrap();
+    let deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_info.index).unwrap();
+    let partitions = deadline.partitions_amt(&rt.store).unwrap();
+    let partition = partitions.get(0).unwrap().unwrap();
+    assert_eq!([0, 1].iter().copied().collect::<BitField>(), partition.faults);
+
+    util::check_state_invariants(&rt);
+}
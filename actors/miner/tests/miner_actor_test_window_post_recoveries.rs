@@ -0,0 +1,277 @@
+use fil_actor_miner::{
+    ext, max_miner_power, DeadlineInfo, Method, PartitionSectorMap, PoStPartition, PoStProof,
+    RecoveryDeclaration, SectorOnChainInfo, Sectors, State, SubmitWindowedPoStParams,
+    SubmitWindowedPoStWithRecoveriesParams,
+};
+use fil_actors_runtime::runtime::Policy;
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::STORAGE_POWER_ACTOR_ADDR;
+
+use bitfield::BitField;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::crypto::randomness::DomainSeparationTag;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::encoding::{Cbor, RawBytes};
+use fvm_shared::error::ExitCode;
+use fvm_shared::randomness::Randomness;
+use fvm_shared::sector::{SectorInfo, SectorSize, WindowPoStVerifyInfo};
+use std::collections::HashSet;
+
+mod util;
+
+/// Adds proven sectors 0 and 1 to a single partition of the currently open deadline and marks
+/// sector 1 faulty, returning the deadline.
+fn setup_faulty_sector(rt: &mut MockRuntime, h: &util::ActorHarness) -> DeadlineInfo {
+    rt.epoch = 10;
+    let mut st: State = rt.get_state().unwrap();
+    let dl_info = st.deadline_info(&rt.policy, rt.epoch);
+    assert!(dl_info.is_open());
+
+    let expiration = rt.policy.max_sector_expiration_extension(rt.network_version);
+    let sectors: Vec<SectorOnChainInfo> = (0..2)
+        .map(|sector_number| SectorOnChainInfo {
+            sector_number,
+            seal_proof: h.seal_proof_type,
+            expiration,
+            ..Default::default()
+        })
+        .collect();
+    st.put_sectors(&rt.store, sectors.clone()).unwrap();
+
+    let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+    let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_info.index).unwrap();
+    deadline
+        .add_sectors(
+            &rt.store,
+            h.partition_size,
+            true,
+            &sectors,
+            h.sector_size,
+            dl_info.quant_spec(),
+        )
+        .unwrap();
+    let sectors_arr = Sectors::load(&rt.store, &st.sectors).unwrap();
+    let mut faults = PartitionSectorMap::default();
+    faults.add_values(0, vec![1]).unwrap();
+    deadline
+        .record_faults(
+            &rt.store,
+            &sectors_arr,
+            h.sector_size,
+            dl_info.quant_spec(),
+            dl_info.last() + rt.policy.fault_max_age,
+            &mut faults,
+        )
+        .unwrap();
+    deadlines.update_deadline(&rt.policy, &rt.store, dl_info.index, &deadline).unwrap();
+    st.save_deadlines(&rt.store, deadlines).unwrap();
+    rt.replace_state(&st);
+    dl_info
+}
+
+/// Expects the randomness and verification of a valid proof over sectors 0 and 1.
+fn expect_verified_post(rt: &mut MockRuntime, h: &util::ActorHarness, dl_info: &DeadlineInfo) {
+    rt.expect_get_randomness_from_tickets(
+        DomainSeparationTag::PoStChainCommit,
+        rt.epoch - 1,
+        vec![],
+        Randomness(vec![1; 32]),
+    );
+    let post_rand = Randomness(vec![2; 32]);
+    rt.expect_get_randomness_from_beacon(
+        DomainSeparationTag::WindowedPoStChallengeSeed,
+        dl_info.challenge,
+        rt.receiver.marshal_cbor().unwrap(),
+        post_rand.clone(),
+    );
+    rt.expect_verify_post(
+        WindowPoStVerifyInfo {
+            randomness: post_rand,
+            proofs: vec![PoStProof {
+                post_proof: h.window_post_proof_type,
+                proof_bytes: vec![0; h.window_post_proof_type.proof_size().unwrap()],
+            }],
+            challenged_sectors: (0..2)
+                .map(|sector_number| SectorInfo {
+                    proof: h.seal_proof_type,
+                    sector_number,
+                    sealed_cid: Default::default(),
+                })
+                .collect(),
+            prover: 0,
+        },
+        ExitCode::Ok,
+    );
+}
+
+fn submit_post_with_recoveries(
+    rt: &mut MockRuntime,
+    h: &util::ActorHarness,
+    dl_info: &DeadlineInfo,
+    recoveries: Vec<RecoveryDeclaration>,
+) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+    let params = SubmitWindowedPoStWithRecoveriesParams {
+        post: SubmitWindowedPoStParams {
+            deadline: dl_info.index,
+            partitions: vec![PoStPartition { index: 0, skipped: BitField::new().into() }],
+            proofs: vec![PoStProof {
+                post_proof: h.window_post_proof_type,
+                proof_bytes: vec![0; h.window_post_proof_type.proof_size().unwrap()],
+            }],
+            chain_commit_epoch: rt.epoch - 1,
+            chain_commit_rand: Randomness(vec![1; 32]),
+        },
+        recoveries,
+    };
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, h.worker);
+    let mut caller_addrs = h.control_addrs.clone();
+    caller_addrs.extend_from_slice(&[h.worker, h.owner]);
+    rt.expect_validate_caller_addr(caller_addrs);
+    rt.call::<fil_actor_miner::Actor>(
+        Method::SubmitWindowedPoStWithRecoveries as u64,
+        &RawBytes::serialize(params).unwrap(),
+    )
+}
+
+#[test]
+fn test_recovery_declared_with_post_restores_power() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let dl_info = setup_faulty_sector(&mut rt, &h);
+
+    // Recovering power means the proof is verified immediately, over both sectors.
+    expect_verified_post(&mut rt, &h, &dl_info);
+    let sector_power = BigInt::from(h.sector_size as u64);
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_CLAIMED_POWER_METHOD,
+        RawBytes::serialize(ext::power::UpdateClaimedPowerParams {
+            raw_byte_delta: sector_power.clone(),
+            quality_adjusted_delta: sector_power,
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+
+    let recoveries = vec![RecoveryDeclaration {
+        deadline: dl_info.index,
+        partition: 0,
+        sectors: [1].iter().copied().collect::<BitField>().into(),
+    }];
+    submit_post_with_recoveries(&mut rt, &h, &dl_info, recoveries).unwrap();
+    rt.verify();
+
+    let st: State = rt.get_state().unwrap();
+    let deadlines = st.load_deadlines(&rt.store).unwrap();
+    let deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_info.index).unwrap();
+    let partitions = deadline.partitions_amt(&rt.store).unwrap();
+    let partition = partitions.get(0).unwrap().unwrap();
+    assert!(partition.faults.is_empty());
+    assert!(partition.recoveries.is_empty());
+    assert!(deadline.faulty_power.is_zero());
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_recovery_for_partition_not_proven_is_rejected() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let dl_info = setup_faulty_sector(&mut rt, &h);
+
+    let recoveries = vec![RecoveryDeclaration {
+        deadline: dl_info.index,
+        partition: 1,
+        sectors: [1].iter().copied().collect::<BitField>().into(),
+    }];
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalArgument,
+        "not being proven",
+        submit_post_with_recoveries(&mut rt, &h, &dl_info, recoveries),
+    );
+
+    let st: State = rt.get_state().unwrap();
+    let deadlines = st.load_deadlines(&rt.store).unwrap();
+    let deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_info.index).unwrap();
+    assert!(!deadline.faulty_power.is_zero());
+
+    util::check_state_invariants(&rt);
+}
+
+#[test]
+fn test_recovery_rejected_when_power_delta_exceeds_maximum() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let dl_info = setup_faulty_sector(&mut rt, &h);
+
+    // No miner may claim any power, so restoring the sector's power is an accounting error.
+    rt.policy.sectors_max = 0;
+    expect_verified_post(&mut rt, &h, &dl_info);
+    let recoveries = vec![RecoveryDeclaration {
+        deadline: dl_info.index,
+        partition: 0,
+        sectors: [1].iter().copied().collect::<BitField>().into(),
+    }];
+    expect_abort_contains_message(
+        ExitCode::ErrIllegalState,
+        "exceeds maximum miner power",
+        submit_post_with_recoveries(&mut rt, &h, &dl_info, recoveries),
+    );
+    rt.verify();
+}
+
+#[test]
+fn test_recovery_accepted_when_power_delta_equals_maximum() {
+    let mut rt = MockRuntime::default();
+    let h = util::ActorHarness::new(0);
+
+    h.construct_and_verify(&mut rt);
+    let dl_info = setup_faulty_sector(&mut rt, &h);
+
+    // A miner may claim at most one sector of the harness size, exactly the recovered power.
+    rt.policy.valid_post_proof_type = HashSet::from([h.window_post_proof_type]);
+    rt.policy.sectors_max = 1;
+    let sector_power = BigInt::from(h.sector_size as u64);
+    assert_eq!(sector_power, max_miner_power(&rt.policy).raw);
+
+    expect_verified_post(&mut rt, &h, &dl_info);
+    rt.expect_send(
+        *STORAGE_POWER_ACTOR_ADDR,
+        ext::power::UPDATE_CLAIMED_POWER_METHOD,
+        RawBytes::serialize(ext::power::UpdateClaimedPowerParams {
+            raw_byte_delta: sector_power.clone(),
+            quality_adjusted_delta: sector_power,
+        })
+        .unwrap(),
+        TokenAmount::from(0),
+        RawBytes::default(),
+        ExitCode::Ok,
+    );
+    let recoveries = vec![RecoveryDeclaration {
+        deadline: dl_info.index,
+        partition: 0,
+        sectors: [1].iter().copied().collect::<BitField>().into(),
+    }];
+    submit_post_with_recoveries(&mut rt, &h, &dl_info, recoveries).unwrap();
+    rt.verify();
+}
+
+#[test]
+fn test_max_miner_power_uses_largest_post_sector_size() {
+    let policy = Policy::default();
+    let max_power = max_miner_power(&policy);
+
+    let raw = BigInt::from(SectorSize::_64GiB as u64) * policy.sectors_max;
+    assert_eq!(raw, max_power.raw);
+    // Verified deals carry ten times the quality of committed capacity.
+    assert_eq!(raw * 10, max_power.qa);
+}
@@ -1,23 +1,40 @@
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::INIT_ACTOR_ADDR;
+use fil_actors_runtime::{INIT_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
 
 use fil_actor_account::Method as AccountMethod;
 use fil_actor_miner::{
-    Actor, ChangeMultiaddrsParams, ChangePeerIDParams, GetControlAddressesReturn, Method,
-    MinerConstructorParams as ConstructorParams, State,
+    ext, Actor, CanDeclareRecoveryParams, CanDeclareRecoveryReturn, ChangeMultiaddrsParams,
+    ChangeNetworkAddressesParams, ChangePeerIDParams, CheckSectorsProvenParams,
+    CheckSectorsProvenReturn, ConsensusFaultStatusReturn, CronEventPayload, DeadlineInfo,
+    DeadlineInfoAtParams, DeadlineInfoAtReturn, DeferredCronEventParams, DisputeWindowedPoStParams,
+    GetActiveDealsParams, GetActiveDealsReturn, GetControlAddressesReturn,
+    GetDeadlineExpirationScheduleParams, GetDeadlineExpirationScheduleReturn,
+    GetDeadlineLayoutReturn, GetDebtStatusReturn, GetEarlyTerminationsReturn, GetFaultStateReturn,
+    GetMinerInfoReturn, GetPendingChangesReturn, GetPledgeSummaryReturn, GetPreCommitInfoParams,
+    GetProvingPeriodInfoReturn, GetSectorCountsReturn, GetSectorHealthParams,
+    GetSectorHealthReturn, GetSectorInfosParams, GetSectorInfosReturn,
+    GetSectorsExpiringInRangeParams, GetSectorsExpiringInRangeReturn, GetUpgradedSectorsParams,
+    GetUpgradedSectorsReturn, Method, MinerConstructorParams as ConstructorParams,
+    NextAllocatableSectorNumberReturn, PartitionSectorMap, PoStPartition, PoStProof, SectorHealth,
+    SectorOnChainInfo, SectorPreCommitOnChainInfo, Sectors, State, SubmitWindowedPoStParams,
+    CRON_EVENT_PROVING_DEADLINE,
 };
 
 use fvm_shared::address::Address;
 use fvm_shared::bigint::BigInt;
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::randomness::DomainSeparationTag;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::encoding::{BytesDe, RawBytes};
 use fvm_shared::error::ExitCode;
+use fvm_shared::randomness::Randomness;
+use fvm_shared::reward::ThisEpochRewardReturn;
 use fvm_shared::sector::{
     RegisteredPoStProof, RegisteredSealProof, SectorNumber, SectorSize, StoragePower,
 };
 use fvm_shared::smooth::FilterEstimate;
 
+use bitfield::BitField;
 use rand::prelude::*;
 
 pub fn new_bls_addr(s: u8) -> Address {
@@ -191,6 +208,60 @@ impl ActorHarness {
         rt.verify();
     }
 
+    pub fn set_network_addresses(
+        self: &Self,
+        rt: &mut MockRuntime,
+        new_id: Vec<u8>,
+        new_multiaddrs: Vec<BytesDe>,
+    ) {
+        let params = ChangeNetworkAddressesParams {
+            new_id: new_id.clone(),
+            new_multi_addrs: new_multiaddrs.clone(),
+        };
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.worker);
+
+        let mut caller_addrs = self.control_addrs.clone();
+        caller_addrs.push(self.worker.clone());
+        caller_addrs.push(self.owner.clone());
+        rt.expect_validate_caller_addr(caller_addrs);
+
+        let result = rt
+            .call::<Actor>(
+                Method::ChangeNetworkAddresses as u64,
+                &RawBytes::serialize(params).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(result.bytes().len(), 0);
+        rt.verify();
+
+        let state = rt.get_state::<State>().unwrap();
+        let info = state.get_info(&rt.store).unwrap();
+
+        assert_eq!(new_id, info.peer_id);
+        assert_eq!(new_multiaddrs, info.multi_address);
+    }
+
+    pub fn set_network_addresses_fail(
+        self: &Self,
+        rt: &mut MockRuntime,
+        new_id: Vec<u8>,
+        new_multiaddrs: Vec<BytesDe>,
+    ) {
+        let params = ChangeNetworkAddressesParams { new_id, new_multi_addrs: new_multiaddrs };
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.worker);
+
+        let result = rt
+            .call::<Actor>(
+                Method::ChangeNetworkAddresses as u64,
+                &RawBytes::serialize(params).unwrap(),
+            )
+            .unwrap_err();
+        assert_eq!(result.exit_code(), ExitCode::ErrIllegalArgument);
+        rt.verify();
+    }
+
     pub fn get_control_addresses(
         self: &Self,
         rt: &mut MockRuntime,
@@ -204,6 +275,486 @@ impl ActorHarness {
         let value = result.deserialize::<GetControlAddressesReturn>().unwrap();
         (value.owner, value.worker, value.control_addresses)
     }
+
+    pub fn get_miner_info(self: &Self, rt: &mut MockRuntime) -> GetMinerInfoReturn {
+        rt.expect_validate_caller_any();
+
+        let result = rt.call::<Actor>(Method::GetMinerInfo as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<GetMinerInfoReturn>().unwrap()
+    }
+
+    pub fn get_pending_changes(self: &Self, rt: &mut MockRuntime) -> GetPendingChangesReturn {
+        rt.expect_validate_caller_any();
+
+        let result =
+            rt.call::<Actor>(Method::GetPendingChanges as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<GetPendingChangesReturn>().unwrap()
+    }
+
+    pub fn get_active_deals(
+        self: &Self,
+        rt: &mut MockRuntime,
+        start_sector: SectorNumber,
+    ) -> GetActiveDealsReturn {
+        let params = GetActiveDealsParams { start_sector };
+        rt.expect_validate_caller_any();
+
+        let result = rt
+            .call::<Actor>(Method::GetActiveDeals as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<GetActiveDealsReturn>().unwrap()
+    }
+
+    pub fn deadline_info_at(
+        self: &Self,
+        rt: &mut MockRuntime,
+        epoch: ChainEpoch,
+    ) -> DeadlineInfoAtReturn {
+        let params = DeadlineInfoAtParams { epoch };
+        rt.expect_validate_caller_any();
+
+        let result = rt
+            .call::<Actor>(Method::DeadlineInfoAt as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<DeadlineInfoAtReturn>().unwrap()
+    }
+
+    pub fn get_upgraded_sectors(
+        self: &Self,
+        rt: &mut MockRuntime,
+        start_sector: SectorNumber,
+    ) -> GetUpgradedSectorsReturn {
+        let params = GetUpgradedSectorsParams { start_sector };
+        rt.expect_validate_caller_any();
+
+        let result = rt
+            .call::<Actor>(Method::GetUpgradedSectors as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<GetUpgradedSectorsReturn>().unwrap()
+    }
+
+    pub fn get_early_terminations(self: &Self, rt: &mut MockRuntime) -> GetEarlyTerminationsReturn {
+        rt.expect_validate_caller_any();
+
+        let result =
+            rt.call::<Actor>(Method::GetEarlyTerminations as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<GetEarlyTerminationsReturn>().unwrap()
+    }
+
+    pub fn get_fault_state(self: &Self, rt: &mut MockRuntime) -> GetFaultStateReturn {
+        rt.expect_validate_caller_any();
+
+        let result = rt.call::<Actor>(Method::GetFaultState as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<GetFaultStateReturn>().unwrap()
+    }
+
+    pub fn get_deadline_expiration_schedule(
+        self: &Self,
+        rt: &mut MockRuntime,
+        deadline: u64,
+    ) -> GetDeadlineExpirationScheduleReturn {
+        rt.expect_validate_caller_any();
+
+        let params = GetDeadlineExpirationScheduleParams { deadline };
+        let result = rt
+            .call::<Actor>(
+                Method::GetDeadlineExpirationSchedule as u64,
+                &RawBytes::serialize(params).unwrap(),
+            )
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<GetDeadlineExpirationScheduleReturn>().unwrap()
+    }
+
+    pub fn consensus_fault_status(self: &Self, rt: &mut MockRuntime) -> ConsensusFaultStatusReturn {
+        rt.expect_validate_caller_any();
+
+        let result =
+            rt.call::<Actor>(Method::ConsensusFaultStatus as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<ConsensusFaultStatusReturn>().unwrap()
+    }
+
+    pub fn get_debt_status(self: &Self, rt: &mut MockRuntime) -> GetDebtStatusReturn {
+        rt.expect_validate_caller_any();
+
+        let result = rt.call::<Actor>(Method::GetDebtStatus as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<GetDebtStatusReturn>().unwrap()
+    }
+
+    pub fn get_pledge_summary(self: &Self, rt: &mut MockRuntime) -> GetPledgeSummaryReturn {
+        rt.expect_validate_caller_any();
+
+        let result =
+            rt.call::<Actor>(Method::GetPledgeSummary as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<GetPledgeSummaryReturn>().unwrap()
+    }
+
+    pub fn get_proving_period_info(
+        self: &Self,
+        rt: &mut MockRuntime,
+    ) -> GetProvingPeriodInfoReturn {
+        rt.expect_validate_caller_any();
+
+        let result =
+            rt.call::<Actor>(Method::GetProvingPeriodInfo as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<GetProvingPeriodInfoReturn>().unwrap()
+    }
+
+    pub fn get_deadline_layout(self: &Self, rt: &mut MockRuntime) -> GetDeadlineLayoutReturn {
+        rt.expect_validate_caller_any();
+
+        let result =
+            rt.call::<Actor>(Method::GetDeadlineLayout as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<GetDeadlineLayoutReturn>().unwrap()
+    }
+
+    pub fn get_sector_counts(self: &Self, rt: &mut MockRuntime) -> GetSectorCountsReturn {
+        rt.expect_validate_caller_any();
+
+        let result =
+            rt.call::<Actor>(Method::GetSectorCounts as u64, &RawBytes::default()).unwrap();
+        rt.verify();
+
+        result.deserialize::<GetSectorCountsReturn>().unwrap()
+    }
+
+    pub fn get_sectors_expiring_in_range(
+        self: &Self,
+        rt: &mut MockRuntime,
+        from_epoch: ChainEpoch,
+        to_epoch: ChainEpoch,
+        start_sector: SectorNumber,
+    ) -> GetSectorsExpiringInRangeReturn {
+        rt.expect_validate_caller_any();
+
+        let params = GetSectorsExpiringInRangeParams { from_epoch, to_epoch, start_sector };
+        let result = rt
+            .call::<Actor>(
+                Method::GetSectorsExpiringInRange as u64,
+                &RawBytes::serialize(params).unwrap(),
+            )
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<GetSectorsExpiringInRangeReturn>().unwrap()
+    }
+
+    pub fn can_declare_recovery(
+        self: &Self,
+        rt: &mut MockRuntime,
+        deadline: u64,
+    ) -> CanDeclareRecoveryReturn {
+        rt.expect_validate_caller_any();
+
+        let params = CanDeclareRecoveryParams { deadline };
+        let result = rt
+            .call::<Actor>(Method::CanDeclareRecovery as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<CanDeclareRecoveryReturn>().unwrap()
+    }
+
+    pub fn next_allocatable_sector_number(self: &Self, rt: &mut MockRuntime) -> SectorNumber {
+        rt.expect_validate_caller_any();
+
+        let result = rt
+            .call::<Actor>(Method::NextAllocatableSectorNumber as u64, &RawBytes::default())
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<NextAllocatableSectorNumberReturn>().unwrap().sector_number
+    }
+
+    pub fn check_sectors_proven(
+        self: &Self,
+        rt: &mut MockRuntime,
+        sector_numbers: &[u64],
+    ) -> (BitField, BitField) {
+        let params = CheckSectorsProvenParams {
+            sector_numbers: sector_numbers.iter().copied().collect::<BitField>().into(),
+        };
+        rt.expect_validate_caller_any();
+
+        let result = rt
+            .call::<Actor>(Method::CheckSectorsProven as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+
+        let value = result.deserialize::<CheckSectorsProvenReturn>().unwrap();
+        (value.proven, value.not_proven)
+    }
+
+    pub fn get_sector_infos(
+        self: &Self,
+        rt: &mut MockRuntime,
+        sector_numbers: &[u64],
+    ) -> Vec<Option<SectorOnChainInfo>> {
+        let params = GetSectorInfosParams {
+            sector_numbers: sector_numbers.iter().copied().collect::<BitField>().into(),
+        };
+        rt.expect_validate_caller_any();
+
+        let result = rt
+            .call::<Actor>(Method::GetSectorInfos as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<GetSectorInfosReturn>().unwrap().sectors
+    }
+
+    pub fn get_pre_commit_info(
+        self: &Self,
+        rt: &mut MockRuntime,
+        sector_number: SectorNumber,
+    ) -> SectorPreCommitOnChainInfo {
+        let params = GetPreCommitInfoParams { sector_number };
+        rt.expect_validate_caller_any();
+
+        let result = rt
+            .call::<Actor>(Method::GetPreCommitInfo as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<SectorPreCommitOnChainInfo>().unwrap()
+    }
+
+    pub fn get_sector_health(
+        self: &Self,
+        rt: &mut MockRuntime,
+        sector_numbers: &[u64],
+    ) -> Vec<Option<SectorHealth>> {
+        let params = GetSectorHealthParams {
+            sector_numbers: sector_numbers.iter().copied().collect::<BitField>().into(),
+        };
+        rt.expect_validate_caller_any();
+
+        let result = rt
+            .call::<Actor>(Method::GetSectorHealth as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+
+        result.deserialize::<GetSectorHealthReturn>().unwrap().sectors
+    }
+
+    /// Adds proven sectors, numbered from zero and sealed with the given proofs, to partition 0
+    /// of the currently open deadline, returning that deadline.
+    pub fn add_proven_sectors(
+        self: &Self,
+        rt: &mut MockRuntime,
+        seal_proofs: &[RegisteredSealProof],
+    ) -> DeadlineInfo {
+        let mut st: State = rt.get_state().unwrap();
+        let dl_info = st.deadline_info(&rt.policy, rt.epoch);
+        assert!(dl_info.is_open());
+
+        let expiration = rt.policy.max_sector_expiration_extension(rt.network_version);
+        let sectors: Vec<SectorOnChainInfo> = seal_proofs
+            .iter()
+            .enumerate()
+            .map(|(i, seal_proof)| SectorOnChainInfo {
+                sector_number: i as SectorNumber,
+                seal_proof: *seal_proof,
+                expiration,
+                ..Default::default()
+            })
+            .collect();
+        st.put_sectors(&rt.store, sectors.clone()).unwrap();
+
+        let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+        let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_info.index).unwrap();
+        deadline
+            .add_sectors(
+                &rt.store,
+                self.partition_size,
+                true,
+                &sectors,
+                self.sector_size,
+                dl_info.quant_spec(),
+            )
+            .unwrap();
+        deadlines.update_deadline(&rt.policy, &rt.store, dl_info.index, &deadline).unwrap();
+        st.save_deadlines(&rt.store, deadlines).unwrap();
+        rt.replace_state(&st);
+        dl_info
+    }
+
+    /// Marks sectors in partition 0 of the deadline faulty, as a declared or detected fault would.
+    pub fn record_faults(
+        self: &Self,
+        rt: &mut MockRuntime,
+        dl_info: &DeadlineInfo,
+        sector_numbers: &[SectorNumber],
+    ) {
+        let mut st: State = rt.get_state().unwrap();
+        let sectors = Sectors::load(&rt.store, &st.sectors).unwrap();
+        let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+        let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_info.index).unwrap();
+        let mut faults = PartitionSectorMap::default();
+        faults.add_values(0, sector_numbers.to_vec()).unwrap();
+        deadline
+            .record_faults(
+                &rt.store,
+                &sectors,
+                self.sector_size,
+                dl_info.quant_spec(),
+                dl_info.last() + rt.policy.fault_max_age,
+                &mut faults,
+            )
+            .unwrap();
+        deadlines.update_deadline(&rt.policy, &rt.store, dl_info.index, &deadline).unwrap();
+        st.save_deadlines(&rt.store, deadlines).unwrap();
+        rt.replace_state(&st);
+    }
+
+    /// Submits a Window PoSt for partition 0 of the open deadline which, recovering no power,
+    /// is accepted optimistically without verification.
+    pub fn submit_optimistic_post(self: &Self, rt: &mut MockRuntime, dl_info: &DeadlineInfo) {
+        let params = SubmitWindowedPoStParams {
+            deadline: dl_info.index,
+            partitions: vec![PoStPartition { index: 0, skipped: BitField::new().into() }],
+            proofs: vec![PoStProof {
+                post_proof: self.window_post_proof_type,
+                proof_bytes: vec![0; self.window_post_proof_type.proof_size().unwrap()],
+            }],
+            chain_commit_epoch: rt.epoch - 1,
+            chain_commit_rand: Randomness(vec![1; 32]),
+        };
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, self.worker);
+        let mut caller_addrs = self.control_addrs.clone();
+        caller_addrs.extend_from_slice(&[self.worker, self.owner]);
+        rt.expect_validate_caller_addr(caller_addrs);
+        rt.expect_get_randomness_from_tickets(
+            DomainSeparationTag::PoStChainCommit,
+            params.chain_commit_epoch,
+            vec![],
+            params.chain_commit_rand.clone(),
+        );
+        rt.call::<Actor>(Method::SubmitWindowedPoSt as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+    }
+
+    /// Ends the deadline's challenge window as cron would, snapshotting its proofs for dispute,
+    /// and moves to the first epoch after it.
+    pub fn close_deadline_for_dispute(self: &Self, rt: &mut MockRuntime, dl_info: &DeadlineInfo) {
+        let mut st: State = rt.get_state().unwrap();
+        let mut deadlines = st.load_deadlines(&rt.store).unwrap();
+        let mut deadline = deadlines.load_deadline(&rt.policy, &rt.store, dl_info.index).unwrap();
+        deadline
+            .process_deadline_end(
+                &rt.store,
+                dl_info.quant_spec(),
+                dl_info.last() + rt.policy.fault_max_age,
+                st.sectors,
+            )
+            .unwrap();
+        deadlines.update_deadline(&rt.policy, &rt.store, dl_info.index, &deadline).unwrap();
+        st.save_deadlines(&rt.store, deadlines).unwrap();
+        rt.replace_state(&st);
+        rt.epoch = dl_info.close;
+    }
+
+    /// Invokes the proving deadline cron as the power actor at the current epoch, expecting the
+    /// next deadline cron to be enrolled at an epoch. Any other sends the cron makes must be
+    /// expected beforehand.
+    pub fn on_deadline_cron(self: &Self, rt: &mut MockRuntime, next_cron_epoch: ChainEpoch) {
+        let payload =
+            RawBytes::serialize(CronEventPayload { event_type: CRON_EVENT_PROVING_DEADLINE })
+                .unwrap();
+        rt.expect_send(
+            *STORAGE_POWER_ACTOR_ADDR,
+            ext::power::ENROLL_CRON_EVENT_METHOD,
+            RawBytes::serialize(ext::power::EnrollCronEventParams {
+                event_epoch: next_cron_epoch,
+                payload: payload.clone(),
+            })
+            .unwrap(),
+            TokenAmount::from(0),
+            RawBytes::default(),
+            ExitCode::Ok,
+        );
+
+        let params = DeferredCronEventParams {
+            event_payload: payload.bytes().to_vec(),
+            reward_smoothed: self.epoch_reward_smooth.clone(),
+            quality_adj_power_smoothed: self.epoch_qa_power_smooth.clone(),
+        };
+        rt.set_caller(*POWER_ACTOR_CODE_ID, *STORAGE_POWER_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![*STORAGE_POWER_ACTOR_ADDR]);
+        rt.call::<Actor>(Method::OnDeferredCronEvent as u64, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
+    }
+
+    /// Expects the current epoch reward and total power queries, answered with harness values.
+    pub fn expect_query_network_info(self: &Self, rt: &mut MockRuntime) {
+        rt.expect_send(
+            *REWARD_ACTOR_ADDR,
+            ext::reward::THIS_EPOCH_REWARD_METHOD,
+            RawBytes::default(),
+            TokenAmount::from(0),
+            RawBytes::serialize(ThisEpochRewardReturn {
+                this_epoch_reward_smoothed: self.epoch_reward_smooth.clone(),
+                this_epoch_baseline_power: self.baseline_power.clone(),
+            })
+            .unwrap(),
+            ExitCode::Ok,
+        );
+        rt.expect_send(
+            *STORAGE_POWER_ACTOR_ADDR,
+            ext::power::CURRENT_TOTAL_POWER_METHOD,
+            RawBytes::default(),
+            TokenAmount::from(0),
+            RawBytes::serialize(ext::power::CurrentTotalPowerReturn {
+                raw_byte_power: self.network_raw_power.clone(),
+                quality_adj_power: self.network_qa_power.clone(),
+                pledge_collateral: self.network_pledge.clone(),
+                quality_adj_power_smoothed: self.epoch_qa_power_smooth.clone(),
+            })
+            .unwrap(),
+            ExitCode::Ok,
+        );
+    }
+
+    pub fn dispute_windowed_post(
+        self: &Self,
+        rt: &mut MockRuntime,
+        reporter: Address,
+        deadline: u64,
+        post_index: u64,
+    ) -> Result<RawBytes, fil_actors_runtime::ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, reporter);
+        rt.expect_validate_caller_type((*CALLER_TYPES_SIGNABLE).clone());
+        let params = DisputeWindowedPoStParams { deadline, post_index };
+        rt.call::<Actor>(Method::DisputeWindowedPoSt as u64, &RawBytes::serialize(params).unwrap())
+    }
 }
 
 pub fn check_state_invariants(_rt: &MockRuntime) {
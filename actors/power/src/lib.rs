@@ -58,6 +58,8 @@ pub enum Method {
     // OnConsensusFault = 7,
     SubmitPoRepForBulkVerify = 8,
     CurrentTotalPower = 9,
+    GetSmoothedPower = 10,
+    GetCommittedPower = 11,
 }
 
 /// Storage Power Actor
@@ -384,6 +386,40 @@ impl Actor {
         })
     }
 
+    /// Returns the smoothed quality-adjusted power estimate used for reward and pledge
+    /// calculations, alongside the raw power of this epoch and the current total QA power.
+    fn get_smoothed_power<BS, RT>(rt: &mut RT) -> Result<GetSmoothedPowerReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+
+        Ok(GetSmoothedPowerReturn {
+            quality_adj_power_smoothed: st.this_epoch_qa_power_smoothed,
+            this_epoch_raw_byte_power: st.this_epoch_raw_byte_power,
+            total_quality_adj_power: st.total_quality_adj_power,
+        })
+    }
+
+    /// Returns the total committed power of all miners next to the total active power.
+    fn get_committed_power<BS, RT>(rt: &mut RT) -> Result<GetCommittedPowerReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+
+        Ok(GetCommittedPowerReturn {
+            total_bytes_committed: st.total_bytes_committed,
+            total_qa_bytes_committed: st.total_qa_bytes_committed,
+            total_raw_byte_power: st.total_raw_byte_power,
+            total_quality_adj_power: st.total_quality_adj_power,
+        })
+    }
+
     fn process_batch_proof_verifies<BS, RT>(
         rt: &mut RT,
         rewret: &ThisEpochRewardReturn,
@@ -685,6 +721,14 @@ impl ActorCode for Actor {
                 let res = Self::current_total_power(rt)?;
                 Ok(RawBytes::serialize(res)?)
             }
+            Some(Method::GetSmoothedPower) => {
+                let res = Self::get_smoothed_power(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
+            Some(Method::GetCommittedPower) => {
+                let res = Self::get_committed_power(rt)?;
+                Ok(RawBytes::serialize(res)?)
+            }
             None => Err(actor_error!(SysErrInvalidMethod; "Invalid method")),
         }
     }
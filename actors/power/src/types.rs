@@ -66,3 +66,26 @@ pub struct CurrentTotalPowerReturn {
     pub pledge_collateral: TokenAmount,
     pub quality_adj_power_smoothed: FilterEstimate,
 }
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetSmoothedPowerReturn {
+    pub quality_adj_power_smoothed: FilterEstimate,
+    #[serde(with = "bigint_ser")]
+    pub this_epoch_raw_byte_power: StoragePower,
+    #[serde(with = "bigint_ser")]
+    pub total_quality_adj_power: StoragePower,
+}
+
+/// Power totals including all committed sectors, alongside the totals that count as active
+/// (from miners meeting the consensus minimum).
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetCommittedPowerReturn {
+    #[serde(with = "bigint_ser")]
+    pub total_bytes_committed: StoragePower,
+    #[serde(with = "bigint_ser")]
+    pub total_qa_bytes_committed: StoragePower,
+    #[serde(with = "bigint_ser")]
+    pub total_raw_byte_power: StoragePower,
+    #[serde(with = "bigint_ser")]
+    pub total_quality_adj_power: StoragePower,
+}
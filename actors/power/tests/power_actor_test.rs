@@ -0,0 +1,95 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fil_actor_power::{
+    Actor as PowerActor, GetCommittedPowerReturn, GetSmoothedPowerReturn, Method, State,
+};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
+use fvm_shared::bigint::BigInt;
+use fvm_shared::encoding::RawBytes;
+use fvm_shared::smooth::FilterEstimate;
+
+fn construct_runtime() -> MockRuntime {
+    MockRuntime {
+        receiver: *STORAGE_POWER_ACTOR_ADDR,
+        caller: *SYSTEM_ACTOR_ADDR,
+        caller_type: *SYSTEM_ACTOR_CODE_ID,
+        ..Default::default()
+    }
+}
+
+fn construct_and_verify(rt: &mut MockRuntime) {
+    rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+    let ret = rt.call::<PowerActor>(Method::Constructor as u64, &RawBytes::default()).unwrap();
+    assert_eq!(RawBytes::default(), ret);
+    rt.verify();
+}
+
+fn get_smoothed_power(rt: &mut MockRuntime) -> GetSmoothedPowerReturn {
+    rt.expect_validate_caller_any();
+    let ret = rt.call::<PowerActor>(Method::GetSmoothedPower as u64, &RawBytes::default()).unwrap();
+    rt.verify();
+    ret.deserialize().unwrap()
+}
+
+fn get_committed_power(rt: &mut MockRuntime) -> GetCommittedPowerReturn {
+    rt.expect_validate_caller_any();
+    let ret =
+        rt.call::<PowerActor>(Method::GetCommittedPower as u64, &RawBytes::default()).unwrap();
+    rt.verify();
+    ret.deserialize().unwrap()
+}
+
+#[test]
+fn smoothed_power_after_construction() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+
+    let st: State = rt.get_state().unwrap();
+    let ret = get_smoothed_power(&mut rt);
+    assert_eq!(st.this_epoch_qa_power_smoothed.position, ret.quality_adj_power_smoothed.position);
+    assert_eq!(st.this_epoch_qa_power_smoothed.velocity, ret.quality_adj_power_smoothed.velocity);
+    assert_eq!(BigInt::from(0), ret.this_epoch_raw_byte_power);
+    assert_eq!(BigInt::from(0), ret.total_quality_adj_power);
+}
+
+#[test]
+fn smoothed_power_reports_state_estimate() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+
+    // The estimate is returned as stored, not recomputed from this epoch's power.
+    let mut st: State = rt.get_state().unwrap();
+    st.this_epoch_qa_power_smoothed =
+        FilterEstimate { position: BigInt::from(1234) << 128, velocity: BigInt::from(-56) << 128 };
+    st.this_epoch_raw_byte_power = BigInt::from(1 << 20);
+    st.total_quality_adj_power = BigInt::from(10 << 20);
+    rt.replace_state(&st);
+
+    let ret = get_smoothed_power(&mut rt);
+    assert_eq!(BigInt::from(1234) << 128, ret.quality_adj_power_smoothed.position);
+    assert_eq!(BigInt::from(-56) << 128, ret.quality_adj_power_smoothed.velocity);
+    assert_eq!(BigInt::from(1 << 20), ret.this_epoch_raw_byte_power);
+    assert_eq!(BigInt::from(10 << 20), ret.total_quality_adj_power);
+}
+
+#[test]
+fn committed_power_reports_committed_and_active_totals() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+
+    // Committed power includes power not yet proven, so it differs from the active totals.
+    let mut st: State = rt.get_state().unwrap();
+    st.total_bytes_committed = BigInt::from(3 << 20);
+    st.total_qa_bytes_committed = BigInt::from(30 << 20);
+    st.total_raw_byte_power = BigInt::from(1 << 20);
+    st.total_quality_adj_power = BigInt::from(10 << 20);
+    rt.replace_state(&st);
+
+    let ret = get_committed_power(&mut rt);
+    assert_eq!(BigInt::from(3 << 20), ret.total_bytes_committed);
+    assert_eq!(BigInt::from(30 << 20), ret.total_qa_bytes_committed);
+    assert_eq!(BigInt::from(1 << 20), ret.total_raw_byte_power);
+    assert_eq!(BigInt::from(10 << 20), ret.total_quality_adj_power);
+}
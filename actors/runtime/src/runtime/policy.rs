@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof};
+use fvm_shared::version::NetworkVersion;
 
 // A trait for runtime policy configuration
 pub trait RuntimePolicy {
@@ -14,8 +15,9 @@ pub struct Policy {
     pub max_aggregated_sectors: u64,
     /// Minimum amount of sectors that can be aggregated.
     pub min_aggregated_sectors: u64,
-    /// Maximum total aggregated proof size.
-    pub max_aggregated_proof_size: usize,
+    /// Maximum total aggregated proof size, keyed by the network version from which each applies.
+    /// Entries are in ascending network version order.
+    pub max_aggregated_proof_sizes: Vec<(NetworkVersion, usize)>,
     /// Maximum total replica update proof size.
     pub max_replica_update_proof_size: usize,
 
@@ -30,6 +32,10 @@ pub struct Policy {
     /// can still prove its non-expired precommits without resubmitting a message
     pub expired_pre_commit_clean_up_delay: i64,
 
+    /// Number of epochs past its due epoch during which a late prove-commit is still accepted,
+    /// for a fee that grows with its lateness.
+    pub prove_commit_grace: ChainEpoch,
+
     /// The period over which all a miner's active sectors will be challenged.
     pub wpost_proving_period: ChainEpoch,
     /// The duration of a deadline's challenge window, the period before a deadline when the challenge is available.
@@ -46,6 +52,10 @@ pub struct Policy {
     /// This also bounds the number of faults that can be declared, etc.
     pub sectors_max: usize,
 
+    /// Maximum serialized size, in bytes, of a miner's allocated sector numbers bitfield.
+    /// New sector numbers are refused beyond this until the miner compacts its sector numbers.
+    pub max_allocated_sectors_size: usize,
+
     /// Maximum number of partitions that will be assigned to a deadline.
     /// For a minimum storage of upto 1Eib, we need 300 partitions per deadline.
     /// 48 * 32GiB * 2349 * 300 = 1.00808144 EiB
@@ -71,6 +81,9 @@ pub struct Policy {
     /// Maximum number of unique "declarations" in batch operations.
     pub delcarations_max: u64,
 
+    /// Maximum number of distinct partitions a single sector expiration extension may load.
+    pub extension_partitions_max: u64,
+
     /// The maximum number of sector infos that may be required to be loaded in a single invocation.
     pub addressed_sectors_max: u64,
 
@@ -101,13 +114,19 @@ pub struct Policy {
     /// key or allowing the owner account to submit PoSts while a key change is pending.
     pub worker_key_change_delay: ChainEpoch,
 
-    /// Minimum number of epochs past the current epoch a sector may be set to expire.
-    pub min_sector_expiration: i64,
+    /// Staging period for a worker key change requested by the owner as an emergency, e.g. after
+    /// the worker key has been compromised.
+    pub emergency_worker_key_change_delay: ChainEpoch,
 
-    /// Maximum number of epochs past the current epoch a sector may be set to expire.
+    /// Minimum number of epochs past the current epoch a sector may be set to expire, keyed by
+    /// the network version from which each applies. Entries are in ascending network version order.
+    pub min_sector_expirations: Vec<(NetworkVersion, i64)>,
+
+    /// Maximum number of epochs past the current epoch a sector may be set to expire, keyed by
+    /// the network version from which each applies. Entries are in ascending network version order.
     /// The actual maximum extension will be the minimum of CurrEpoch + MaximumSectorExpirationExtension
     /// and sector.ActivationEpoch+sealProof.SectorMaximumLifetime()
-    pub max_sector_expiration_extension: i64,
+    pub max_sector_expiration_extensions: Vec<(NetworkVersion, i64)>,
 
     /// Ratio of sector size to maximum deals per sector.
     /// The maximum number of deals is the sector size divided by this number (2^27)
@@ -118,6 +137,11 @@ pub struct Policy {
     /// for permissioned actor methods and winning block elections.
     pub consensus_fault_ineligibility_duration: ChainEpoch,
 
+    /// Multiple of terminated sectors' total initial pledge by which their termination penalty
+    /// may exceed that pledge. Penalties beyond this are clamped, so the multiple must stay above
+    /// what the penalty formula can produce.
+    pub termination_penalty_pledge_multiple: u64,
+
     /// The maximum number of new sectors that may be staged by a miner during a single proving period.
     pub new_sectors_per_period_max: usize,
 
@@ -130,6 +154,109 @@ pub struct Policy {
 
     /// Allowed pre commit proof types for new miners
     pub valid_pre_commit_proof_type: HashSet<RegisteredSealProof>,
+
+    /// Vesting schedule applied to the locked portion of block rewards.
+    pub reward_vesting_spec: VestSpec,
+
+    /// The number of blocks between payouts for deals
+    pub deal_updates_interval: ChainEpoch,
+
+    /// Maximum number of deals that may be published in a single message.
+    pub max_deals_per_publish: u64,
+
+    /// Maximum length of a deal label.
+    pub deal_max_label_size: usize,
+
+    /// Maximum number of scheduled deals the market cron tick processes. Deals beyond this are
+    /// deferred to the following epoch and processed by a later tick.
+    pub max_deals_per_cron_tick: u64,
+
+    /// Maximum fraction of a deal's provider collateral that may be slashed when the deal fails,
+    /// as a numerator and denominator. Any collateral above this is returned to the provider.
+    pub provider_collateral_slash_max_num: u64,
+    pub provider_collateral_slash_max_denom: u64,
+
+    /// Fraction of a deal's client collateral burnt when the client terminates the deal before
+    /// it is activated, as a numerator and denominator. The rest is returned to the client.
+    /// By default the whole collateral is burnt, since the provider may already have sealed the
+    /// data and has no other compensation for the abandoned deal.
+    pub client_termination_penalty_num: u64,
+    pub client_termination_penalty_denom: u64,
+
+    /// Whether miner methods check that the actor's balance still covers its locked funds after
+    /// mutating state. Disabling this saves gas but means a bug in fund accounting is no longer
+    /// caught before it is committed, so it should stay enabled outside of well-tested deployments.
+    pub check_balance_invariants: bool,
+
+    /// Whether the market accepts a zero-value AddBalance, creating an empty escrow entry so
+    /// that clients can register an address ahead of depositing funds. Disabled by default.
+    pub allow_zero_value_add_balance: bool,
+
+    /// Whether miners keep their most recent reward applications, with the memos attached, in
+    /// state. Each reward application then rewrites the history, at a storage cost borne by
+    /// every miner on every block reward, so it is disabled by default.
+    pub record_reward_applications: bool,
+
+    /// Epoch before which continued-fault penalties are waived in miner cron, letting
+    /// governance forgive penalties while the network recovers from a widespread outage.
+    /// Unset by default, so faults are always penalized.
+    pub continued_fault_grace_until_epoch: Option<ChainEpoch>,
+
+    /// Whether Window PoSt submissions skip checking their chain commit randomness against the
+    /// chain. Only available in test vector builds, and never enabled by default.
+    #[cfg(feature = "no-post-commit-rand-check")]
+    pub skip_post_commit_rand_check: bool,
+
+    /// Proving period offset assigned to every new miner in place of the one derived from its
+    /// address and creation epoch, so tests get predictable deadlines. Only available in builds
+    /// for integration tests, and unset by default.
+    #[cfg(feature = "fixed-proving-period-offset")]
+    pub fixed_proving_period_offset: Option<ChainEpoch>,
+}
+
+impl Policy {
+    /// Returns the maximum total aggregated proof size at a network version, or zero if no size
+    /// applies at that version.
+    pub fn max_aggregated_proof_size(&self, nv: NetworkVersion) -> usize {
+        self.max_aggregated_proof_sizes
+            .iter()
+            .rev()
+            .find(|(from, _)| *from <= nv)
+            .map_or(0, |(_, size)| *size)
+    }
+
+    /// Returns the minimum sector expiration at a network version, or zero if no minimum
+    /// applies at that version.
+    pub fn min_sector_expiration(&self, nv: NetworkVersion) -> i64 {
+        self.min_sector_expirations
+            .iter()
+            .rev()
+            .find(|(from, _)| *from <= nv)
+            .map_or(0, |(_, epochs)| *epochs)
+    }
+
+    /// Returns the maximum sector expiration extension at a network version, or zero if no
+    /// extension applies at that version.
+    pub fn max_sector_expiration_extension(&self, nv: NetworkVersion) -> i64 {
+        self.max_sector_expiration_extensions
+            .iter()
+            .rev()
+            .find(|(from, _)| *from <= nv)
+            .map_or(0, |(_, epochs)| *epochs)
+    }
+
+    /// Returns whether continued-fault penalties are waived at an epoch.
+    pub fn in_continued_fault_grace(&self, epoch: ChainEpoch) -> bool {
+        self.continued_fault_grace_until_epoch.map_or(false, |grace_until| epoch < grace_until)
+    }
+}
+
+/// Specification for a linear vesting schedule.
+pub struct VestSpec {
+    pub initial_delay: ChainEpoch, // Delay before any amount starts vesting.
+    pub vest_period: ChainEpoch, // Period over which the total should vest, after the initial delay.
+    pub step_duration: ChainEpoch, // Duration between successive incremental vests (independent of vesting period).
+    pub quantization: ChainEpoch, // Maximum precision of vesting table (limits cardinality of table).
 }
 
 impl Default for Policy {
@@ -138,23 +265,29 @@ impl Default for Policy {
         let mut policy = Policy {
             max_aggregated_sectors: policy_constants::MAX_AGGREGATED_SECTORS,
             min_aggregated_sectors: policy_constants::MIN_AGGREGATED_SECTORS,
-            max_aggregated_proof_size: policy_constants::MAX_AGGREGATED_PROOF_SIZE,
+            max_aggregated_proof_sizes: vec![(
+                NetworkVersion::V0,
+                policy_constants::MAX_AGGREGATED_PROOF_SIZE,
+            )],
             max_replica_update_proof_size: policy_constants::MAX_REPLICA_UPDATE_PROOF_SIZE,
             pre_commit_sector_batch_max_size: policy_constants::PRE_COMMIT_SECTOR_BATCH_MAX_SIZE,
             prove_replica_updates_max_size: policy_constants::PROVE_REPLICA_UPDATES_MAX_SIZE,
             expired_pre_commit_clean_up_delay: policy_constants::EXPIRED_PRE_COMMIT_CLEAN_UP_DELAY,
+            prove_commit_grace: policy_constants::PROVE_COMMIT_GRACE,
             wpost_proving_period: policy_constants::WPOST_PROVING_PERIOD,
             wpost_challenge_window: policy_constants::WPOST_CHALLENGE_WINDOW,
             wpost_period_deadlines: policy_constants::WPOST_PERIOD_DEADLINES,
             wpost_max_chain_commit_age: policy_constants::WPOST_MAX_CHAIN_COMMIT_AGE,
             wpost_dispute_window: policy_constants::WPOST_DISPUTE_WINDOW,
             sectors_max: policy_constants::SECTORS_MAX,
+            max_allocated_sectors_size: policy_constants::MAX_ALLOCATED_SECTORS_SIZE,
             max_partitions_per_deadline: policy_constants::MAX_PARTITIONS_PER_DEADLINE,
             max_control_addresses: policy_constants::MAX_CONTROL_ADDRESSES,
             max_peer_id_length: policy_constants::MAX_PEER_ID_LENGTH,
             max_multiaddr_data: policy_constants::MAX_MULTIADDR_DATA,
             addressed_partitions_max: policy_constants::ADDRESSED_PARTITIONS_MAX,
             delcarations_max: policy_constants::DELCARATIONS_MAX,
+            extension_partitions_max: policy_constants::EXTENSION_PARTITIONS_MAX,
             addressed_sectors_max: policy_constants::ADDRESSED_SECTORS_MAX,
             max_pre_commit_randomness_lookback:
                 policy_constants::MAX_PRE_COMMIT_RANDOMNESS_LOOKBACK,
@@ -163,11 +296,20 @@ impl Default for Policy {
             fault_declaration_cutoff: policy_constants::FAULT_DECLARATION_CUTOFF,
             fault_max_age: policy_constants::FAULT_MAX_AGE,
             worker_key_change_delay: policy_constants::WORKER_KEY_CHANGE_DELAY,
-            min_sector_expiration: policy_constants::MIN_SECTOR_EXPIRATION,
-            max_sector_expiration_extension: policy_constants::MAX_SECTOR_EXPIRATION_EXTENSION,
+            emergency_worker_key_change_delay: policy_constants::EMERGENCY_WORKER_KEY_CHANGE_DELAY,
+            min_sector_expirations: vec![(
+                NetworkVersion::V0,
+                policy_constants::MIN_SECTOR_EXPIRATION,
+            )],
+            max_sector_expiration_extensions: vec![(
+                NetworkVersion::V0,
+                policy_constants::MAX_SECTOR_EXPIRATION_EXTENSION,
+            )],
             deal_limit_denominator: policy_constants::DEAL_LIMIT_DENOMINATOR,
             consensus_fault_ineligibility_duration:
                 policy_constants::CONSENSUS_FAULT_INELIGIBILITY_DURATION,
+            termination_penalty_pledge_multiple:
+                policy_constants::TERMINATION_PENALTY_PLEDGE_MULTIPLE,
             new_sectors_per_period_max: policy_constants::NEW_SECTORS_PER_PERIOD_MAX,
             chain_finality: policy_constants::CHAIN_FINALITY,
 
@@ -195,6 +337,27 @@ impl Default for Policy {
                 #[cfg(feature = "sector-64g")]
                 RegisteredSealProof::StackedDRG64GiBV1P1,
             ]),
+
+            reward_vesting_spec: policy_constants::REWARD_VESTING_SPEC,
+            deal_updates_interval: policy_constants::DEAL_UPDATES_INTERVAL,
+            max_deals_per_publish: policy_constants::MAX_DEALS_PER_PUBLISH,
+            deal_max_label_size: policy_constants::DEAL_MAX_LABEL_SIZE,
+            max_deals_per_cron_tick: policy_constants::MAX_DEALS_PER_CRON_TICK,
+            provider_collateral_slash_max_num: policy_constants::PROVIDER_COLLATERAL_SLASH_MAX_NUM,
+            provider_collateral_slash_max_denom:
+                policy_constants::PROVIDER_COLLATERAL_SLASH_MAX_DENOM,
+            client_termination_penalty_num: policy_constants::CLIENT_TERMINATION_PENALTY_NUM,
+            client_termination_penalty_denom: policy_constants::CLIENT_TERMINATION_PENALTY_DENOM,
+            check_balance_invariants: true,
+            allow_zero_value_add_balance: false,
+            record_reward_applications: false,
+            continued_fault_grace_until_epoch: None,
+
+            #[cfg(feature = "no-post-commit-rand-check")]
+            skip_post_commit_rand_check: false,
+
+            #[cfg(feature = "fixed-proving-period-offset")]
+            fixed_proving_period_offset: None,
         };
 
         policy
@@ -202,6 +365,7 @@ impl Default for Policy {
 }
 
 mod policy_constants {
+    use super::VestSpec;
     use crate::builtin::*;
     use fvm_shared::clock::ChainEpoch;
     use fvm_shared::clock::EPOCH_DURATION_SECONDS;
@@ -228,6 +392,9 @@ mod policy_constants {
     /// can still prove its non-expired precommits without resubmitting a message
     pub const EXPIRED_PRE_COMMIT_CLEAN_UP_DELAY: i64 = 8 * EPOCHS_IN_HOUR;
 
+    /// Late prove-commits are rejected outright by default.
+    pub const PROVE_COMMIT_GRACE: ChainEpoch = 0;
+
     /// The period over which all a miner's active sectors will be challenged.
     pub const WPOST_PROVING_PERIOD: ChainEpoch = EPOCHS_IN_DAY;
     /// The duration of a deadline's challenge window, the period before a deadline when the challenge is available.
@@ -244,6 +411,9 @@ mod policy_constants {
     /// This also bounds the number of faults that can be declared, etc.
     pub const SECTORS_MAX: usize = 32 << 20;
 
+    /// Maximum serialized size, in bytes, of a miner's allocated sector numbers bitfield.
+    pub const MAX_ALLOCATED_SECTORS_SIZE: usize = 64 << 10;
+
     /// Maximum number of partitions that will be assigned to a deadline.
     /// For a minimum storage of upto 1Eib, we need 300 partitions per deadline.
     /// 48 * 32GiB * 2349 * 300 = 1.00808144 EiB
@@ -269,6 +439,12 @@ mod policy_constants {
     /// Maximum number of unique "declarations" in batch operations.
     pub const DELCARATIONS_MAX: u64 = ADDRESSED_PARTITIONS_MAX;
 
+    /// Maximum number of distinct partitions a single sector expiration extension may load.
+    /// Each declaration names one partition, so this must be below DELCARATIONS_MAX to bind.
+    /// At this limit an extension of ADDRESSED_SECTORS_MAX sectors averages 125 per partition, so
+    /// only extensions spread thinly over many partitions need to be split across messages.
+    pub const EXTENSION_PARTITIONS_MAX: u64 = 200;
+
     /// The maximum number of sector infos that may be required to be loaded in a single invocation.
     pub const ADDRESSED_SECTORS_MAX: u64 = 25_000;
 
@@ -302,6 +478,10 @@ mod policy_constants {
     /// key or allowing the owner account to submit PoSts while a key change is pending.
     pub const WORKER_KEY_CHANGE_DELAY: ChainEpoch = CHAIN_FINALITY;
 
+    /// Staging period for an emergency worker key change.
+    /// This is the same as the regular delay unless a network chooses to shorten it.
+    pub const EMERGENCY_WORKER_KEY_CHANGE_DELAY: ChainEpoch = WORKER_KEY_CHANGE_DELAY;
+
     /// Minimum number of epochs past the current epoch a sector may be set to expire.
     pub const MIN_SECTOR_EXPIRATION: i64 = 180 * EPOCHS_IN_DAY;
 
@@ -319,10 +499,47 @@ mod policy_constants {
     /// for permissioned actor methods and winning block elections.
     pub const CONSENSUS_FAULT_INELIGIBILITY_DURATION: ChainEpoch = CHAIN_FINALITY;
 
+    /// Multiple of terminated sectors' initial pledge by which their termination penalty may
+    /// exceed that pledge. A sector's initial pledge covers its 20-day storage pledge, and an
+    /// upgrade never lowers it. The penalty is at most that storage pledge plus half of 140 days
+    /// of expected daily reward, 4.5 times the pledge, unless the 3.5-day floor at current
+    /// estimates is higher. That floor reaches 11 times the pledge only if the reward per unit of
+    /// power grows more than 60-fold after activation, so the clamp only catches a regression.
+    pub const TERMINATION_PENALTY_PLEDGE_MULTIPLE: u64 = 10;
+
     /// The maximum number of new sectors that may be staged by a miner during a single proving period.
     pub const NEW_SECTORS_PER_PERIOD_MAX: usize = 128 << 10;
 
     /// Epochs after which chain state is final with overwhelming probability (hence the likelihood of two fork of this size is negligible)
     /// This is a conservative value that is chosen via simulations of all known attacks.
     pub const CHAIN_FINALITY: ChainEpoch = 900;
+
+    pub const REWARD_VESTING_SPEC: VestSpec = VestSpec {
+        initial_delay: 0,                  // PARAM_FINISH
+        vest_period: 180 * EPOCHS_IN_DAY,  // PARAM_FINISH
+        step_duration: EPOCHS_IN_DAY,      // PARAM_FINISH
+        quantization: 12 * EPOCHS_IN_HOUR, // PARAM_FINISH
+    };
+
+    /// The number of blocks between payouts for deals
+    pub const DEAL_UPDATES_INTERVAL: ChainEpoch = EPOCHS_IN_DAY;
+
+    /// Maximum number of deals that may be published in a single message.
+    pub const MAX_DEALS_PER_PUBLISH: u64 = 8192;
+
+    /// Maximum length of a deal label.
+    pub const DEAL_MAX_LABEL_SIZE: usize = 256;
+
+    /// Maximum number of scheduled deals processed by a single market cron tick.
+    pub const MAX_DEALS_PER_CRON_TICK: u64 = 32768;
+
+    /// Maximum fraction of a deal's provider collateral that may be slashed.
+    /// By default the whole collateral is slashed.
+    pub const PROVIDER_COLLATERAL_SLASH_MAX_NUM: u64 = 1;
+    pub const PROVIDER_COLLATERAL_SLASH_MAX_DENOM: u64 = 1;
+
+    /// Fraction of a deal's client collateral burnt when the client terminates a pending deal.
+    /// By default the whole collateral is burnt.
+    pub const CLIENT_TERMINATION_PENALTY_NUM: u64 = 1;
+    pub const CLIENT_TERMINATION_PENALTY_DENOM: u64 = 1;
 }
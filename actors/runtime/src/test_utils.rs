@@ -22,8 +22,8 @@ use fvm_shared::error::ExitCode;
 use fvm_shared::piece::PieceInfo;
 use fvm_shared::randomness::Randomness;
 use fvm_shared::sector::{
-    AggregateSealVerifyProofAndInfos, RegisteredSealProof, ReplicaUpdateInfo, SealVerifyInfo,
-    WindowPoStVerifyInfo,
+    AggregateSealVerifyInfo, AggregateSealVerifyProofAndInfos, RegisteredSealProof,
+    ReplicaUpdateInfo, SealVerifyInfo, WindowPoStVerifyInfo,
 };
 use fvm_shared::version::NetworkVersion;
 use fvm_shared::{ActorID, MethodNum};
@@ -82,6 +82,7 @@ pub struct MockRuntime {
     pub value_received: TokenAmount,
     pub hash_func: Box<dyn Fn(&[u8]) -> [u8; 32]>,
     pub network_version: NetworkVersion,
+    pub circulating_supply: TokenAmount,
 
     // Actor State
     pub state: Option<Cid>,
@@ -111,8 +112,12 @@ pub struct Expectations {
     pub expect_verify_sigs: VecDeque<ExpectedVerifySig>,
     pub expect_verify_seal: Option<ExpectVerifySeal>,
     pub expect_verify_post: Option<ExpectVerifyPoSt>,
+    pub expect_aggregate_verify_seals: Option<ExpectAggregateVerifySeals>,
+    pub expect_replica_verify: VecDeque<ExpectReplicaVerify>,
     pub expect_compute_unsealed_sector_cid: Option<ExpectComputeUnsealedSectorCid>,
     pub expect_verify_consensus_fault: Option<ExpectVerifyConsensusFault>,
+    pub expect_get_randomness_tickets: VecDeque<ExpectRandomness>,
+    pub expect_get_randomness_beacon: VecDeque<ExpectRandomness>,
 }
 
 impl Expectations {
@@ -125,8 +130,12 @@ impl Expectations {
         self.expect_verify_sigs.clear();
         self.expect_verify_seal = None;
         self.expect_verify_post = None;
+        self.expect_aggregate_verify_seals = None;
+        self.expect_replica_verify.clear();
         self.expect_compute_unsealed_sector_cid = None;
         self.expect_verify_consensus_fault = None;
+        self.expect_get_randomness_tickets.clear();
+        self.expect_get_randomness_beacon.clear();
     }
     fn verify(&mut self) {
         assert!(!self.expect_validate_caller_any, "expected ValidateCallerAny, not received");
@@ -155,6 +164,16 @@ impl Expectations {
             "expect_verify_seal {:?}, not received",
             self.expect_verify_seal.as_ref().unwrap()
         );
+        assert!(
+            self.expect_aggregate_verify_seals.is_none(),
+            "expect_aggregate_verify_seals {:?}, not received",
+            self.expect_aggregate_verify_seals.as_ref().unwrap()
+        );
+        assert!(
+            self.expect_replica_verify.is_empty(),
+            "expect_replica_verify {:?}, not received",
+            self.expect_replica_verify
+        );
         assert!(
             self.expect_compute_unsealed_sector_cid.is_none(),
             "expect_compute_unsealed_sector_cid not received",
@@ -163,6 +182,16 @@ impl Expectations {
             self.expect_verify_consensus_fault.is_none(),
             "expect_verify_consensus_fault not received",
         );
+        assert!(
+            self.expect_get_randomness_tickets.is_empty(),
+            "expect_get_randomness_tickets {:?}, not received",
+            self.expect_get_randomness_tickets
+        );
+        assert!(
+            self.expect_get_randomness_beacon.is_empty(),
+            "expect_get_randomness_beacon {:?}, not received",
+            self.expect_get_randomness_beacon
+        );
     }
 }
 
@@ -181,6 +210,7 @@ impl Default for MockRuntime {
             value_received: Default::default(),
             hash_func: Box::new(|_| [0u8; 32]),
             network_version: NetworkVersion::V0,
+            circulating_supply: Default::default(),
             state: Default::default(),
             balance: Default::default(),
             received: Default::default(),
@@ -231,6 +261,19 @@ pub struct ExpectVerifyPoSt {
     exit_code: ExitCode,
 }
 
+#[derive(Clone, Debug)]
+pub struct ExpectAggregateVerifySeals {
+    in_svis: Vec<AggregateSealVerifyInfo>,
+    in_proof: Vec<u8>,
+    exit_code: ExitCode,
+}
+
+#[derive(Clone, Debug)]
+pub struct ExpectReplicaVerify {
+    replica: ReplicaUpdateInfo,
+    exit_code: ExitCode,
+}
+
 #[derive(Clone)]
 pub struct ExpectVerifyConsensusFault {
     require_correct_input: bool,
@@ -241,12 +284,20 @@ pub struct ExpectVerifyConsensusFault {
     exit_code: ExitCode,
 }
 
+#[derive(Clone, Debug)]
+pub struct ExpectRandomness {
+    tag: DomainSeparationTag,
+    epoch: ChainEpoch,
+    entropy: Vec<u8>,
+    out: Randomness,
+}
+
 #[derive(Clone)]
 pub struct ExpectComputeUnsealedSectorCid {
-    reg: RegisteredSealProof,
-    pieces: Vec<PieceInfo>,
-    cid: Cid,
-    exit_code: ExitCode,
+    pub reg: RegisteredSealProof,
+    pub pieces: Vec<PieceInfo>,
+    pub cid: Cid,
+    pub exit_code: ExitCode,
 }
 
 pub fn expect_ok<T: fmt::Debug>(res: Result<T, ActorError>) -> T {
@@ -353,6 +404,38 @@ impl MockRuntime {
         self.expectations.borrow_mut().expect_compute_unsealed_sector_cid = Some(exp);
     }
 
+    #[allow(dead_code)]
+    pub fn expect_get_randomness_from_tickets(
+        &self,
+        tag: DomainSeparationTag,
+        epoch: ChainEpoch,
+        entropy: Vec<u8>,
+        out: Randomness,
+    ) {
+        self.expectations.borrow_mut().expect_get_randomness_tickets.push_back(ExpectRandomness {
+            tag,
+            epoch,
+            entropy,
+            out,
+        });
+    }
+
+    #[allow(dead_code)]
+    pub fn expect_get_randomness_from_beacon(
+        &self,
+        tag: DomainSeparationTag,
+        epoch: ChainEpoch,
+        entropy: Vec<u8>,
+        out: Randomness,
+    ) {
+        self.expectations.borrow_mut().expect_get_randomness_beacon.push_back(ExpectRandomness {
+            tag,
+            epoch,
+            entropy,
+            out,
+        });
+    }
+
     #[allow(dead_code)]
     pub fn expect_validate_caller_type(&mut self, types: Vec<Cid>) {
         assert!(!types.is_empty(), "addrs must be non-empty");
@@ -430,6 +513,23 @@ impl MockRuntime {
         self.expectations.borrow_mut().expect_verify_post = Some(a);
     }
 
+    #[allow(dead_code)]
+    pub fn expect_aggregate_verify_seals(
+        &mut self,
+        in_svis: Vec<AggregateSealVerifyInfo>,
+        in_proof: Vec<u8>,
+        exit_code: ExitCode,
+    ) {
+        let a = ExpectAggregateVerifySeals { in_svis, in_proof, exit_code };
+        self.expectations.borrow_mut().expect_aggregate_verify_seals = Some(a);
+    }
+
+    #[allow(dead_code)]
+    pub fn expect_replica_verify(&mut self, replica: ReplicaUpdateInfo, exit_code: ExitCode) {
+        let a = ExpectReplicaVerify { replica, exit_code };
+        self.expectations.borrow_mut().expect_replica_verify.push_back(a);
+    }
+
     #[allow(dead_code)]
     pub fn set_caller(&mut self, code_id: Cid, address: Address) {
         self.caller = address;
@@ -579,20 +679,38 @@ impl Runtime<MemoryBlockstore> for MockRuntime {
 
     fn get_randomness_from_tickets(
         &self,
-        _personalization: DomainSeparationTag,
-        _rand_epoch: ChainEpoch,
-        _entropy: &[u8],
+        personalization: DomainSeparationTag,
+        rand_epoch: ChainEpoch,
+        entropy: &[u8],
     ) -> Result<Randomness, ActorError> {
-        unimplemented!()
+        let exp = self
+            .expectations
+            .borrow_mut()
+            .expect_get_randomness_tickets
+            .pop_front()
+            .expect("unexpected call to get randomness for tickets");
+        assert_eq!(exp.tag, personalization, "unexpected domain separation tag");
+        assert_eq!(exp.epoch, rand_epoch, "unexpected randomness epoch");
+        assert_eq!(&exp.entropy[..], entropy, "unexpected randomness entropy");
+        Ok(exp.out)
     }
 
     fn get_randomness_from_beacon(
         &self,
-        _personalization: DomainSeparationTag,
-        _rand_epoch: ChainEpoch,
-        _entropy: &[u8],
+        personalization: DomainSeparationTag,
+        rand_epoch: ChainEpoch,
+        entropy: &[u8],
     ) -> Result<Randomness, ActorError> {
-        unimplemented!()
+        let exp = self
+            .expectations
+            .borrow_mut()
+            .expect_get_randomness_beacon
+            .pop_front()
+            .expect("unexpected call to get randomness for beacon");
+        assert_eq!(exp.tag, personalization, "unexpected domain separation tag");
+        assert_eq!(exp.epoch, rand_epoch, "unexpected randomness epoch");
+        assert_eq!(&exp.entropy[..], entropy, "unexpected randomness entropy");
+        Ok(exp.out)
     }
 
     fn create<C: Cbor>(&mut self, obj: &C) -> Result<(), ActorError> {
@@ -726,7 +844,7 @@ impl Runtime<MemoryBlockstore> for MockRuntime {
     }
 
     fn total_fil_circ_supply(&self) -> TokenAmount {
-        unimplemented!();
+        self.circulating_supply.clone()
     }
 
     fn charge_gas(&mut self, _: &'static str, _: i64) {
@@ -799,7 +917,7 @@ impl Syscalls for MockRuntime {
             )));
         }
 
-        if exp.pieces[..].eq(pieces) {
+        if !exp.pieces[..].eq(pieces) {
             return Err(anyhow!(actor_error!(ErrIllegalState;
                 "Unexpected compute_unsealed_sector_cid : pieces mismatch"
             )));
@@ -865,14 +983,36 @@ impl Syscalls for MockRuntime {
     }
     fn verify_aggregate_seals(
         &self,
-        _aggregate: &AggregateSealVerifyProofAndInfos,
+        aggregate: &AggregateSealVerifyProofAndInfos,
     ) -> anyhow::Result<()> {
-        // TODO: Implement this if we need it. Currently don't have a need.
-        todo!()
+        let exp = self.expectations.borrow_mut().expect_aggregate_verify_seals.take().ok_or_else(
+            || actor_error!(ErrIllegalState; "Unexpected syscall to verify aggregate seals"),
+        )?;
+
+        if exp.in_svis != aggregate.infos || exp.in_proof != aggregate.proof {
+            return Err(anyhow!(
+                actor_error!(ErrIllegalState; "Unexpected aggregate seal verification"),
+            ));
+        }
+        if exp.exit_code != ExitCode::Ok {
+            return Err(anyhow!(ActorError::new(exp.exit_code, "Expected Failure".to_string(),)));
+        }
+        Ok(())
     }
-    fn verify_replica_update(&self, _replica: &ReplicaUpdateInfo) -> Result<(), anyhow::Error> {
-        // TODO: Implement this if we need it. Currently don't have a need.
-        todo!()
+    fn verify_replica_update(&self, replica: &ReplicaUpdateInfo) -> Result<(), anyhow::Error> {
+        let exp = self.expectations.borrow_mut().expect_replica_verify.pop_front().ok_or_else(
+            || actor_error!(ErrIllegalState; "Unexpected syscall to verify replica update"),
+        )?;
+
+        if exp.replica != *replica {
+            return Err(anyhow!(
+                actor_error!(ErrIllegalState; "Unexpected replica update verification"),
+            ));
+        }
+        if exp.exit_code != ExitCode::Ok {
+            return Err(anyhow!(ActorError::new(exp.exit_code, "Expected Failure".to_string(),)));
+        }
+        Ok(())
     }
 }
 
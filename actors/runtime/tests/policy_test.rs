@@ -0,0 +1,88 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fil_actors_runtime::runtime::Policy;
+use fvm_shared::version::NetworkVersion;
+
+#[test]
+fn max_aggregated_proof_size_by_network_version() {
+    let policy = Policy {
+        max_aggregated_proof_sizes: vec![(NetworkVersion::V13, 1000), (NetworkVersion::V15, 2000)],
+        ..Default::default()
+    };
+
+    assert_eq!(0, policy.max_aggregated_proof_size(NetworkVersion::V12));
+    assert_eq!(1000, policy.max_aggregated_proof_size(NetworkVersion::V13));
+    assert_eq!(1000, policy.max_aggregated_proof_size(NetworkVersion::V14));
+    assert_eq!(2000, policy.max_aggregated_proof_size(NetworkVersion::V15));
+}
+
+#[test]
+fn default_max_aggregated_proof_size_applies_to_all_versions() {
+    let policy = Policy::default();
+    assert_eq!(81960, policy.max_aggregated_proof_size(NetworkVersion::V0));
+    assert_eq!(81960, policy.max_aggregated_proof_size(NetworkVersion::V15));
+}
+
+#[test]
+fn sector_expiration_bounds_by_network_version() {
+    let policy = Policy {
+        min_sector_expirations: vec![(NetworkVersion::V0, 100), (NetworkVersion::V16, 200)],
+        max_sector_expiration_extensions: vec![
+            (NetworkVersion::V0, 1000),
+            (NetworkVersion::V16, 3000),
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(100, policy.min_sector_expiration(NetworkVersion::V15));
+    assert_eq!(200, policy.min_sector_expiration(NetworkVersion::V16));
+    assert_eq!(1000, policy.max_sector_expiration_extension(NetworkVersion::V15));
+    assert_eq!(3000, policy.max_sector_expiration_extension(NetworkVersion::V16));
+}
+
+#[test]
+fn default_policy_checks_balance_invariants() {
+    assert!(Policy::default().check_balance_invariants);
+}
+
+#[test]
+fn default_policy_burns_all_client_collateral_on_termination() {
+    let policy = Policy::default();
+    assert_eq!(1, policy.client_termination_penalty_num);
+    assert_eq!(1, policy.client_termination_penalty_denom);
+}
+
+#[test]
+fn default_policy_allows_termination_penalty_above_formula_maximum() {
+    // The termination penalty formula charges at most 4.5 times a sector's initial pledge.
+    assert_eq!(10, Policy::default().termination_penalty_pledge_multiple);
+}
+
+#[test]
+fn default_extension_partition_cap_is_below_declaration_limit() {
+    // Each extension declaration names one partition, so a cap at the declaration limit
+    // could never be reached.
+    let policy = Policy::default();
+    assert!(policy.extension_partitions_max < policy.delcarations_max);
+}
+
+#[test]
+fn default_policy_does_not_record_reward_applications() {
+    assert!(!Policy::default().record_reward_applications);
+}
+
+#[test]
+fn default_policy_rejects_zero_value_add_balance() {
+    assert!(!Policy::default().allow_zero_value_add_balance);
+}
+
+#[test]
+fn continued_fault_grace_until_epoch() {
+    assert!(!Policy::default().in_continued_fault_grace(0));
+
+    let policy = Policy { continued_fault_grace_until_epoch: Some(100), ..Default::default() };
+    assert!(policy.in_continued_fault_grace(0));
+    assert!(policy.in_continued_fault_grace(99));
+    assert!(!policy.in_continued_fault_grace(100));
+}